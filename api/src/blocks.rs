@@ -0,0 +1,160 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    param::{BlockHeightParam, Param, TransactionVersionParam},
+};
+
+use diem_api_types::{Block, Error, LedgerInfo, Response, Transaction};
+
+use anyhow::Result;
+use serde::Deserialize;
+use storage_interface::CommittedBlock;
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+/// Number of transactions of a block read from the db at once.
+const TRANSACTIONS_BATCH_SIZE: u64 = 1000;
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct BlockQuery {
+    with_transactions: Option<Param<bool>>,
+}
+
+impl BlockQuery {
+    fn with_transactions(self) -> Result<bool, Error> {
+        self.with_transactions
+            .map_or(Ok(false), |param| param.parse("with_transactions"))
+    }
+}
+
+// GET /blocks/by_height/{height}?with_transactions={bool}
+pub fn get_block_by_height(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("blocks" / "by_height" / BlockHeightParam)
+        .and(warp::get())
+        .and(warp::query::<BlockQuery>())
+        .and(context.filter())
+        .and_then(handle_get_block_by_height)
+        .with(metrics("get_block_by_height"))
+        .boxed()
+}
+
+// GET /blocks/by_version/{version}?with_transactions={bool}
+pub fn get_block_by_version(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("blocks" / "by_version" / TransactionVersionParam)
+        .and(warp::get())
+        .and(warp::query::<BlockQuery>())
+        .and(context.filter())
+        .and_then(handle_get_block_by_version)
+        .with(metrics("get_block_by_version"))
+        .boxed()
+}
+
+async fn handle_get_block_by_height(
+    height: BlockHeightParam,
+    query: BlockQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_block_by_height")?;
+    Ok(Blocks::new(context)?.get_by_height(height.parse("block height")?, query)?)
+}
+
+async fn handle_get_block_by_version(
+    version: TransactionVersionParam,
+    query: BlockQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_block_by_version")?;
+    Ok(Blocks::new(context)?.get_by_version(version.parse("version")?, query)?)
+}
+
+struct Blocks {
+    ledger_info: LedgerInfo,
+    context: Context,
+}
+
+impl Blocks {
+    fn new(context: Context) -> Result<Self, Error> {
+        let ledger_info = context.get_latest_ledger_info()?;
+        Ok(Self {
+            ledger_info,
+            context,
+        })
+    }
+
+    pub fn get_by_height(self, height: u64, query: BlockQuery) -> Result<impl Reply, Error> {
+        let block = self
+            .context
+            .get_block_by_height(height, self.ledger_info.version())?
+            .ok_or_else(|| {
+                Error::not_found(
+                    "block",
+                    format!("height({})", height),
+                    self.ledger_info.version(),
+                )
+            })?;
+        self.render_block(block, query)
+    }
+
+    pub fn get_by_version(self, version: u64, query: BlockQuery) -> Result<impl Reply, Error> {
+        let block = self
+            .context
+            .get_block_by_version(version, self.ledger_info.version())?
+            .ok_or_else(|| {
+                Error::not_found(
+                    "block",
+                    format!("version({})", version),
+                    self.ledger_info.version(),
+                )
+            })?;
+        self.render_block(block, query)
+    }
+
+    fn render_block(self, block: CommittedBlock, query: BlockQuery) -> Result<impl Reply, Error> {
+        let transactions = if query.with_transactions()? {
+            Some(self.get_transactions(&block)?)
+        } else {
+            None
+        };
+        let block = Block::new(
+            block.height,
+            block.first_version,
+            block.last_version,
+            block.metadata.as_ref(),
+            transactions,
+        );
+        Response::new(self.ledger_info, &block)
+    }
+
+    fn get_transactions(&self, block: &CommittedBlock) -> Result<Vec<Transaction>> {
+        let converter = self.context.move_converter();
+        // all the transactions of a block have the timestamp of its block metadata transaction
+        let timestamp = block
+            .metadata
+            .as_ref()
+            .map_or(0, |metadata| metadata.timestamp_usec());
+        let mut txns = vec![];
+        let mut start_version = block.first_version;
+        while start_version <= block.last_version {
+            let limit = std::cmp::min(
+                TRANSACTIONS_BATCH_SIZE,
+                block.last_version - start_version + 1,
+            );
+            let data = self.context.get_transactions(
+                start_version,
+                limit as u16,
+                self.ledger_info.version(),
+            )?;
+            if data.is_empty() {
+                break;
+            }
+            start_version += data.len() as u64;
+            for txn in data {
+                txns.push(converter.try_into_onchain_transaction(timestamp, txn)?);
+            }
+        }
+        Ok(txns)
+    }
+}
@@ -4,8 +4,11 @@
 use diem_api_types::{Error, LedgerInfo, MoveConverter, TransactionOnChainData};
 use diem_config::config::{ApiConfig, JsonRpcConfig, RoleType};
 use diem_crypto::HashValue;
-use diem_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use diem_mempool::{ClientSource, MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use diem_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use diem_state_view::StateView;
 use diem_types::{
+    access_path::AccessPath,
     account_address::AccountAddress,
     account_state::AccountState,
     account_state_blob::AccountStateBlob,
@@ -13,17 +16,20 @@ use diem_types::{
     contract_event::ContractEvent,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
-    transaction::{SignedTransaction, TransactionWithProof},
+    transaction::{SignedTransaction, TransactionOutput, TransactionWithProof, Version},
 };
-use storage_interface::{MoveDbReader, Order};
+use diem_vm::DiemVM;
+use storage_interface::{CommittedBlock, MoveDbReader, Order};
 
 use anyhow::{ensure, format_err, Result};
 use futures::{channel::oneshot, SinkExt};
 use std::{
     borrow::Borrow,
+    collections::HashMap,
     convert::{Infallible, TryFrom},
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use warp::{filters::BoxedFilter, Filter, Reply};
 
 // Context holds application scope context
@@ -35,6 +41,8 @@ pub struct Context {
     role: RoleType,
     jsonrpc_config: JsonRpcConfig,
     api_config: ApiConfig,
+    simulation_rate_limiter: Arc<TokenBucketRateLimiter<ClientSource>>,
+    streams: Arc<Semaphore>,
 }
 
 impl Context {
@@ -46,6 +54,18 @@ impl Context {
         jsonrpc_config: JsonRpcConfig,
         api_config: ApiConfig,
     ) -> Self {
+        let simulation_rate_limiter = Arc::new(match api_config.simulation_rate_limit {
+            Some(config) => TokenBucketRateLimiter::new(
+                "simulation",
+                "api".to_string(),
+                100,
+                config.bucket_size,
+                config.fill_rate,
+                None,
+            ),
+            None => TokenBucketRateLimiter::open("simulation"),
+        });
+        let streams = Arc::new(Semaphore::new(api_config.max_streams));
         Self {
             chain_id,
             db,
@@ -53,6 +73,8 @@ impl Context {
             role,
             jsonrpc_config,
             api_config,
+            simulation_rate_limiter,
+            streams,
         }
     }
 
@@ -72,16 +94,64 @@ impl Context {
         warp::any().map(move || self.clone())
     }
 
-    pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
+    /// Identifies the client of a request, for mempool admission and simulation rate limiting.
+    pub fn client_source(
+        &self,
+    ) -> impl Filter<Extract = (ClientSource,), Error = Infallible> + Clone {
+        let api_keys = self.jsonrpc_config.api_keys.clone();
+        warp::header::optional::<String>(ClientSource::API_KEY_HEADER)
+            .and(warp::addr::remote())
+            .map(move |api_key, remote_addr| ClientSource::new(api_key, remote_addr, &api_keys))
+    }
+
+    pub async fn submit_transaction(
+        &self,
+        txn: SignedTransaction,
+        source: ClientSource,
+    ) -> Result<SubmissionStatus> {
         let (req_sender, callback) = oneshot::channel();
         self.mp_sender
             .clone()
-            .send(MempoolClientRequest::SubmitTransaction(txn, req_sender))
+            .send(MempoolClientRequest::SubmitTransaction(
+                txn, source, req_sender,
+            ))
             .await?;
 
         callback.await?
     }
 
+    /// Whether a simulation requested by the client is admitted. In-process clients are not
+    /// limited.
+    pub fn admit_simulation(&self, source: &ClientSource) -> bool {
+        *source == ClientSource::InProcess
+            || self
+                .simulation_rate_limiter
+                .bucket(source.clone())
+                .lock()
+                .acquire_all_tokens(1)
+                .is_ok()
+    }
+
+    /// Drops the buckets of the clients that haven't sent anything for a while.
+    pub fn garbage_collect_rate_limiters(&self) {
+        self.simulation_rate_limiter.garbage_collect_full_buckets();
+    }
+
+    /// Executes the transaction on top of the state at `version` without committing it. The
+    /// signature of the transaction is not checked.
+    pub async fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+        version: Version,
+    ) -> Result<TransactionOutput> {
+        let state_view = DbStateView::new(self.db.clone(), version);
+        let (_vm_status, output) = tokio::task::spawn_blocking(move || {
+            DiemVM::simulate_signed_transaction(&txn, &state_view)
+        })
+        .await?;
+        Ok(output)
+    }
+
     pub fn get_latest_ledger_info(&self) -> Result<LedgerInfo, Error> {
         Ok(LedgerInfo::new(
             &self.chain_id(),
@@ -120,6 +190,22 @@ impl Context {
         self.db.get_block_timestamp(version)
     }
 
+    pub fn get_block_by_height(
+        &self,
+        height: u64,
+        ledger_version: u64,
+    ) -> Result<Option<CommittedBlock>> {
+        self.db.get_block_by_height(height, ledger_version)
+    }
+
+    pub fn get_block_by_version(
+        &self,
+        version: u64,
+        ledger_version: u64,
+    ) -> Result<Option<CommittedBlock>> {
+        self.db.get_block_by_version(version, ledger_version)
+    }
+
     pub fn get_transactions(
         &self,
         start_version: u64,
@@ -241,16 +327,51 @@ impl Context {
         limit: u16,
         ledger_version: u64,
     ) -> Result<Vec<ContractEvent>> {
+        Ok(self
+            .get_versioned_events(event_key, start, limit, ledger_version)?
+            .into_iter()
+            .map(|(_, event)| event)
+            .collect::<Vec<_>>())
+    }
+
+    /// Same as `get_events`, with the version of the transaction that emitted each event.
+    pub fn get_versioned_events(
+        &self,
+        event_key: &EventKey,
+        start: u64,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<(Version, ContractEvent)>> {
         let events = self
             .db
             .get_events(event_key, start, Order::Ascending, limit as u64)?;
         Ok(events
             .into_iter()
             .filter(|(version, _event)| version <= &ledger_version)
-            .map(|(_, event)| event)
             .collect::<Vec<_>>())
     }
 
+    /// The sequence number of the first event with the key emitted after `version`.
+    pub fn get_next_event_sequence_number(
+        &self,
+        event_key: &EventKey,
+        version: u64,
+        ledger_version: u64,
+    ) -> Result<u64> {
+        let bounds = self
+            .db
+            .get_event_by_version_with_proof(event_key, version, ledger_version)?;
+        Ok(bounds
+            .lower_bound_incl
+            .map_or(0, |event| event.event.sequence_number() + 1))
+    }
+
+    /// Returns the permit to keep a stream of the ledger open, or `None` if there are already
+    /// `max_streams` open streams.
+    pub fn try_open_stream(&self) -> Option<OwnedSemaphorePermit> {
+        self.streams.clone().try_acquire_owned().ok()
+    }
+
     pub fn health_check_route(&self) -> BoxedFilter<(impl Reply,)> {
         diem_json_rpc::runtime::health_check_route(self.db.clone())
     }
@@ -265,3 +386,49 @@ impl Context {
         )
     }
 }
+
+/// Reads the state at a committed version of the db, for simulating transactions on top of it.
+/// The state of each account is read from the db once, the VM reading several resources of the
+/// same accounts.
+struct DbStateView {
+    db: Arc<dyn MoveDbReader>,
+    version: Version,
+    account_states: Mutex<HashMap<AccountAddress, Option<AccountState>>>,
+}
+
+impl DbStateView {
+    fn new(db: Arc<dyn MoveDbReader>, version: Version) -> Self {
+        Self {
+            db,
+            version,
+            account_states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get_account_state(&self, address: AccountAddress) -> Result<Option<AccountState>> {
+        if let Some(account_state) = self.account_states.lock().unwrap().get(&address) {
+            return Ok(account_state.clone());
+        }
+        let (blob, _) = self
+            .db
+            .get_account_state_with_proof_by_version(address, self.version)?;
+        let account_state = blob.as_ref().map(AccountState::try_from).transpose()?;
+        self.account_states
+            .lock()
+            .unwrap()
+            .insert(address, account_state.clone());
+        Ok(account_state)
+    }
+}
+
+impl StateView for DbStateView {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .get_account_state(access_path.address)?
+            .and_then(|account_state| account_state.get(&access_path.path).cloned()))
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
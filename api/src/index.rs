@@ -2,15 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    accounts,
+    accounts, blocks,
     context::Context,
     events,
     failpoint::fail_point,
     log,
     metrics::{metrics, status_metrics},
-    transactions,
+    streams, transactions,
 };
 use diem_api_types::{Error, Response};
+use diem_mempool::ClientSource;
 
 use std::convert::Infallible;
 use warp::{
@@ -43,8 +44,14 @@ pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Inf
         .or(transactions::submit_bcs_transactions(context.clone()))
         .or(transactions::submit_json_transactions(context.clone()))
         .or(transactions::create_signing_message(context.clone()))
+        .or(transactions::simulate_transaction(context.clone()))
+        .or(blocks::get_block_by_height(context.clone()))
+        .or(blocks::get_block_by_version(context.clone()))
         .or(events::get_events_by_event_key(context.clone()))
         .or(events::get_events_by_event_handle(context.clone()))
+        .or(streams::stream_transactions(context.clone()))
+        .or(streams::stream_account_transactions(context.clone()))
+        .or(streams::stream_events(context.clone()))
         .or(context.health_check_route().with(metrics("health_check")))
         // jsonrpc routes must before `recover` and after `index`
         // so that POST '/' can be handled by jsonrpc routes instead of `index` route
@@ -53,7 +60,10 @@ pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Inf
             warp::cors()
                 .allow_any_origin()
                 .allow_methods(vec!["POST", "GET"])
-                .allow_headers(vec![header::CONTENT_TYPE]),
+                .allow_headers(vec![
+                    header::CONTENT_TYPE.as_str(),
+                    ClientSource::API_KEY_HEADER,
+                ]),
         )
         .recover(handle_rejection)
         .with(log::logger())
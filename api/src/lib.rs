@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts;
+mod blocks;
 mod context;
 mod events;
 mod index;
@@ -10,6 +11,7 @@ mod metrics;
 mod page;
 pub(crate) mod param;
 pub mod runtime;
+mod streams;
 mod transactions;
 
 mod failpoint;
@@ -12,6 +12,7 @@ pub type AddressParam = Param<Address>;
 pub type TransactionIdParam = Param<TransactionId>;
 pub type TransactionVersionParam = Param<u64>;
 pub type LedgerVersionParam = Param<u64>;
+pub type BlockHeightParam = Param<u64>;
 pub type EventKeyParam = Param<EventKey>;
 pub type MoveStructTagParam = Param<MoveStructTag>;
 pub type MoveIdentifierParam = Param<Identifier>;
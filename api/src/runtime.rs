@@ -10,9 +10,11 @@ use futures::join;
 use storage_interface::MoveDbReader;
 use warp::{Filter, Reply};
 
-use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
 use tokio::runtime::{Builder, Runtime};
 
+const RATE_LIMITER_GC_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Creates HTTP server (warp-based) serves for both REST and JSON-RPC API.
 /// When api and json-rpc are configured with same port, both API will be served for the port.
 /// When api and json-rpc are configured with different port, both API will be served for
@@ -36,8 +38,9 @@ pub fn bootstrap(
     let api = WebServer::from(api_config.clone());
     let jsonrpc = WebServer::from(json_rpc_config.clone());
 
+    let context = Context::new(chain_id, db, mp_sender, role, json_rpc_config, api_config);
+    runtime.spawn(garbage_collect_rate_limiters(context.clone()));
     runtime.spawn(async move {
-        let context = Context::new(chain_id, db, mp_sender, role, json_rpc_config, api_config);
         let routes = index::routes(context);
         if api.address.port() == jsonrpc.address.port() {
             // when we rollout api, it's likely there is no api configuration for diem
@@ -56,6 +59,14 @@ pub fn bootstrap(
     Ok(runtime)
 }
 
+async fn garbage_collect_rate_limiters(context: Context) {
+    let mut interval = tokio::time::interval(RATE_LIMITER_GC_INTERVAL);
+    loop {
+        interval.tick().await;
+        context.garbage_collect_rate_limiters();
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 struct WebServer {
     pub address: SocketAddr,
@@ -0,0 +1,390 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Server-sent event streams of the committed transactions and events. A stream tails the ledger
+//! from a start version: it reads batches of transactions or events through the index of its
+//! filter on a blocking thread, and waits for new commits once it caught up. The number of open
+//! streams is limited by `max_streams` of the `ApiConfig`.
+//!
+//! The SSE `id` of a message is the version of its transaction. A client that reconnects with the
+//! `Last-Event-ID` header resumes right after that version. When a transaction emits several
+//! events of a stream, only the last one carries the id, so that a client disconnected in the
+//! middle gets all of them again.
+//!
+//! The next batch is only read once the previous one is written to the connection, so a slow
+//! client slows down its own stream instead of making the server buffer the ledger for it.
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    param::{AddressParam, EventKeyParam, TransactionVersionParam},
+};
+
+use diem_api_types::{Error, Transaction};
+use diem_types::{account_address::AccountAddress, event::EventKey, transaction::Version};
+
+use anyhow::Result;
+use futures::{stream, Stream};
+use serde::Deserialize;
+use std::{collections::VecDeque, convert::Infallible, time::Duration};
+use tokio::sync::OwnedSemaphorePermit;
+use warp::{filters::BoxedFilter, http::StatusCode, sse, Filter, Rejection, Reply};
+
+/// Number of transactions read from the db at once.
+const BATCH_SIZE: u64 = 100;
+/// How long a stream that caught up with the ledger waits before checking for new commits.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct StreamStart {
+    start: Option<TransactionVersionParam>,
+}
+
+// GET /stream/transactions?start={u64}
+pub fn stream_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("stream" / "transactions")
+        .and(warp::get())
+        .and(warp::query::<StreamStart>())
+        .and(warp::header::optional::<String>(LAST_EVENT_ID_HEADER))
+        .and(context.filter())
+        .and_then(handle_stream_transactions)
+        .with(metrics("stream_transactions"))
+        .boxed()
+}
+
+// GET /stream/accounts/{address}/transactions?start={u64}
+pub fn stream_account_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("stream" / "accounts" / AddressParam / "transactions")
+        .and(warp::get())
+        .and(warp::query::<StreamStart>())
+        .and(warp::header::optional::<String>(LAST_EVENT_ID_HEADER))
+        .and(context.filter())
+        .and_then(handle_stream_account_transactions)
+        .with(metrics("stream_account_transactions"))
+        .boxed()
+}
+
+// GET /stream/events/{event_key}?start={u64}
+pub fn stream_events(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("stream" / "events" / EventKeyParam)
+        .and(warp::get())
+        .and(warp::query::<StreamStart>())
+        .and(warp::header::optional::<String>(LAST_EVENT_ID_HEADER))
+        .and(context.filter())
+        .and_then(handle_stream_events)
+        .with(metrics("stream_events"))
+        .boxed()
+}
+
+async fn handle_stream_transactions(
+    start: StreamStart,
+    last_event_id: Option<String>,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_stream_transactions")?;
+    let start_version = start_version(start, last_event_id, &context)?;
+    Ok(sse_reply(context, StreamFilter::All, start_version)?)
+}
+
+async fn handle_stream_account_transactions(
+    address: AddressParam,
+    start: StreamStart,
+    last_event_id: Option<String>,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_stream_account_transactions")?;
+    let address = address.parse("account address")?.into();
+    let start_version = start_version(start, last_event_id, &context)?;
+    Ok(sse_reply(
+        context,
+        StreamFilter::Account(address),
+        start_version,
+    )?)
+}
+
+async fn handle_stream_events(
+    event_key: EventKeyParam,
+    start: StreamStart,
+    last_event_id: Option<String>,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_stream_events")?;
+    let key = event_key.parse("event key")?.into();
+    let start_version = start_version(start, last_event_id, &context)?;
+    Ok(sse_reply(
+        context,
+        StreamFilter::EventKey(key),
+        start_version,
+    )?)
+}
+
+/// A resumed stream starts right after the last delivered version, otherwise at the requested
+/// version, by default with the next commit.
+fn start_version(
+    start: StreamStart,
+    last_event_id: Option<String>,
+    context: &Context,
+) -> Result<Version, Error> {
+    if let Some(id) = last_event_id {
+        let version: Version = id
+            .parse()
+            .map_err(|_| Error::bad_request(format!("invalid Last-Event-ID header: {}", id)))?;
+        return Ok(version.saturating_add(1));
+    }
+    match start.start {
+        Some(version) => version.parse("start"),
+        None => Ok(context.get_latest_ledger_info()?.version() + 1),
+    }
+}
+
+fn sse_reply(
+    context: Context,
+    filter: StreamFilter,
+    start_version: Version,
+) -> Result<impl Reply, Error> {
+    let permit = context.try_open_stream().ok_or_else(|| {
+        Error::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "too many open streams".to_owned(),
+        )
+    })?;
+    Ok(sse::reply(sse::keep_alive().stream(ledger_stream(
+        context,
+        filter,
+        start_version,
+        permit,
+    ))))
+}
+
+/// Which transactions and events of the ledger a stream delivers.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum StreamFilter {
+    /// All the committed transactions.
+    All,
+    /// The user transactions sent by the account.
+    Account(AccountAddress),
+    /// The events with the key.
+    EventKey(EventKey),
+}
+
+/// The stream of SSE messages of the ledger from `start_version`. The stream ends after an error
+/// message if the ledger cannot be read. The stream is open as long as it holds `permit`.
+pub(crate) fn ledger_stream(
+    context: Context,
+    filter: StreamFilter,
+    start_version: Version,
+    permit: OwnedSemaphorePermit,
+) -> impl Stream<Item = Result<sse::Event, Infallible>> + Send + 'static {
+    let tail = LedgerTail {
+        reader: Some(LedgerReader {
+            context,
+            filter,
+            next_version: start_version,
+            next_seq_number: None,
+        }),
+        pending: VecDeque::new(),
+        _permit: permit,
+    };
+    stream::unfold(tail, |mut tail| async move {
+        tail.next_message().await.map(|message| (Ok(message), tail))
+    })
+}
+
+struct LedgerTail {
+    // `None` once the stream failed
+    reader: Option<LedgerReader>,
+    // messages of the last batch not sent yet
+    pending: VecDeque<sse::Event>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl LedgerTail {
+    async fn next_message(&mut self) -> Option<sse::Event> {
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                return Some(message);
+            }
+            let mut reader = self.reader.take()?;
+            let result = tokio::task::spawn_blocking(move || {
+                let batch = reader.read_batch();
+                batch.map(|messages| (reader, messages))
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+            match result {
+                Ok((reader, Some(messages))) => {
+                    self.reader = Some(reader);
+                    self.pending.extend(messages);
+                }
+                Ok((reader, None)) => {
+                    self.reader = Some(reader);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+                Err(err) => {
+                    let error = Error::internal(err);
+                    self.pending.push_back(
+                        sse::Event::default()
+                            .event("error")
+                            .json_data(&error)
+                            .unwrap_or_else(|_| sse::Event::default().event("error")),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Reads the ledger through the indexes of the filter: the transactions by version, the
+/// transactions of an account by sequence number, the events of a key by sequence number.
+struct LedgerReader {
+    context: Context,
+    filter: StreamFilter,
+    // the messages of the versions below are delivered
+    next_version: Version,
+    // sequence number of the next account transaction or event, looked up at the first read
+    next_seq_number: Option<u64>,
+}
+
+impl LedgerReader {
+    /// Reads the messages of the next batch. Returns `None` if there are no new transactions.
+    fn read_batch(&mut self) -> Result<Option<Vec<sse::Event>>> {
+        let ledger_version = self
+            .context
+            .get_latest_ledger_info_with_signatures()?
+            .ledger_info()
+            .version();
+        if self.next_version > ledger_version {
+            return Ok(None);
+        }
+        match self.filter {
+            StreamFilter::All => self.read_transactions(ledger_version),
+            StreamFilter::Account(address) => {
+                self.read_account_transactions(address, ledger_version)
+            }
+            StreamFilter::EventKey(key) => self.read_events(&key, ledger_version),
+        }
+        .map(Some)
+    }
+
+    fn read_transactions(&mut self, ledger_version: Version) -> Result<Vec<sse::Event>> {
+        let limit = std::cmp::min(BATCH_SIZE, ledger_version - self.next_version + 1);
+        let txns =
+            self.context
+                .get_transactions(self.next_version, limit as u16, ledger_version)?;
+        let converter = self.context.move_converter();
+        // the timestamp of a transaction is the one of the last block metadata transaction
+        let mut timestamp = self.context.get_block_timestamp(self.next_version)?;
+        let mut messages = vec![];
+        for data in txns {
+            let version = data.version;
+            let txn = converter.try_into_onchain_transaction(timestamp, data)?;
+            timestamp = txn.timestamp();
+            messages.push(transaction_message(version, &txn)?);
+            self.next_version = version + 1;
+        }
+        Ok(messages)
+    }
+
+    fn read_account_transactions(
+        &mut self,
+        address: AccountAddress,
+        ledger_version: Version,
+    ) -> Result<Vec<sse::Event>> {
+        let seq_number = match (self.next_seq_number, self.next_version.checked_sub(1)) {
+            (Some(seq_number), _) => seq_number,
+            (None, Some(version)) => match self.context.get_account_state(address, version)? {
+                Some(state) => state
+                    .get_account_resource()?
+                    .map_or(0, |account| account.sequence_number()),
+                None => 0,
+            },
+            (None, None) => 0,
+        };
+        let txns = self.context.get_account_transactions(
+            address,
+            seq_number,
+            BATCH_SIZE as u16,
+            ledger_version,
+        )?;
+        self.next_seq_number = Some(seq_number + txns.len() as u64);
+        self.next_version = match txns.last() {
+            Some(data) if txns.len() as u64 == BATCH_SIZE => data.version + 1,
+            _ => ledger_version + 1,
+        };
+        let converter = self.context.move_converter();
+        txns.into_iter()
+            .map(|data| {
+                let version = data.version;
+                let timestamp = self.context.get_block_timestamp(version)?;
+                let txn = converter.try_into_onchain_transaction(timestamp, data)?;
+                transaction_message(version, &txn)
+            })
+            .collect()
+    }
+
+    fn read_events(&mut self, key: &EventKey, ledger_version: Version) -> Result<Vec<sse::Event>> {
+        let seq_number = match (self.next_seq_number, self.next_version.checked_sub(1)) {
+            (Some(seq_number), _) => seq_number,
+            (None, Some(version)) => {
+                self.context
+                    .get_next_event_sequence_number(key, version, ledger_version)?
+            }
+            (None, None) => 0,
+        };
+        // one more event tells whether the last event of the batch is the last of its transaction
+        let mut events = self.context.get_versioned_events(
+            key,
+            seq_number,
+            BATCH_SIZE as u16 + 1,
+            ledger_version,
+        )?;
+        let next = if events.len() as u64 > BATCH_SIZE {
+            events.pop()
+        } else {
+            None
+        };
+        self.next_seq_number = Some(seq_number + events.len() as u64);
+        self.next_version = match (&next, events.last()) {
+            (Some(_), Some((version, _))) => *version,
+            _ => ledger_version + 1,
+        };
+
+        let next_versions = events
+            .iter()
+            .skip(1)
+            .chain(next.iter())
+            .map(|(version, _)| Some(*version))
+            .chain(std::iter::once(None))
+            .collect::<Vec<_>>();
+        let (versions, contract_events): (Vec<_>, Vec<_>) = events.into_iter().unzip();
+        let events = self
+            .context
+            .move_converter()
+            .try_into_events(&contract_events)?;
+        versions
+            .into_iter()
+            .zip(next_versions)
+            .zip(events)
+            .map(|((version, next_version), event)| {
+                let message = sse::Event::default().event("event").json_data(&event)?;
+                let last_of_txn = next_version.map_or(true, |next| next != version);
+                Ok(if last_of_txn {
+                    message.id(version.to_string())
+                } else {
+                    message
+                })
+            })
+            .collect()
+    }
+}
+
+fn transaction_message(version: Version, txn: &Transaction) -> Result<sse::Event> {
+    Ok(sse::Event::default()
+        .id(version.to_string())
+        .event("transaction")
+        .json_data(txn)?)
+}
@@ -0,0 +1,127 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{assert_json, new_test_context};
+
+use diem_crypto::HashValue;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_get_genesis_block() {
+    let context = new_test_context();
+    let resp = context.get("/blocks/by_height/0").await;
+    assert_json(
+        resp,
+        json!({
+            "height": "0",
+            "id": HashValue::zero().to_hex_literal(),
+            "round": "0",
+            "proposer": "0x0",
+            "timestamp": "0",
+            "first_version": "0",
+            "last_version": "0",
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_get_block_by_height_and_version() {
+    let mut context = new_test_context();
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let mut tc_account = context.tc_account();
+    let txn1 = context.create_parent_vasp_by_account(&mut tc_account, &account1);
+    let txn2 = context.create_parent_vasp_by_account(&mut tc_account, &account2);
+    context.commit_block(&[txn1, txn2]).await;
+
+    let metadata_txn = context.get("/transactions/1").await;
+    let expected = json!({
+        "height": "1",
+        "id": metadata_txn["id"],
+        "round": metadata_txn["round"],
+        "proposer": context.validator_owner.to_hex_literal(),
+        "timestamp": metadata_txn["timestamp"],
+        "first_version": "1",
+        "last_version": "3",
+    });
+    assert_json(context.get("/blocks/by_height/1").await, expected.clone());
+    for version in 1..=3 {
+        assert_json(
+            context
+                .get(&format!("/blocks/by_version/{}", version))
+                .await,
+            expected.clone(),
+        );
+    }
+    assert_eq!(context.get("/blocks/by_version/0").await["height"], "0");
+}
+
+#[tokio::test]
+async fn test_get_block_with_transactions() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+
+    let resp = context
+        .get("/blocks/by_height/1?with_transactions=true")
+        .await;
+    let txns = resp["transactions"].as_array().unwrap();
+    assert_eq!(txns.len(), 2);
+    assert_eq!(txns[0]["type"], "block_metadata_transaction");
+    assert_eq!(txns[0]["version"], "1");
+    assert_eq!(txns[1]["type"], "user_transaction");
+    assert_eq!(txns[1]["version"], "2");
+    assert_eq!(txns[1]["timestamp"], resp["timestamp"]);
+
+    let resp = context
+        .get("/blocks/by_height/1?with_transactions=false")
+        .await;
+    assert!(resp.get("transactions").is_none());
+}
+
+#[tokio::test]
+async fn test_get_block_not_found() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(404)
+        .get("/blocks/by_height/1")
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 404,
+            "message": "block not found by height(1)",
+            "diem_ledger_version": "0"
+        }),
+    );
+
+    let resp = context
+        .expect_status_code(404)
+        .get("/blocks/by_version/1")
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 404,
+            "message": "block not found by version(1)",
+            "diem_ledger_version": "0"
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_get_block_with_invalid_query() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(400)
+        .get("/blocks/by_height/0?with_transactions=yes")
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "invalid parameter with_transactions: yes"
+        }),
+    );
+}
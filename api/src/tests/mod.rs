@@ -2,15 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts_test;
+mod blocks_test;
 mod events_test;
 mod index_test;
 mod invalid_post_request_test;
+mod streams_test;
 mod string_resource_test;
 mod test_context;
 mod transactions_test;
 
 use serde_json::Value;
-pub use test_context::{new_test_context, TestContext};
+pub use test_context::{new_test_context, new_test_context_with_configs, TestContext};
 
 pub fn find_value(val: &Value, filter: for<'r> fn(&'r &Value) -> bool) -> Value {
     let resources = val
@@ -0,0 +1,266 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    index, streams,
+    tests::{assert_json, new_test_context, new_test_context_with_configs},
+};
+
+use diem_config::config::{ApiConfig, JsonRpcConfig};
+use diem_crypto::hash::CryptoHash;
+use diem_types::transaction::Transaction;
+use hyper::body::HttpBody;
+use serde_json::{json, Value};
+use std::time::Duration;
+use warp::{filters::BoxedFilter, Reply};
+
+const DIEM_ROOT_CREATE_ACCOUNT_EVENT_KEY: &str =
+    "0x00000000000000000000000000000000000000000a550c18";
+
+#[tokio::test]
+async fn test_stream_transactions_from_start_version() {
+    let mut context = new_test_context();
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let mut tc_account = context.tc_account();
+    let txn1 = context.create_parent_vasp_by_account(&mut tc_account, &account1);
+    let txn2 = context.create_parent_vasp_by_account(&mut tc_account, &account2);
+    context.commit_block(&[txn1.clone(), txn2.clone()]).await;
+
+    let messages = read_messages(
+        warp::test::request().path("/stream/transactions?start=1"),
+        streams::stream_transactions(context.context.clone()),
+        3,
+    )
+    .await;
+
+    assert_eq!(ids(&messages), vec!["1", "2", "3"]);
+    assert!(messages.iter().all(|m| m.event == "transaction"));
+    assert_eq!(messages[0].data["type"], "block_metadata_transaction");
+    for (message, txn) in messages[1..].iter().zip(vec![txn1, txn2]) {
+        assert_eq!(message.data["type"], "user_transaction");
+        assert_eq!(
+            message.data["hash"],
+            Transaction::UserTransaction(txn).hash().to_hex_literal()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_stream_account_transactions_waits_for_new_commits() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let next_version = context.get_latest_ledger_info().version() + 1;
+
+    let (messages, _) = futures::join!(
+        read_messages(
+            warp::test::request().path("/stream/accounts/0xb1e55ed/transactions"),
+            streams::stream_account_transactions(context.context.clone()),
+            1,
+        ),
+        async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            context.commit_block(&[txn.clone()]).await;
+        }
+    );
+
+    // the block metadata transaction is not sent by the account
+    assert_eq!(ids(&messages), vec![(next_version + 1).to_string()]);
+    assert_eq!(messages[0].data["sender"], "0xb1e55ed");
+    assert_eq!(
+        messages[0].data["hash"],
+        Transaction::UserTransaction(txn).hash().to_hex_literal()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_account_transactions_resumes_from_last_event_id() {
+    let mut context = new_test_context();
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let mut tc_account = context.tc_account();
+    let txn1 = context.create_parent_vasp_by_account(&mut tc_account, &account1);
+    let txn2 = context.create_parent_vasp_by_account(&mut tc_account, &account2);
+    context.commit_block(&[txn1]).await;
+    let first_version = context.get_latest_ledger_info().version();
+    context.commit_block(&[txn2.clone()]).await;
+
+    let messages = read_messages(
+        warp::test::request()
+            .path("/stream/accounts/0xb1e55ed/transactions?start=0")
+            .header("last-event-id", first_version.to_string()),
+        streams::stream_account_transactions(context.context.clone()),
+        1,
+    )
+    .await;
+
+    assert_eq!(
+        ids(&messages),
+        vec![context.get_latest_ledger_info().version().to_string()]
+    );
+    assert_eq!(
+        messages[0].data["hash"],
+        Transaction::UserTransaction(txn2).hash().to_hex_literal()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_events_only_ids_the_last_event_of_a_transaction() {
+    let context = new_test_context();
+
+    // genesis creates several accounts
+    let messages = read_messages(
+        warp::test::request().path(&format!(
+            "/stream/events/{}?start=0",
+            DIEM_ROOT_CREATE_ACCOUNT_EVENT_KEY
+        )),
+        streams::stream_events(context.context.clone()),
+        2,
+    )
+    .await;
+
+    assert!(messages.iter().all(|m| m.event == "event"));
+    assert_eq!(messages[0].id, None);
+    assert_eq!(messages[0].data["data"]["created"], "0xa550c18");
+}
+
+#[tokio::test]
+async fn test_stream_events_resumes_from_last_event_id() {
+    let mut context = new_test_context();
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let mut tc_account = context.tc_account();
+    let txn1 = context.create_parent_vasp_by_account(&mut tc_account, &account1);
+    let txn2 = context.create_parent_vasp_by_account(&mut tc_account, &account2);
+    context.commit_block(&[txn1]).await;
+    let first_version = context.get_latest_ledger_info().version();
+    context.commit_block(&[txn2]).await;
+
+    // the start version is ignored when resuming
+    let messages = read_messages(
+        warp::test::request()
+            .path(&format!(
+                "/stream/events/{}?start=0",
+                DIEM_ROOT_CREATE_ACCOUNT_EVENT_KEY
+            ))
+            .header("last-event-id", first_version.to_string()),
+        streams::stream_events(context.context.clone()),
+        1,
+    )
+    .await;
+
+    assert_eq!(
+        ids(&messages),
+        vec![context.get_latest_ledger_info().version().to_string()]
+    );
+    assert_eq!(
+        messages[0].data["data"]["created"],
+        account2.address().to_hex_literal()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_rejects_invalid_last_event_id() {
+    let context = new_test_context();
+    let req = warp::test::request()
+        .path("/stream/transactions")
+        .header("last-event-id", "invalid");
+
+    let resp = context.expect_status_code(400).execute(req).await;
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "invalid Last-Event-ID header: invalid"
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_stream_rejected_when_too_many_are_open() {
+    let context = new_test_context_with_configs(
+        JsonRpcConfig::default(),
+        ApiConfig {
+            max_streams: 1,
+            ..ApiConfig::default()
+        },
+    );
+    let route = index::routes(context.context.clone());
+    let open = || {
+        warp::test::request()
+            .path("/stream/transactions")
+            .filter(&route)
+    };
+
+    let stream = open().await.unwrap().into_response();
+    assert_eq!(stream.status(), 200);
+    let resp = open().await.unwrap().into_response();
+    assert_eq!(resp.status(), 429);
+
+    // closing the stream makes room for another one
+    drop(stream);
+    let resp = open().await.unwrap().into_response();
+    assert_eq!(resp.status(), 200);
+}
+
+#[derive(Debug)]
+struct Message {
+    id: Option<String>,
+    event: String,
+    data: Value,
+}
+
+fn ids(messages: &[Message]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|m| m.id.clone().expect("message should have an id"))
+        .collect()
+}
+
+/// Reads the first `num_messages` messages of the stream, skipping the keep-alive comments.
+async fn read_messages(
+    req: warp::test::RequestBuilder,
+    route: BoxedFilter<(impl Reply + 'static,)>,
+    num_messages: usize,
+) -> Vec<Message> {
+    let resp = req.filter(&route).await.unwrap().into_response();
+    assert_eq!(resp.status(), 200);
+    let mut body = resp.into_body();
+    let mut buffer = String::new();
+    let mut messages = vec![];
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while messages.len() < num_messages {
+            let chunk = body.data().await.expect("stream ended").unwrap();
+            buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+            while let Some(end) = buffer.find("\n\n") {
+                let block: String = buffer.drain(..end + 2).collect();
+                messages.extend(parse_message(&block));
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for messages");
+    messages.truncate(num_messages);
+    messages
+}
+
+fn parse_message(block: &str) -> Option<Message> {
+    let mut id = None;
+    let mut event = None;
+    let mut data = None;
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("id:") {
+            id = Some(value.trim_start().to_owned());
+        } else if let Some(value) = line.strip_prefix("event:") {
+            event = Some(value.trim_start().to_owned());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data = Some(serde_json::from_str(value.trim_start()).unwrap());
+        }
+    }
+    Some(Message {
+        id,
+        event: event?,
+        data: data?,
+    })
+}
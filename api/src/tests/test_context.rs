@@ -48,6 +48,13 @@ use vm_validator::vm_validator::VMValidator;
 use warp::http::header::CONTENT_TYPE;
 
 pub fn new_test_context() -> TestContext {
+    new_test_context_with_configs(JsonRpcConfig::default(), ApiConfig::default())
+}
+
+pub fn new_test_context_with_configs(
+    jsonrpc_config: JsonRpcConfig,
+    api_config: ApiConfig,
+) -> TestContext {
     let tmp_dir = TempPath::new();
     tmp_dir.create_as_dir().unwrap();
 
@@ -74,8 +81,8 @@ pub fn new_test_context() -> TestContext {
             db.clone(),
             mempool.ac_client.clone(),
             RoleType::Validator,
-            JsonRpcConfig::default(),
-            ApiConfig::default(),
+            jsonrpc_config,
+            api_config,
         ),
         rng,
         root_keys,
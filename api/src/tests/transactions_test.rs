@@ -1,14 +1,18 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::tests::{assert_json, find_value, new_test_context, pretty, TestContext};
+use crate::tests::{
+    assert_json, find_value, new_test_context, new_test_context_with_configs, pretty, TestContext,
+};
 
 use diem_api_types::{HashValue, HexEncodedBytes};
+use diem_config::config::{ApiConfig, ApiRateLimitConfig, JsonRpcConfig};
 use diem_crypto::{
     hash::CryptoHash,
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
     SigningKey, Uniform,
 };
+use diem_mempool::ClientSource;
 use diem_sdk::{client::SignedTransaction, transaction_builder::Currency, types::LocalAccount};
 use diem_types::{
     access_path::{AccessPath, Path},
@@ -1673,6 +1677,155 @@ async fn test_create_signing_message_rejects_no_content_length_request() {
     );
 }
 
+#[tokio::test]
+async fn test_simulate_transaction_with_empty_signature() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let request = simulation_request(&context, &account, 0);
+    let resp = context.post("/transactions/simulate", request).await;
+
+    assert_eq!(resp["success"], true, "{}", pretty(&resp));
+    assert_eq!(resp["vm_status"], "Executed successfully");
+    assert_eq!(resp["sender"], "0xb1e55ed");
+    assert!(!resp["events"].as_array().unwrap().is_empty());
+    let changes = resp["changes"].as_array().unwrap();
+    assert!(changes.iter().any(|change| {
+        change["type"] == "write_resource"
+            && change["address"] == account.address().to_hex_literal()
+            && change["data"]["type"] == "0x1::DiemAccount::DiemAccount"
+    }));
+
+    // nothing is committed
+    context
+        .expect_status_code(404)
+        .get(&format!(
+            "/accounts/{}/resources",
+            account.address().to_hex_literal()
+        ))
+        .await;
+}
+
+#[tokio::test]
+async fn test_simulate_discarded_transaction() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let request = simulation_request(&context, &account, 1);
+    let resp = context.post("/transactions/simulate", request).await;
+
+    assert_eq!(resp["success"], false);
+    assert_eq!(
+        resp["vm_status"],
+        "Transaction discarded: SEQUENCE_NUMBER_TOO_NEW"
+    );
+    assert_eq!(resp["gas_used"], "0");
+    assert_eq!(resp["events"], json!([]));
+    assert_eq!(resp["changes"], json!([]));
+}
+
+#[tokio::test]
+async fn test_simulate_transaction_requires_public_key() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let mut request = simulation_request(&context, &account, 0);
+    request.as_object_mut().unwrap().remove("signature");
+    let resp = context
+        .expect_status_code(400)
+        .post("/transactions/simulate", request)
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "invalid request body: failed to create SignedTransaction from UserTransactionRequest: missing signature"
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_simulate_transaction_is_rate_limited_per_client() {
+    let mut context = new_test_context_with_configs(
+        JsonRpcConfig {
+            api_keys: vec!["client-1".to_string(), "client-2".to_string()],
+            ..JsonRpcConfig::default()
+        },
+        ApiConfig {
+            simulation_rate_limit: Some(ApiRateLimitConfig {
+                bucket_size: 1,
+                fill_rate: 1,
+            }),
+            ..ApiConfig::default()
+        },
+    );
+    let account = context.gen_account();
+    let request = simulation_request(&context, &account, 0);
+    let simulate = |api_key: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/transactions/simulate")
+            .header(ClientSource::API_KEY_HEADER, api_key)
+            .json(&request)
+    };
+
+    context.execute(simulate("client-1")).await;
+    let resp = context
+        .expect_status_code(429)
+        .execute(simulate("client-1"))
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 429,
+            "message": "too many simulation requests"
+        }),
+    );
+    // the limit is per client
+    context.execute(simulate("client-2")).await;
+
+    // unknown API keys don't get a limit of their own
+    context.execute(simulate("unknown-1")).await;
+    context
+        .expect_status_code(429)
+        .execute(simulate("unknown-2"))
+        .await;
+}
+
+/// The treasury compliance account creating a parent VASP account for `account`, with an empty
+/// signature.
+fn simulation_request(
+    context: &TestContext,
+    account: &LocalAccount,
+    sequence_number: u64,
+) -> serde_json::Value {
+    let tc_account = context.tc_account();
+    json!({
+        "sender": tc_account.address().to_hex_literal(),
+        "sequence_number": sequence_number.to_string(),
+        "gas_unit_price": "0",
+        "max_gas_amount": "1000000",
+        "gas_currency_code": "XUS",
+        "expiration_timestamp_secs": "16373698888888",
+        "payload": {
+            "type": "script_function_payload",
+            "function": "0x1::AccountCreationScripts::create_parent_vasp_account",
+            "type_arguments": [
+                "0x1::XUS::XUS"
+            ],
+            "arguments": [
+                "0",
+                account.address().to_hex_literal(),
+                format!("0x{}", hex::encode(account.authentication_key().prefix())),
+                format!("0x{}", hex::encode("vasp".as_bytes())),
+                true
+            ]
+        },
+        "signature": {
+            "type": "ed25519_signature",
+            "public_key": HexEncodedBytes::from(tc_account.public_key().to_bytes().to_vec()),
+            "signature": "0x",
+        },
+    })
+}
+
 fn gen_string(len: u64) -> String {
     let mut rng = thread_rng();
     std::iter::repeat(())
@@ -11,7 +11,8 @@ use crate::{
 
 use diem_api_types::{
     mime_types::BCS_SIGNED_TRANSACTION, Error, LedgerInfo, Response, Transaction, TransactionData,
-    TransactionId, TransactionOnChainData, TransactionSigningMessage, UserTransactionRequest,
+    TransactionId, TransactionOnChainData, TransactionSignature, TransactionSigningMessage,
+    UserTransactionRequest,
 };
 use diem_types::{
     mempool_status::MempoolStatusCode,
@@ -19,6 +20,7 @@ use diem_types::{
 };
 
 use anyhow::Result;
+use diem_mempool::ClientSource;
 use warp::{
     filters::BoxedFilter,
     http::{header::CONTENT_TYPE, StatusCode},
@@ -65,6 +67,7 @@ pub fn submit_json_transactions(context: Context) -> BoxedFilter<(impl Reply,)>
             context.content_length_limit(),
         ))
         .and(warp::body::json::<UserTransactionRequest>())
+        .and(context.client_source())
         .and(context.filter())
         .and_then(handle_submit_json_transactions)
         .with(metrics("submit_json_transactions"))
@@ -88,6 +91,7 @@ pub fn submit_bcs_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
             BCS_SIGNED_TRANSACTION,
         ))
         .and(warp::body::bytes())
+        .and(context.client_source())
         .and(context.filter())
         .and_then(handle_submit_bcs_transactions)
         .with(metrics("submit_bcs_transactions"))
@@ -108,6 +112,21 @@ pub fn create_signing_message(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+// POST /transactions/simulate
+pub fn simulate_transaction(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("transactions" / "simulate")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<UserTransactionRequest>())
+        .and(context.client_source())
+        .and(context.filter())
+        .and_then(handle_simulate_transaction)
+        .with(metrics("simulate_transaction"))
+        .boxed()
+}
+
 async fn handle_get_transaction(
     id: TransactionIdParam,
     context: Context,
@@ -134,22 +153,24 @@ async fn handle_get_account_transactions(
 
 async fn handle_submit_json_transactions(
     body: UserTransactionRequest,
+    source: ClientSource,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_submit_json_transactions")?;
     Ok(Transactions::new(context)?
-        .create_from_request(body)
+        .create_from_request(body, source)
         .await?)
 }
 
 async fn handle_submit_bcs_transactions(
     body: bytes::Bytes,
+    source: ClientSource,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_submit_bcs_transactions")?;
     let txn = bcs::from_bytes(&body)
         .map_err(|err| Error::invalid_request_body(format!("deserialize error: {}", err)))?;
-    Ok(Transactions::new(context)?.create(txn).await?)
+    Ok(Transactions::new(context)?.create(txn, source).await?)
 }
 
 async fn handle_create_signing_message(
@@ -160,6 +181,22 @@ async fn handle_create_signing_message(
     Ok(Transactions::new(context)?.signing_message(body)?)
 }
 
+async fn handle_simulate_transaction(
+    body: UserTransactionRequest,
+    source: ClientSource,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_simulate_transaction")?;
+    if !context.admit_simulation(&source) {
+        return Err(Error::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "too many simulation requests".to_owned(),
+        )
+        .into());
+    }
+    Ok(Transactions::new(context)?.simulate(body).await?)
+}
+
 struct Transactions {
     ledger_info: LedgerInfo,
     context: Context,
@@ -177,6 +214,7 @@ impl Transactions {
     pub async fn create_from_request(
         self,
         req: UserTransactionRequest,
+        source: ClientSource,
     ) -> Result<impl Reply, Error> {
         let txn = self
             .context
@@ -188,11 +226,16 @@ impl Transactions {
                     e
                 ))
             })?;
-        self.create(txn).await
+        self.create(txn, source).await
     }
 
-    pub async fn create(self, txn: SignedTransaction) -> Result<impl Reply, Error> {
-        let (mempool_status, vm_status_opt) = self.context.submit_transaction(txn.clone()).await?;
+    pub async fn create(
+        self,
+        txn: SignedTransaction,
+        source: ClientSource,
+    ) -> Result<impl Reply, Error> {
+        let (mempool_status, vm_status_opt) =
+            self.context.submit_transaction(txn.clone(), source).await?;
         match mempool_status.code {
             MempoolStatusCode::Accepted => {
                 let converter = self.context.move_converter();
@@ -206,6 +249,10 @@ impl Transactions {
                     .map(|s| format!("{:?}", s))
                     .unwrap_or_else(|| "UNKNOWN".to_owned())
             ))),
+            MempoolStatusCode::RateLimited => Err(Error::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("transaction is rejected: {}", mempool_status),
+            )),
             _ => Err(Error::bad_request(format!(
                 "transaction is rejected: {}",
                 mempool_status,
@@ -294,6 +341,31 @@ impl Transactions {
         )
     }
 
+    pub async fn simulate(self, mut req: UserTransactionRequest) -> Result<impl Reply, Error> {
+        req.signature = req
+            .signature
+            .map(TransactionSignature::with_placeholder_signature);
+        let txn = self
+            .context
+            .move_converter()
+            .try_into_signed_transaction(req, self.context.chain_id())
+            .map_err(|e| {
+                Error::invalid_request_body(format!(
+                    "failed to create SignedTransaction from UserTransactionRequest: {}",
+                    e
+                ))
+            })?;
+        let output = self
+            .context
+            .simulate_transaction(txn.clone(), self.ledger_info.version())
+            .await?;
+        let simulated_txn = self
+            .context
+            .move_converter()
+            .try_into_simulated_transaction(txn, output)?;
+        Response::new(self.ledger_info, &simulated_txn)
+    }
+
     fn transaction_not_found(&self, id: TransactionId) -> Error {
         Error::not_found("transaction", id, self.ledger_info.version())
     }
@@ -0,0 +1,53 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, HashValue, Transaction, U64};
+
+use diem_types::{account_address::AccountAddress, block_metadata::BlockMetadata};
+
+use serde::{Deserialize, Serialize};
+
+/// A committed block. The genesis block has no block metadata transaction, it has a zero id,
+/// round and timestamp, and the `0x0` proposer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub height: U64,
+    pub id: HashValue,
+    pub round: U64,
+    pub proposer: Address,
+    pub timestamp: U64,
+    pub first_version: U64,
+    pub last_version: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<Transaction>>,
+}
+
+impl Block {
+    pub fn new(
+        height: u64,
+        first_version: u64,
+        last_version: u64,
+        metadata: Option<&BlockMetadata>,
+        transactions: Option<Vec<Transaction>>,
+    ) -> Self {
+        let (id, round, proposer, timestamp) = match metadata {
+            Some(metadata) => (
+                metadata.id(),
+                metadata.round(),
+                metadata.proposer(),
+                metadata.timestamp_usec(),
+            ),
+            None => (diem_crypto::HashValue::zero(), 0, AccountAddress::ZERO, 0),
+        };
+        Self {
+            height: height.into(),
+            id: id.into(),
+            round: round.into(),
+            proposer: proposer.into(),
+            timestamp: timestamp.into(),
+            first_version: first_version.into(),
+            last_version: last_version.into(),
+            transactions,
+        }
+    }
+}
@@ -4,8 +4,9 @@
 use crate::{
     Bytecode, DirectWriteSet, Event, HexEncodedBytes, MoveFunction, MoveModuleBytecode,
     MoveResource, MoveScriptBytecode, MoveType, MoveValue, ScriptFunctionId, ScriptFunctionPayload,
-    ScriptPayload, ScriptWriteSet, Transaction, TransactionInfo, TransactionOnChainData,
-    TransactionPayload, UserTransactionRequest, WriteSet, WriteSetChange, WriteSetPayload,
+    ScriptPayload, ScriptWriteSet, SimulatedTransaction, Transaction, TransactionInfo,
+    TransactionOnChainData, TransactionPayload, UserTransactionRequest, WriteSet, WriteSetChange,
+    WriteSetPayload,
 };
 use diem_crypto::HashValue;
 use diem_transaction_builder::error_explain;
@@ -13,7 +14,10 @@ use diem_types::{
     access_path::{AccessPath, Path},
     chain_id::ChainId,
     contract_event::ContractEvent,
-    transaction::{ModuleBundle, RawTransaction, Script, ScriptFunction, SignedTransaction},
+    transaction::{
+        ModuleBundle, RawTransaction, Script, ScriptFunction, SignedTransaction, TransactionOutput,
+        TransactionStatus,
+    },
     vm_status::{AbortLocation, KeptVMStatus},
     write_set::WriteOp,
 };
@@ -69,6 +73,37 @@ impl<'a, R: MoveResolver + ?Sized> MoveConverter<'a, R> {
         Ok((txn, payload).into())
     }
 
+    pub fn try_into_simulated_transaction(
+        &self,
+        txn: SignedTransaction,
+        output: TransactionOutput,
+    ) -> Result<SimulatedTransaction> {
+        let payload = self.try_into_transaction_payload(txn.payload().clone())?;
+        let (success, vm_status) = match output.status() {
+            TransactionStatus::Keep(status) => {
+                (status.is_success(), self.explain_vm_status(status))
+            }
+            TransactionStatus::Discard(code) => {
+                (false, format!("Transaction discarded: {:?}", code))
+            }
+            TransactionStatus::Retry => (false, "Transaction retried".to_owned()),
+        };
+        let gas_used = output.gas_used();
+        let (write_set, events) = output.into();
+        Ok(SimulatedTransaction {
+            hash: txn.committed_hash().into(),
+            request: (&txn, payload).into(),
+            gas_used: gas_used.into(),
+            success,
+            vm_status,
+            events: self.try_into_events(&events)?,
+            changes: write_set
+                .into_iter()
+                .map(|(access_path, op)| self.try_into_write_set_change(access_path, op))
+                .collect::<Result<_>>()?,
+        })
+    }
+
     pub fn try_into_onchain_transaction(
         &self,
         timestamp: u64,
@@ -3,6 +3,7 @@
 
 mod account;
 mod address;
+mod block;
 mod bytecode;
 mod convert;
 mod error;
@@ -16,6 +17,7 @@ mod transaction;
 
 pub use account::AccountData;
 pub use address::Address;
+pub use block::Block;
 pub use bytecode::Bytecode;
 pub use convert::MoveConverter;
 pub use error::Error;
@@ -30,8 +32,8 @@ pub use move_types::{
 pub use response::{Response, X_DIEM_CHAIN_ID, X_DIEM_LEDGER_TIMESTAMP, X_DIEM_LEDGER_VERSION};
 pub use transaction::{
     BlockMetadataTransaction, DirectWriteSet, Event, GenesisTransaction, PendingTransaction,
-    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, Transaction, TransactionData,
-    TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
-    TransactionSigningMessage, UserTransaction, UserTransactionRequest, WriteSet, WriteSetChange,
-    WriteSetPayload,
+    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, SimulatedTransaction, Transaction,
+    TransactionData, TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
+    TransactionSignature, TransactionSigningMessage, UserTransaction, UserTransactionRequest,
+    WriteSet, WriteSetChange, WriteSetPayload,
 };
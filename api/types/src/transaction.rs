@@ -253,6 +253,20 @@ pub struct PendingTransaction {
     pub request: UserTransactionRequest,
 }
 
+/// What a user transaction would do if it was executed on top of the latest ledger state. Nothing
+/// is committed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedTransaction {
+    pub hash: HashValue,
+    #[serde(flatten)]
+    pub request: UserTransactionRequest,
+    pub gas_used: U64,
+    pub success: bool,
+    pub vm_status: String,
+    pub events: Vec<Event>,
+    pub changes: Vec<WriteSetChange>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct UserTransaction {
     #[serde(flatten)]
@@ -427,6 +441,26 @@ pub enum TransactionSignature {
     MultiAgentSignature(MultiAgentSignature),
 }
 
+impl TransactionSignature {
+    /// Replaces an empty Ed25519 signature with a placeholder of the right length, so that a
+    /// transaction can be simulated before it is signed: the signature of a simulated transaction
+    /// is not checked, but the public key is.
+    pub fn with_placeholder_signature(self) -> Self {
+        match self {
+            TransactionSignature::Ed25519Signature(Ed25519Signature {
+                public_key,
+                signature,
+            }) if signature.inner().is_empty() => {
+                TransactionSignature::Ed25519Signature(Ed25519Signature {
+                    public_key,
+                    signature: vec![0; ed25519::ED25519_SIGNATURE_LENGTH].into(),
+                })
+            }
+            signature => signature,
+        }
+    }
+}
+
 impl TryFrom<TransactionSignature> for TransactionAuthenticator {
     type Error = anyhow::Error;
     fn try_from(ts: TransactionSignature) -> anyhow::Result<Self> {
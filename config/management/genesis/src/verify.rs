@@ -218,7 +218,8 @@ fn compute_genesis(
         false,
         None,
         RocksdbConfig::default(),
-        true, /* account_count_migration */
+        true,  /* account_count_migration */
+        false, /* enable_resource_type_index */
     )
     .map_err(|e| Error::UnexpectedError(e.to_string()))?;
     let db_rw = DbReaderWriter::new(diemdb);
@@ -127,6 +127,9 @@ impl Config {
             config::SecureBackend::InMemoryStorage => panic!("Unsupported namespace for InMemory"),
             config::SecureBackend::Vault(config) => config.namespace = Some(namespace),
             config::SecureBackend::OnDiskStorage(config) => config.namespace = Some(namespace),
+            config::SecureBackend::EncryptedOnDiskStorage(config) => {
+                config.namespace = Some(namespace)
+            }
         };
         StorageWrapper {
             storage_name: "shared",
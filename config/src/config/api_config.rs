@@ -18,16 +18,50 @@ pub struct ApiConfig {
     // optional for compatible with old configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content_length_limit: Option<u64>,
+    // transaction simulation executes the VM, so it is limited per client, `None` disables it
+    #[serde(default = "default_simulation_rate_limit")]
+    pub simulation_rate_limit: Option<ApiRateLimitConfig>,
+    // every open stream of the ledger keeps polling the db
+    #[serde(default = "default_max_streams")]
+    pub max_streams: usize,
+}
+
+/// Token bucket limit on the number of requests served to a single client.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ApiRateLimitConfig {
+    /// Maximum burst of requests
+    pub bucket_size: usize,
+    /// Number of requests/s the bucket is refilled with
+    pub fill_rate: usize,
+}
+
+impl Default for ApiRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            bucket_size: 20,
+            fill_rate: 5,
+        }
+    }
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_PORT: u16 = 8080;
 pub const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 4 * 1024 * 1024; // 4mb
+pub const DEFAULT_MAX_STREAMS: usize = 100;
 
 fn default_enabled() -> bool {
     true
 }
 
+fn default_simulation_rate_limit() -> Option<ApiRateLimitConfig> {
+    Some(ApiRateLimitConfig::default())
+}
+
+fn default_max_streams() -> usize {
+    DEFAULT_MAX_STREAMS
+}
+
 impl Default for ApiConfig {
     fn default() -> ApiConfig {
         ApiConfig {
@@ -38,6 +72,8 @@ impl Default for ApiConfig {
             tls_cert_path: None,
             tls_key_path: None,
             content_length_limit: None,
+            simulation_rate_limit: default_simulation_rate_limit(),
+            max_streams: default_max_streams(),
         }
     }
 }
@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::config::SafetyRulesConfig;
+use crate::config::{invariant, Error, SafetyRulesConfig};
 use diem_types::{account_address::AccountAddress, block_info::Round};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf};
@@ -30,6 +30,7 @@ pub struct ConsensusConfig {
     pub decoupled_execution: bool,
     #[serde(default)]
     pub back_pressure_limit: u64,
+    pub quorum_store: QuorumStoreConfig,
 }
 
 impl Default for ConsensusConfig {
@@ -44,6 +45,7 @@ impl Default for ConsensusConfig {
             proposer_type: ConsensusProposerType::LeaderReputation(LeaderReputationConfig {
                 active_weights: 99,
                 inactive_weights: 1,
+                heuristic: ReputationHeuristicType::ActiveInactive,
             }),
             safety_rules: SafetyRulesConfig::default(),
             sync_only: false,
@@ -51,6 +53,7 @@ impl Default for ConsensusConfig {
             channel_size: 30, // hard-coded
             decoupled_execution: false,
             back_pressure_limit: 10,
+            quorum_store: QuorumStoreConfig::default(),
         }
     }
 }
@@ -59,6 +62,13 @@ impl ConsensusConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
         self.safety_rules.set_data_dir(data_dir);
     }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        if let ConsensusProposerType::LeaderReputation(config) = &self.proposer_type {
+            config.verify()?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -80,4 +90,100 @@ pub enum ConsensusProposerType {
 pub struct LeaderReputationConfig {
     pub active_weights: u64,
     pub inactive_weights: u64,
+    #[serde(default)]
+    pub heuristic: ReputationHeuristicType,
+}
+
+impl LeaderReputationConfig {
+    /// Checks that the leader of a round can be elected from an active candidate, whose weight
+    /// can't be 0 whatever the heuristic.
+    pub fn verify(&self) -> Result<(), Error> {
+        invariant(
+            self.active_weights > 0,
+            "The active weight of leader reputation can't be 0".into(),
+        )?;
+        match &self.heuristic {
+            ReputationHeuristicType::ActiveInactive => Ok(()),
+            ReputationHeuristicType::FailedRounds(config) => config.verify(),
+            ReputationHeuristicType::Combined(config) => {
+                invariant(
+                    config.active_inactive_factor > 0 || config.failed_rounds_factor > 0,
+                    "The factors of the combined heuristic can't both be 0".into(),
+                )?;
+                config.failed_rounds.verify()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ReputationHeuristicType {
+    // Candidates that proposed or voted in the window get the active weight, others the
+    // inactive weight
+    ActiveInactive,
+    // Candidates that were leaders of too many failed rounds in the window get the failed weight
+    FailedRounds(FailedRoundsConfig),
+    // Weighted sum of the weights of the active/inactive and the failed rounds heuristics
+    Combined(CombinedHeuristicConfig),
+}
+
+impl Default for ReputationHeuristicType {
+    fn default() -> Self {
+        ReputationHeuristicType::ActiveInactive
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FailedRoundsConfig {
+    pub ok_weight: u64,
+    pub failed_weight: u64,
+    // Percentage of failed rounds out of the rounds a candidate led in the window, above which
+    // it gets the failed weight
+    pub failure_threshold_percent: u64,
+}
+
+impl FailedRoundsConfig {
+    pub fn verify(&self) -> Result<(), Error> {
+        invariant(
+            self.ok_weight > 0,
+            "The ok weight of the failed rounds heuristic can't be 0".into(),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CombinedHeuristicConfig {
+    pub active_inactive_factor: u64,
+    pub failed_rounds_factor: u64,
+    pub failed_rounds: FailedRoundsConfig,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuorumStoreConfig {
+    // Disseminate the transactions in batches ahead of the proposals, and propose proofs of
+    // store of the batches instead of the transactions. Batches and proposals of other
+    // validators are handled either way.
+    pub enabled: bool,
+    // How often to pull a batch of transactions from mempool (in milliseconds)
+    pub batch_interval_ms: u64,
+    // Max number of transactions in a batch
+    pub max_batch_size: u64,
+    // Max number of uncommitted batches stored per author, batches beyond it are not signed
+    pub max_batches_per_author: usize,
+    // Timeout for requesting a missing batch from a validator that signed it (in milliseconds)
+    pub batch_request_timeout_ms: u64,
+}
+
+impl Default for QuorumStoreConfig {
+    fn default() -> QuorumStoreConfig {
+        QuorumStoreConfig {
+            enabled: false,
+            batch_interval_ms: 100,
+            max_batch_size: 500,
+            max_batches_per_author: 100,
+            batch_request_timeout_ms: 1000,
+        }
+    }
 }
@@ -15,6 +15,8 @@ pub enum Error {
     Yaml(String, #[source] serde_yaml::Error),
     #[error("Config is missing expected value: {0}")]
     Missing(&'static str),
+    #[error("Error opening secure storage {0}: {1}")]
+    SecureStorage(String, #[source] diem_secure_storage::Error),
 }
 
 pub fn invariant(cond: bool, msg: String) -> Result<(), Error> {
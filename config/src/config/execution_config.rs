@@ -23,6 +23,10 @@ pub struct ExecutionConfig {
     pub service: ExecutionCorrectnessService,
     pub backend: SecureBackend,
     pub network_timeout_ms: u64,
+    /// Re-executes the state sync chunks with the parallel executor, even if parallel execution
+    /// is not enabled on chain. The outputs are still verified against the transaction infos of
+    /// the chunks.
+    pub parallel_chunk_execution: bool,
 }
 
 impl std::fmt::Debug for ExecutionConfig {
@@ -40,8 +44,8 @@ impl std::fmt::Debug for ExecutionConfig {
         )?;
         write!(
             f,
-            ", sign_vote_proposal: {:?}, service: {:?}, backend: {:?} }}",
-            self.sign_vote_proposal, self.service, self.backend
+            ", sign_vote_proposal: {:?}, service: {:?}, backend: {:?}, parallel_chunk_execution: {:?} }}",
+            self.sign_vote_proposal, self.service, self.backend, self.parallel_chunk_execution
         )?;
         self.service.fmt(f)
     }
@@ -57,6 +61,7 @@ impl Default for ExecutionConfig {
             sign_vote_proposal: true,
             // Default value of 30 seconds for the network timeout.
             network_timeout_ms: 30_000,
+            parallel_chunk_execution: false,
         }
     }
 }
@@ -91,9 +96,7 @@ impl ExecutionConfig {
     }
 
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
-        if let SecureBackend::OnDiskStorage(backend) = &mut self.backend {
-            backend.set_data_dir(data_dir);
-        }
+        self.backend.set_data_dir(data_dir);
     }
 }
 
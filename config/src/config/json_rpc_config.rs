@@ -14,6 +14,9 @@ pub struct JsonRpcConfig {
     pub content_length_limit: usize,
     pub tls_cert_path: Option<String>,
     pub tls_key_path: Option<String>,
+    // API keys of the clients rate limited per key rather than per IP address. Other keys sent by
+    // clients are ignored.
+    pub api_keys: Vec<String>,
 }
 
 pub const DEFAULT_JSON_RPC_ADDRESS: &str = "127.0.0.1";
@@ -33,6 +36,7 @@ impl Default for JsonRpcConfig {
             content_length_limit: DEFAULT_CONTENT_LENGTH_LIMIT,
             tls_cert_path: None,
             tls_key_path: None,
+            api_keys: vec![],
         }
     }
 }
@@ -53,8 +53,6 @@ impl KeyManagerConfig {
     }
 
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
-        if let SecureBackend::OnDiskStorage(backend) = &mut self.secure_backend {
-            backend.set_data_dir(data_dir);
-        }
+        self.secure_backend.set_data_dir(data_dir);
     }
 }
@@ -19,6 +19,17 @@ pub struct MempoolConfig {
     pub shared_mempool_tick_interval_ms: u64,
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
+    // max number of txns in a block touching the same account, None disables the cap
+    pub max_txns_per_hot_key_in_block: Option<usize>,
+    // txns waiting in mempool for longer than this are never deferred because of the cap above
+    pub max_conflict_deferral_ms: u64,
+    // persist accepted txns in the storage dir and reload them on startup
+    pub enable_persistence: bool,
+    pub persistence_flush_interval_ms: u64,
+    // admission rate limits per submission source, None disables the limit
+    pub client_rate_limit: Option<MempoolRateLimitConfig>,
+    pub peer_rate_limit: Option<MempoolRateLimitConfig>,
+    pub account_rate_limit: Option<MempoolRateLimitConfig>,
 }
 
 impl Default for MempoolConfig {
@@ -36,6 +47,32 @@ impl Default for MempoolConfig {
             default_failovers: 3,
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
+            max_txns_per_hot_key_in_block: None,
+            max_conflict_deferral_ms: 5_000,
+            enable_persistence: false,
+            persistence_flush_interval_ms: 100,
+            client_rate_limit: None,
+            peer_rate_limit: None,
+            account_rate_limit: None,
+        }
+    }
+}
+
+/// Token bucket limit on the number of txns admitted into mempool from a single source.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct MempoolRateLimitConfig {
+    /// Maximum burst of txns
+    pub bucket_size: usize,
+    /// Number of txns/s the bucket is refilled with
+    pub fill_rate: usize,
+}
+
+impl Default for MempoolRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            bucket_size: 100,
+            fill_rate: 10,
         }
     }
 }
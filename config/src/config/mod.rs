@@ -234,6 +234,8 @@ impl NodeConfig {
 
         let mut config = config.validate_network_configs()?;
         config.set_data_dir(config.data_dir().to_path_buf());
+        config.consensus.verify()?;
+        config.verify_secure_backends()?;
         Ok(config)
     }
 
@@ -282,6 +284,29 @@ impl NodeConfig {
         Ok(self)
     }
 
+    /// Checks that the secure storages used by the node can be opened, so that a bad passphrase
+    /// fails the load rather than the creation of the storage
+    fn verify_secure_backends(&self) -> Result<(), Error> {
+        self.consensus.safety_rules.backend.verify()?;
+        self.execution.backend.verify()?;
+        if let WaypointConfig::FromStorage(backend) = &self.base.waypoint {
+            backend.verify()?;
+        }
+        for network in self
+            .validator_network
+            .iter()
+            .chain(&self.full_node_networks)
+        {
+            if let Identity::FromStorage(identity) = &network.identity {
+                identity.backend.verify()?;
+            }
+            if let Some(backend) = &network.network_address_key_backend {
+                backend.verify()?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn save<P: AsRef<Path>>(&mut self, output_path: P) -> Result<(), Error> {
         let output_dir = RootPath::new(&output_path);
         self.execution.save(&output_dir)?;
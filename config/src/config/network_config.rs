@@ -20,7 +20,7 @@ use rand::{
 use serde::{Deserialize, Serialize};
 use short_hex_str::AsShortHexStr;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
     path::PathBuf,
     string::ToString,
@@ -45,6 +45,23 @@ pub const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024; /* 16 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+/// Names of the DiemNet application protocols, these must match `ProtocolId::as_str` in the
+/// network crate.
+pub const PROTOCOL_NAMES: &[&str] = &[
+    "ConsensusRpcBcs",
+    "ConsensusDirectSendBcs",
+    "MempoolDirectSend",
+    "StateSyncDirectSend",
+    "DiscoveryDirectSend",
+    "HealthCheckerRpc",
+    "ConsensusDirectSendJson",
+    "ConsensusRpcJson",
+    "StorageServiceRpc",
+    "MempoolRpc",
+    "ConsensusRpcCompressed",
+    "ConsensusDirectSendCompressed",
+    "StateSyncDirectSendCompressed",
+];
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
@@ -98,6 +115,9 @@ pub struct NetworkConfig {
     pub inbound_rate_limit_config: Option<RateLimitConfig>,
     // Outbound rate limiting configuration, if not specified, no rate limiting
     pub outbound_rate_limit_config: Option<RateLimitConfig>,
+    // Per-peer outbound rate limits for individual application protocols, keyed by the
+    // protocol name (e.g. `StateSyncDirectSend`). Protocols without an entry aren't limited.
+    pub outbound_protocol_rate_limits: BTreeMap<String, ProtocolRateLimitConfig>,
 }
 
 impl Default for NetworkConfig {
@@ -132,6 +152,7 @@ impl NetworkConfig {
             max_inbound_connections: MAX_INBOUND_CONNECTIONS,
             inbound_rate_limit_config: None,
             outbound_rate_limit_config: None,
+            outbound_protocol_rate_limits: BTreeMap::new(),
         };
         config.prepare_identity();
         config
@@ -210,7 +231,7 @@ impl NetworkConfig {
         }
 
         self.prepare_identity();
-        Ok(())
+        self.verify_outbound_protocol_rate_limits()
     }
 
     pub fn peer_id(&self) -> PeerId {
@@ -276,6 +297,36 @@ impl NetworkConfig {
         )
     }
 
+    // Verifies that the per-protocol outbound rate limits name known protocols, and that every
+    // message allowed by `max_frame_size` can eventually pass them
+    pub fn verify_outbound_protocol_rate_limits(&self) -> Result<(), Error> {
+        for (protocol, limit) in self.outbound_protocol_rate_limits.iter() {
+            crate::config::invariant(
+                PROTOCOL_NAMES.contains(&protocol.as_str()),
+                format!("Outbound rate limit for unknown protocol {}", protocol),
+            )?;
+            crate::config::invariant(
+                limit.byte_rate > 0,
+                format!("Outbound rate limit for {} must be positive", protocol),
+            )?;
+            crate::config::invariant(
+                limit.byte_bucket_size >= limit.byte_rate,
+                format!(
+                    "Outbound bucket size for {} must be at least its rate",
+                    protocol
+                ),
+            )?;
+            crate::config::invariant(
+                limit.byte_bucket_size >= self.max_frame_size,
+                format!(
+                    "Outbound bucket size for {} ({}) is smaller than max_frame_size ({})",
+                    protocol, limit.byte_bucket_size, self.max_frame_size,
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
     // Verifies both the `seed_addrs` and `seeds` before they're merged
     pub fn verify_seeds(&self) -> Result<(), Error> {
         for (peer_id, addrs) in self.seed_addrs.iter() {
@@ -369,6 +420,17 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// An outbound rate limit for a single application protocol, applied to each peer separately.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProtocolRateLimitConfig {
+    /// Maximum number of bytes/s sent to a peer over the protocol
+    pub byte_rate: usize,
+    /// Maximum burst of bytes sent to a peer over the protocol. Must be at least
+    /// `max_frame_size` so that every message can eventually be sent.
+    pub byte_bucket_size: usize,
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
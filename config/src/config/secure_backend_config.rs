@@ -3,10 +3,12 @@
 
 use crate::config::Error;
 use diem_secure_storage::{
-    GitHubStorage, InMemoryStorage, Namespaced, OnDiskStorage, Storage, VaultStorage,
+    EncryptedOnDiskStorage, GitHubStorage, InMemoryStorage, KVStorage, Namespaced, OnDiskStorage,
+    Storage, VaultStorage,
 };
 use serde::{Deserialize, Serialize};
 use std::{
+    convert::TryFrom,
     fs::File,
     io::Read,
     path::{Path, PathBuf},
@@ -19,6 +21,7 @@ pub enum SecureBackend {
     InMemoryStorage,
     Vault(VaultConfig),
     OnDiskStorage(OnDiskStorageConfig),
+    EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig),
 }
 
 impl SecureBackend {
@@ -26,9 +29,11 @@ impl SecureBackend {
         match self {
             SecureBackend::GitHub(GitHubConfig { namespace, .. })
             | SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
-                namespace.as_deref()
-            }
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+                namespace,
+                ..
+            }) => namespace.as_deref(),
             SecureBackend::InMemoryStorage => None,
         }
     }
@@ -37,12 +42,47 @@ impl SecureBackend {
         match self {
             SecureBackend::GitHub(GitHubConfig { namespace, .. })
             | SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+                namespace,
+                ..
+            }) => {
                 *namespace = None;
             }
             SecureBackend::InMemoryStorage => {}
         }
     }
+
+    /// Sets the directory that relative on-disk storage paths are resolved against.
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        match self {
+            SecureBackend::OnDiskStorage(config) => config.set_data_dir(data_dir),
+            SecureBackend::EncryptedOnDiskStorage(config) => config.set_data_dir(data_dir),
+            SecureBackend::GitHub(_) | SecureBackend::InMemoryStorage | SecureBackend::Vault(_) => {
+            }
+        }
+    }
+
+    /// Verifies that the passphrase of an encrypted store can be read and opens the store, if it
+    /// already exists.
+    pub fn verify(&self) -> Result<(), Error> {
+        if let SecureBackend::EncryptedOnDiskStorage(config) = self {
+            let passphrase = config.passphrase.read_token()?;
+            crate::config::invariant(
+                !passphrase.is_empty(),
+                "The passphrase of an encrypted on disk storage can't be empty".into(),
+            )?;
+            let path = config.path();
+            if path.exists() {
+                let storage = EncryptedOnDiskStorage::new(path.clone(), passphrase.into_bytes())
+                    .map_err(|e| Error::SecureStorage(path.display().to_string(), e))?;
+                storage
+                    .available()
+                    .map_err(|e| Error::SecureStorage(path.display().to_string(), e))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -107,6 +147,21 @@ pub struct OnDiskStorageConfig {
     data_dir: PathBuf,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EncryptedOnDiskStorageConfig {
+    // Required path for encrypted on disk storage
+    pub path: PathBuf,
+    /// The passphrase the encryption key is derived from. Reading it from disk allows a key file
+    /// to be used instead, in which case the entire contents of the file are the passphrase.
+    pub passphrase: Token,
+    /// A namespace is an optional portion of the path to a key stored within
+    /// EncryptedOnDiskStorage. For example, a key, S, without a namespace would be available in
+    /// S, with a namespace, N, it would be in N/S.
+    pub namespace: Option<String>,
+    #[serde(skip)]
+    data_dir: PathBuf,
+}
+
 /// Tokens can either be directly within this config or stored somewhere on disk.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -159,6 +214,29 @@ impl OnDiskStorageConfig {
     }
 }
 
+impl EncryptedOnDiskStorageConfig {
+    pub fn new(path: PathBuf, passphrase: Token) -> Self {
+        Self {
+            path,
+            passphrase,
+            namespace: None,
+            data_dir: PathBuf::from("/opt/diem/data"),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        if self.path.is_relative() {
+            self.data_dir.join(&self.path)
+        } else {
+            self.path.clone()
+        }
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+}
+
 fn read_file(path: &Path) -> Result<String, Error> {
     let mut file =
         File::open(path).map_err(|e| Error::IO(path.to_str().unwrap().to_string(), e))?;
@@ -170,7 +248,15 @@ fn read_file(path: &Path) -> Result<String, Error> {
 
 impl From<&SecureBackend> for Storage {
     fn from(backend: &SecureBackend) -> Self {
-        match backend {
+        Storage::try_from(backend).expect("Unable to create secure storage")
+    }
+}
+
+impl TryFrom<&SecureBackend> for Storage {
+    type Error = Error;
+
+    fn try_from(backend: &SecureBackend) -> Result<Self, Error> {
+        let storage = match backend {
             SecureBackend::GitHub(config) => {
                 let storage = Storage::from(GitHubStorage::new(
                     config.repository_owner.clone(),
@@ -180,7 +266,7 @@ impl From<&SecureBackend> for Storage {
                         .as_ref()
                         .cloned()
                         .unwrap_or_else(|| "master".to_string()),
-                    config.token.read_token().expect("Unable to read token"),
+                    config.token.read_token()?,
                 ));
                 if let Some(namespace) = &config.namespace {
                     Storage::from(Namespaced::new(namespace, Box::new(storage)))
@@ -197,10 +283,23 @@ impl From<&SecureBackend> for Storage {
                     storage
                 }
             }
+            SecureBackend::EncryptedOnDiskStorage(config) => {
+                let path = config.path();
+                let passphrase = config.passphrase.read_token()?.into_bytes();
+                let storage = Storage::from(
+                    EncryptedOnDiskStorage::new(path.clone(), passphrase)
+                        .map_err(|e| Error::SecureStorage(path.display().to_string(), e))?,
+                );
+                if let Some(namespace) = &config.namespace {
+                    Storage::from(Namespaced::new(namespace, Box::new(storage)))
+                } else {
+                    storage
+                }
+            }
             SecureBackend::Vault(config) => {
                 let storage = Storage::from(VaultStorage::new(
                     config.server.clone(),
-                    config.token.read_token().expect("Unable to read token"),
+                    config.token.read_token()?,
                     config
                         .ca_certificate
                         .as_ref()
@@ -216,7 +315,8 @@ impl From<&SecureBackend> for Storage {
                     storage
                 }
             }
-        }
+        };
+        Ok(storage)
     }
 }
 #[cfg(test)]
@@ -315,6 +415,27 @@ vault:
         serde_yaml::to_string(&from_disk).unwrap();
     }
 
+    #[test]
+    fn test_encrypted_on_disk_parsing() {
+        let text = r#"
+type: "encrypted_on_disk_storage"
+path: "secure_storage.enc"
+passphrase:
+    from_disk: "/opt/diem/etc/storage.key"
+namespace: "validator"
+        "#;
+
+        let backend: SecureBackend = serde_yaml::from_str(text).unwrap();
+        let mut expected = EncryptedOnDiskStorageConfig::new(
+            PathBuf::from("secure_storage.enc"),
+            Token::FromDisk(PathBuf::from("/opt/diem/etc/storage.key")),
+        );
+        expected.namespace = Some("validator".into());
+        assert_eq!(backend, SecureBackend::EncryptedOnDiskStorage(expected));
+        assert_eq!(backend.namespace(), Some("validator"));
+        serde_yaml::to_string(&backend).unwrap();
+    }
+
     #[test]
     fn test_token_reading() {
         let temppath = diem_temppath::TempPath::new();
@@ -328,4 +449,39 @@ vault:
         let config = Token::FromConfig("config_token".to_string());
         assert_eq!("config_token", config.read_token().unwrap());
     }
+
+    #[test]
+    fn test_encrypted_on_disk_verification() {
+        let temppath = diem_temppath::TempPath::new();
+        let passphrase = Token::FromConfig("correct horse battery staple".to_string());
+        let backend = SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig::new(
+            temppath.path().to_path_buf(),
+            passphrase,
+        ));
+
+        // A missing store is created later, with the passphrase
+        backend.verify().unwrap();
+        Storage::try_from(&backend).unwrap();
+        backend.verify().unwrap();
+
+        // An existing store must open with the passphrase
+        let wrong_passphrase =
+            SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig::new(
+                temppath.path().to_path_buf(),
+                Token::FromConfig("wrong passphrase".to_string()),
+            ));
+        assert!(matches!(
+            wrong_passphrase.verify(),
+            Err(Error::SecureStorage(_, _))
+        ));
+
+        // And the passphrase must be readable
+        let missing_passphrase =
+            SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig::new(
+                temppath.path().to_path_buf(),
+                Token::FromDisk(diem_temppath::TempPath::new().path().to_path_buf()),
+            ));
+        assert!(matches!(missing_passphrase.verify(), Err(Error::IO(_, _))));
+        assert!(Storage::try_from(&missing_passphrase).is_err());
+    }
 }
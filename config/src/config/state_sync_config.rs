@@ -159,14 +159,22 @@ impl Default for DataStreamingServiceConfig {
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct DiemDataClientConfig {
+    pub max_num_subrequests: u64, // Max number of peers a single request for a range of data is split across
+    pub max_subrequest_retries: u64, // Max number of times a failed subrequest is sent to another peer
+    pub min_subrequest_size: u64, // Min number of data items (e.g., transactions) requested in a subrequest
     pub response_timeout_ms: u64, // Timeout (in milliseconds) when waiting for a response
+    pub slow_response_threshold_ms: u64, // Responses slower than this (in milliseconds) demote the peer
     pub summary_poll_interval_ms: u64, // Interval (in milliseconds) between data summary polls
 }
 
 impl Default for DiemDataClientConfig {
     fn default() -> Self {
         Self {
+            max_num_subrequests: 4,
+            max_subrequest_retries: 2,
+            min_subrequest_size: 250,
             response_timeout_ms: 3_000,
+            slow_response_threshold_ms: 1_000,
             summary_poll_interval_ms: 300,
         }
     }
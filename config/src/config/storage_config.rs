@@ -32,6 +32,26 @@ impl Default for RocksdbConfig {
     }
 }
 
+/// Configuration of the online checkpoint endpoint served by the backup service.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct CheckpointConfig {
+    /// Bearer token required on checkpoint requests. None disables the endpoint.
+    pub auth_token: Option<String>,
+    /// Directory that checkpoints are created in. Relative paths are resolved against the data
+    /// dir.
+    pub dir: PathBuf,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            auth_token: None,
+            dir: PathBuf::from("checkpoints"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct StorageConfig {
@@ -55,6 +75,13 @@ pub struct StorageConfig {
     /// wiped and re-synced.
     #[serde(default)]
     pub account_count_migration: bool,
+    /// Online DB checkpoint endpoint configurations
+    pub checkpoint: CheckpointConfig,
+    /// If enabled, a secondary index from resource types to the accounts holding them is
+    /// maintained on commit and pruned along with the state.
+    /// The index only covers transactions committed while it is enabled, so it's supposed to be
+    /// enabled on a node syncing from genesis.
+    pub enable_resource_type_index: bool,
 }
 
 impl Default for StorageConfig {
@@ -76,6 +103,8 @@ impl Default for StorageConfig {
             timeout_ms: 30_000,
             rocksdb_config: RocksdbConfig::default(),
             account_count_migration: false,
+            checkpoint: CheckpointConfig::default(),
+            enable_resource_type_index: false,
         }
     }
 }
@@ -89,6 +118,14 @@ impl StorageConfig {
         }
     }
 
+    pub fn checkpoint_dir(&self) -> PathBuf {
+        if self.checkpoint.dir.is_relative() {
+            self.data_dir.join(&self.checkpoint.dir)
+        } else {
+            self.checkpoint.dir.clone()
+        }
+    }
+
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
         self.data_dir = data_dir;
     }
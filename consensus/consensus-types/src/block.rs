@@ -4,6 +4,7 @@
 use crate::{
     block_data::{BlockData, BlockType},
     common::{Author, Payload, Round},
+    experimental::quorum_store::ProofOfStore,
     quorum_cert::QuorumCert,
 };
 use anyhow::{bail, ensure, format_err};
@@ -21,7 +22,10 @@ use diem_types::{
 };
 use mirai_annotations::debug_checked_verify_eq;
 use serde::{Deserialize, Deserializer, Serialize};
-use std::fmt::{self, Display, Formatter};
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+};
 
 #[path = "block_test_utils.rs"]
 #[cfg(any(test, feature = "fuzzing"))]
@@ -94,6 +98,10 @@ impl Block {
         self.block_data.payload()
     }
 
+    pub fn proofs(&self) -> Option<&Vec<ProofOfStore>> {
+        self.block_data.proofs()
+    }
+
     pub fn quorum_cert(&self) -> &QuorumCert {
         self.block_data.quorum_cert()
     }
@@ -199,6 +207,24 @@ impl Block {
         Self::new_proposal_from_block_data(block_data, validator_signer)
     }
 
+    pub fn new_quorum_store_proposal(
+        proofs: Vec<ProofOfStore>,
+        round: Round,
+        timestamp_usecs: u64,
+        quorum_cert: QuorumCert,
+        validator_signer: &ValidatorSigner,
+    ) -> Self {
+        let block_data = BlockData::new_quorum_store_proposal(
+            proofs,
+            validator_signer.author(),
+            round,
+            timestamp_usecs,
+            quorum_cert,
+        );
+
+        Self::new_proposal_from_block_data(block_data, validator_signer)
+    }
+
     pub fn new_proposal_from_block_data(
         block_data: BlockData,
         validator_signer: &ValidatorSigner,
@@ -232,6 +258,17 @@ impl Block {
                 validator.verify(*author, &self.block_data, signature)?;
                 self.quorum_cert().verify(validator)
             }
+            BlockType::QuorumStoreProposal { author, proofs } => {
+                let signature = self
+                    .signature
+                    .as_ref()
+                    .ok_or_else(|| format_err!("Missing signature in Proposal"))?;
+                validator.verify(*author, &self.block_data, signature)?;
+                for proof in proofs {
+                    proof.verify(validator)?;
+                }
+                self.quorum_cert().verify(validator)
+            }
         }
     }
 
@@ -253,10 +290,27 @@ impl Block {
         );
         if parent.has_reconfiguration() {
             ensure!(
-                self.payload().map_or(true, |p| p.is_empty()),
+                self.payload().map_or(true, |p| p.is_empty())
+                    && self.proofs().map_or(true, |p| p.is_empty()),
                 "Reconfiguration suffix should not carry payload"
             );
         }
+        if let Some(proofs) = self.proofs() {
+            let mut digests = HashSet::new();
+            for proof in proofs {
+                ensure!(
+                    proof.epoch() == self.epoch(),
+                    "Proof of store from epoch {} in a block of epoch {}",
+                    proof.epoch(),
+                    self.epoch()
+                );
+                ensure!(
+                    digests.insert(proof.digest()),
+                    "Duplicate proof of store for batch {}",
+                    proof.digest()
+                );
+            }
+        }
         if self.is_nil_block() || parent.has_reconfiguration() {
             ensure!(
                 self.timestamp_usecs() == parent.timestamp_usecs(),
@@ -289,16 +343,27 @@ impl Block {
         Ok(())
     }
 
-    pub fn transactions_to_execute(&self) -> Vec<Transaction> {
-        std::iter::once(Transaction::BlockMetadata(self.into()))
+    /// The transactions of a block carrying proofs of store are not in the block: they are
+    /// resolved from the batches by the caller and passed as `resolved_payload`.
+    pub fn transactions_to_execute(
+        &self,
+        resolved_payload: Option<&Payload>,
+    ) -> anyhow::Result<Vec<Transaction>> {
+        ensure!(
+            self.proofs().is_none() || resolved_payload.is_some(),
+            "Payload of block {} not resolved",
+            self.id()
+        );
+        Ok(std::iter::once(Transaction::BlockMetadata(self.into()))
             .chain(
                 self.payload()
+                    .or(resolved_payload)
                     .unwrap_or(&Vec::new())
                     .iter()
                     .cloned()
                     .map(Transaction::UserTransaction),
             )
-            .collect()
+            .collect())
     }
 }
 
@@ -3,6 +3,7 @@
 
 use crate::{
     common::{Author, Payload, Round},
+    experimental::quorum_store::ProofOfStore,
     quorum_cert::QuorumCert,
     vote_data::VoteData,
 };
@@ -32,6 +33,12 @@ pub enum BlockType {
     /// from the previous epoch.  The genesis block is used as the the first root block of the
     /// BlockTree for all epochs.
     Genesis,
+    /// A proposal whose transactions were disseminated ahead of it in batches: it carries the
+    /// proofs of store of the batches, to be resolved to transactions before execution.
+    QuorumStoreProposal {
+        proofs: Vec<ProofOfStore>,
+        author: Author,
+    },
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher, BCSCryptoHash)]
@@ -69,10 +76,11 @@ pub struct BlockData {
 
 impl BlockData {
     pub fn author(&self) -> Option<Author> {
-        if let BlockType::Proposal { author, .. } = self.block_type {
-            Some(author)
-        } else {
-            None
+        match self.block_type {
+            BlockType::Proposal { author, .. } | BlockType::QuorumStoreProposal { author, .. } => {
+                Some(author)
+            }
+            _ => None,
         }
     }
 
@@ -96,6 +104,14 @@ impl BlockData {
         }
     }
 
+    pub fn proofs(&self) -> Option<&Vec<ProofOfStore>> {
+        if let BlockType::QuorumStoreProposal { proofs, .. } = &self.block_type {
+            Some(proofs)
+        } else {
+            None
+        }
+    }
+
     pub fn round(&self) -> Round {
         self.round
     }
@@ -201,6 +217,22 @@ impl BlockData {
         }
     }
 
+    pub fn new_quorum_store_proposal(
+        proofs: Vec<ProofOfStore>,
+        author: Author,
+        round: Round,
+        timestamp_usecs: u64,
+        quorum_cert: QuorumCert,
+    ) -> Self {
+        Self {
+            epoch: quorum_cert.certified_block().epoch(),
+            round,
+            timestamp_usecs,
+            quorum_cert,
+            block_type: BlockType::QuorumStoreProposal { proofs, author },
+        }
+    }
+
     /// It's a reconfiguration suffix block if the parent block's executed state indicates next epoch.
     pub fn is_reconfiguration_suffix(&self) -> bool {
         self.quorum_cert.certified_block().has_reconfiguration()
@@ -6,6 +6,7 @@ use crate::{
         block_test_utils::{certificate_for_genesis, *},
         Block,
     },
+    experimental::quorum_store::{Batch, ProofOfStore},
     quorum_cert::QuorumCert,
 };
 use diem_crypto::hash::HashValue;
@@ -134,3 +135,27 @@ fn test_same_qc_different_authors() {
     assert!(block_round_1.id() != block_round_1_altered.id());
     assert_eq!(block_round_1.id(), block_round_1_same.id());
 }
+
+#[test]
+fn test_unresolved_quorum_store_payload() {
+    let signer = ValidatorSigner::random(None);
+    let batch = Batch::new(1, signer.author(), 0, vec![]);
+    let proof = ProofOfStore::new(batch.info(), BTreeMap::new());
+    let current_timestamp = diem_infallible::duration_since_epoch().as_micros() as u64;
+    let block = Block::new_quorum_store_proposal(
+        vec![proof],
+        1,
+        current_timestamp,
+        certificate_for_genesis(),
+        &signer,
+    );
+
+    assert!(block.transactions_to_execute(None).is_err());
+    assert_eq!(
+        block
+            .transactions_to_execute(Some(batch.txns()))
+            .unwrap()
+            .len(),
+        1
+    );
+}
@@ -105,20 +105,24 @@ impl ExecutedBlock {
         }
     }
 
-    pub fn transactions_to_commit(&self) -> Vec<Transaction> {
+    /// See `Block::transactions_to_execute` for `resolved_payload`.
+    pub fn transactions_to_commit(
+        &self,
+        resolved_payload: Option<&Payload>,
+    ) -> anyhow::Result<Vec<Transaction>> {
         // reconfiguration suffix don't execute
         if self.is_reconfiguration_suffix() {
-            return vec![];
+            return Ok(vec![]);
         }
-        itertools::zip_eq(
-            self.block.transactions_to_execute(),
+        Ok(itertools::zip_eq(
+            self.block.transactions_to_execute(resolved_payload)?,
             self.state_compute_result.compute_status(),
         )
         .filter_map(|(txn, status)| match status {
             TransactionStatus::Keep(_) => Some(txn),
             _ => None,
         })
-        .collect()
+        .collect())
     }
 
     pub fn reconfig_event(&self) -> Vec<ContractEvent> {
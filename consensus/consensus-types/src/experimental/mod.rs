@@ -3,3 +3,4 @@
 
 pub mod commit_decision;
 pub mod commit_vote;
+pub mod quorum_store;
@@ -0,0 +1,324 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Types of the quorum store: validators disseminate transactions in batches ahead of the
+//! proposals, and a quorum of signatures on a batch (a proof of store) guarantees that at least
+//! one honest validator stores it. Proposals then only carry the proofs of store.
+
+use crate::common::{Author, Payload};
+use anyhow::{ensure, format_err, Context};
+use diem_crypto::{ed25519::Ed25519Signature, hash::CryptoHash, HashValue};
+use diem_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use diem_types::{validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+use short_hex_str::AsShortHexStr;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// Batches of an author are numbered from 0 in each epoch.
+pub type BatchId = u64;
+
+/// Transactions disseminated by a validator. The digest of a batch is its hash.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher, BCSCryptoHash)]
+pub struct Batch {
+    epoch: u64,
+    author: Author,
+    batch_id: BatchId,
+    txns: Payload,
+}
+
+impl Display for Batch {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Batch: [author: {}, epoch: {}, batch_id: {}, txns: {}]",
+            self.author.short_str(),
+            self.epoch,
+            self.batch_id,
+            self.txns.len()
+        )
+    }
+}
+
+impl Batch {
+    pub fn new(epoch: u64, author: Author, batch_id: BatchId, txns: Payload) -> Self {
+        Self {
+            epoch,
+            author,
+            batch_id,
+            txns,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    pub fn batch_id(&self) -> BatchId {
+        self.batch_id
+    }
+
+    pub fn txns(&self) -> &Payload {
+        &self.txns
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.hash()
+    }
+
+    pub fn info(&self) -> BatchInfo {
+        BatchInfo {
+            epoch: self.epoch,
+            author: self.author,
+            batch_id: self.batch_id,
+            digest: self.digest(),
+            num_txns: self.txns.len() as u64,
+        }
+    }
+
+    /// Batches are not signed: they are only accepted from their author, who must be a validator
+    /// of the current epoch.
+    pub fn verify(&self, sender: Author, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        ensure!(
+            self.author == sender,
+            "Batch of {} sent by {}",
+            self.author,
+            sender
+        );
+        ensure!(
+            validator.get_public_key(&self.author).is_some(),
+            "Batch author {} is not a validator",
+            self.author
+        );
+        Ok(())
+    }
+}
+
+/// What the validators storing a batch sign.
+#[derive(
+    Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash, CryptoHasher, BCSCryptoHash,
+)]
+pub struct BatchInfo {
+    epoch: u64,
+    author: Author,
+    batch_id: BatchId,
+    digest: HashValue,
+    num_txns: u64,
+}
+
+impl Display for BatchInfo {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "BatchInfo: [author: {}, epoch: {}, batch_id: {}, digest: {}, txns: {}]",
+            self.author.short_str(),
+            self.epoch,
+            self.batch_id,
+            self.digest,
+            self.num_txns
+        )
+    }
+}
+
+impl BatchInfo {
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    pub fn batch_id(&self) -> BatchId {
+        self.batch_id
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.digest
+    }
+
+    pub fn num_txns(&self) -> u64 {
+        self.num_txns
+    }
+}
+
+/// Sent back to the author of a batch by a validator that stores it.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignedBatchInfo {
+    info: BatchInfo,
+    signer: Author,
+    signature: Ed25519Signature,
+}
+
+impl Display for SignedBatchInfo {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "SignedBatchInfo: [signer: {}, {}]",
+            self.signer.short_str(),
+            self.info
+        )
+    }
+}
+
+impl SignedBatchInfo {
+    pub fn new(info: BatchInfo, validator_signer: &ValidatorSigner) -> Self {
+        let signature = validator_signer.sign(&info);
+        Self::new_with_signature(info, validator_signer.author(), signature)
+    }
+
+    /// Signature is generated by safety rules, which hold the private key.
+    pub fn new_with_signature(
+        info: BatchInfo,
+        signer: Author,
+        signature: Ed25519Signature,
+    ) -> Self {
+        Self {
+            info,
+            signer,
+            signature,
+        }
+    }
+
+    pub fn info(&self) -> &BatchInfo {
+        &self.info
+    }
+
+    pub fn signer(&self) -> Author {
+        self.signer
+    }
+
+    pub fn signature(&self) -> &Ed25519Signature {
+        &self.signature
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.info.epoch()
+    }
+
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify(self.signer, &self.info, &self.signature)
+            .context("Failed to verify SignedBatchInfo")
+    }
+}
+
+/// Signatures of a quorum of validators on a batch: at least one honest validator stores it, so
+/// a proposal can refer to the batch by its digest.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofOfStore {
+    info: BatchInfo,
+    signatures: BTreeMap<Author, Ed25519Signature>,
+}
+
+impl Display for ProofOfStore {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "ProofOfStore: [{}, signers: {}]",
+            self.info,
+            self.signatures.len()
+        )
+    }
+}
+
+impl ProofOfStore {
+    pub fn new(info: BatchInfo, signatures: BTreeMap<Author, Ed25519Signature>) -> Self {
+        Self { info, signatures }
+    }
+
+    pub fn info(&self) -> &BatchInfo {
+        &self.info
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.info.digest()
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.info.epoch()
+    }
+
+    /// The validators that signed the batch, i.e. the ones it can be requested from.
+    pub fn signers(&self) -> impl Iterator<Item = &Author> {
+        self.signatures.keys()
+    }
+
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify_aggregated_struct_signature(&self.info, &self.signatures)
+            .context("Failed to verify ProofOfStore")
+    }
+}
+
+/// RPC to get the batch with the given digest from a validator that signed it.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchRequest {
+    digest: HashValue,
+}
+
+impl Display for BatchRequest {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "[BatchRequest digest: {}]", self.digest)
+    }
+}
+
+impl BatchRequest {
+    pub fn new(digest: HashValue) -> Self {
+        Self { digest }
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.digest
+    }
+}
+
+/// Carries the requested batch, if the validator stores it.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchResponse {
+    batch: Option<Batch>,
+}
+
+impl Display for BatchResponse {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match &self.batch {
+            Some(batch) => write!(f, "[BatchResponse {}]", batch),
+            None => write!(f, "[BatchResponse not found]"),
+        }
+    }
+}
+
+impl BatchResponse {
+    pub fn new(batch: Option<Batch>) -> Self {
+        Self { batch }
+    }
+
+    pub fn batch(&self) -> Option<&Batch> {
+        self.batch.as_ref()
+    }
+
+    pub fn into_batch(self) -> Option<Batch> {
+        self.batch
+    }
+
+    /// Makes sure the response carries the requested batch.
+    pub fn verify(&self, request: &BatchRequest) -> anyhow::Result<()> {
+        let batch = self
+            .batch
+            .as_ref()
+            .ok_or_else(|| format_err!("Batch {} not found", request.digest()))?;
+        ensure!(
+            batch.digest() == request.digest(),
+            "Requested batch {} but received {}",
+            request.digest(),
+            batch.digest()
+        );
+        Ok(())
+    }
+}
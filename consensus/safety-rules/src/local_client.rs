@@ -4,6 +4,7 @@
 use crate::{ConsensusState, Error, SafetyRules, TSafetyRules};
 use consensus_types::{
     block_data::BlockData,
+    experimental::quorum_store::BatchInfo,
     timeout::Timeout,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote::Vote,
@@ -83,4 +84,8 @@ impl TSafetyRules for LocalClient {
             .write()
             .sign_commit_vote(ledger_info, new_ledger_info)
     }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        self.internal.write().sign_batch_info(batch_info)
+    }
 }
@@ -14,6 +14,7 @@ use consensus_types::{
     block::Block,
     block_data::BlockData,
     common::{Author, Round},
+    experimental::quorum_store::BatchInfo,
     quorum_cert::QuorumCert,
     safety_data::SafetyData,
     timeout::Timeout,
@@ -493,6 +494,21 @@ impl SafetyRules {
 
         Ok(signature)
     }
+
+    fn guarded_sign_batch_info(
+        &mut self,
+        batch_info: &BatchInfo,
+    ) -> Result<Ed25519Signature, Error> {
+        self.signer()?;
+
+        let epoch = self.epoch_state()?.epoch;
+        if batch_info.epoch() != epoch {
+            return Err(Error::IncorrectEpoch(batch_info.epoch(), epoch));
+        }
+
+        let signature = self.sign(batch_info)?;
+        Ok(signature)
+    }
 }
 
 impl TSafetyRules for SafetyRules {
@@ -563,6 +579,11 @@ impl TSafetyRules for SafetyRules {
         let cb = || self.guarded_sign_commit_vote(ledger_info, new_ledger_info);
         run_and_log(cb, |log| log, LogEntry::SignCommitVote)
     }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        let cb = || self.guarded_sign_batch_info(batch_info);
+        run_and_log(cb, |log| log, LogEntry::SignBatchInfo)
+    }
 }
 
 fn run_and_log<F, L, R>(callback: F, log_cb: L, log_entry: LogEntry) -> Result<R, Error>
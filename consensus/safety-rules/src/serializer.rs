@@ -4,6 +4,7 @@
 use crate::{counters, logging::LogEntry, ConsensusState, Error, SafetyRules, TSafetyRules};
 use consensus_types::{
     block_data::BlockData,
+    experimental::quorum_store::BatchInfo,
     timeout::Timeout,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote::Vote,
@@ -34,6 +35,7 @@ pub enum SafetyRulesInput {
         Box<Option<TwoChainTimeoutCertificate>>,
     ),
     SignCommitVote(Box<LedgerInfoWithSignatures>, Box<LedgerInfo>),
+    SignBatchInfo(Box<BatchInfo>),
 }
 
 pub struct SerializerService {
@@ -80,6 +82,9 @@ impl SerializerService {
                     .internal
                     .sign_commit_vote(*ledger_info, *new_ledger_info),
             ),
+            SafetyRulesInput::SignBatchInfo(batch_info) => {
+                serde_json::to_vec(&self.internal.sign_batch_info(&batch_info))
+            }
         };
 
         Ok(output?)
@@ -181,6 +186,14 @@ impl TSafetyRules for SerializerClient {
         ))?;
         serde_json::from_slice(&response)?
     }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_timer("external", LogEntry::SignBatchInfo.as_str());
+        let response = self.request(SafetyRulesInput::SignBatchInfo(Box::new(
+            batch_info.clone(),
+        )))?;
+        serde_json::from_slice(&response)?
+    }
 }
 
 pub trait TSerializerClient: Send + Sync {
@@ -5,6 +5,7 @@ use crate::{test_utils, test_utils::make_timeout_cert, Error, SafetyRules, TSafe
 use consensus_types::{
     block::block_test_utils::random_payload,
     common::Round,
+    experimental::quorum_store::Batch,
     quorum_cert::QuorumCert,
     timeout::Timeout,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
@@ -77,6 +78,7 @@ pub fn run_test_suite(safety_rules: &Callback) {
     test_2chain_timeout(safety_rules);
     test_sign_commit_vote(safety_rules);
     test_bad_execution_output(safety_rules);
+    test_sign_batch_info(safety_rules);
 }
 
 fn test_bad_execution_output(safety_rules: &Callback) {
@@ -1064,3 +1066,31 @@ fn test_sign_commit_vote(constructor: &Callback) {
         Error::InconsistentExecutionResult(_, _)
     ));
 }
+
+/// Test that we only sign batches of the current epoch
+fn test_sign_batch_info(constructor: &Callback) {
+    let (mut safety_rules, signer, _key) = constructor();
+    let (proof, _genesis_qc) = test_utils::make_genesis(&signer);
+
+    let batch_info = Batch::new(0, signer.author(), 0, random_payload(2)).info();
+    assert!(matches!(
+        safety_rules.sign_batch_info(&batch_info).unwrap_err(),
+        Error::NotInitialized(_)
+    ));
+
+    safety_rules.initialize(&proof).unwrap();
+    let epoch = safety_rules.consensus_state().unwrap().epoch();
+
+    let batch_info = Batch::new(epoch, signer.author(), 0, random_payload(2)).info();
+    let signature = safety_rules.sign_batch_info(&batch_info).unwrap();
+    let verifier = ValidatorVerifier::new_single(signer.author(), signer.public_key());
+    verifier
+        .verify(signer.author(), &batch_info, &signature)
+        .unwrap();
+
+    let batch_info = Batch::new(epoch + 1, signer.author(), 0, random_payload(2)).info();
+    assert!(matches!(
+        safety_rules.sign_batch_info(&batch_info).unwrap_err(),
+        Error::IncorrectEpoch(_, _)
+    ));
+}
@@ -5,17 +5,16 @@ use crate::{tests::suite, PersistentSafetyStorage, SafetyRulesManager};
 use diem_crypto::{ed25519::Ed25519PrivateKey, Uniform};
 use diem_secure_storage::{KVStorage, Storage, VaultStorage};
 use diem_types::validator_signer::ValidatorSigner;
-use diem_vault_client::dev::{self, ROOT_TOKEN};
+use diem_vault_client::{
+    dev::{self, ROOT_TOKEN},
+    mock,
+};
 
-/// A test for verifying VaultStorage properly supports the SafetyRule backend.  This test
-/// depends on running Vault, which can be done by using the provided docker run script in
-/// `docker/vault/run.sh`
+/// A test for verifying VaultStorage properly supports the SafetyRule backend.  This test runs
+/// against Vault if it is installed (or started by the provided docker run script in
+/// `docker/vault/run.sh`) and otherwise against the mock Vault.
 #[test]
 fn test() {
-    if dev::test_host_safe().is_none() {
-        return;
-    }
-
     let boolean_values = [false, true];
     for verify_vote_proposal_signature in &boolean_values {
         for export_consensus_key in &boolean_values {
@@ -34,7 +33,7 @@ fn safety_rules(
     Box::new(move || {
         let signer = ValidatorSigner::from_int(0);
         let mut storage = Storage::from(VaultStorage::new(
-            dev::test_host(),
+            dev::test_host_safe().unwrap_or_else(mock::test_host),
             ROOT_TOKEN.to_string(),
             None,
             None,
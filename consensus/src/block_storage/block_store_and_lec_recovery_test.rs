@@ -4,6 +4,7 @@
 use crate::{
     block_storage::{block_store::BlockStore, BlockReader},
     persistent_liveness_storage::{LedgerRecoveryData, RecoveryData, RootMetadata},
+    quorum_store::batch_store::BatchStore,
     state_computer::ExecutionProxy,
     test_utils::{EmptyStorage, MockTransactionManager, TreeInserter},
     util::mock_time_service::SimulatedTimeService,
@@ -74,6 +75,7 @@ fn build_inserter(
         Arc::new(MockTransactionManager::new(None)),
         Arc::new(consensus_notifier),
         &tokio::runtime::Handle::current(),
+        Arc::new(BatchStore::new(&config.consensus.quorum_store)),
     ));
 
     TreeInserter::new_with_store(
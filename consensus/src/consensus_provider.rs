@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    consensusdb::ConsensusDB,
     counters,
     epoch_manager::EpochManager,
     network::NetworkTask,
     network_interface::{ConsensusNetworkEvents, ConsensusNetworkSender},
     persistent_liveness_storage::StorageWriteProxy,
+    quorum_store::batch_store::BatchStore,
     state_computer::ExecutionProxy,
     txn_manager::MempoolProxy,
     util::time_service::ClockTimeService,
@@ -19,13 +21,30 @@ use event_notifications::ReconfigNotificationListener;
 use execution_correctness::ExecutionCorrectnessManager;
 use futures::channel::mpsc;
 use network::application::storage::PeerMetadataStorage;
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 use storage_interface::DbReaderWriter;
 use tokio::runtime::{self, Runtime};
 
+/// Shared handle to the consensus DB, so that the node can checkpoint it while consensus runs.
+#[derive(Clone)]
+pub struct ConsensusDbHandle(Arc<ConsensusDB>);
+
+impl ConsensusDbHandle {
+    /// Opens (or creates) the consensus DB under the storage dir of `node_config`.
+    pub fn open(node_config: &NodeConfig) -> Self {
+        Self(Arc::new(ConsensusDB::new(node_config.storage.dir())))
+    }
+
+    /// Creates new physical DB checkpoint in directory specified by `path`.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        self.0.create_checkpoint(path)
+    }
+}
+
 /// Helper function to start consensus based on configuration and return the runtime
 pub fn start_consensus(
     node_config: &NodeConfig,
+    consensus_db: ConsensusDbHandle,
     mut network_sender: ConsensusNetworkSender,
     network_events: ConsensusNetworkEvents,
     state_sync_notifier: Arc<dyn ConsensusNotificationSender>,
@@ -39,7 +58,10 @@ pub fn start_consensus(
         .enable_all()
         .build()
         .expect("Failed to create Tokio runtime!");
-    let storage = Arc::new(StorageWriteProxy::new(node_config, diem_db.reader.clone()));
+    let storage = Arc::new(StorageWriteProxy::new(
+        consensus_db.0,
+        diem_db.reader.clone(),
+    ));
     let txn_manager = Arc::new(MempoolProxy::new(
         consensus_to_mempool_sender,
         node_config.consensus.mempool_poll_count,
@@ -47,12 +69,14 @@ pub fn start_consensus(
         node_config.consensus.mempool_executed_txn_timeout_ms,
     ));
     let execution_correctness_manager = ExecutionCorrectnessManager::new(node_config, diem_db);
+    let batch_store = Arc::new(BatchStore::new(&node_config.consensus.quorum_store));
 
     let state_computer = Arc::new(ExecutionProxy::new(
         execution_correctness_manager.client(),
         txn_manager.clone(),
         state_sync_notifier,
         runtime.handle(),
+        batch_store.clone(),
     ));
 
     let time_service = Arc::new(ClockTimeService::new(runtime.handle().clone()));
@@ -71,6 +95,7 @@ pub fn start_consensus(
         state_computer,
         storage,
         reconfig_events,
+        batch_store,
     );
 
     let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
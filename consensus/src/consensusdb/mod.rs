@@ -141,6 +141,18 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    /// Creates new physical DB checkpoint in directory specified by `path`.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let start = Instant::now();
+        self.db.create_checkpoint(&path)?;
+        info!(
+            "Made ConsensusDB checkpoint at {:?} in {} ms",
+            path.as_ref(),
+            start.elapsed().as_millis()
+        );
+        Ok(())
+    }
+
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support.
     fn commit(&self, batch: SchemaBatch) -> Result<(), DbError> {
@@ -98,6 +98,15 @@ pub static COMMITTED_VOTES_IN_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Failed rounds led by this validator when using LeaderReputation as the ProposerElection
+pub static FAILED_ROUNDS_IN_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_failed_rounds_in_window",
+        "Total number of failed rounds this validator led in the current reputation window"
+    )
+    .unwrap()
+});
+
 //////////////////////
 // RoundState COUNTERS
 //////////////////////
@@ -300,3 +309,69 @@ pub static BLOCK_RETRIEVAL_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Counters(queued,dequeued,dropped) related to quorum store messages channel
+pub static QUORUM_STORE_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_consensus_quorum_store_channel_msgs_count",
+        "Counters(queued,dequeued,dropped) related to quorum store messages channel",
+        &["state"]
+    )
+    .unwrap()
+});
+
+/// Counters(queued,dequeued,dropped) related to batch retrieval channel
+pub static BATCH_RETRIEVAL_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_consensus_batch_retrieval_channel_msgs_count",
+        "Counters(queued,dequeued,dropped) related to batch retrieval channel",
+        &["state"]
+    )
+    .unwrap()
+});
+
+/// Counters(queued,dequeued,dropped) related to the messages pending in the quorum store
+pub static QUORUM_STORE_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_consensus_quorum_store_msgs_count",
+        "Counters(queued,dequeued,dropped) related to pending quorum store messages",
+        &["state"]
+    )
+    .unwrap()
+});
+
+/// Count of the batches created by this validator.
+pub static BATCHES_CREATED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_consensus_batches_created",
+        "Count of the batches created by this validator"
+    )
+    .unwrap()
+});
+
+/// Count of the proofs of store formed for the batches of this validator.
+pub static PROOFS_OF_STORE_FORMED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_consensus_proofs_of_store_formed",
+        "Count of the proofs of store formed for the batches of this validator"
+    )
+    .unwrap()
+});
+
+/// Count of the batches fetched from other validators to execute a block.
+pub static BATCHES_FETCHED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_consensus_batches_fetched",
+        "Count of the batches fetched from other validators to execute a block"
+    )
+    .unwrap()
+});
+
+/// Number of proofs of store available to be proposed.
+pub static PENDING_PROOFS_OF_STORE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_consensus_pending_proofs_of_store",
+        "Number of proofs of store available to be proposed"
+    )
+    .unwrap()
+});
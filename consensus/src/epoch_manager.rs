@@ -11,7 +11,10 @@ use crate::{
         ordering_state_computer::OrderingStateComputer,
     },
     liveness::{
-        leader_reputation::{ActiveInactiveHeuristic, DiemDBBackend, LeaderReputation},
+        leader_reputation::{
+            ActiveInactiveHeuristic, CombinedHeuristic, DiemDBBackend, FailedRoundsHeuristic,
+            LeaderReputation, ReputationHeuristic,
+        },
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
         rotating_proposer_election::{choose_leader, RotatingProposer},
@@ -20,9 +23,12 @@ use crate::{
     },
     logging::{LogEvent, LogSchema},
     metrics_safety_rules::MetricsSafetyRules,
-    network::{IncomingBlockRetrievalRequest, NetworkReceivers, NetworkSender},
+    network::{
+        IncomingBatchRequest, IncomingBlockRetrievalRequest, NetworkReceivers, NetworkSender,
+    },
     network_interface::{ConsensusMsg, ConsensusNetworkSender},
     persistent_liveness_storage::{LedgerRecoveryData, PersistentLivenessStorage, RecoveryData},
+    quorum_store::{batch_store::BatchStore, dissemination::QuorumStore},
     round_manager::{RoundManager, UnverifiedEvent, VerifiedEvent},
     state_replication::{StateComputer, TxnManager},
     util::time_service::TimeService,
@@ -32,8 +38,12 @@ use channel::{diem_channel, message_queues::QueueStyle};
 use consensus_types::{
     common::{Author, Round},
     epoch_retrieval::EpochRetrievalRequest,
+    experimental::quorum_store::BatchResponse,
+};
+use diem_config::config::{
+    ConsensusConfig, ConsensusProposerType, FailedRoundsConfig, LeaderReputationConfig, NodeConfig,
+    ReputationHeuristicType,
 };
-use diem_config::config::{ConsensusConfig, ConsensusProposerType, NodeConfig};
 use diem_infallible::{duration_since_epoch, Mutex};
 use diem_logger::prelude::*;
 use diem_metrics::monitor;
@@ -90,6 +100,7 @@ pub struct EpochManager {
     storage: Arc<dyn PersistentLivenessStorage>,
     safety_rules_manager: SafetyRulesManager,
     reconfig_events: ReconfigNotificationListener,
+    batch_store: Arc<BatchStore>,
     // channels to buffer manager
     buffer_manager_msg_tx: Option<diem_channel::Sender<AccountAddress, VerifiedEvent>>,
     buffer_manager_reset_tx: Option<UnboundedSender<ResetRequest>>,
@@ -97,6 +108,8 @@ pub struct EpochManager {
     round_manager_tx: Option<
         diem_channel::Sender<(Author, Discriminant<VerifiedEvent>), (Author, VerifiedEvent)>,
     >,
+    // channel to quorum store
+    quorum_store_tx: Option<diem_channel::Sender<Author, (Author, VerifiedEvent)>>,
     epoch_state: Option<EpochState>,
 }
 
@@ -111,6 +124,7 @@ impl EpochManager {
         commit_state_computer: Arc<dyn StateComputer>,
         storage: Arc<dyn PersistentLivenessStorage>,
         reconfig_events: ReconfigNotificationListener,
+        batch_store: Arc<BatchStore>,
     ) -> Self {
         let author = node_config.validator_network.as_ref().unwrap().peer_id();
         let config = node_config.consensus.clone();
@@ -128,9 +142,11 @@ impl EpochManager {
             storage,
             safety_rules_manager,
             reconfig_events,
+            batch_store,
             buffer_manager_msg_tx: None,
             buffer_manager_reset_tx: None,
             round_manager_tx: None,
+            quorum_store_tx: None,
             epoch_state: None,
         }
     }
@@ -185,16 +201,13 @@ impl EpochManager {
             }
             ConsensusProposerType::LeaderReputation(heuristic_config) => {
                 let backend = Box::new(DiemDBBackend::new(proposers.len(), self.storage.diem_db()));
-                let heuristic = Box::new(ActiveInactiveHeuristic::new(
-                    self.author,
-                    heuristic_config.active_weights,
-                    heuristic_config.inactive_weights,
-                ));
+                let exclude_round = onchain_config.leader_reputation_exclude_round();
+                let heuristic = self.create_reputation_heuristic(heuristic_config, exclude_round);
                 Box::new(LeaderReputation::new(
                     proposers,
                     backend,
                     heuristic,
-                    onchain_config.leader_reputation_exclude_round(),
+                    exclude_round,
                 ))
             }
             ConsensusProposerType::RoundProposer(round_proposers) => {
@@ -208,6 +221,47 @@ impl EpochManager {
         }
     }
 
+    /// Create the heuristic weighing the candidates of leader reputation
+    fn create_reputation_heuristic(
+        &self,
+        config: &LeaderReputationConfig,
+        exclude_round: u64,
+    ) -> Box<dyn ReputationHeuristic> {
+        let active_inactive = || -> Box<dyn ReputationHeuristic> {
+            Box::new(ActiveInactiveHeuristic::new(
+                self.author,
+                config.active_weights,
+                config.inactive_weights,
+            ))
+        };
+        let failed_rounds =
+            |failed_rounds_config: &FailedRoundsConfig| -> Box<dyn ReputationHeuristic> {
+                Box::new(FailedRoundsHeuristic::new(
+                    self.author,
+                    active_inactive(),
+                    exclude_round,
+                    failed_rounds_config.ok_weight,
+                    failed_rounds_config.failed_weight,
+                    failed_rounds_config.failure_threshold_percent,
+                ))
+            };
+        match &config.heuristic {
+            ReputationHeuristicType::ActiveInactive => active_inactive(),
+            ReputationHeuristicType::FailedRounds(failed_rounds_config) => {
+                failed_rounds(failed_rounds_config)
+            }
+            ReputationHeuristicType::Combined(combined_config) => {
+                Box::new(CombinedHeuristic::new(vec![
+                    (combined_config.active_inactive_factor, active_inactive()),
+                    (
+                        combined_config.failed_rounds_factor,
+                        failed_rounds(&combined_config.failed_rounds),
+                    ),
+                ]))
+            }
+        }
+    }
+
     async fn process_epoch_retrieval(
         &mut self,
         request: EpochRetrievalRequest,
@@ -354,6 +408,9 @@ impl EpochManager {
         }
         self.round_manager_tx = None;
 
+        // Shutdown the previous quorum store: it stops once its channel is closed
+        self.quorum_store_tx = None;
+
         // Shutdown the previous buffer manager, to release the SafetyRule client
         self.buffer_manager_msg_tx = None;
         if let Some(mut tx) = self.buffer_manager_reset_tx.take() {
@@ -414,6 +471,26 @@ impl EpochManager {
 
         let safety_rules_container = Arc::new(Mutex::new(safety_rules));
 
+        info!(epoch = epoch, "Create QuorumStore");
+        self.batch_store.start_epoch(epoch, network_sender.clone());
+        let quorum_store = QuorumStore::new(
+            epoch,
+            self.author,
+            self.config.quorum_store,
+            epoch_state.verifier.clone(),
+            self.batch_store.clone(),
+            self.txn_manager.clone(),
+            safety_rules_container.clone(),
+            network_sender.clone(),
+        );
+        let (quorum_store_tx, quorum_store_rx) = diem_channel::new(
+            QueueStyle::FIFO,
+            self.config.channel_size,
+            Some(&counters::QUORUM_STORE_MSGS),
+        );
+        self.quorum_store_tx = Some(quorum_store_tx);
+        tokio::spawn(quorum_store.start(quorum_store_rx));
+
         let state_computer = if onchain_config.decoupled_execution() {
             Arc::new(self.spawn_decoupled_execution(
                 safety_rules_container.clone(),
@@ -442,6 +519,10 @@ impl EpochManager {
             self.txn_manager.clone(),
             self.time_service.clone(),
             self.config.max_block_size,
+            self.config
+                .quorum_store
+                .enabled
+                .then(|| self.batch_store.clone()),
         );
 
         let mut round_manager = RoundManager::new(
@@ -500,7 +581,7 @@ impl EpochManager {
             // same epoch -> run well-formedness + signature check
             let verified_event = unverified_event
                 .clone()
-                .verify(&self.epoch_state().verifier)
+                .verify(peer_id, &self.epoch_state().verifier)
                 .context("[EpochManager] Verify event")
                 .map_err(|err| {
                     error!(
@@ -528,7 +609,10 @@ impl EpochManager {
             | ConsensusMsg::SyncInfo(_)
             | ConsensusMsg::VoteMsg(_)
             | ConsensusMsg::CommitVoteMsg(_)
-            | ConsensusMsg::CommitDecisionMsg(_) => {
+            | ConsensusMsg::CommitDecisionMsg(_)
+            | ConsensusMsg::BatchMsg(_)
+            | ConsensusMsg::SignedBatchInfoMsg(_)
+            | ConsensusMsg::ProofOfStoreMsg(_) => {
                 let event: UnverifiedEvent = msg.into();
                 if event.epoch() == self.epoch() {
                     return Ok(Some(event));
@@ -592,6 +676,15 @@ impl EpochManager {
                     bail!("Commit Phase not started but received Commit Message (CommitVote/CommitDecision)");
                 }
             }
+            quorum_store_event @ (VerifiedEvent::Batch(_)
+            | VerifiedEvent::SignedBatchInfo(_)
+            | VerifiedEvent::ProofOfStore(_)) => {
+                if let Some(sender) = &mut self.quorum_store_tx {
+                    sender.push(peer_id, (peer_id, quorum_store_event))?;
+                } else {
+                    bail!("QuorumStore not started but received Batch Message");
+                }
+            }
             round_manager_event => {
                 self.forward_to_round_manager(peer_id, round_manager_event);
             }
@@ -616,6 +709,19 @@ impl EpochManager {
         );
     }
 
+    fn process_batch_retrieval(&self, request: IncomingBatchRequest) -> anyhow::Result<()> {
+        let batch = self.batch_store.get_batch(&request.req.digest());
+        let response = Box::new(BatchResponse::new(batch));
+        let response_bytes = request
+            .protocol
+            .to_bytes(&ConsensusMsg::BatchResponse(response))?;
+        request
+            .response_sender
+            .send(Ok(response_bytes.into()))
+            .map_err(|e| anyhow::anyhow!("{:?}", e))
+            .context("[EpochManager] Failed to process batch retrieval")
+    }
+
     fn process_local_timeout(&mut self, round: u64) {
         self.forward_to_round_manager(self.author, VerifiedEvent::LocalTimeout(round));
     }
@@ -644,9 +750,19 @@ impl EpochManager {
                         error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
                     }
                 }
+                Some((peer, msg)) = network_receivers.quorum_store_messages.next() => {
+                    if let Err(e) = self.process_message(peer, msg).await {
+                        error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
+                    }
+                }
                 Some(request) = network_receivers.block_retrieval.next() => {
                     self.process_block_retrieval(request);
                 }
+                Some(request) = network_receivers.batch_retrieval.next() => {
+                    if let Err(e) = self.process_batch_retrieval(request) {
+                        error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
+                    }
+                }
                 Some(round) = round_timeout_sender_rx.next() => {
                     self.process_local_timeout(round);
                 }
@@ -38,6 +38,10 @@ use futures::channel::mpsc::unbounded;
 
 pub const BUFFER_MANAGER_RETRY_INTERVAL: u64 = 1000;
 
+/// Up to how many blocks of consecutive ordered items are sent to the execution phase together,
+/// so that they are executed in one run instead of one by one.
+pub const MAX_BLOCKS_PER_EXECUTION_REQUEST: usize = 20;
+
 pub type ResetAck = ();
 
 pub fn sync_ack_new() -> ResetAck {}
@@ -158,7 +162,20 @@ impl BufferManager {
             cursor, self.execution_root
         );
         if self.execution_root.is_some() {
-            let ordered_blocks = self.buffer.get(&self.execution_root).get_blocks().clone();
+            let mut ordered_blocks = self.buffer.get(&self.execution_root).get_blocks().clone();
+            // coalesce the following ordered items into the same request
+            let mut cursor = self.buffer.get_next(&self.execution_root);
+            while cursor.is_some() {
+                let item = self.buffer.get(&cursor);
+                if !item.is_ordered()
+                    || ordered_blocks.len() + item.get_blocks().len()
+                        > MAX_BLOCKS_PER_EXECUTION_REQUEST
+                {
+                    break;
+                }
+                ordered_blocks.extend(item.get_blocks().iter().cloned());
+                cursor = self.buffer.get_next(&cursor);
+            }
             self.execution_phase_tx
                 .send(ExecutionRequest { ordered_blocks })
                 .await
@@ -252,39 +269,83 @@ impl BufferManager {
         tx.send(sync_ack_new()).unwrap();
     }
 
-    /// If the response is successful, advance the item to Executed, otherwise panic (TODO fix).
+    /// If the response is successful, advance the items to Executed, otherwise panic (TODO fix).
+    /// A response may cover several consecutive items, each of them gets its own blocks.
     async fn process_execution_response(&mut self, response: ExecutionResponse) {
         let ExecutionResponse { block_id, inner } = response;
         // find the corresponding item, may not exist if a reset or aggregated happened
-        let current_cursor = self.buffer.find_elem_by_key(self.execution_root, block_id);
-        if current_cursor.is_none() {
+        let last_cursor = self.buffer.find_elem_by_key(self.execution_root, block_id);
+        if last_cursor.is_none() {
             return;
         }
 
-        let executed_blocks = match inner {
+        let mut executed_blocks = match inner {
             Ok(result) => result,
             Err(e) => {
                 error!("Execution error {:?}", e);
                 return;
             }
         };
-        // if this batch of blocks are all suffix blocks (reconfiguration block is in one of previous batch)
-        // we pause the execution from here and wait for the reconfiguration to be committed.
-        if executed_blocks.first().unwrap().is_reconfiguration_suffix() {
-            debug!("Ignore reconfiguration suffix execution, waiting for epoch to be committed");
-            return;
+        // skip the blocks of the items that are gone since the request was sent
+        let first_block_id = self.buffer.get(&self.execution_root).get_blocks()[0].id();
+        match executed_blocks
+            .iter()
+            .position(|b| b.id() == first_block_id)
+        {
+            Some(pos) => {
+                executed_blocks.drain(..pos);
+            }
+            None => return,
         }
         debug!(
             "Receive executed response {}",
             executed_blocks.last().unwrap().block_info()
         );
 
-        let item = self.buffer.take(&current_cursor);
-        let new_item = item.advance_to_executed_or_aggregated(executed_blocks, &self.verifier);
-        let aggregated = new_item.is_aggregated();
-        self.buffer.set(&current_cursor, new_item);
-        if aggregated {
-            self.advance_head(block_id).await;
+        let mut executed_blocks = executed_blocks.into_iter();
+        let mut cursor = self.execution_root;
+        let mut aggregated_block_id = None;
+        loop {
+            let item = self.buffer.take(&cursor);
+            let item_blocks: Vec<_> = executed_blocks
+                .by_ref()
+                .take(item.get_blocks().len())
+                .collect();
+            // the response doesn't cover the whole item, keep it ordered
+            if item_blocks.len() != item.get_blocks().len() {
+                error!(
+                    "Execution response of {} misses blocks of item {}",
+                    block_id,
+                    item.block_id()
+                );
+                self.buffer.set(&cursor, item);
+                break;
+            }
+            // if this batch of blocks are all suffix blocks (reconfiguration block is in one of previous batch)
+            // we pause the execution from here and wait for the reconfiguration to be committed.
+            if item_blocks
+                .first()
+                .map_or(false, |b| b.is_reconfiguration_suffix())
+            {
+                debug!(
+                    "Ignore reconfiguration suffix execution, waiting for epoch to be committed"
+                );
+                self.buffer.set(&cursor, item);
+                break;
+            }
+            let item_block_id = item.block_id();
+            let new_item = item.advance_to_executed_or_aggregated(item_blocks, &self.verifier);
+            if new_item.is_aggregated() {
+                aggregated_block_id = Some(item_block_id);
+            }
+            self.buffer.set(&cursor, new_item);
+            if cursor == last_cursor {
+                break;
+            }
+            cursor = self.buffer.get_next(&cursor);
+        }
+        if let Some(aggregated_block_id) = aggregated_block_id {
+            self.advance_head(aggregated_block_id).await;
         }
     }
 
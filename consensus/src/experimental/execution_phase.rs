@@ -65,25 +65,23 @@ impl StatelessPipeline for ExecutionPhase {
         }
 
         let block_id = ordered_blocks.last().unwrap().id();
-        let mut result = vec![];
+        let parent_block_id = ordered_blocks.first().unwrap().parent_id();
+        let blocks: Vec<_> = ordered_blocks.iter().map(|b| b.block().clone()).collect();
 
-        for b in ordered_blocks {
-            match self.execution_proxy.compute(b.block(), b.parent_id()).await {
-                Ok(compute_result) => {
-                    result.push(ExecutedBlock::new(b.block().clone(), compute_result));
-                }
-                Err(e) => {
-                    return ExecutionResponse {
-                        block_id,
-                        inner: Err(e),
-                    }
-                }
-            }
-        }
+        // the ordered blocks form a chain, execute them together to make better use of the
+        // parallel executor than going block by block
+        let inner = self
+            .execution_proxy
+            .compute_blocks(&blocks, parent_block_id)
+            .await
+            .map(|compute_results| {
+                blocks
+                    .into_iter()
+                    .zip(compute_results)
+                    .map(|(block, compute_result)| ExecutedBlock::new(block, compute_result))
+                    .collect()
+            });
 
-        ExecutionResponse {
-            block_id,
-            inner: Ok(result),
-        }
+        ExecutionResponse { block_id, inner }
     }
 }
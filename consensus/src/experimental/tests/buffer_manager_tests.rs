@@ -94,7 +94,7 @@ pub fn prepare_buffer_manager() -> (
     let (connection_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
 
     let network_sender = ConsensusNetworkSender::new(
-        PeerManagerRequestSender::new(network_reqs_tx),
+        PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
         ConnectionRequestSender::new(connection_reqs_tx),
     );
 
@@ -207,7 +207,9 @@ async fn loopback_commit_vote(
         Some(Event::Message(author, msg)) => {
             let event: UnverifiedEvent = msg.into();
             // verify the message and send the message into self loop
-            msg_tx.push(author, event.verify(verifier).unwrap()).ok();
+            msg_tx
+                .push(author, event.verify(author, verifier).unwrap())
+                .ok();
         }
         _ => {
             panic!("We are expecting a commit vote message.");
@@ -26,6 +26,7 @@ mod network;
 mod network_tests;
 mod pending_votes;
 mod persistent_liveness_storage;
+mod quorum_store;
 mod round_manager;
 mod state_computer;
 mod state_replication;
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters::{COMMITTED_PROPOSALS_IN_WINDOW, COMMITTED_VOTES_IN_WINDOW},
+    counters::{COMMITTED_PROPOSALS_IN_WINDOW, COMMITTED_VOTES_IN_WINDOW, FAILED_ROUNDS_IN_WINDOW},
     liveness::proposer_election::{next, ProposerElection},
 };
 use consensus_types::{
@@ -12,7 +12,10 @@ use consensus_types::{
 use diem_crypto::HashValue;
 use diem_infallible::Mutex;
 use diem_logger::prelude::*;
-use diem_types::block_metadata::{new_block_event_key, NewBlockEvent};
+use diem_types::{
+    account_address::AccountAddress,
+    block_metadata::{new_block_event_key, NewBlockEvent},
+};
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet},
@@ -153,6 +156,152 @@ impl ReputationHeuristic for ActiveInactiveHeuristic {
     }
 }
 
+/// Guesses the leaders of the rounds that failed in the history, i.e. the rounds skipped between
+/// two consecutive committed blocks and the rounds of NIL blocks, and gives the failed weight to
+/// the candidates whose share of failed rounds among the rounds they led is above the threshold.
+/// The history is ordered from the newest block, as returned by `DiemDBBackend`. The committed
+/// blocks don't record the leaders of the failed rounds, so the leader of a failed round is the
+/// one `leader_weights` elects from the part of the history that was committed `exclude_round`
+/// rounds before it, as `LeaderReputation` did back then. Only the blocks within the window are
+/// known, and the failed rounds component of the election made back then isn't reproduced.
+pub struct FailedRoundsHeuristic {
+    author: Author,
+    leader_weights: Box<dyn ReputationHeuristic>,
+    exclude_round: u64,
+    ok_weight: u64,
+    failed_weight: u64,
+    failure_threshold_percent: u64,
+}
+
+impl FailedRoundsHeuristic {
+    pub fn new(
+        author: Author,
+        leader_weights: Box<dyn ReputationHeuristic>,
+        exclude_round: u64,
+        ok_weight: u64,
+        failed_weight: u64,
+        failure_threshold_percent: u64,
+    ) -> Self {
+        Self {
+            author,
+            leader_weights,
+            exclude_round,
+            ok_weight,
+            failed_weight,
+            failure_threshold_percent,
+        }
+    }
+
+    /// Returns the leader of the failed `round`, `older` being the history committed before it.
+    fn failed_round_leader(
+        &self,
+        candidates: &[Author],
+        older: &[NewBlockEvent],
+        round: Round,
+    ) -> Author {
+        let target_round = round.saturating_sub(self.exclude_round);
+        let start = older
+            .iter()
+            .position(|meta| meta.round() <= target_round)
+            .unwrap_or(older.len());
+        let weights = self.leader_weights.get_weights(candidates, &older[start..]);
+        candidates[choose_index(&weights, round)]
+    }
+}
+
+impl ReputationHeuristic for FailedRoundsHeuristic {
+    fn get_weights(&self, candidates: &[Author], history: &[NewBlockEvent]) -> Vec<u64> {
+        let mut proposed_rounds: HashMap<Author, u64> = HashMap::new();
+        for meta in history.iter().filter(|meta| !is_nil_block(meta)) {
+            *proposed_rounds.entry(meta.proposer()).or_default() += 1;
+        }
+
+        let mut failed_rounds: HashMap<Author, u64> = HashMap::new();
+        if !candidates.is_empty() {
+            for (i, meta) in history.iter().enumerate() {
+                let older = &history[i + 1..];
+                let mut failed = vec![];
+                // the round of a NIL block failed
+                if is_nil_block(meta) {
+                    failed.push(meta.round());
+                }
+                // rounds restart from an epoch to the next one
+                if let Some(previous) = older.first().filter(|prev| prev.round() < meta.round()) {
+                    failed.extend(previous.round() + 1..meta.round());
+                }
+                for round in failed {
+                    let leader = self.failed_round_leader(candidates, older, round);
+                    *failed_rounds.entry(leader).or_default() += 1;
+                }
+            }
+        }
+
+        FAILED_ROUNDS_IN_WINDOW.set(failed_rounds.get(&self.author).copied().unwrap_or(0) as i64);
+
+        candidates
+            .iter()
+            .map(|author| {
+                let failed = failed_rounds.get(author).copied().unwrap_or(0);
+                let led = failed + proposed_rounds.get(author).copied().unwrap_or(0);
+                if failed > 0 && failed * 100 > self.failure_threshold_percent * led {
+                    self.failed_weight
+                } else {
+                    self.ok_weight
+                }
+            })
+            .collect()
+    }
+}
+
+/// NIL blocks are committed with the 0x0 proposer, see `BlockMetadata`.
+fn is_nil_block(meta: &NewBlockEvent) -> bool {
+    meta.proposer() == AccountAddress::ZERO
+}
+
+/// Sums the weights of several heuristics, each one multiplied by its factor.
+pub struct CombinedHeuristic {
+    heuristics: Vec<(u64, Box<dyn ReputationHeuristic>)>,
+}
+
+impl CombinedHeuristic {
+    pub fn new(heuristics: Vec<(u64, Box<dyn ReputationHeuristic>)>) -> Self {
+        Self { heuristics }
+    }
+}
+
+impl ReputationHeuristic for CombinedHeuristic {
+    fn get_weights(&self, candidates: &[Author], history: &[NewBlockEvent]) -> Vec<u64> {
+        let mut weights = vec![0; candidates.len()];
+        for (factor, heuristic) in &self.heuristics {
+            let heuristic_weights = heuristic.get_weights(candidates, history);
+            assert_eq!(heuristic_weights.len(), candidates.len());
+            for (w, hw) in weights.iter_mut().zip(heuristic_weights) {
+                *w += factor * hw;
+            }
+        }
+        weights
+    }
+}
+
+/// Chooses the index of the leader of the round, with a probability proportional to its weight,
+/// or uniformly if all the weights are 0.
+fn choose_index(weights: &[u64], round: Round) -> usize {
+    let total_weight: u64 = weights.iter().sum();
+    let mut state = round.to_le_bytes().to_vec();
+    if total_weight == 0 {
+        return (next(&mut state) % weights.len() as u64) as usize;
+    }
+    let chosen_weight = next(&mut state) % total_weight;
+    let mut accumulated_weight = 0;
+    weights
+        .iter()
+        .position(|w| {
+            accumulated_weight += *w;
+            accumulated_weight > chosen_weight
+        })
+        .expect("The chosen weight is below the total weight")
+}
+
 /// Committed history based proposer election implementation that could help bias towards
 /// successful leaders to help improve performance.
 pub struct LeaderReputation {
@@ -184,25 +333,9 @@ impl ProposerElection for LeaderReputation {
     fn get_valid_proposer(&self, round: Round) -> Author {
         let target_round = round.saturating_sub(self.exclude_round);
         let sliding_window = self.backend.get_block_metadata(target_round);
-        let mut weights = self.heuristic.get_weights(&self.proposers, &sliding_window);
+        let weights = self.heuristic.get_weights(&self.proposers, &sliding_window);
         assert_eq!(weights.len(), self.proposers.len());
-        let mut total_weight = 0;
-        for w in &mut weights {
-            total_weight += *w;
-            *w = total_weight;
-        }
-        let mut state = round.to_le_bytes().to_vec();
-        let chosen_weight = next(&mut state) % total_weight;
-        let chosen_index = weights
-            .binary_search_by(|w| {
-                if *w <= chosen_weight {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            })
-            .unwrap_err();
-        self.proposers[chosen_index]
+        self.proposers[choose_index(&weights, round)]
     }
 
     /// This function will return true for at most one proposal per valid proposer for a given round.
@@ -3,7 +3,8 @@
 
 use crate::liveness::{
     leader_reputation::{
-        ActiveInactiveHeuristic, LeaderReputation, MetadataBackend, ReputationHeuristic,
+        ActiveInactiveHeuristic, CombinedHeuristic, FailedRoundsHeuristic, LeaderReputation,
+        MetadataBackend, ReputationHeuristic,
     },
     proposer_election::{next, ProposerElection},
 };
@@ -11,7 +12,10 @@ use consensus_types::{
     block::{block_test_utils::certificate_for_genesis, Block},
     common::{Author, Round},
 };
-use diem_types::{block_metadata::NewBlockEvent, validator_signer::ValidatorSigner};
+use diem_types::{
+    account_address::AccountAddress, block_metadata::NewBlockEvent,
+    validator_signer::ValidatorSigner,
+};
 
 struct MockHistory {
     window_size: usize,
@@ -39,6 +43,25 @@ fn create_block(proposer: Author, voters: Vec<&ValidatorSigner>) -> NewBlockEven
     NewBlockEvent::new(0, proposer, voters.iter().map(|v| v.author()).collect(), 0)
 }
 
+fn create_block_at_round(round: Round, proposer: Author) -> NewBlockEvent {
+    NewBlockEvent::new(round, proposer, vec![], 0)
+}
+
+/// Returns the same weights whatever the history.
+struct FixedWeights(Vec<u64>);
+
+impl ReputationHeuristic for FixedWeights {
+    fn get_weights(&self, _candidates: &[Author], _history: &[NewBlockEvent]) -> Vec<u64> {
+        self.0.clone()
+    }
+}
+
+fn create_proposers(num: u8) -> Vec<Author> {
+    (0..num)
+        .map(|i| ValidatorSigner::random([i; 32]).author())
+        .collect()
+}
+
 #[test]
 fn test_simple_heuristic() {
     let active_weight = 9;
@@ -159,3 +182,172 @@ fn test_api() {
     // good proposal still passes
     assert!(proposer_election.is_valid_proposal(&good_proposal));
 }
+
+#[test]
+fn test_failed_rounds_heuristic() {
+    let ok_weight = 10;
+    let failed_weight = 1;
+    let proposers = create_proposers(4);
+    // proposer 2 is the leader of all the failed rounds
+    let heuristic = FailedRoundsHeuristic::new(
+        proposers[0],
+        Box::new(FixedWeights(vec![0, 0, 1, 0])),
+        0,
+        ok_weight,
+        failed_weight,
+        50,
+    );
+
+    // 1. No history
+    let weights = heuristic.get_weights(&proposers, &[]);
+    assert_eq!(weights, vec![ok_weight; 4]);
+
+    // 2. Rounds 8 and 9 failed, proposer 2 didn't propose anything else
+    let history = vec![
+        create_block_at_round(10, proposers[0]),
+        create_block_at_round(7, proposers[1]),
+        create_block_at_round(6, proposers[0]),
+        create_block_at_round(5, proposers[3]),
+    ];
+    let weights = heuristic.get_weights(&proposers, &history);
+    assert_eq!(
+        weights,
+        vec![ok_weight, ok_weight, failed_weight, ok_weight]
+    );
+
+    // 3. Proposer 2 failed 2 out of the 5 rounds it led, below the threshold
+    let history = vec![
+        create_block_at_round(10, proposers[0]),
+        create_block_at_round(7, proposers[2]),
+        create_block_at_round(6, proposers[2]),
+        create_block_at_round(5, proposers[2]),
+    ];
+    let weights = heuristic.get_weights(&proposers, &history);
+    assert_eq!(weights, vec![ok_weight; 4]);
+
+    // 4. Rounds restart at the epoch boundary, nothing failed
+    let history = vec![
+        create_block_at_round(2, proposers[0]),
+        create_block_at_round(1, proposers[1]),
+        create_block_at_round(30, proposers[0]),
+        create_block_at_round(29, proposers[1]),
+    ];
+    let weights = heuristic.get_weights(&proposers, &history);
+    assert_eq!(weights, vec![ok_weight; 4]);
+
+    // 5. The round of a NIL block failed
+    let history = vec![
+        create_block_at_round(10, proposers[0]),
+        create_block_at_round(9, AccountAddress::ZERO),
+        create_block_at_round(8, proposers[1]),
+        create_block_at_round(7, proposers[3]),
+    ];
+    let weights = heuristic.get_weights(&proposers, &history);
+    assert_eq!(
+        weights,
+        vec![ok_weight, ok_weight, failed_weight, ok_weight]
+    );
+}
+
+#[test]
+fn test_failed_rounds_heuristic_leader_election() {
+    let proposers = create_proposers(4);
+    let leader_weights = vec![1, 2, 3, 4];
+    let heuristic = FailedRoundsHeuristic::new(
+        proposers[0],
+        Box::new(FixedWeights(leader_weights.clone())),
+        0,
+        10,
+        1,
+        0,
+    );
+    let history = vec![
+        create_block_at_round(20, proposers[0]),
+        create_block_at_round(10, proposers[0]),
+    ];
+    let weights = heuristic.get_weights(&proposers, &history);
+
+    // the leaders of the failed rounds are the ones elected with the leader weights
+    let leader_reputation = LeaderReputation::new(
+        proposers.clone(),
+        Box::new(MockHistory::new(2, history)),
+        Box::new(FixedWeights(leader_weights)),
+        0,
+    );
+    let failed_leaders: Vec<_> = (11..20)
+        .map(|round| leader_reputation.get_valid_proposer(round))
+        .collect();
+    for (proposer, weight) in proposers.iter().zip(weights) {
+        let expected = if failed_leaders.contains(proposer) {
+            1
+        } else {
+            10
+        };
+        assert_eq!(weight, expected);
+    }
+}
+
+#[test]
+fn test_failed_rounds_leader_elected_from_older_history() {
+    let proposers = create_proposers(4);
+    let heuristic = |exclude_round| {
+        FailedRoundsHeuristic::new(
+            proposers[0],
+            Box::new(ActiveInactiveHeuristic::new(proposers[0], 1, 0)),
+            exclude_round,
+            10,
+            1,
+            40,
+        )
+    };
+
+    // the leaders of rounds 8 and 9 are elected from the blocks committed before them, in which
+    // proposer 1 wasn't active
+    let history = vec![
+        create_block_at_round(10, proposers[1]),
+        create_block_at_round(7, proposers[0]),
+    ];
+    assert_eq!(
+        heuristic(0).get_weights(&proposers, &history),
+        vec![1, 10, 10, 10]
+    );
+
+    // and only from the blocks committed exclude_round rounds before them
+    let history = vec![
+        create_block_at_round(9, proposers[1]),
+        create_block_at_round(7, proposers[0]),
+        create_block_at_round(6, proposers[3]),
+        create_block_at_round(5, proposers[2]),
+    ];
+    assert_eq!(
+        heuristic(3).get_weights(&proposers, &history),
+        vec![10, 10, 1, 10]
+    );
+}
+
+#[test]
+fn test_zero_weights() {
+    let proposers = create_proposers(4);
+    let leader_reputation = LeaderReputation::new(
+        proposers.clone(),
+        Box::new(MockHistory::new(1, vec![])),
+        Box::new(FixedWeights(vec![0; 4])),
+        0,
+    );
+    // falls back to a uniform choice
+    for round in 1..10 {
+        assert!(proposers.contains(&leader_reputation.get_valid_proposer(round)));
+    }
+}
+
+#[test]
+fn test_combined_heuristic() {
+    let proposers = create_proposers(4);
+    let heuristics: Vec<(u64, Box<dyn ReputationHeuristic>)> = vec![
+        (2, Box::new(FixedWeights(vec![1, 2, 3, 4]))),
+        (1, Box::new(FixedWeights(vec![10, 0, 0, 0]))),
+    ];
+    let heuristic = CombinedHeuristic::new(heuristics);
+    let weights = heuristic.get_weights(&proposers, &[]);
+    assert_eq!(weights, vec![12, 4, 6, 8]);
+}
@@ -2,19 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_storage::BlockReader, state_replication::TxnManager, util::time_service::TimeService,
+    block_storage::BlockReader, quorum_store::batch_store::BatchStore,
+    state_replication::TxnManager, util::time_service::TimeService,
 };
 use anyhow::{bail, ensure, format_err, Context};
 use consensus_types::{
     block::Block,
     block_data::BlockData,
     common::{Author, Round},
+    experimental::quorum_store::ProofOfStore,
     quorum_cert::QuorumCert,
 };
 
 use diem_infallible::Mutex;
 use futures::future::BoxFuture;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 #[cfg(test)]
 #[path = "proposal_generator_test.rs"]
@@ -25,7 +27,8 @@ mod proposal_generator_test;
 /// round.
 /// ProposalGenerator is the one choosing the branch to extend:
 /// - round is given by the caller (typically determined by RoundState).
-/// The transactions for the proposed block are delivered by TxnManager.
+/// The transactions for the proposed block are delivered by TxnManager, or are referred to by the
+/// proofs of store of the BatchStore when the quorum store is enabled.
 ///
 /// TxnManager should be aware of the pending transactions in the branch that it is extending,
 /// such that it will filter them out to avoid transaction duplication.
@@ -41,6 +44,9 @@ pub struct ProposalGenerator {
     time_service: Arc<dyn TimeService>,
     // Max number of transactions to be added to a proposed block.
     max_block_size: u64,
+    // Delivers the proofs of store to propose instead of the transactions, if the quorum store is
+    // enabled.
+    batch_store: Option<Arc<BatchStore>>,
     // Last round that a proposal was generated
     last_round_generated: Mutex<Round>,
 }
@@ -52,6 +58,7 @@ impl ProposalGenerator {
         txn_manager: Arc<dyn TxnManager>,
         time_service: Arc<dyn TimeService>,
         max_block_size: u64,
+        batch_store: Option<Arc<BatchStore>>,
     ) -> Self {
         Self {
             author,
@@ -59,6 +66,7 @@ impl ProposalGenerator {
             txn_manager,
             time_service,
             max_block_size,
+            batch_store,
             last_round_generated: Mutex::new(0),
         }
     }
@@ -114,6 +122,29 @@ impl ProposalGenerator {
             // deliver the commit proof to others without delay.
             pending_blocks.push(self.block_store.commit_root());
 
+            // All proposed blocks in a branch are guaranteed to have increasing timestamps
+            // since their predecessor block will not be added to the BlockStore until
+            // the local time exceeds it.
+            let timestamp = self.time_service.get_current_timestamp();
+
+            if let Some(batch_store) = &self.batch_store {
+                // Exclude the batches of the pending blocks, the same way as their transactions.
+                let exclude_digests: HashSet<_> = pending_blocks
+                    .iter()
+                    .flat_map(|block| block.block().proofs())
+                    .flatten()
+                    .map(ProofOfStore::digest)
+                    .collect();
+                let proofs = batch_store.pull_proofs(self.max_block_size, &exclude_digests);
+                return Ok(BlockData::new_quorum_store_proposal(
+                    proofs,
+                    self.author,
+                    round,
+                    timestamp.as_micros() as u64,
+                    hqc.as_ref().clone(),
+                ));
+            }
+
             // Exclude all the pending transactions: these are all the ancestors of
             // parent (including) up to the root (including).
             let exclude_payload: Vec<&Vec<_>> = pending_blocks
@@ -128,11 +159,6 @@ impl ProposalGenerator {
                 .iter()
                 .any(|block| !block.payload().map_or(true, |txns| txns.is_empty()));
 
-            // All proposed blocks in a branch are guaranteed to have increasing timestamps
-            // since their predecessor block will not be added to the BlockStore until
-            // the local time exceeds it.
-            let timestamp = self.time_service.get_current_timestamp();
-
             let payload = self
                 .txn_manager
                 .pull_txns(
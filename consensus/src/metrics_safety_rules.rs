@@ -4,6 +4,7 @@
 use crate::persistent_liveness_storage::PersistentLivenessStorage;
 use consensus_types::{
     block_data::BlockData,
+    experimental::quorum_store::BatchInfo,
     timeout::Timeout,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote::Vote,
@@ -143,6 +144,10 @@ impl TSafetyRules for MetricsSafetyRules {
             )
         })
     }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        self.retry(|inner| monitor!("safety_rules", inner.sign_batch_info(batch_info)))
+    }
 }
 
 #[cfg(test)]
@@ -151,6 +156,7 @@ mod tests {
     use claim::{assert_matches, assert_ok};
     use consensus_types::{
         block_data::BlockData,
+        experimental::quorum_store::BatchInfo,
         timeout::Timeout,
         timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
         vote::Vote,
@@ -241,6 +247,10 @@ mod tests {
         ) -> Result<Ed25519Signature, Error> {
             unimplemented!()
         }
+
+        fn sign_batch_info(&mut self, _: &BatchInfo) -> Result<Ed25519Signature, Error> {
+            unimplemented!()
+        }
     }
 
     #[test]
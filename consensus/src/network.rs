@@ -12,7 +12,10 @@ use channel::{self, diem_channel, message_queues::QueueStyle};
 use consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse, MAX_BLOCKS_PER_REQUEST},
     common::Author,
-    experimental::commit_decision::CommitDecision,
+    experimental::{
+        commit_decision::CommitDecision,
+        quorum_store::{Batch, BatchRequest},
+    },
     sync_info::SyncInfo,
     vote_msg::VoteMsg,
 };
@@ -35,6 +38,9 @@ use std::{
     time::Duration,
 };
 
+/// Max number of pending quorum store messages and batch requests from each peer.
+const QUORUM_STORE_CHANNEL_SIZE: usize = 100;
+
 /// The block retrieval request is used internally for implementing RPC: the callback is executed
 /// for carrying the response
 #[derive(Debug)]
@@ -44,6 +50,15 @@ pub struct IncomingBlockRetrievalRequest {
     pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
 }
 
+/// The batch request is used internally for implementing RPC: the callback is executed for
+/// carrying the response
+#[derive(Debug)]
+pub struct IncomingBatchRequest {
+    pub req: BatchRequest,
+    pub protocol: ProtocolId,
+    pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+}
+
 /// Just a convenience struct to keep all the network proxy receiving queues in one place.
 /// Will be returned by the NetworkTask upon startup.
 pub struct NetworkReceivers {
@@ -53,6 +68,10 @@ pub struct NetworkReceivers {
         (AccountAddress, ConsensusMsg),
     >,
     pub block_retrieval: diem_channel::Receiver<AccountAddress, IncomingBlockRetrievalRequest>,
+    /// Provide a FIFO buffer for each Author: batches must not be dropped in favor of newer ones
+    pub quorum_store_messages:
+        diem_channel::Receiver<AccountAddress, (AccountAddress, ConsensusMsg)>,
+    pub batch_retrieval: diem_channel::Receiver<AccountAddress, IncomingBatchRequest>,
 }
 
 /// Implements the actual networking support for all consensus messaging.
@@ -114,6 +133,29 @@ impl NetworkSender {
         Ok(response)
     }
 
+    /// Tries to retrieve the batch with the given digest from the given peer.
+    pub async fn request_batch(
+        &self,
+        request: BatchRequest,
+        from: Author,
+        timeout: Duration,
+    ) -> anyhow::Result<Batch> {
+        ensure!(from != self.author, "Retrieve batch from self");
+        let msg = ConsensusMsg::BatchRequest(Box::new(request.clone()));
+        let response_msg = monitor!(
+            "batch_retrieval",
+            self.network_sender.send_rpc(from, msg, timeout).await?
+        );
+        let response = match response_msg {
+            ConsensusMsg::BatchResponse(resp) => *resp,
+            _ => return Err(anyhow!("Invalid response to request")),
+        };
+        response.verify(&request)?;
+        Ok(response
+            .into_batch()
+            .expect("Verified response carries the batch"))
+    }
+
     /// Tries to send the given msg to all the participants.
     ///
     /// The future is fulfilled as soon as the message put into the mpsc channel to network
@@ -202,6 +244,8 @@ pub struct NetworkTask {
         (AccountAddress, ConsensusMsg),
     >,
     block_retrieval_tx: diem_channel::Sender<AccountAddress, IncomingBlockRetrievalRequest>,
+    quorum_store_messages_tx: diem_channel::Sender<AccountAddress, (AccountAddress, ConsensusMsg)>,
+    batch_retrieval_tx: diem_channel::Sender<AccountAddress, IncomingBatchRequest>,
     all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
 }
 
@@ -218,16 +262,30 @@ impl NetworkTask {
             1,
             Some(&counters::BLOCK_RETRIEVAL_CHANNEL_MSGS),
         );
+        let (quorum_store_messages_tx, quorum_store_messages) = diem_channel::new(
+            QueueStyle::FIFO,
+            QUORUM_STORE_CHANNEL_SIZE,
+            Some(&counters::QUORUM_STORE_CHANNEL_MSGS),
+        );
+        let (batch_retrieval_tx, batch_retrieval) = diem_channel::new(
+            QueueStyle::FIFO,
+            QUORUM_STORE_CHANNEL_SIZE,
+            Some(&counters::BATCH_RETRIEVAL_CHANNEL_MSGS),
+        );
         let all_events = Box::new(select(network_events, self_receiver));
         (
             NetworkTask {
                 consensus_messages_tx,
                 block_retrieval_tx,
+                quorum_store_messages_tx,
+                batch_retrieval_tx,
                 all_events,
             },
             NetworkReceivers {
                 consensus_messages,
                 block_retrieval,
+                quorum_store_messages,
+                batch_retrieval,
             },
         )
     }
@@ -235,6 +293,19 @@ impl NetworkTask {
     pub async fn start(mut self) {
         while let Some(message) = self.all_events.next().await {
             match message {
+                Event::Message(
+                    peer_id,
+                    msg @ (ConsensusMsg::BatchMsg(_)
+                    | ConsensusMsg::SignedBatchInfoMsg(_)
+                    | ConsensusMsg::ProofOfStoreMsg(_)),
+                ) => {
+                    if let Err(e) = self.quorum_store_messages_tx.push(peer_id, (peer_id, msg)) {
+                        warn!(
+                            remote_peer = peer_id,
+                            error = ?e, "Error pushing quorum store msg",
+                        );
+                    }
+                }
                 Event::Message(peer_id, msg) => {
                     if let Err(e) = self
                         .consensus_messages_tx
@@ -271,6 +342,16 @@ impl NetworkTask {
                             warn!(error = ?e, "diem channel closed");
                         }
                     }
+                    ConsensusMsg::BatchRequest(request) => {
+                        let req_with_callback = IncomingBatchRequest {
+                            req: *request,
+                            protocol,
+                            response_sender: callback,
+                        };
+                        if let Err(e) = self.batch_retrieval_tx.push(peer_id, req_with_callback) {
+                            warn!(error = ?e, "diem channel closed");
+                        }
+                    }
                     _ => {
                         warn!(remote_peer = peer_id, "Unexpected msg: {:?}", msg);
                         continue;
@@ -10,7 +10,11 @@ use channel::{diem_channel, message_queues::QueueStyle};
 use consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
     epoch_retrieval::EpochRetrievalRequest,
-    experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
+    experimental::{
+        commit_decision::CommitDecision,
+        commit_vote::CommitVote,
+        quorum_store::{Batch, BatchRequest, BatchResponse, ProofOfStore, SignedBatchInfo},
+    },
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     vote_msg::VoteMsg,
@@ -62,6 +66,17 @@ pub enum ConsensusMsg {
     /// than 2f + 1 signatures on the commit proposal. This part is not on the critical path, but
     /// it can save slow machines to quickly confirm the execution result.
     CommitDecisionMsg(Box<CommitDecision>),
+    /// Batch of transactions disseminated by a validator ahead of the proposals referring to it.
+    BatchMsg(Box<Batch>),
+    /// Signature of a validator storing a batch, sent back to the author of the batch.
+    SignedBatchInfoMsg(Box<SignedBatchInfo>),
+    /// Signatures of a quorum of validators storing a batch, broadcast by the author of the
+    /// batch so that any leader can propose it.
+    ProofOfStoreMsg(Box<ProofOfStore>),
+    /// RPC to get a batch from a validator that signed it.
+    BatchRequest(Box<BatchRequest>),
+    /// Carries the requested batch, if the validator stores it.
+    BatchResponse(Box<BatchResponse>),
 }
 
 /// The interface from Network to Consensus layer.
@@ -87,9 +102,14 @@ pub struct ConsensusNetworkSender {
 }
 
 /// Supported protocols in preferred order (from highest priority to lowest).
-pub const RPC: &[ProtocolId] = &[ProtocolId::ConsensusRpcJson, ProtocolId::ConsensusRpcBcs];
+pub const RPC: &[ProtocolId] = &[
+    ProtocolId::ConsensusRpcCompressed,
+    ProtocolId::ConsensusRpcJson,
+    ProtocolId::ConsensusRpcBcs,
+];
 /// Supported protocols in preferred order (from highest priority to lowest).
 pub const DIRECT_SEND: &[ProtocolId] = &[
+    ProtocolId::ConsensusDirectSendCompressed,
     ProtocolId::ConsensusDirectSendJson,
     ProtocolId::ConsensusDirectSendBcs,
 ];
@@ -227,7 +227,7 @@ impl NetworkPlayground {
         // copy message data
         let msg_copy = match &msg_notif {
             PeerManagerNotification::RecvMessage(src, msg) => {
-                let msg: ConsensusMsg = msg.to_message().unwrap();
+                let msg: ConsensusMsg = msg.to_message(network::constants::MAX_FRAME_SIZE).unwrap();
                 (*src, msg)
             }
             msg_notif => panic!(
@@ -274,7 +274,7 @@ impl NetworkPlayground {
 
             let dst_twin_ids = self.get_twin_ids(dst);
             for (idx, dst_twin_id) in dst_twin_ids.iter().enumerate() {
-                let consensus_msg = msg.to_message().unwrap();
+                let consensus_msg = msg.to_message(network::constants::MAX_FRAME_SIZE).unwrap();
 
                 // Deliver and copy message if it's not dropped
                 if !self.is_message_dropped(&src_twin_id, dst_twin_id, consensus_msg) {
@@ -390,7 +390,7 @@ impl NetworkPlayground {
             for dst_twin_id in dst_twin_ids.iter() {
                 let msg_notif =
                     PeerManagerNotification::RecvMessage(src_twin_id.author, msg.clone());
-                let consensus_msg = msg.to_message().unwrap();
+                let consensus_msg = msg.to_message(network::constants::MAX_FRAME_SIZE).unwrap();
 
                 // Deliver and copy message it if it's not dropped
                 if !self.is_message_dropped(&src_twin_id, dst_twin_id, consensus_msg) {
@@ -575,11 +575,15 @@ mod tests {
                 ],
             );
             let mut network_sender = ConsensusNetworkSender::new(
-                PeerManagerRequestSender::new(network_reqs_tx),
+                PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
                 ConnectionRequestSender::new(connection_reqs_tx),
             );
             network_sender.initialize(peer_metadata_storage.clone());
-            let network_events = ConsensusNetworkEvents::new(consensus_rx, conn_status_rx);
+            let network_events = ConsensusNetworkEvents::new(
+                consensus_rx,
+                conn_status_rx,
+                network::constants::MAX_FRAME_SIZE,
+            );
 
             let twin_id = TwinId {
                 id: peer_id,
@@ -663,7 +667,7 @@ mod tests {
             let (_conn_mgr_reqs_tx, conn_mgr_reqs_rx) = channel::new_test(8);
             let (_, conn_status_rx) = conn_notifs_channel::new();
             let mut network_sender = ConsensusNetworkSender::new(
-                PeerManagerRequestSender::new(network_reqs_tx),
+                PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
                 ConnectionRequestSender::new(connection_reqs_tx),
             );
 
@@ -677,7 +681,11 @@ mod tests {
                 ],
             );
             network_sender.initialize(peer_metadata_storage.clone());
-            let network_events = ConsensusNetworkEvents::new(consensus_rx, conn_status_rx);
+            let network_events = ConsensusNetworkEvents::new(
+                consensus_rx,
+                conn_status_rx,
+                network::constants::MAX_FRAME_SIZE,
+            );
 
             let twin_id = TwinId {
                 id: peer_id,
@@ -751,8 +759,11 @@ mod tests {
         let (peer_mgr_notifs_tx, peer_mgr_notifs_rx) = diem_channel::new(QueueStyle::FIFO, 8, None);
         let (connection_notifs_tx, connection_notifs_rx) =
             diem_channel::new(QueueStyle::FIFO, 8, None);
-        let consensus_network_events =
-            ConsensusNetworkEvents::new(peer_mgr_notifs_rx, connection_notifs_rx);
+        let consensus_network_events = ConsensusNetworkEvents::new(
+            peer_mgr_notifs_rx,
+            connection_notifs_rx,
+            network::constants::MAX_FRAME_SIZE,
+        );
         let (self_sender, self_receiver) = channel::new_test(8);
 
         let (network_task, mut network_receivers) =
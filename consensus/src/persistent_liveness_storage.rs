@@ -8,7 +8,6 @@ use consensus_types::{
     timeout_2chain::TwoChainTimeoutCertificate, timeout_certificate::TimeoutCertificate,
     vote::Vote, vote_data::VoteData,
 };
-use diem_config::config::NodeConfig;
 use diem_crypto::{ed25519::Ed25519Signature, HashValue};
 use diem_logger::prelude::*;
 use diem_types::{
@@ -311,8 +310,7 @@ pub struct StorageWriteProxy {
 }
 
 impl StorageWriteProxy {
-    pub fn new(config: &NodeConfig, diem_db: Arc<dyn DbReader>) -> Self {
-        let db = Arc::new(ConsensusDB::new(config.storage.dir()));
+    pub fn new(db: Arc<ConsensusDB>, diem_db: Arc<dyn DbReader>) -> Self {
         StorageWriteProxy { db, diem_db }
     }
 }
@@ -0,0 +1,238 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters, network::NetworkSender};
+use anyhow::{bail, format_err};
+use consensus_types::{
+    common::{Author, Payload},
+    experimental::quorum_store::{Batch, BatchRequest, ProofOfStore},
+};
+use diem_config::config::QuorumStoreConfig;
+use diem_crypto::HashValue;
+use diem_infallible::Mutex;
+use diem_logger::prelude::*;
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+/// The number of committed batch digests remembered, so that late proofs of store of committed
+/// batches are not proposed again.
+const MAX_COMMITTED_DIGESTS: usize = 10_000;
+
+/// Stores the batches disseminated by the validators and the proofs of store available to be
+/// proposed. It outlives the epochs: execution resolves the proofs of store of the blocks to
+/// transactions through it, requesting the batches it misses from the validators that signed
+/// them.
+pub struct BatchStore {
+    inner: Mutex<BatchStoreInner>,
+    max_batches_per_author: usize,
+    batch_request_timeout: Duration,
+}
+
+#[derive(Default)]
+struct BatchStoreInner {
+    epoch: u64,
+    batches: HashMap<HashValue, Batch>,
+    batches_per_author: HashMap<Author, usize>,
+    // Proofs of store not committed yet, in the order they were received.
+    proofs: Vec<ProofOfStore>,
+    // The latest committed batches of the current epoch, oldest first.
+    committed: HashSet<HashValue>,
+    committed_order: VecDeque<HashValue>,
+    // Sends the batch requests of the current epoch.
+    network_sender: Option<NetworkSender>,
+}
+
+impl BatchStoreInner {
+    fn insert_batch(&mut self, batch: Batch) {
+        if let Entry::Vacant(entry) = self.batches.entry(batch.digest()) {
+            *self.batches_per_author.entry(batch.author()).or_default() += 1;
+            entry.insert(batch);
+        }
+    }
+
+    fn remove_batch(&mut self, digest: &HashValue) {
+        if let Some(batch) = self.batches.remove(digest) {
+            if let Some(num_batches) = self.batches_per_author.get_mut(&batch.author()) {
+                *num_batches = num_batches.saturating_sub(1);
+            }
+        }
+    }
+
+    fn mark_committed(&mut self, digest: HashValue) {
+        if self.committed.insert(digest) {
+            self.committed_order.push_back(digest);
+            if self.committed_order.len() > MAX_COMMITTED_DIGESTS {
+                if let Some(oldest) = self.committed_order.pop_front() {
+                    self.committed.remove(&oldest);
+                }
+            }
+        }
+    }
+}
+
+impl BatchStore {
+    pub fn new(config: &QuorumStoreConfig) -> Self {
+        Self {
+            inner: Mutex::new(BatchStoreInner::default()),
+            max_batches_per_author: config.max_batches_per_author,
+            batch_request_timeout: Duration::from_millis(config.batch_request_timeout_ms),
+        }
+    }
+
+    /// Drops the batches and proofs of the previous epochs.
+    pub fn start_epoch(&self, epoch: u64, network_sender: NetworkSender) {
+        let mut inner = self.inner.lock();
+        inner.epoch = epoch;
+        inner.batches.retain(|_, batch| batch.epoch() == epoch);
+        let mut batches_per_author = HashMap::new();
+        for batch in inner.batches.values() {
+            *batches_per_author.entry(batch.author()).or_default() += 1;
+        }
+        inner.batches_per_author = batches_per_author;
+        inner.proofs.retain(|proof| proof.epoch() == epoch);
+        inner.committed.clear();
+        inner.committed_order.clear();
+        inner.network_sender = Some(network_sender);
+        counters::PENDING_PROOFS_OF_STORE.set(inner.proofs.len() as i64);
+    }
+
+    /// Stores a batch disseminated in the current epoch, returns whether it is stored. The
+    /// uncommitted batches of each author are bounded, so that a validator cannot fill the
+    /// memory of the others.
+    pub fn insert_batch(&self, batch: Batch) -> bool {
+        let mut inner = self.inner.lock();
+        if batch.epoch() != inner.epoch || inner.committed.contains(&batch.digest()) {
+            return false;
+        }
+        if inner.batches.contains_key(&batch.digest()) {
+            return true;
+        }
+        let num_batches = inner
+            .batches_per_author
+            .get(&batch.author())
+            .copied()
+            .unwrap_or(0);
+        if num_batches >= self.max_batches_per_author {
+            return false;
+        }
+        inner.insert_batch(batch);
+        true
+    }
+
+    pub fn get_batch(&self, digest: &HashValue) -> Option<Batch> {
+        self.inner.lock().batches.get(digest).cloned()
+    }
+
+    pub fn contains_batch(&self, digest: &HashValue) -> bool {
+        self.inner.lock().batches.contains_key(digest)
+    }
+
+    /// Makes the batch of a proof of store of the current epoch available to be proposed, unless
+    /// the batch was committed already.
+    pub fn insert_proof(&self, proof: ProofOfStore) {
+        let mut inner = self.inner.lock();
+        if proof.epoch() != inner.epoch
+            || inner.committed.contains(&proof.digest())
+            || inner
+                .proofs
+                .iter()
+                .any(|pending| pending.digest() == proof.digest())
+        {
+            return;
+        }
+        inner.proofs.push(proof);
+        counters::PENDING_PROOFS_OF_STORE.set(inner.proofs.len() as i64);
+    }
+
+    /// Returns the oldest proofs of store that are not excluded, up to `max_txns` transactions in
+    /// total.
+    pub fn pull_proofs(&self, max_txns: u64, exclude: &HashSet<HashValue>) -> Vec<ProofOfStore> {
+        let inner = self.inner.lock();
+        let mut num_txns = 0;
+        let mut proofs = vec![];
+        for proof in &inner.proofs {
+            if exclude.contains(&proof.digest()) {
+                continue;
+            }
+            if num_txns + proof.info().num_txns() > max_txns {
+                break;
+            }
+            num_txns += proof.info().num_txns();
+            proofs.push(proof.clone());
+        }
+        proofs
+    }
+
+    /// Drops the committed batches and their proofs of store.
+    pub fn commit(&self, digests: &[HashValue]) {
+        let mut inner = self.inner.lock();
+        for digest in digests {
+            inner.remove_batch(digest);
+            inner.mark_committed(*digest);
+        }
+        let digests: HashSet<_> = digests.iter().collect();
+        inner
+            .proofs
+            .retain(|proof| !digests.contains(&proof.digest()));
+        counters::PENDING_PROOFS_OF_STORE.set(inner.proofs.len() as i64);
+    }
+
+    /// Drops the batches and proofs of store of the current epoch after state sync. The batches
+    /// committed by state sync are not known, and the others are disseminated again by their
+    /// authors, whose pending batches are the ones still in the store.
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock();
+        inner.batches.clear();
+        inner.batches_per_author.clear();
+        inner.proofs.clear();
+        counters::PENDING_PROOFS_OF_STORE.set(0);
+    }
+
+    /// Resolves proofs of store to the transactions of their batches, in order. The batches
+    /// missing locally are requested from the validators that signed them.
+    pub async fn get_payload(&self, proofs: &[ProofOfStore]) -> anyhow::Result<Payload> {
+        let mut payload = vec![];
+        for proof in proofs {
+            let batch = match self.get_batch(&proof.digest()) {
+                Some(batch) => batch,
+                None => self.fetch_batch(proof).await?,
+            };
+            payload.extend(batch.txns().iter().cloned());
+        }
+        Ok(payload)
+    }
+
+    async fn fetch_batch(&self, proof: &ProofOfStore) -> anyhow::Result<Batch> {
+        let network_sender = self
+            .inner
+            .lock()
+            .network_sender
+            .clone()
+            .ok_or_else(|| format_err!("No network to request batch {}", proof.digest()))?;
+        for signer in proof.signers() {
+            let request = BatchRequest::new(proof.digest());
+            match network_sender
+                .request_batch(request, *signer, self.batch_request_timeout)
+                .await
+            {
+                Ok(batch) => {
+                    counters::BATCHES_FETCHED.inc();
+                    // the batch is certified, it is stored regardless of the author's bound
+                    self.inner.lock().insert_batch(batch.clone());
+                    return Ok(batch);
+                }
+                Err(e) => {
+                    warn!(
+                        remote_peer = *signer,
+                        error = ?e,
+                        "Failed to fetch batch {}",
+                        proof.digest()
+                    );
+                }
+            }
+        }
+        bail!("Failed to fetch batch {} from its signers", proof.digest())
+    }
+}
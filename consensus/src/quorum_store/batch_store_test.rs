@@ -0,0 +1,263 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network::{NetworkSender, NetworkTask},
+    network_interface::{ConsensusMsg, ConsensusNetworkEvents, ConsensusNetworkSender},
+    network_tests::{NetworkPlayground, TwinId},
+    quorum_store::batch_store::BatchStore,
+    round_manager::{UnverifiedEvent, VerifiedEvent},
+    test_utils::{consensus_runtime, timed_block_on},
+};
+use channel::{diem_channel, message_queues::QueueStyle};
+use consensus_types::{
+    block::block_test_utils::random_payload,
+    experimental::quorum_store::{Batch, BatchResponse, ProofOfStore, SignedBatchInfo},
+};
+use diem_config::{config::QuorumStoreConfig, network_id::NetworkId};
+use diem_types::{
+    validator_signer::ValidatorSigner,
+    validator_verifier::{random_validator_verifier, ValidatorVerifier},
+};
+use futures::StreamExt;
+use network::{
+    application::storage::PeerMetadataStorage,
+    peer_manager::{conn_notifs_channel, ConnectionRequestSender, PeerManagerRequestSender},
+    protocols::wire::handshake::v1::ProtocolIdSet,
+    transport::ConnectionMetadata,
+    ProtocolId,
+};
+use std::{collections::HashSet, iter::FromIterator, sync::Arc};
+
+fn mock_network_sender(signer: &ValidatorSigner, verifier: ValidatorVerifier) -> NetworkSender {
+    let (network_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
+    let (connection_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
+    let network_sender = ConsensusNetworkSender::new(
+        PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
+        ConnectionRequestSender::new(connection_reqs_tx),
+    );
+    let (self_sender, _) = channel::new_test(8);
+    NetworkSender::new(signer.author(), network_sender, self_sender, verifier)
+}
+
+fn proof_of_store(batch: &Batch, signers: &[ValidatorSigner]) -> ProofOfStore {
+    let signatures = signers
+        .iter()
+        .map(|signer| {
+            let signed_info = SignedBatchInfo::new(batch.info(), signer);
+            (signed_info.signer(), signed_info.signature().clone())
+        })
+        .collect();
+    ProofOfStore::new(batch.info(), signatures)
+}
+
+#[test]
+fn test_verify_batch_sender() {
+    let (signers, verifier) = random_validator_verifier(2, None, false);
+    let batch = Batch::new(1, signers[0].author(), 0, random_payload(1));
+    let verify = |sender| {
+        UnverifiedEvent::from(ConsensusMsg::BatchMsg(Box::new(batch.clone())))
+            .verify(sender, &verifier)
+    };
+
+    assert!(matches!(
+        verify(signers[0].author()),
+        Ok(VerifiedEvent::Batch(_))
+    ));
+    // relayed by another validator
+    assert!(verify(signers[1].author()).is_err());
+
+    // from outside of the validator set
+    let outsider = ValidatorSigner::random(None);
+    let batch = Batch::new(1, outsider.author(), 0, random_payload(1));
+    assert!(
+        UnverifiedEvent::from(ConsensusMsg::BatchMsg(Box::new(batch)))
+            .verify(outsider.author(), &verifier)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_batches_per_author() {
+    let (signers, verifier) = random_validator_verifier(2, None, false);
+    let batch_store = BatchStore::new(&QuorumStoreConfig {
+        max_batches_per_author: 2,
+        ..QuorumStoreConfig::default()
+    });
+    batch_store.start_epoch(1, mock_network_sender(&signers[0], verifier));
+    let author = signers[0].author();
+
+    let batches: Vec<_> = (0..3)
+        .map(|batch_id| Batch::new(1, author, batch_id, random_payload(2)))
+        .collect();
+    assert!(batch_store.insert_batch(batches[0].clone()));
+    assert!(batch_store.insert_batch(batches[1].clone()));
+    // the same batch is not counted twice
+    assert!(batch_store.insert_batch(batches[1].clone()));
+    assert!(!batch_store.insert_batch(batches[2].clone()));
+    // the bound is per author
+    assert!(batch_store.insert_batch(Batch::new(1, signers[1].author(), 0, random_payload(2))));
+    // batches of other epochs are not stored
+    assert!(!batch_store.insert_batch(Batch::new(2, signers[1].author(), 1, random_payload(2))));
+
+    batch_store.commit(&[batches[0].digest()]);
+    assert!(!batch_store.contains_batch(&batches[0].digest()));
+    assert!(batch_store.insert_batch(batches[2].clone()));
+    assert_eq!(
+        batch_store.get_batch(&batches[2].digest()),
+        Some(batches[2].clone())
+    );
+}
+
+#[test]
+fn test_pull_proofs() {
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let batch_store = BatchStore::new(&QuorumStoreConfig::default());
+    batch_store.start_epoch(1, mock_network_sender(&signers[0], verifier));
+
+    let proofs: Vec<_> = (0..3)
+        .map(|batch_id| {
+            let batch = Batch::new(1, signers[0].author(), batch_id, random_payload(2));
+            proof_of_store(&batch, &signers[..3])
+        })
+        .collect();
+    for proof in &proofs {
+        batch_store.insert_proof(proof.clone());
+    }
+    // duplicated proofs and proofs of other epochs are ignored
+    batch_store.insert_proof(proofs[0].clone());
+    let other_epoch = Batch::new(2, signers[0].author(), 0, random_payload(2));
+    batch_store.insert_proof(proof_of_store(&other_epoch, &signers[..3]));
+
+    assert_eq!(
+        batch_store.pull_proofs(4, &HashSet::new()),
+        proofs[..2].to_vec()
+    );
+    assert_eq!(batch_store.pull_proofs(100, &HashSet::new()), proofs);
+    let exclude = HashSet::from_iter(vec![proofs[0].digest()]);
+    assert_eq!(batch_store.pull_proofs(4, &exclude), proofs[1..].to_vec());
+
+    batch_store.commit(&[proofs[1].digest()]);
+    assert_eq!(
+        batch_store.pull_proofs(100, &HashSet::new()),
+        vec![proofs[0].clone(), proofs[2].clone()]
+    );
+
+    // a new epoch drops the proofs of the previous one
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    batch_store.start_epoch(2, mock_network_sender(&signers[0], verifier));
+    assert!(batch_store.pull_proofs(100, &HashSet::new()).is_empty());
+}
+
+#[test]
+fn test_committed_batches() {
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let batch_store = BatchStore::new(&QuorumStoreConfig {
+        max_batches_per_author: 1,
+        ..QuorumStoreConfig::default()
+    });
+    batch_store.start_epoch(1, mock_network_sender(&signers[0], verifier));
+    let author = signers[0].author();
+
+    let batch = Batch::new(1, author, 0, random_payload(2));
+    let proof = proof_of_store(&batch, &signers[..3]);
+    assert!(batch_store.insert_batch(batch.clone()));
+    batch_store.insert_proof(proof.clone());
+    batch_store.commit(&[batch.digest()]);
+
+    // a late proof or batch doesn't bring the committed batch back
+    batch_store.insert_proof(proof);
+    assert!(batch_store.pull_proofs(100, &HashSet::new()).is_empty());
+    assert!(!batch_store.insert_batch(batch));
+
+    // state sync drops all the pending batches and proofs
+    let batch = Batch::new(1, author, 1, random_payload(2));
+    assert!(batch_store.insert_batch(batch.clone()));
+    batch_store.insert_proof(proof_of_store(&batch, &signers[..3]));
+    assert!(!batch_store.insert_batch(Batch::new(1, author, 2, random_payload(2))));
+    batch_store.reset();
+    assert!(!batch_store.contains_batch(&batch.digest()));
+    assert!(batch_store.pull_proofs(100, &HashSet::new()).is_empty());
+    assert!(batch_store.insert_batch(Batch::new(1, author, 2, random_payload(2))));
+}
+
+#[test]
+fn test_fetch_missing_batch() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let (signers, verifier) = random_validator_verifier(2, None, false);
+    let peer_metadata_storage = PeerMetadataStorage::new(&[NetworkId::Validator]);
+    let mut senders = vec![];
+    let mut receivers = vec![];
+    for (id, signer) in signers.iter().enumerate() {
+        let (network_reqs_tx, network_reqs_rx) = diem_channel::new(QueueStyle::FIFO, 8, None);
+        let (connection_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
+        let (consensus_tx, consensus_rx) = diem_channel::new(QueueStyle::FIFO, 8, None);
+        let (_conn_mgr_reqs_tx, conn_mgr_reqs_rx) = channel::new_test(8);
+        let (_, conn_status_rx) = conn_notifs_channel::new();
+        let mut conn_meta = ConnectionMetadata::mock(signer.author());
+        conn_meta.application_protocols = ProtocolIdSet::from_iter(&[
+            ProtocolId::ConsensusDirectSendBcs,
+            ProtocolId::ConsensusRpcBcs,
+        ]);
+        peer_metadata_storage.insert_connection(NetworkId::Validator, conn_meta);
+        let mut network_sender = ConsensusNetworkSender::new(
+            PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
+            ConnectionRequestSender::new(connection_reqs_tx),
+        );
+        network_sender.initialize(peer_metadata_storage.clone());
+        let network_events = ConsensusNetworkEvents::new(
+            consensus_rx,
+            conn_status_rx,
+            network::constants::MAX_FRAME_SIZE,
+        );
+        let twin_id = TwinId {
+            id,
+            author: signer.author(),
+        };
+        playground.add_node(twin_id, consensus_tx, network_reqs_rx, conn_mgr_reqs_rx);
+
+        let (self_sender, self_receiver) = channel::new_test(8);
+        let (task, receiver) = NetworkTask::new(network_events, self_receiver);
+        runtime.handle().spawn(task.start());
+        senders.push(NetworkSender::new(
+            signer.author(),
+            network_sender,
+            self_sender,
+            verifier.clone(),
+        ));
+        receivers.push(receiver);
+    }
+
+    let batch = Batch::new(1, signers[1].author(), 0, random_payload(5));
+    let proof = proof_of_store(&batch, &signers[1..]);
+    let local_store = Arc::new(BatchStore::new(&QuorumStoreConfig::default()));
+    local_store.start_epoch(1, senders[0].clone());
+    let remote_store = Arc::new(BatchStore::new(&QuorumStoreConfig::default()));
+    remote_store.start_epoch(1, senders[1].clone());
+    assert!(remote_store.insert_batch(batch.clone()));
+
+    // answer the batch requests of node 0 from the store of node 1
+    let mut batch_retrieval = receivers.remove(1).batch_retrieval;
+    runtime.handle().spawn(async move {
+        while let Some(request) = batch_retrieval.next().await {
+            let response = Box::new(BatchResponse::new(
+                remote_store.get_batch(&request.req.digest()),
+            ));
+            let response_bytes = request
+                .protocol
+                .to_bytes(&ConsensusMsg::BatchResponse(response))
+                .unwrap();
+            request
+                .response_sender
+                .send(Ok(response_bytes.into()))
+                .unwrap();
+        }
+    });
+
+    timed_block_on(&mut runtime, async {
+        let payload = local_store.get_payload(&[proof]).await.unwrap();
+        assert_eq!(&payload, batch.txns());
+        assert!(local_store.contains_batch(&batch.digest()));
+    });
+}
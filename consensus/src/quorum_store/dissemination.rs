@@ -0,0 +1,211 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters, metrics_safety_rules::MetricsSafetyRules, network::NetworkSender,
+    network_interface::ConsensusMsg, quorum_store::batch_store::BatchStore,
+    round_manager::VerifiedEvent, state_replication::TxnManager,
+};
+use channel::diem_channel;
+use consensus_types::{
+    common::Author,
+    experimental::quorum_store::{Batch, BatchId, BatchInfo, ProofOfStore, SignedBatchInfo},
+};
+use diem_config::config::QuorumStoreConfig;
+use diem_crypto::{ed25519::Ed25519Signature, HashValue};
+use diem_infallible::Mutex;
+use diem_logger::prelude::*;
+use diem_types::validator_verifier::ValidatorVerifier;
+use futures::{FutureExt, StreamExt};
+use safety_rules::TSafetyRules;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Disseminates the transactions of the validator in batches, stores and signs the batches of
+/// the other validators, and aggregates the signatures on its own batches into proofs of store
+/// that are broadcast to the proposers.
+/// A new QuorumStore is started in each epoch, the batch store is kept across the epochs.
+pub struct QuorumStore {
+    epoch: u64,
+    author: Author,
+    config: QuorumStoreConfig,
+    verifier: ValidatorVerifier,
+    batch_store: Arc<BatchStore>,
+    txn_manager: Arc<dyn TxnManager>,
+    safety_rules: Arc<Mutex<MetricsSafetyRules>>,
+    network_sender: NetworkSender,
+    next_batch_id: BatchId,
+    // Own batches not committed yet, their transactions are not pulled again.
+    pending_batches: Vec<Batch>,
+    // Own batches waiting for a quorum of signatures.
+    pending_signatures: HashMap<HashValue, (BatchInfo, BTreeMap<Author, Ed25519Signature>)>,
+}
+
+impl QuorumStore {
+    pub fn new(
+        epoch: u64,
+        author: Author,
+        config: QuorumStoreConfig,
+        verifier: ValidatorVerifier,
+        batch_store: Arc<BatchStore>,
+        txn_manager: Arc<dyn TxnManager>,
+        safety_rules: Arc<Mutex<MetricsSafetyRules>>,
+        network_sender: NetworkSender,
+    ) -> Self {
+        Self {
+            epoch,
+            author,
+            config,
+            verifier,
+            batch_store,
+            txn_manager,
+            safety_rules,
+            network_sender,
+            next_batch_id: 0,
+            pending_batches: vec![],
+            pending_signatures: HashMap::new(),
+        }
+    }
+
+    /// Pulls the transactions that are not in a pending batch yet from mempool and broadcasts
+    /// them in a new batch.
+    async fn create_batch(&mut self) {
+        let batch_store = self.batch_store.clone();
+        self.pending_batches
+            .retain(|batch| batch_store.contains_batch(&batch.digest()));
+        self.pending_signatures
+            .retain(|digest, _| batch_store.contains_batch(digest));
+
+        let exclude_payloads = self.pending_batches.iter().map(Batch::txns).collect();
+        let txns = match self
+            .txn_manager
+            .pull_txns(
+                self.config.max_batch_size,
+                exclude_payloads,
+                async {}.boxed(),
+                true,
+            )
+            .await
+        {
+            Ok(txns) => txns,
+            Err(e) => {
+                warn!(error = ?e, "Failed to pull transactions for a batch");
+                return;
+            }
+        };
+        if txns.is_empty() {
+            return;
+        }
+
+        let batch = Batch::new(self.epoch, self.author, self.next_batch_id, txns);
+        // the own batches are bounded like the others: no new batch until some are committed
+        if !self.batch_store.insert_batch(batch.clone()) {
+            debug!("Too many pending batches, skip {}", batch);
+            return;
+        }
+        self.next_batch_id += 1;
+        counters::BATCHES_CREATED.inc();
+        self.pending_signatures
+            .insert(batch.digest(), (batch.info(), BTreeMap::new()));
+        self.pending_batches.push(batch.clone());
+        self.network_sender
+            .broadcast(ConsensusMsg::BatchMsg(Box::new(batch)))
+            .await;
+    }
+
+    /// Stores the batch and sends the signature back to its author.
+    async fn process_batch(&mut self, peer_id: Author, batch: Batch) {
+        if batch.author() != peer_id {
+            warn!(
+                remote_peer = peer_id,
+                "Batch from a different author: {}", batch
+            );
+            return;
+        }
+        if batch.txns().len() as u64 > self.config.max_batch_size {
+            warn!(remote_peer = peer_id, "Batch is too large: {}", batch);
+            return;
+        }
+        if !self.batch_store.insert_batch(batch.clone()) {
+            debug!(remote_peer = peer_id, "Batch not stored: {}", batch);
+            return;
+        }
+        let info = batch.info();
+        let signature = match self.safety_rules.lock().sign_batch_info(&info) {
+            Ok(signature) => signature,
+            Err(e) => {
+                error!(error = ?e, "Failed to sign {}", info);
+                return;
+            }
+        };
+        let signed_info = SignedBatchInfo::new_with_signature(info, self.author, signature);
+        self.network_sender
+            .send(
+                ConsensusMsg::SignedBatchInfoMsg(Box::new(signed_info)),
+                vec![peer_id],
+            )
+            .await;
+    }
+
+    /// Aggregates the signature on an own batch, and broadcasts the proof of store once a quorum
+    /// signed it.
+    async fn process_signed_batch_info(&mut self, signed_info: SignedBatchInfo) {
+        let digest = signed_info.info().digest();
+        let proof_formed = match self.pending_signatures.get_mut(&digest) {
+            Some((info, signatures)) if info == signed_info.info() => {
+                signatures.insert(signed_info.signer(), signed_info.signature().clone());
+                self.verifier.check_voting_power(signatures.keys()).is_ok()
+            }
+            _ => return,
+        };
+        if proof_formed {
+            let (info, signatures) = self
+                .pending_signatures
+                .remove(&digest)
+                .expect("Pending signatures must exist");
+            counters::PROOFS_OF_STORE_FORMED.inc();
+            let proof = ProofOfStore::new(info, signatures);
+            self.network_sender
+                .broadcast(ConsensusMsg::ProofOfStoreMsg(Box::new(proof)))
+                .await;
+        }
+    }
+
+    async fn process_event(&mut self, peer_id: Author, event: VerifiedEvent) {
+        match event {
+            VerifiedEvent::Batch(batch) => self.process_batch(peer_id, *batch).await,
+            VerifiedEvent::SignedBatchInfo(signed_info) => {
+                self.process_signed_batch_info(*signed_info).await
+            }
+            VerifiedEvent::ProofOfStore(proof) => self.batch_store.insert_proof(*proof),
+            unexpected_event => unreachable!("Unexpected event: {:?}", unexpected_event),
+        }
+    }
+
+    /// Runs until the epoch manager drops the sender of `event_rx` at the end of the epoch.
+    pub async fn start(
+        mut self,
+        mut event_rx: diem_channel::Receiver<Author, (Author, VerifiedEvent)>,
+    ) {
+        info!(epoch = self.epoch, "QuorumStore starts.");
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(self.config.batch_interval_ms));
+        loop {
+            tokio::select! {
+                event = event_rx.next() => match event {
+                    Some((peer_id, event)) => self.process_event(peer_id, event).await,
+                    None => break,
+                },
+                _ = interval.tick() => {
+                    if self.config.enabled {
+                        self.create_batch().await;
+                    }
+                }
+            }
+        }
+        info!(epoch = self.epoch, "QuorumStore stops.");
+    }
+}
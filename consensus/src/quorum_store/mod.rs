@@ -0,0 +1,11 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Quorum store: the validators disseminate the transactions in batches ahead of the proposals,
+//! and the proposals only carry the proofs of store of the batches.
+
+pub(crate) mod batch_store;
+pub(crate) mod dissemination;
+
+#[cfg(test)]
+mod batch_store_test;
@@ -26,7 +26,11 @@ use consensus_types::{
     block::Block,
     block_retrieval::{BlockRetrievalResponse, BlockRetrievalStatus},
     common::{Author, Round},
-    experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
+    experimental::{
+        commit_decision::CommitDecision,
+        commit_vote::CommitVote,
+        quorum_store::{Batch, ProofOfStore, SignedBatchInfo},
+    },
     proposal_msg::ProposalMsg,
     quorum_cert::QuorumCert,
     sync_info::SyncInfo,
@@ -58,10 +62,17 @@ pub enum UnverifiedEvent {
     SyncInfo(Box<SyncInfo>),
     CommitVote(Box<CommitVote>),
     CommitDecision(Box<CommitDecision>),
+    Batch(Box<Batch>),
+    SignedBatchInfo(Box<SignedBatchInfo>),
+    ProofOfStore(Box<ProofOfStore>),
 }
 
 impl UnverifiedEvent {
-    pub fn verify(self, validator: &ValidatorVerifier) -> Result<VerifiedEvent, VerifyError> {
+    pub fn verify(
+        self,
+        peer_id: Author,
+        validator: &ValidatorVerifier,
+    ) -> Result<VerifiedEvent, VerifyError> {
         Ok(match self {
             UnverifiedEvent::ProposalMsg(p) => {
                 p.verify(validator)?;
@@ -81,6 +92,18 @@ impl UnverifiedEvent {
                 cd.verify(validator)?;
                 VerifiedEvent::CommitDecision(cd)
             }
+            UnverifiedEvent::Batch(b) => {
+                b.verify(peer_id, validator)?;
+                VerifiedEvent::Batch(b)
+            }
+            UnverifiedEvent::SignedBatchInfo(sb) => {
+                sb.verify(validator)?;
+                VerifiedEvent::SignedBatchInfo(sb)
+            }
+            UnverifiedEvent::ProofOfStore(p) => {
+                p.verify(validator)?;
+                VerifiedEvent::ProofOfStore(p)
+            }
         })
     }
 
@@ -91,6 +114,9 @@ impl UnverifiedEvent {
             UnverifiedEvent::SyncInfo(s) => s.epoch(),
             UnverifiedEvent::CommitVote(cv) => cv.epoch(),
             UnverifiedEvent::CommitDecision(cd) => cd.epoch(),
+            UnverifiedEvent::Batch(b) => b.epoch(),
+            UnverifiedEvent::SignedBatchInfo(sb) => sb.epoch(),
+            UnverifiedEvent::ProofOfStore(p) => p.epoch(),
         }
     }
 }
@@ -103,6 +129,9 @@ impl From<ConsensusMsg> for UnverifiedEvent {
             ConsensusMsg::SyncInfo(m) => UnverifiedEvent::SyncInfo(m),
             ConsensusMsg::CommitVoteMsg(m) => UnverifiedEvent::CommitVote(m),
             ConsensusMsg::CommitDecisionMsg(m) => UnverifiedEvent::CommitDecision(m),
+            ConsensusMsg::BatchMsg(m) => UnverifiedEvent::Batch(m),
+            ConsensusMsg::SignedBatchInfoMsg(m) => UnverifiedEvent::SignedBatchInfo(m),
+            ConsensusMsg::ProofOfStoreMsg(m) => UnverifiedEvent::ProofOfStore(m),
             _ => unreachable!("Unexpected conversion"),
         }
     }
@@ -116,6 +145,9 @@ pub enum VerifiedEvent {
     UnverifiedSyncInfo(Box<SyncInfo>),
     CommitVote(Box<CommitVote>),
     CommitDecision(Box<CommitDecision>),
+    Batch(Box<Batch>),
+    SignedBatchInfo(Box<SignedBatchInfo>),
+    ProofOfStore(Box<ProofOfStore>),
     BlockRetrievalRequest(Box<IncomingBlockRetrievalRequest>),
     // local messages
     LocalTimeout(Round),
@@ -115,7 +115,7 @@ fn create_node_for_fuzzing() -> RoundManager {
     let (network_reqs_tx, _network_reqs_rx) = diem_channel::new(QueueStyle::FIFO, 8, None);
     let (connection_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
     let network_sender = ConsensusNetworkSender::new(
-        PeerManagerRequestSender::new(network_reqs_tx),
+        PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
         ConnectionRequestSender::new(connection_reqs_tx),
     );
     let (self_sender, _self_receiver) = channel::new_test(8);
@@ -145,6 +145,7 @@ fn create_node_for_fuzzing() -> RoundManager {
         Arc::new(MockTransactionManager::new(None)),
         time_service,
         1,
+        None,
     );
 
     //
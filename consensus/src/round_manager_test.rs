@@ -163,11 +163,15 @@ impl NodeSetup {
         let (_conn_mgr_reqs_tx, conn_mgr_reqs_rx) = channel::new_test(8);
         let (_, conn_status_rx) = conn_notifs_channel::new();
         let mut network_sender = ConsensusNetworkSender::new(
-            PeerManagerRequestSender::new(network_reqs_tx),
+            PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
             ConnectionRequestSender::new(connection_reqs_tx),
         );
         network_sender.initialize(playground.peer_protocols());
-        let network_events = ConsensusNetworkEvents::new(consensus_rx, conn_status_rx);
+        let network_events = ConsensusNetworkEvents::new(
+            consensus_rx,
+            conn_status_rx,
+            network::constants::MAX_FRAME_SIZE,
+        );
         let author = signer.author();
 
         let twin_id = TwinId { id, author };
@@ -204,6 +208,7 @@ impl NodeSetup {
             Arc::new(MockTransactionManager::new(None)),
             time_service.clone(),
             1,
+            None,
         );
 
         let round_state = Self::create_round_state(time_service);
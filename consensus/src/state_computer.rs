@@ -4,11 +4,12 @@
 use crate::{
     counters,
     error::StateSyncError,
+    quorum_store::batch_store::BatchStore,
     state_replication::{StateComputer, StateComputerCommitCallBackType, TxnManager},
 };
 use anyhow::Result;
 use consensus_notifications::ConsensusNotificationSender;
-use consensus_types::{block::Block, executed_block::ExecutedBlock};
+use consensus_types::{block::Block, common::Payload, executed_block::ExecutedBlock};
 use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_metrics::monitor;
@@ -34,6 +35,8 @@ pub struct ExecutionProxy {
     mempool_notifier: Arc<dyn TxnManager>,
     state_sync_notifier: Arc<dyn ConsensusNotificationSender>,
     async_state_sync_notifier: channel::Sender<NotificationType>,
+    // Resolves the proofs of store of the quorum store proposals to their transactions.
+    batch_store: Arc<BatchStore>,
 }
 
 impl ExecutionProxy {
@@ -42,6 +45,7 @@ impl ExecutionProxy {
         mempool_notifier: Arc<dyn TxnManager>,
         state_sync_notifier: Arc<dyn ConsensusNotificationSender>,
         handle: &tokio::runtime::Handle,
+        batch_store: Arc<BatchStore>,
     ) -> Self {
         let (tx, mut rx) =
             channel::new::<NotificationType>(10, &counters::PENDING_STATE_SYNC_NOTIFICATION);
@@ -63,6 +67,23 @@ impl ExecutionProxy {
             mempool_notifier,
             state_sync_notifier,
             async_state_sync_notifier: tx,
+            batch_store,
+        }
+    }
+
+    /// Gets the transactions of the batches a quorum store proposal refers to, `None` for the
+    /// other blocks.
+    async fn resolve_payload(&self, block: &Block) -> Result<Option<Payload>, ExecutionError> {
+        match block.proofs() {
+            Some(proofs) => monitor!(
+                "resolve_payload",
+                self.batch_store.get_payload(proofs).await
+            )
+            .map(Some)
+            .map_err(|e| ExecutionError::InternalError {
+                error: format!("Failed to resolve payload of block {}: {}", block.id(), e),
+            }),
+            None => Ok(None),
         }
     }
 }
@@ -87,17 +108,21 @@ impl StateComputer for ExecutionProxy {
             "Executing block",
         );
 
+        let resolved_payload = self.resolve_payload(block).await?;
         // TODO: figure out error handling for the prologue txn
         let compute_result = monitor!(
             "execute_block",
-            self.execution_correctness_client
-                .execute_block(block.clone(), parent_block_id)
+            self.execution_correctness_client.execute_block(
+                block.clone(),
+                resolved_payload.clone(),
+                parent_block_id
+            )
         )?;
 
         // notify mempool about failed transaction
         if let Err(e) = self
             .mempool_notifier
-            .notify_failed_txn(block, &compute_result)
+            .notify_failed_txn(block, resolved_payload.as_ref(), &compute_result)
             .await
         {
             error!(
@@ -107,6 +132,52 @@ impl StateComputer for ExecutionProxy {
         Ok(compute_result)
     }
 
+    async fn compute_blocks(
+        &self,
+        // The blocks to be executed.
+        blocks: &[Block],
+        // The parent block id of the first block.
+        parent_block_id: HashValue,
+    ) -> Result<Vec<StateComputeResult>, ExecutionError> {
+        fail_point!("consensus::compute", |_| {
+            Err(ExecutionError::InternalError {
+                error: "Injected error in compute".into(),
+            })
+        });
+        debug!(
+            block_id = ?blocks.last().map(Block::id),
+            parent_id = parent_block_id,
+            num_blocks = blocks.len(),
+            "Executing blocks",
+        );
+
+        let mut blocks_to_execute = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            blocks_to_execute.push((block.clone(), self.resolve_payload(block).await?));
+        }
+        let compute_results = monitor!(
+            "execute_blocks",
+            self.execution_correctness_client
+                .execute_blocks(blocks_to_execute.clone(), parent_block_id)
+        )?;
+
+        // notify mempool about failed transaction
+        for ((block, resolved_payload), compute_result) in
+            blocks_to_execute.iter().zip(compute_results.iter())
+        {
+            if let Err(e) = self
+                .mempool_notifier
+                .notify_failed_txn(block, resolved_payload.as_ref(), compute_result)
+                .await
+            {
+                error!(
+                    error = ?e, "Failed to notify mempool of rejected txns",
+                );
+            }
+        }
+        Ok(compute_results)
+    }
+
     /// Send a successful commit. A future is fulfilled when the state is finalized.
     async fn commit(
         &self,
@@ -117,11 +188,16 @@ impl StateComputer for ExecutionProxy {
         let mut block_ids = Vec::new();
         let mut txns = Vec::new();
         let mut reconfig_events = Vec::new();
+        let mut committed_batches = Vec::new();
 
         for block in blocks {
             block_ids.push(block.id());
-            txns.extend(block.transactions_to_commit());
+            let resolved_payload = self.resolve_payload(block.block()).await?;
+            txns.extend(block.transactions_to_commit(resolved_payload.as_ref())?);
             reconfig_events.extend(block.reconfig_event());
+            if let Some(proofs) = block.block().proofs() {
+                committed_batches.extend(proofs.iter().map(|proof| proof.digest()));
+            }
         }
 
         monitor!(
@@ -129,6 +205,7 @@ impl StateComputer for ExecutionProxy {
             self.execution_correctness_client
                 .commit_blocks(block_ids, finality_proof.clone())?
         );
+        self.batch_store.commit(&committed_batches);
 
         let blocks = blocks.to_vec();
         let wrapped_callback = move || {
@@ -160,6 +237,9 @@ impl StateComputer for ExecutionProxy {
         // Similarily, after the state synchronization, we have to reset the cache
         // of BlockExecutor to guarantee the latest committed state is up to date.
         self.execution_correctness_client.reset()?;
+        // The batches and proofs of store of the blocks committed by state sync must not be
+        // proposed again, nor count against the bound of their authors.
+        self.batch_store.reset();
 
         res.map_err(|error| {
             let anyhow_error: anyhow::Error = error.into();
@@ -31,10 +31,12 @@ pub trait TxnManager: Send + Sync {
     ) -> Result<Payload, MempoolError>;
 
     /// Notifies TxnManager about the txns which failed execution. (Committed txns is notified by
-    /// state sync.)
+    /// state sync.) `resolved_payload` carries the transactions of the batches of a quorum store
+    /// proposal.
     async fn notify_failed_txn(
         &self,
         block: &Block,
+        resolved_payload: Option<&Payload>,
         compute_result: &StateComputeResult,
     ) -> Result<(), MempoolError>;
 
@@ -58,6 +60,24 @@ pub trait StateComputer: Send + Sync {
         parent_block_id: HashValue,
     ) -> Result<StateComputeResult, ExecutionError>;
 
+    /// Computes consecutive blocks, each one the child of the previous one, returning one result
+    /// per block. The results are the same as computing the blocks one by one.
+    async fn compute_blocks(
+        &self,
+        // The blocks that will be computed.
+        blocks: &[Block],
+        // The parent block root hash of the first block.
+        parent_block_id: HashValue,
+    ) -> Result<Vec<StateComputeResult>, ExecutionError> {
+        let mut parent_block_id = parent_block_id;
+        let mut results = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            results.push(self.compute(block, parent_block_id).await?);
+            parent_block_id = block.id();
+        }
+        Ok(results)
+    }
+
     /// Send a successful commit. A future is fulfilled when the state is finalized.
     async fn commit(
         &self,
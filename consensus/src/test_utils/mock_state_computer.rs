@@ -4,14 +4,17 @@
 use crate::{
     error::StateSyncError,
     state_replication::{StateComputer, StateComputerCommitCallBackType},
-    test_utils::mock_storage::MockStorage,
+    test_utils::mock_storage::{CommittedBlockInfo, MockStorage},
 };
 use anyhow::{format_err, Result};
 use consensus_types::{block::Block, common::Payload, executed_block::ExecutedBlock};
 use diem_crypto::HashValue;
 use diem_infallible::Mutex;
 use diem_logger::prelude::*;
-use diem_types::ledger_info::LedgerInfoWithSignatures;
+use diem_types::{
+    block_metadata::{BlockMetadata, NewBlockEvent},
+    ledger_info::LedgerInfoWithSignatures,
+};
 use executor_types::{Error, StateComputeResult};
 use futures::channel::mpsc;
 use std::{collections::HashMap, sync::Arc};
@@ -61,6 +64,23 @@ impl StateComputer for MockStateComputer {
     ) -> Result<(), Error> {
         self.consensus_db
             .commit_to_storage(commit.ledger_info().clone());
+        self.consensus_db
+            .shared_storage
+            .new_block_events
+            .lock()
+            .extend(blocks.iter().map(|block| {
+                let (round, timestamp, votes, proposer) =
+                    BlockMetadata::from(block.block()).into_inner();
+                NewBlockEvent::new(round, proposer, votes, timestamp)
+            }));
+        self.consensus_db
+            .shared_storage
+            .committed_blocks
+            .lock()
+            .extend(blocks.iter().map(|block| CommittedBlockInfo {
+                info: block.block_info(),
+                synced: false,
+            }));
 
         // mock sending commit notif to state sync
         let mut txns = vec![];
@@ -91,6 +111,14 @@ impl StateComputer for MockStateComputer {
         );
         self.consensus_db
             .commit_to_storage(commit.ledger_info().clone());
+        self.consensus_db
+            .shared_storage
+            .committed_blocks
+            .lock()
+            .push(CommittedBlockInfo {
+                info: commit.ledger_info().commit_info().clone(),
+                synced: true,
+            });
         self.commit_callback
             .unbounded_send(commit)
             .expect("Fail to notify about sync");
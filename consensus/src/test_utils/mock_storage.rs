@@ -15,15 +15,23 @@ use consensus_types::{
 use diem_crypto::HashValue;
 use diem_infallible::Mutex;
 use diem_types::{
+    block_info::BlockInfo,
+    block_metadata::{new_block_event_key, NewBlockEvent},
+    contract_event::ContractEvent,
     epoch_change::EpochChangeProof,
+    event::EventKey,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     on_chain_config::ValidatorSet,
 };
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS},
+};
 use std::{
     collections::{BTreeMap, HashMap},
     sync::Arc,
 };
-use storage_interface::DbReader;
+use storage_interface::{DbReader, Order};
 
 pub struct MockSharedStorage {
     // Safety state
@@ -36,6 +44,19 @@ pub struct MockSharedStorage {
     pub highest_timeout_certificate: Mutex<Option<TimeoutCertificate>>,
     pub highest_2chain_timeout_certificate: Mutex<Option<TwoChainTimeoutCertificate>>,
     pub validator_set: ValidatorSet,
+
+    // Committed history, the events emitted by the BlockMetadata of the committed blocks
+    pub new_block_events: Mutex<Vec<NewBlockEvent>>,
+    // The committed blocks, in order
+    pub committed_blocks: Mutex<Vec<CommittedBlockInfo>>,
+}
+
+/// A block committed by the node. State sync skips the blocks up to the one it syncs to, which
+/// is marked as synced: the blocks committed between the previous one and it are unknown.
+#[derive(Clone, Debug)]
+pub struct CommittedBlockInfo {
+    pub info: BlockInfo,
+    pub synced: bool,
 }
 
 impl MockSharedStorage {
@@ -48,6 +69,8 @@ impl MockSharedStorage {
             highest_timeout_certificate: Mutex::new(None),
             highest_2chain_timeout_certificate: Mutex::new(None),
             validator_set,
+            new_block_events: Mutex::new(vec![]),
+            committed_blocks: Mutex::new(vec![]),
         }
     }
 }
@@ -245,7 +268,67 @@ impl PersistentLivenessStorage for MockStorage {
     }
 
     fn diem_db(&self) -> Arc<dyn DbReader> {
-        unimplemented!()
+        Arc::new(MockDiemDB {
+            shared_storage: self.shared_storage.clone(),
+        })
+    }
+}
+
+/// A DbReader over the ledger infos and the committed NewBlockEvents of the shared storage. The
+/// version of the i-th committed block is i + 1.
+struct MockDiemDB {
+    shared_storage: Arc<MockSharedStorage>,
+}
+
+impl DbReader for MockDiemDB {
+    /// Only supports reading the latest NewBlockEvents, as leader reputation does.
+    fn get_events(
+        &self,
+        event_key: &EventKey,
+        _start: u64,
+        _order: Order,
+        limit: u64,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        if *event_key != new_block_event_key() {
+            return Ok(vec![]);
+        }
+        let type_tag = TypeTag::Struct(StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("DiemBlock")?,
+            name: Identifier::new("NewBlockEvent")?,
+            type_params: vec![],
+        });
+        self.shared_storage
+            .new_block_events
+            .lock()
+            .iter()
+            .enumerate()
+            .rev()
+            .take(limit as usize)
+            .map(|(i, event)| {
+                let event = ContractEvent::new(
+                    *event_key,
+                    i as u64,
+                    type_tag.clone(),
+                    bcs::to_bytes(event)?,
+                );
+                Ok((i as u64 + 1, event))
+            })
+            .collect()
+    }
+
+    fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures> {
+        self.shared_storage
+            .lis
+            .lock()
+            .iter()
+            .max_by_key(|(version, _)| **version)
+            .map(|(_, li)| li.clone())
+            .ok_or_else(|| anyhow::anyhow!("No LedgerInfo found"))
+    }
+
+    fn get_latest_version(&self) -> Result<u64> {
+        Ok(self.shared_storage.new_block_events.lock().len() as u64)
     }
 }
 
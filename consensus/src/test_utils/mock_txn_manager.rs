@@ -64,6 +64,7 @@ impl TxnManager for MockTransactionManager {
     async fn notify_failed_txn(
         &self,
         block: &Block,
+        resolved_payload: Option<&Payload>,
         compute_results: &StateComputeResult,
     ) -> Result<(), MempoolError> {
         if self.mempool_proxy.is_some() {
@@ -74,7 +75,12 @@ impl TxnManager for MockTransactionManager {
                 compute_results.parent_frozen_subtree_roots().clone(),
                 compute_results.parent_num_leaves(),
                 compute_results.epoch_state().clone(),
-                mock_transaction_status(block.payload().map_or(0, |txns| txns.len())),
+                mock_transaction_status(
+                    block
+                        .payload()
+                        .or(resolved_payload)
+                        .map_or(0, |txns| txns.len()),
+                ),
                 compute_results.transaction_info_hashes().clone(),
                 compute_results.reconfig_events().to_vec(),
             );
@@ -82,7 +88,7 @@ impl TxnManager for MockTransactionManager {
                 .mempool_proxy
                 .as_ref()
                 .unwrap()
-                .notify_failed_txn(block, &mock_compute_result)
+                .notify_failed_txn(block, resolved_payload, &mock_compute_result)
                 .await
                 .is_ok());
         }
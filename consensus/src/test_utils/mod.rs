@@ -26,7 +26,7 @@ use diem_types::block_info::BlockInfo;
 pub use mock_state_computer::{
     EmptyStateComputer, MockStateComputer, RandomComputeResultStateComputer,
 };
-pub use mock_storage::{EmptyStorage, MockSharedStorage, MockStorage};
+pub use mock_storage::{CommittedBlockInfo, EmptyStorage, MockSharedStorage, MockStorage};
 pub use mock_txn_manager::MockTransactionManager;
 
 pub const TEST_TIMEOUT: Duration = Duration::from_secs(60);
@@ -2,13 +2,27 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    liveness::{
+        leader_reputation::{
+            ActiveInactiveHeuristic, FailedRoundsHeuristic,
+            LeaderReputation as LeaderReputationElection, MetadataBackend, ReputationHeuristic,
+        },
+        proposer_election::ProposerElection,
+    },
     network_interface::ConsensusMsg,
     network_tests::{NetworkPlayground, TwinId},
     test_utils::{consensus_runtime, timed_block_on},
     twins::twins_node::SMRNode,
 };
-use consensus_types::{block::Block, common::Round};
-use diem_config::config::ConsensusProposerType::{FixedProposer, RotatingProposer, RoundProposer};
+use consensus_types::{
+    block::Block,
+    common::{Author, Round},
+};
+use diem_config::config::{
+    ConsensusProposerType::{FixedProposer, LeaderReputation, RotatingProposer, RoundProposer},
+    FailedRoundsConfig, LeaderReputationConfig, ReputationHeuristicType,
+};
+use diem_types::{account_address::AccountAddress, block_metadata::NewBlockEvent};
 use futures::StreamExt;
 use std::collections::HashMap;
 
@@ -310,3 +324,127 @@ fn twins_commit_test() {
         }
     });
 }
+
+#[test]
+/// This test checks that the rounds failed by a leader are noticed by leader
+/// reputation, and make it less likely to be elected.
+///
+/// Setup:
+///
+/// 4 honest nodes (n0, n1, n2, n3), and 0 twins, electing leaders
+/// with the failed rounds heuristic. n3 is partitioned away, so that every
+/// round it leads fails.
+///
+/// Test:
+///
+/// Run consensus until n0 commits a block after a failed round. Electing
+/// leaders on top of the committed history, n3 is penalized and gets elected
+/// less often than before the failure.
+///
+/// Run the test:
+/// cargo xtest -p consensus leader_reputation_failed_rounds_test -- --nocapture
+fn leader_reputation_failed_rounds_test() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let num_nodes = 4;
+    let num_twins = 0;
+
+    // Candidates all get the same weight until they fail, so that the leaders of the failed
+    // rounds are guessed right.
+    let (weight, ok_weight, failed_weight) = (10, 10, 1);
+    let mut nodes = SMRNode::start_num_nodes_with_twins(
+        num_nodes,
+        num_twins,
+        &mut playground,
+        LeaderReputation(LeaderReputationConfig {
+            active_weights: weight,
+            inactive_weights: weight,
+            heuristic: ReputationHeuristicType::FailedRounds(FailedRoundsConfig {
+                ok_weight,
+                failed_weight,
+                failure_threshold_percent: 50,
+            }),
+        }),
+        None,
+    );
+    let mut proposers: Vec<Author> = nodes.iter().map(|node| node.id.author).collect();
+    proposers.sort();
+    let faulty = nodes[3].id;
+    assert!(playground.split_network(vec![faulty], vec![nodes[0].id, nodes[1].id, nodes[2].id]));
+    runtime.spawn(playground.start());
+
+    // The index of the first committed block revealing a failed round: a NIL block, or a block
+    // skipping rounds.
+    let first_failure = |history: &[NewBlockEvent]| {
+        history.iter().enumerate().position(|(i, event)| {
+            event.proposer() == AccountAddress::ZERO
+                || (i > 0 && event.round() > history[i - 1].round() + 1)
+        })
+    };
+    let history = timed_block_on(&mut runtime, async {
+        loop {
+            assert!(nodes[0].commit_cb_receiver.next().await.is_some());
+            let history = nodes[0]
+                .storage
+                .shared_storage
+                .new_block_events
+                .lock()
+                .clone();
+            if let Some(i) = first_failure(&history) {
+                break history[..=i].to_vec();
+            }
+        }
+    });
+
+    // The histories before and after the failure, newest first.
+    let window = |history: &[NewBlockEvent]| {
+        history
+            .iter()
+            .rev()
+            .take(num_nodes)
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let before = window(&history[..history.len() - 1]);
+    let after = window(&history);
+
+    let heuristic = || {
+        Box::new(FailedRoundsHeuristic::new(
+            proposers[0],
+            Box::new(ActiveInactiveHeuristic::new(proposers[0], weight, weight)),
+            0,
+            ok_weight,
+            failed_weight,
+            50,
+        ))
+    };
+    let mut expected_weights = vec![ok_weight; num_nodes];
+    let faulty_index = proposers.iter().position(|p| *p == faulty.author).unwrap();
+    expected_weights[faulty_index] = failed_weight;
+    assert_eq!(
+        heuristic().get_weights(&proposers, &after),
+        expected_weights
+    );
+
+    let times_elected = |history: Vec<NewBlockEvent>| {
+        let election = LeaderReputationElection::new(
+            proposers.clone(),
+            Box::new(FixedHistory(history)),
+            heuristic(),
+            0,
+        );
+        (1..=100)
+            .filter(|round| election.get_valid_proposer(*round) == faulty.author)
+            .count()
+    };
+    assert!(times_elected(after) < times_elected(before));
+}
+
+/// Always returns the same history.
+struct FixedHistory(Vec<NewBlockEvent>);
+
+impl MetadataBackend for FixedHistory {
+    fn get_block_metadata(&self, _target_round: Round) -> Vec<NewBlockEvent> {
+        self.0.clone()
+    }
+}
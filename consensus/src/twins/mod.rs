@@ -2,4 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod basic_twins_test;
+mod scenario;
+mod scenario_generator;
+mod scenario_test;
 mod twins_node;
@@ -0,0 +1,327 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Declarative twins scenarios.
+//!
+//! A scenario describes the nodes and twins of a network, the leader and the network partitions
+//! of each round, and the safety and liveness properties expected from running it. Nodes are
+//! referred to by index: `0..num_nodes` are the nodes, and `num_nodes + i` is the twin of node
+//! `i`. Scenarios are written in YAML (or JSON, which is valid YAML), e.g.
+//!
+//! ```yaml
+//! name: minority partition
+//! num_nodes: 4
+//! round_leaders:
+//!   1: 0
+//! round_partitions:
+//!   1: [[0, 1, 2], [3]]
+//! expected:
+//!   liveness:
+//!     nodes: [0, 1, 2]
+//!     min_commits: 1
+//! ```
+
+use crate::{
+    network_tests::{NetworkPlayground, TwinId},
+    test_utils::{consensus_runtime, CommittedBlockInfo, TEST_TIMEOUT},
+    twins::twins_node::SMRNode,
+};
+use anyhow::{ensure, format_err, Result};
+use consensus_types::common::Round;
+use diem_config::config::ConsensusProposerType::RoundProposer;
+use diem_types::{block_info::BlockInfo, ledger_info::LedgerInfoWithSignatures};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    pub num_nodes: usize,
+    #[serde(default)]
+    pub num_twins: usize,
+    /// Leader of each round, by node index. Rounds without a leader are led by node 0.
+    #[serde(default)]
+    pub round_leaders: BTreeMap<Round, usize>,
+    /// Network partitions of each round, by node index. Messages of the round between nodes in
+    /// different partitions are dropped.
+    #[serde(default)]
+    pub round_partitions: BTreeMap<Round, Vec<Vec<usize>>>,
+    /// Initial round timeout of the nodes. Timeouts are disabled if not set, and a round only
+    /// ends with a quorum certificate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round_timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub expected: Expectations,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Expectations {
+    /// Whether the honest nodes, i.e. the ones without a twin, never commit conflicting blocks.
+    pub safety: bool,
+    pub liveness: Option<Liveness>,
+    /// How long to keep running after the liveness expectation is met, to collect the commits
+    /// checked for safety.
+    pub duration_ms: u64,
+}
+
+impl Default for Expectations {
+    fn default() -> Self {
+        Self {
+            safety: true,
+            liveness: None,
+            duration_ms: 1000,
+        }
+    }
+}
+
+/// Each of the nodes commits at least `min_commits` times.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Liveness {
+    pub nodes: Vec<usize>,
+    pub min_commits: usize,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let scenario: Self = serde_yaml::from_str(&contents)
+            .map_err(|e| format_err!("Failed to parse {}: {}", path.display(), e))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    fn num_all_nodes(&self) -> usize {
+        self.num_nodes + self.num_twins
+    }
+
+    fn validate(&self) -> Result<()> {
+        ensure!(self.num_nodes > 0, "[{}] No nodes", self.name);
+        ensure!(
+            self.num_twins <= self.num_nodes,
+            "[{}] More twins than nodes",
+            self.name
+        );
+        let num_all_nodes = self.num_all_nodes();
+        let valid_node = |idx: &usize| *idx < num_all_nodes;
+        ensure!(
+            self.round_leaders.values().all(|idx| *idx < self.num_nodes),
+            "[{}] Leaders must be nodes, not twins",
+            self.name
+        );
+        ensure!(
+            self.round_partitions
+                .values()
+                .flatten()
+                .flatten()
+                .all(valid_node),
+            "[{}] Unknown node in partitions",
+            self.name
+        );
+        if let Some(liveness) = &self.expected.liveness {
+            ensure!(
+                liveness.nodes.iter().all(valid_node),
+                "[{}] Unknown node in liveness expectation",
+                self.name
+            );
+        }
+        Ok(())
+    }
+
+    /// Runs the scenario and checks its expectations.
+    pub fn run(&self) -> Result<()> {
+        self.validate()?;
+        let runtime = consensus_runtime();
+        let mut playground = NetworkPlayground::new(runtime.handle().clone());
+        let round_leaders: HashMap<_, _> = self
+            .round_leaders
+            .iter()
+            .map(|(round, idx)| (*round, *idx))
+            .collect();
+        let mut nodes = match self.round_timeout_ms {
+            Some(round_timeout_ms) => SMRNode::start_num_nodes_with_twins_and_timeout(
+                self.num_nodes,
+                self.num_twins,
+                &mut playground,
+                RoundProposer(HashMap::new()),
+                Some(round_leaders),
+                round_timeout_ms,
+            ),
+            None => SMRNode::start_num_nodes_with_twins(
+                self.num_nodes,
+                self.num_twins,
+                &mut playground,
+                RoundProposer(HashMap::new()),
+                Some(round_leaders),
+            ),
+        };
+
+        let twin_ids: Vec<TwinId> = nodes.iter().map(|node| node.id).collect();
+        let round_partitions: HashMap<_, _> = self
+            .round_partitions
+            .iter()
+            .map(|(round, partitions)| {
+                let partitions = partitions
+                    .iter()
+                    .map(|partition| partition.iter().map(|idx| twin_ids[*idx]).collect())
+                    .collect();
+                (*round, partitions)
+            })
+            .collect();
+        playground.split_network_round(&round_partitions);
+        runtime.spawn(playground.start());
+
+        let duration = Duration::from_millis(self.expected.duration_ms);
+        let liveness = self.expected.liveness.clone();
+        let commits = runtime.block_on(async {
+            let mut commits: Vec<Vec<LedgerInfoWithSignatures>> = vec![vec![]; nodes.len()];
+            if let Some(liveness) = liveness {
+                // a liveness violation shows up as a timeout
+                let _ = tokio::time::timeout(TEST_TIMEOUT, async {
+                    for idx in liveness.nodes {
+                        while commits[idx].len() < liveness.min_commits {
+                            match nodes[idx].commit_cb_receiver.next().await {
+                                Some(commit) => commits[idx].push(commit),
+                                None => break,
+                            }
+                        }
+                    }
+                })
+                .await;
+            }
+            tokio::time::sleep(duration).await;
+            for (node, node_commits) in nodes.iter_mut().zip(commits.iter_mut()) {
+                while let Ok(Some(commit)) = node.commit_cb_receiver.try_next() {
+                    node_commits.push(commit);
+                }
+            }
+            commits
+        });
+
+        if let Some(liveness) = &self.expected.liveness {
+            for idx in &liveness.nodes {
+                ensure!(
+                    commits[*idx].len() >= liveness.min_commits,
+                    "[{}] Node {} committed {} times, expected at least {}",
+                    self.name,
+                    idx,
+                    commits[*idx].len(),
+                    liveness.min_commits
+                );
+            }
+        }
+        if self.expected.safety {
+            let chains: Vec<_> = nodes
+                .iter()
+                .map(|node| node.storage.shared_storage.committed_blocks.lock().clone())
+                .collect();
+            self.check_safety(&chains)?;
+        }
+        Ok(())
+    }
+
+    /// The nodes with a twin are the Byzantine ones, the chains committed by the other nodes must
+    /// be prefixes of each other: the nodes commit the same block, with the same version, at each
+    /// round they both committed, and none of them skips a block the other committed.
+    pub fn check_safety(&self, chains: &[Vec<CommittedBlockInfo>]) -> Result<()> {
+        let honest = (self.num_twins..self.num_nodes).filter(|idx| *idx < chains.len());
+        for idx in honest.clone() {
+            for other_idx in honest.clone().filter(|other_idx| *other_idx != idx) {
+                self.check_prefix(idx, &chains[idx], other_idx, &chains[other_idx])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that each block of `chain` is in `other_chain`, up to the last block of
+    /// `other_chain`. The blocks `other_chain` skipped by state sync are unknown.
+    fn check_prefix(
+        &self,
+        idx: usize,
+        chain: &[CommittedBlockInfo],
+        other_idx: usize,
+        other_chain: &[CommittedBlockInfo],
+    ) -> Result<()> {
+        let position = |block: &BlockInfo| (block.epoch(), block.round());
+        for block in chain {
+            let info = &block.info;
+            let other = match other_chain
+                .iter()
+                .find(|other| position(&other.info) >= position(info))
+            {
+                Some(other) => other,
+                // the other node didn't commit that far
+                None => continue,
+            };
+            if position(&other.info) == position(info) {
+                ensure!(
+                    other.info.id() == info.id() && other.info.version() == info.version(),
+                    "[{}] Safety violation: nodes {} and {} committed {} (version {}) and {} (version {}) at epoch {} round {}",
+                    self.name,
+                    idx,
+                    other_idx,
+                    info.id(),
+                    info.version(),
+                    other.info.id(),
+                    other.info.version(),
+                    info.epoch(),
+                    info.round()
+                );
+            } else {
+                ensure!(
+                    other.synced,
+                    "[{}] Safety violation: node {} committed {} at epoch {} round {}, node {} committed {} at epoch {} round {} without it",
+                    self.name,
+                    idx,
+                    info.id(),
+                    info.epoch(),
+                    info.round(),
+                    other_idx,
+                    other.info.id(),
+                    other.info.epoch(),
+                    other.info.round()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the scenario files of the directory, in the order of their names.
+pub fn scenario_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<_> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    files.retain(|path| {
+        matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yaml") | Some("yml") | Some("json")
+        )
+    });
+    files.sort();
+    Ok(files)
+}
+
+/// Runs all the scenarios of the directory, returning the errors of the failed ones.
+pub fn run_scenarios_in_dir(dir: &Path) -> Result<Vec<anyhow::Error>> {
+    let mut errors = vec![];
+    for path in scenario_files(dir)? {
+        if let Err(e) = Scenario::load(&path).and_then(|scenario| scenario.run()) {
+            errors.push(e);
+        }
+    }
+    Ok(errors)
+}
@@ -0,0 +1,80 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Random twins scenarios, to fuzz safety without writing each partition schedule by hand.
+
+use crate::twins::scenario::{Expectations, Scenario};
+use consensus_types::common::Round;
+use diem_config::config::ConsensusConfig;
+use proptest::{
+    collection::vec,
+    prelude::*,
+    strategy::ValueTree,
+    test_runner::{Config, RngAlgorithm, TestRng, TestRunner},
+};
+use std::collections::BTreeMap;
+
+/// Up to how many partitions the nodes are split into at each round.
+const MAX_PARTITIONS: usize = 3;
+
+/// Scenarios with a random leader and random network partitions for each of the first
+/// `num_rounds` rounds. Only the safety of the honest nodes is expected. The nodes time out
+/// rounds as they would by default, so that the rounds whose leader is partitioned away end.
+pub fn scenario_strategy(
+    num_nodes: usize,
+    num_twins: usize,
+    num_rounds: Round,
+) -> impl Strategy<Value = Scenario> {
+    let num_all_nodes = num_nodes + num_twins;
+    let round_timeout_ms = ConsensusConfig::default().round_initial_timeout_ms;
+    let round_schedule = (0..num_nodes, vec(0..MAX_PARTITIONS, num_all_nodes));
+    vec(round_schedule, num_rounds as usize).prop_map(move |schedule| {
+        let mut round_leaders = BTreeMap::new();
+        let mut round_partitions = BTreeMap::new();
+        for (round, (leader, assignment)) in (1..).zip(schedule) {
+            round_leaders.insert(round, leader);
+            let mut partitions = vec![vec![]; MAX_PARTITIONS];
+            for (idx, partition) in assignment.into_iter().enumerate() {
+                partitions[partition].push(idx);
+            }
+            partitions.retain(|partition| !partition.is_empty());
+            if partitions.len() > 1 {
+                round_partitions.insert(round, partitions);
+            }
+        }
+        Scenario {
+            name: "random".to_string(),
+            num_nodes,
+            num_twins,
+            round_leaders,
+            round_partitions,
+            round_timeout_ms: Some(round_timeout_ms),
+            expected: Expectations::default(),
+        }
+    })
+}
+
+/// Generates `num_scenarios` random scenarios, the same ones for the same seed.
+pub fn generate_scenarios(
+    num_scenarios: usize,
+    num_nodes: usize,
+    num_twins: usize,
+    num_rounds: Round,
+    seed: [u8; 32],
+) -> Vec<Scenario> {
+    let mut runner = TestRunner::new_with_rng(
+        Config::default(),
+        TestRng::from_seed(RngAlgorithm::ChaCha, &seed),
+    );
+    let strategy = scenario_strategy(num_nodes, num_twins, num_rounds);
+    (0..num_scenarios)
+        .map(|i| {
+            let mut scenario = strategy
+                .new_tree(&mut runner)
+                .expect("Failed to generate a scenario")
+                .current();
+            scenario.name = format!("random {}", i);
+            scenario
+        })
+        .collect()
+}
@@ -0,0 +1,141 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    test_utils::CommittedBlockInfo,
+    twins::{
+        scenario::{run_scenarios_in_dir, Expectations, Scenario},
+        scenario_generator::generate_scenarios,
+    },
+};
+use diem_crypto::HashValue;
+use diem_types::block_info::BlockInfo;
+use std::{collections::BTreeMap, path::Path};
+
+#[test]
+/// Runs the scenarios of `src/twins/scenarios`.
+///
+/// Run the test:
+/// cargo xtest -p consensus twins_scenarios_test -- --nocapture
+fn twins_scenarios_test() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/twins/scenarios");
+    let errors = run_scenarios_in_dir(&dir).unwrap();
+    assert!(
+        errors.is_empty(),
+        "failed scenarios:\n{}",
+        errors
+            .iter()
+            .map(|e| format!("{:#}", e))
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn scenario_save_and_load_test() {
+    let dir = tempfile::tempdir().unwrap();
+    for (i, scenario) in generate_scenarios(5, 4, 1, 10, [0; 32])
+        .into_iter()
+        .enumerate()
+    {
+        let path = dir.path().join(format!("{}.yaml", i));
+        scenario.save(&path).unwrap();
+        assert_eq!(Scenario::load(&path).unwrap(), scenario);
+    }
+    // the same seed generates the same scenarios
+    assert_eq!(
+        generate_scenarios(5, 4, 1, 10, [0; 32]),
+        generate_scenarios(5, 4, 1, 10, [0; 32])
+    );
+}
+
+#[test]
+fn scenario_validation_test() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("twin_leader.json");
+    std::fs::write(
+        &path,
+        r#"{"name": "twin leader", "num_nodes": 4, "num_twins": 1, "round_leaders": {"1": 4}}"#,
+    )
+    .unwrap();
+    assert!(Scenario::load(&path).is_err());
+
+    std::fs::write(
+        &path,
+        r#"{"name": "twin leader", "num_nodes": 4, "num_twins": 1, "round_leaders": {"1": 0}}"#,
+    )
+    .unwrap();
+    assert!(Scenario::load(&path).is_ok());
+}
+
+#[test]
+#[ignore]
+/// Fuzzes safety with random partition schedules, with one twin out of four nodes.
+///
+/// Run the test:
+/// cargo xtest -p consensus twins_random_scenarios_test -- --ignored --nocapture
+fn twins_random_scenarios_test() {
+    let mut seed = [0u8; 32];
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    seed[..16].copy_from_slice(&nanos.to_le_bytes());
+    println!("Seed: {:?}", seed);
+    for scenario in generate_scenarios(20, 4, 1, 10, seed) {
+        if let Err(e) = scenario.run() {
+            panic!("{}\n{}", e, serde_yaml::to_string(&scenario).unwrap());
+        }
+    }
+}
+
+#[test]
+fn scenario_safety_check_test() {
+    let scenario = Scenario {
+        name: "safety".to_string(),
+        num_nodes: 3,
+        num_twins: 1,
+        round_leaders: BTreeMap::new(),
+        round_partitions: BTreeMap::new(),
+        round_timeout_ms: None,
+        expected: Expectations::default(),
+    };
+    let block = |round, id: u8, synced| CommittedBlockInfo {
+        info: BlockInfo::new(
+            1,
+            round,
+            HashValue::new([id; HashValue::LENGTH]),
+            HashValue::zero(),
+            round,
+            0,
+            None,
+        ),
+        synced,
+    };
+    let check = |chain: Vec<CommittedBlockInfo>, other_chain: Vec<CommittedBlockInfo>| {
+        // node 0 has a twin, whatever it commits is ignored
+        let chains = vec![vec![block(1, 9, false)], chain, other_chain];
+        scenario.check_safety(&chains)
+    };
+
+    // a chain is a prefix of the other
+    assert!(check(
+        vec![block(1, 1, false), block(2, 2, false)],
+        vec![block(1, 1, false), block(2, 2, false), block(4, 4, false)],
+    )
+    .is_ok());
+    // conflicting blocks at the same round
+    assert!(check(vec![block(1, 1, false)], vec![block(1, 2, false)]).is_err());
+    // a node skipped a block the other committed
+    assert!(check(
+        vec![block(1, 1, false), block(2, 2, false)],
+        vec![block(1, 1, false), block(3, 3, false)],
+    )
+    .is_err());
+    // unless it synced past it
+    assert!(check(
+        vec![block(1, 1, false), block(2, 2, false)],
+        vec![block(1, 1, false), block(3, 3, true)],
+    )
+    .is_ok());
+}
@@ -7,6 +7,7 @@ use crate::{
     network::NetworkTask,
     network_interface::{ConsensusNetworkEvents, ConsensusNetworkSender},
     network_tests::{NetworkPlayground, TwinId},
+    quorum_store::batch_store::BatchStore,
     test_utils::{MockStateComputer, MockStorage, MockTransactionManager},
     util::time_service::ClockTimeService,
 };
@@ -68,11 +69,15 @@ impl SMRNode {
         let (_conn_mgr_reqs_tx, conn_mgr_reqs_rx) = channel::new_test(8);
         let (_, conn_notifs_channel) = conn_notifs_channel::new();
         let mut network_sender = ConsensusNetworkSender::new(
-            PeerManagerRequestSender::new(network_reqs_tx),
+            PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
             ConnectionRequestSender::new(connection_reqs_tx),
         );
         network_sender.initialize(playground.peer_protocols());
-        let network_events = ConsensusNetworkEvents::new(consensus_rx, conn_notifs_channel);
+        let network_events = ConsensusNetworkEvents::new(
+            consensus_rx,
+            conn_notifs_channel,
+            network::constants::MAX_FRAME_SIZE,
+        );
 
         playground.add_node(twin_id, consensus_tx, network_reqs_rx, conn_mgr_reqs_rx);
 
@@ -134,6 +139,7 @@ impl SMRNode {
             state_computer,
             storage.clone(),
             reconfig_listener,
+            Arc::new(BatchStore::new(&config.consensus.quorum_store)),
         );
         let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
 
@@ -156,6 +162,26 @@ impl SMRNode {
         playground: &mut NetworkPlayground,
         proposer_type: ConsensusProposerType,
         round_proposers_idx: Option<HashMap<Round, usize>>,
+    ) -> Vec<Self> {
+        // Disable timeout in twins test to avoid flakiness
+        Self::start_num_nodes_with_twins_and_timeout(
+            num_nodes,
+            num_twins,
+            playground,
+            proposer_type,
+            round_proposers_idx,
+            2_000_000,
+        )
+    }
+
+    /// Starts a given number of nodes and their twins, with the given initial round timeout
+    pub fn start_num_nodes_with_twins_and_timeout(
+        num_nodes: usize,
+        num_twins: usize,
+        playground: &mut NetworkPlayground,
+        proposer_type: ConsensusProposerType,
+        round_proposers_idx: Option<HashMap<Round, usize>>,
+        round_initial_timeout_ms: u64,
     ) -> Vec<Self> {
         assert!(num_nodes >= num_twins);
         let ValidatorSwarm {
@@ -227,8 +253,7 @@ impl SMRNode {
             config.base.waypoint = WaypointConfig::FromConfig(waypoint);
             config.consensus.proposer_type = proposer_type.clone();
             config.consensus.safety_rules.verify_vote_proposal_signature = false;
-            // Disable timeout in twins test to avoid flakiness
-            config.consensus.round_initial_timeout_ms = 2_000_000;
+            config.consensus.round_initial_timeout_ms = round_initial_timeout_ms;
 
             let author = author_from_config(&config);
 
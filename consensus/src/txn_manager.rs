@@ -130,10 +130,11 @@ impl TxnManager for MempoolProxy {
     async fn notify_failed_txn(
         &self,
         block: &Block,
+        resolved_payload: Option<&Payload>,
         compute_results: &StateComputeResult,
     ) -> Result<(), MempoolError> {
         let mut rejected_txns = vec![];
-        let txns = match block.payload() {
+        let txns = match block.payload().or(resolved_payload) {
             Some(txns) => txns,
             None => return Ok(()),
         };
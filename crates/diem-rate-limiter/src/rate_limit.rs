@@ -171,6 +171,16 @@ impl<Key: Eq + Hash + Clone + Debug> TokenBucketRateLimiter<Key> {
         }
         remove
     }
+
+    /// Garbage collects all the buckets that aren't in use and have refilled completely, as they
+    /// are no different from a newly created bucket.  Useful when keys are unbounded (e.g.
+    /// accounts).  Returns the number of buckets removed.
+    pub fn garbage_collect_full_buckets(&self) -> usize {
+        let mut buckets = self.buckets.write();
+        let num_buckets = buckets.len();
+        buckets.retain(|_, bucket| Arc::strong_count(bucket) > 1 || !bucket.lock().is_full());
+        num_buckets - buckets.len()
+    }
 }
 
 /// A token bucket object that keeps track of everything related to a key
@@ -370,6 +380,15 @@ impl Bucket {
         self.tokens = min(self.size, self.tokens.saturating_add(new_tokens));
     }
 
+    /// Whether the bucket is at its maximum size after refilling
+    pub fn is_full(&mut self) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        self.refill();
+        self.tokens >= self.size
+    }
+
     /// Returns tokens that were unused
     pub fn return_tokens(&mut self, new_tokens: usize) {
         self.allowed_in_period = self.allowed_in_period.saturating_sub(new_tokens);
@@ -546,4 +565,27 @@ mod tests {
         assert!(!rate_limiter.try_garbage_collect_key(&key_to_keep));
         assert_num_keys(&rate_limiter, 1);
     }
+
+    #[test]
+    fn test_garbage_collect_full_buckets() {
+        let rate_limiter = TokenBucketRateLimiter::test(2, 1);
+
+        // A full bucket, and one with tokens taken out
+        {
+            rate_limiter.bucket("full");
+            rate_limiter
+                .bucket("used")
+                .lock()
+                .acquire_tokens(1)
+                .unwrap();
+        }
+        // A full bucket still in use
+        let _bucket_arc = rate_limiter.bucket("in use");
+        assert_num_keys(&rate_limiter, 3);
+
+        assert_eq!(rate_limiter.garbage_collect_full_buckets(), 1);
+        assert_num_keys(&rate_limiter, 2);
+        assert!(!rate_limiter.try_garbage_collect_key(&"in use"));
+        assert!(rate_limiter.try_garbage_collect_key(&"used"));
+    }
 }
@@ -173,6 +173,8 @@ mod test {
             expired: 0,
             latency: 0,
             latency_buckets: histogram.snapshot(),
+            by_kind: Default::default(),
+            target_tps: None,
         };
         let res = stat.latency_buckets.percentile(9, 10);
         assert_eq!(res, 900);
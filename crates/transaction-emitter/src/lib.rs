@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{format_err, Context, Result};
+use anyhow::{bail, format_err, Context, Result};
 use diem_client::Response;
 use diem_logger::*;
 use diem_rest_client::{Client as RestClient, PendingTransaction};
@@ -12,7 +12,7 @@ use diem_sdk::{
     types::{
         account_config::XUS_NAME,
         chain_id::ChainId,
-        transaction::{authenticator::AuthenticationKey, SignedTransaction},
+        transaction::{authenticator::AuthenticationKey, ScriptFunction, SignedTransaction},
         LocalAccount,
     },
 };
@@ -26,25 +26,27 @@ use rand::{
 use rand_core::SeedableRng;
 use std::{
     cmp::{max, min},
-    collections::HashSet,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fmt,
     num::NonZeroU64,
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     time::{Duration, Instant},
 };
-use tokio::{runtime::Handle, task::JoinHandle, time};
+use tokio::{runtime::Handle, sync::Semaphore, task::JoinHandle, time};
 
 pub mod atomic_histogram;
 pub mod cluster;
 pub mod instance;
+pub mod workload;
 
 use atomic_histogram::*;
 use diem_crypto::ed25519::Ed25519PrivateKey;
 use diem_sdk::types::AccountKey;
 use rand::rngs::StdRng;
+pub use workload::{TpsProfile, TransactionKind, TransactionType, Workload};
 
 /// Max transactions per account in mempool
 const MAX_TXN_BATCH_SIZE: usize = 100;
@@ -54,6 +56,8 @@ const TXN_EXPIRATION_SECONDS: u64 = 180;
 const TXN_MAX_WAIT: Duration = Duration::from_secs(TXN_EXPIRATION_SECONDS as u64 + 30);
 const MAX_CHILD_VASP_NUM: usize = 65536;
 const MAX_VASP_ACCOUNT_NUM: usize = 16;
+/// Bound on the submit requests of an open-loop worker waiting for a response.
+const MAX_IN_FLIGHT_SUBMITS: usize = 1_000;
 
 #[derive(Clone)]
 pub struct EmitThreadParams {
@@ -79,6 +83,9 @@ pub struct EmitJobRequest {
     gas_price: u64,
     invalid_transaction_ratio: usize,
     vasp: bool,
+    workload: Workload,
+    tps_profile: Option<TpsProfile>,
+    open_loop_tps: Option<NonZeroU64>,
 }
 
 impl Default for EmitJobRequest {
@@ -91,6 +98,9 @@ impl Default for EmitJobRequest {
             gas_price: 0,
             invalid_transaction_ratio: 0,
             vasp: false,
+            workload: Workload::default(),
+            tps_profile: None,
+            open_loop_tps: None,
         }
     }
 }
@@ -147,6 +157,28 @@ impl EmitJobRequest {
         self.vasp = true;
         self
     }
+
+    pub fn workload(mut self, workload: Workload) -> Self {
+        self.workload = workload;
+        self
+    }
+
+    /// Paces the workers so that the job submits at most the target TPS of `tps_profile` at any
+    /// point of the job. Workers waiting for commits may submit less.
+    pub fn tps_profile(mut self, tps_profile: TpsProfile) -> Self {
+        self.tps_profile = Some(tps_profile);
+        self
+    }
+
+    /// Submits transactions at `target_tps` no matter how fast they commit, instead of waiting
+    /// for the commits of a batch before sending the next one. Latencies are measured from the
+    /// time each transaction was scheduled to be sent, so that they include the queueing of an
+    /// overloaded system. `thread_params` and `tps_profile` are ignored in this mode, and there
+    /// should be enough accounts per client to stay under the mempool limit of each account.
+    pub fn open_loop(mut self, target_tps: NonZeroU64) -> Self {
+        self.open_loop_tps = Some(target_tps);
+        self
+    }
 }
 
 #[derive(Debug, Default)]
@@ -156,6 +188,9 @@ pub struct TxnStats {
     pub expired: u64,
     pub latency: u64,
     pub latency_buckets: AtomicHistogramSnapshot,
+    pub by_kind: BTreeMap<TransactionKind, KindStats>,
+    /// Target rate of an open-loop job.
+    pub target_tps: Option<u64>,
 }
 
 #[derive(Debug, Default)]
@@ -165,15 +200,60 @@ pub struct TxnStatsRate {
     pub expired: u64,
     pub latency: u64,
     pub p99_latency: u64,
+    pub by_kind: BTreeMap<TransactionKind, KindStatsRate>,
+    pub target_tps: Option<u64>,
+}
+
+/// Stats of the transactions of a single `TransactionKind`.
+#[derive(Clone, Debug, Default)]
+pub struct KindStats {
+    pub submitted: u64,
+    pub committed: u64,
+    pub expired: u64,
+    pub latency: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct KindStatsRate {
+    pub submitted: u64,
+    pub committed: u64,
+    pub expired: u64,
+    pub latency: u64,
 }
 
 #[derive(Default)]
+struct KindStatsAccumulator {
+    submitted: AtomicU64,
+    committed: AtomicU64,
+    expired: AtomicU64,
+    latency: AtomicU64,
+}
+
 struct StatsAccumulator {
     submitted: AtomicU64,
     committed: AtomicU64,
     expired: AtomicU64,
     latency: AtomicU64,
     latencies: Arc<AtomicHistogramAccumulator>,
+    by_kind: HashMap<TransactionKind, KindStatsAccumulator>,
+    target_tps: Option<u64>,
+}
+
+impl Default for StatsAccumulator {
+    fn default() -> Self {
+        Self {
+            submitted: AtomicU64::default(),
+            committed: AtomicU64::default(),
+            expired: AtomicU64::default(),
+            latency: AtomicU64::default(),
+            latencies: Arc::default(),
+            by_kind: TransactionKind::ALL
+                .iter()
+                .map(|kind| (*kind, KindStatsAccumulator::default()))
+                .collect(),
+            target_tps: None,
+        }
+    }
 }
 
 struct Worker {
@@ -188,6 +268,8 @@ pub struct EmitJob {
 
 struct SubmissionWorker {
     accounts: Vec<LocalAccount>,
+    /// Parent VASP account creating the accounts of `TransactionType::AccountCreation`.
+    account_creator: Option<LocalAccount>,
     client: RestClient,
     all_addresses: Arc<Vec<AccountAddress>>,
     stop: Arc<AtomicBool>,
@@ -195,6 +277,10 @@ struct SubmissionWorker {
     stats: Arc<StatsAccumulator>,
     txn_factory: TransactionFactory,
     invalid_transaction_ratio: usize,
+    workload: Workload,
+    tps_profile: Option<TpsProfile>,
+    job_start_time: Instant,
+    num_workers: usize,
     rng: ::rand::rngs::StdRng,
 }
 
@@ -203,32 +289,55 @@ impl SubmissionWorker {
     async fn run(mut self, gas_price: u64) -> Vec<LocalAccount> {
         let wait_duration = Duration::from_millis(self.params.wait_millis);
         while !self.stop.load(Ordering::Relaxed) {
+            let target_tps = self
+                .tps_profile
+                .as_ref()
+                .map(|profile| profile.target_tps(self.job_start_time.elapsed()));
+            if target_tps == Some(0) {
+                time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
             let requests = self.gen_requests(gas_price);
             let num_requests = requests.len();
             let start_time = Instant::now();
-            let wait_until = start_time + wait_duration;
+            let wait_until = match target_tps {
+                // Each worker submits its share of the target TPS.
+                Some(tps) => {
+                    start_time
+                        + Duration::from_secs_f64(
+                            (num_requests * self.num_workers) as f64 / tps as f64,
+                        )
+                }
+                None => start_time + wait_duration,
+            };
             let mut txn_offset_time = 0u64;
-            for request in requests {
+            let mut kinds = Vec::with_capacity(num_requests);
+            for (request, kind) in requests {
                 let cur_time = Instant::now();
                 txn_offset_time += (cur_time - start_time).as_millis() as u64;
                 self.stats.submitted.fetch_add(1, Ordering::Relaxed);
+                self.stats.by_kind[&kind]
+                    .submitted
+                    .fetch_add(1, Ordering::Relaxed);
+                kinds.push((request.sender(), kind));
                 let resp = self.client.submit(&request).await;
                 if let Err(e) = resp {
                     warn!("[{:?}] Failed to submit request: {:?}", self.client, e);
                 }
             }
             if self.params.wait_committed {
-                if let Err(uncommitted) =
-                    wait_for_accounts_sequence(&self.client, &mut self.accounts).await
-                {
+                let result = self.wait_for_committed().await;
+                let latency = (Instant::now() - start_time).as_millis() as u64
+                    - txn_offset_time / num_requests as u64;
+                self.stats
+                    .record_kinds(&kinds, result.as_ref().err(), latency);
+                if let Err(uncommitted) = result {
                     let num_committed = (num_requests - uncommitted.len()) as u64;
                     // To avoid negative result caused by uncommitted tx occur
                     // Simplified from:
                     // end_time * num_committed - (txn_offset_time/num_requests) * num_committed
                     // to
                     // (end_time - txn_offset_time / num_requests) * num_committed
-                    let latency = (Instant::now() - start_time).as_millis() as u64
-                        - txn_offset_time / num_requests as u64;
                     let committed_latency = latency * num_committed as u64;
                     self.stats
                         .committed
@@ -247,8 +356,6 @@ impl SubmissionWorker {
                         self.client, uncommitted
                     );
                 } else {
-                    let latency = (Instant::now() - start_time).as_millis() as u64
-                        - txn_offset_time / num_requests as u64;
                     self.stats
                         .committed
                         .fetch_add(num_requests as u64, Ordering::Relaxed);
@@ -268,13 +375,96 @@ impl SubmissionWorker {
         self.accounts
     }
 
-    fn gen_requests(&mut self, gas_price: u64) -> Vec<SignedTransaction> {
+    /// Submits transactions at a fixed rate, without waiting for them to commit. Commits are
+    /// tracked by a separate task, the latency of a transaction is measured from the time it was
+    /// scheduled to be sent rather than the time it was actually sent, which corrects for the
+    /// coordinated omission of a submitter falling behind its schedule.
+    async fn run_open_loop(mut self, gas_price: u64, target_tps: u64) -> Vec<LocalAccount> {
+        let state = Arc::new(OpenLoopState::default());
+        let submits = Arc::new(Semaphore::new(MAX_IN_FLIGHT_SUBMITS));
+        let tracker = tokio::spawn(track_commits(
+            self.client.clone(),
+            Arc::clone(&self.stats),
+            Arc::clone(&state),
+            Arc::clone(&self.stop),
+        ));
+        let mut schedule = OpenLoopSchedule::new(self.num_workers, target_tps);
+        while !self.stop.load(Ordering::Relaxed) {
+            let resyncs = std::mem::take(&mut *state.resync.lock().unwrap());
+            // the account creator sends the account creation transactions
+            resync_sequence_numbers(
+                self.accounts
+                    .iter_mut()
+                    .chain(self.account_creator.as_mut()),
+                &resyncs,
+            );
+            for (request, kind) in self.gen_requests(gas_price) {
+                if self.stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let scheduled_time = schedule.wait_next().await;
+                let permit = submits
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("submit semaphore is never closed");
+                self.stats.submitted.fetch_add(1, Ordering::Relaxed);
+                self.stats.by_kind[&kind]
+                    .submitted
+                    .fetch_add(1, Ordering::Relaxed);
+                state.pending.lock().unwrap().push(PendingTransaction {
+                    sender: request.sender(),
+                    sequence_number: request.sequence_number(),
+                    scheduled_time,
+                    kind,
+                });
+                let client = self.client.clone();
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    if let Err(e) = client.submit(&request).await {
+                        warn!("[{:?}] Failed to submit request: {:?}", client, e);
+                        state
+                            .failed
+                            .lock()
+                            .unwrap()
+                            .insert((request.sender(), request.sequence_number()));
+                    }
+                    drop(permit);
+                });
+            }
+        }
+        if let Err(e) = tracker.await {
+            warn!("[{:?}] Commit tracker failed: {:?}", self.client, e);
+        }
+        self.accounts
+    }
+
+    async fn wait_for_committed(&mut self) -> Result<(), Vec<AccountAddress>> {
+        let result = wait_for_accounts_sequence(&self.client, &mut self.accounts).await;
+        let creator_result = match &mut self.account_creator {
+            Some(creator) => {
+                wait_for_accounts_sequence(&self.client, std::slice::from_mut(creator)).await
+            }
+            None => Ok(()),
+        };
+        match (result, creator_result) {
+            (Ok(()), Ok(())) => Ok(()),
+            (Err(uncommitted), Ok(())) | (Ok(()), Err(uncommitted)) => Err(uncommitted),
+            (Err(mut uncommitted), Err(mut uncommitted_creator)) => {
+                uncommitted.append(&mut uncommitted_creator);
+                Err(uncommitted)
+            }
+        }
+    }
+
+    fn gen_requests(&mut self, gas_price: u64) -> Vec<(SignedTransaction, TransactionKind)> {
         let batch_size = max(MAX_TXN_BATCH_SIZE, self.accounts.len());
         let accounts = self
             .accounts
             .iter_mut()
             .choose_multiple(&mut self.rng, batch_size);
         let mut requests = Vec::with_capacity(accounts.len());
+        let mut kinds = Vec::with_capacity(accounts.len());
         let invalid_size = if self.invalid_transaction_ratio != 0 {
             // if enable mix invalid tx, at least 1 invalid tx per batch
             max(1, accounts.len() * self.invalid_transaction_ratio / 100)
@@ -289,14 +479,78 @@ impl SubmissionWorker {
                 .expect("all_addresses can't be empty");
             let request = if num_valid_tx > 0 {
                 num_valid_tx -= 1;
-                gen_transfer_txn_request(
-                    sender,
-                    receiver,
-                    SEND_AMOUNT,
-                    &self.txn_factory,
-                    gas_price,
-                )
+                let txn_type = self.workload.sample(&mut self.rng);
+                let request = match txn_type {
+                    TransactionType::P2p => gen_transfer_txn_request(
+                        sender,
+                        receiver,
+                        SEND_AMOUNT,
+                        &self.txn_factory,
+                        gas_price,
+                    ),
+                    TransactionType::ZipfP2p { exponent } => {
+                        let index = workload::zipf_index(
+                            &mut self.rng,
+                            self.all_addresses.len(),
+                            *exponent,
+                        );
+                        gen_transfer_txn_request(
+                            sender,
+                            &self.all_addresses[index],
+                            SEND_AMOUNT,
+                            &self.txn_factory,
+                            gas_price,
+                        )
+                    }
+                    TransactionType::AccountCreation => {
+                        let creator = self
+                            .account_creator
+                            .as_mut()
+                            .expect("account creator is set for account creation workloads");
+                        creator.sign_with_transaction_builder(
+                            self.txn_factory
+                                .create_child_vasp_account(
+                                    Currency::XUS,
+                                    AccountKey::generate(&mut self.rng).authentication_key(),
+                                    false,
+                                    0,
+                                )
+                                .gas_unit_price(gas_price),
+                        )
+                    }
+                    TransactionType::ScriptFunction {
+                        module,
+                        function,
+                        ty_args,
+                        args,
+                    } => sender.sign_with_transaction_builder(
+                        self.txn_factory
+                            .script_function(ScriptFunction::new(
+                                module.clone(),
+                                function.clone(),
+                                ty_args.clone(),
+                                args.clone(),
+                            ))
+                            .gas_unit_price(gas_price),
+                    ),
+                    TransactionType::HotAccount { num_hot_accounts } => {
+                        let hot_accounts =
+                            &self.all_addresses[..min(*num_hot_accounts, self.all_addresses.len())];
+                        gen_transfer_txn_request(
+                            sender,
+                            hot_accounts
+                                .choose(&mut self.rng)
+                                .expect("all_addresses can't be empty"),
+                            SEND_AMOUNT,
+                            &self.txn_factory,
+                            gas_price,
+                        )
+                    }
+                };
+                kinds.push(txn_type.kind());
+                request
             } else {
+                kinds.push(TransactionKind::Invalid);
                 generate_invalid_transaction(
                     sender,
                     receiver,
@@ -309,7 +563,7 @@ impl SubmissionWorker {
             };
             requests.push(request);
         }
-        requests
+        requests.into_iter().zip(kinds).collect()
     }
 }
 
@@ -541,6 +795,9 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
     }
 
     pub async fn start_job(&mut self, req: EmitJobRequest) -> Result<EmitJob> {
+        if req.vasp && req.workload.has_account_creation() {
+            bail!("Account creation workloads are not supported with VASP accounts");
+        }
         let workers_per_endpoint = match req.workers_per_endpoint {
             Some(x) => x,
             None => {
@@ -564,12 +821,24 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
         );
         self.mint_accounts(&req, num_accounts).await?;
         let all_accounts = self.accounts.split_off(self.accounts.len() - num_accounts);
+        // Each worker creates accounts from a parent VASP account of its own.
+        let mut account_creators = if req.workload.has_account_creation() {
+            self.get_seed_accounts(&req.rest_clients, num_clients, false)
+                .await?
+        } else {
+            vec![]
+        }
+        .into_iter();
+        let job_start_time = Instant::now();
         let mut workers = vec![];
         let all_addresses: Vec<_> = all_accounts.iter().map(|d| d.address()).collect();
         let all_addresses = Arc::new(all_addresses);
         let mut all_accounts = all_accounts.into_iter();
         let stop = Arc::new(AtomicBool::new(false));
-        let stats = Arc::new(StatsAccumulator::default());
+        let stats = Arc::new(StatsAccumulator {
+            target_tps: req.open_loop_tps.map(NonZeroU64::get),
+            ..StatsAccumulator::default()
+        });
         let tokio_handle = Handle::current();
         for client in req.rest_clients {
             for _ in 0..workers_per_endpoint {
@@ -580,6 +849,7 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
                 let stats = Arc::clone(&stats);
                 let worker = SubmissionWorker {
                     accounts,
+                    account_creator: account_creators.next(),
                     client: client.clone(),
                     all_addresses,
                     stop,
@@ -587,9 +857,20 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
                     stats,
                     txn_factory: self.txn_factory.clone(),
                     invalid_transaction_ratio: req.invalid_transaction_ratio,
+                    workload: req.workload.clone(),
+                    tps_profile: req.tps_profile.clone(),
+                    job_start_time,
+                    num_workers: num_clients,
                     rng: self.from_rng(),
                 };
-                let join_handle = tokio_handle.spawn(worker.run(req.gas_price).boxed());
+                let join_handle = match req.open_loop_tps {
+                    Some(target_tps) => tokio_handle.spawn(
+                        worker
+                            .run_open_loop(req.gas_price, target_tps.get())
+                            .boxed(),
+                    ),
+                    None => tokio_handle.spawn(worker.run(req.gas_price).boxed()),
+                };
                 workers.push(Worker { join_handle });
             }
         }
@@ -731,6 +1012,156 @@ pub async fn execute_and_wait_transactions(
     Ok(())
 }
 
+/// A transaction submitted by an open-loop worker.
+struct PendingTransaction {
+    sender: AccountAddress,
+    sequence_number: u64,
+    scheduled_time: Instant,
+    kind: TransactionKind,
+}
+
+/// State shared by an open-loop worker, its submit tasks and its commit tracker.
+#[derive(Default)]
+struct OpenLoopState {
+    /// Transactions submitted since the last poll of the tracker.
+    pending: Mutex<Vec<PendingTransaction>>,
+    /// Sender and sequence number of the transactions the node refused.
+    failed: Mutex<HashSet<(AccountAddress, u64)>>,
+    /// Sequence numbers on chain of the senders left with a gap, for the worker to restart from.
+    resync: Mutex<HashMap<AccountAddress, u64>>,
+}
+
+/// Send times of an open-loop worker, spaced so that `num_workers` workers together send
+/// `target_tps` transactions per second.
+struct OpenLoopSchedule {
+    next: Instant,
+    interval: Duration,
+}
+
+impl OpenLoopSchedule {
+    fn new(num_workers: usize, target_tps: u64) -> Self {
+        Self {
+            next: Instant::now(),
+            interval: Duration::from_secs_f64(num_workers as f64 / target_tps as f64),
+        }
+    }
+
+    /// Waits for the next send time and returns it. The schedule doesn't slip when the caller
+    /// falls behind, the following send times are returned without waiting until it catches up.
+    async fn wait_next(&mut self) -> Instant {
+        let scheduled_time = self.next;
+        let now = Instant::now();
+        if scheduled_time > now {
+            time::sleep(scheduled_time - now).await;
+        }
+        self.next += self.interval;
+        scheduled_time
+    }
+}
+
+/// Polls the sequence numbers of the senders of pending transactions until `stop` is set, and
+/// records the latency of each committed transaction from the time it was scheduled to be sent.
+/// Senders whose transactions can no longer commit are handed back to the worker for a resync.
+async fn track_commits(
+    client: RestClient,
+    stats: Arc<StatsAccumulator>,
+    state: Arc<OpenLoopState>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut by_sender: HashMap<AccountAddress, VecDeque<PendingTransaction>> = HashMap::new();
+    let mut failed = HashSet::new();
+    while !stop.load(Ordering::Relaxed) {
+        time::sleep(Duration::from_millis(500)).await;
+        let new_txns = std::mem::take(&mut *state.pending.lock().unwrap());
+        for txn in new_txns {
+            by_sender.entry(txn.sender).or_default().push_back(txn);
+        }
+        failed.extend(state.failed.lock().unwrap().drain());
+        let addresses: Vec<_> = by_sender.keys().copied().collect();
+        if addresses.is_empty() {
+            continue;
+        }
+        let sequence_numbers = match query_sequence_numbers(&client, &addresses).await {
+            Ok(sequence_numbers) => sequence_numbers,
+            Err(e) => {
+                warn!("[{:?}] Failed to query sequence numbers: {:?}", client, e);
+                continue;
+            }
+        };
+        let now = Instant::now();
+        for (address, sequence_number) in zip(addresses, sequence_numbers) {
+            let txns = by_sender
+                .get_mut(&address)
+                .expect("sender has pending txns");
+            if settle_pending(&stats, txns, &mut failed, sequence_number, now) {
+                info!(
+                    "[{:?}] Resyncing {} to sequence number {}",
+                    client, address, sequence_number
+                );
+                state
+                    .resync
+                    .lock()
+                    .unwrap()
+                    .insert(address, sequence_number);
+            }
+            if txns.is_empty() {
+                by_sender.remove(&address);
+            }
+        }
+    }
+}
+
+/// Records the pending transactions of a sender that committed or expired, given the sequence
+/// number of the sender on chain. Returns whether the sender was left with a gap, i.e. its next
+/// transaction was refused by the node or expired, in which case none of its later transactions
+/// can commit and they are all expired.
+fn settle_pending(
+    stats: &StatsAccumulator,
+    txns: &mut VecDeque<PendingTransaction>,
+    failed: &mut HashSet<(AccountAddress, u64)>,
+    sequence_number: u64,
+    now: Instant,
+) -> bool {
+    while let Some(txn) = txns.front() {
+        let key = (txn.sender, txn.sequence_number);
+        if txn.sequence_number < sequence_number {
+            let latency = (now - txn.scheduled_time).as_millis() as u64;
+            let kind_stats = &stats.by_kind[&txn.kind];
+            stats.committed.fetch_add(1, Ordering::Relaxed);
+            stats.latency.fetch_add(latency, Ordering::Relaxed);
+            stats.latencies.record_data_point(latency, 1);
+            kind_stats.committed.fetch_add(1, Ordering::Relaxed);
+            kind_stats.latency.fetch_add(latency, Ordering::Relaxed);
+            failed.remove(&key);
+        } else if failed.contains(&key) || now - txn.scheduled_time > TXN_MAX_WAIT {
+            for txn in txns.drain(..) {
+                stats.expired.fetch_add(1, Ordering::Relaxed);
+                stats.by_kind[&txn.kind]
+                    .expired
+                    .fetch_add(1, Ordering::Relaxed);
+                failed.remove(&(txn.sender, txn.sequence_number));
+            }
+            return true;
+        } else {
+            break;
+        }
+        txns.pop_front();
+    }
+    false
+}
+
+/// Moves the accounts back to the sequence numbers the commit tracker found them at on chain.
+fn resync_sequence_numbers<'a>(
+    accounts: impl IntoIterator<Item = &'a mut LocalAccount>,
+    resyncs: &HashMap<AccountAddress, u64>,
+) {
+    for account in accounts {
+        if let Some(sequence_number) = resyncs.get(&account.address()) {
+            *account.sequence_number_mut() = *sequence_number;
+        }
+    }
+}
+
 async fn wait_for_accounts_sequence(
     client: &RestClient,
     accounts: &mut [LocalAccount],
@@ -965,6 +1396,40 @@ impl StatsAccumulator {
             expired: self.expired.load(Ordering::Relaxed),
             latency: self.latency.load(Ordering::Relaxed),
             latency_buckets: self.latencies.snapshot(),
+            by_kind: self
+                .by_kind
+                .iter()
+                .map(|(kind, stats)| {
+                    let stats = KindStats {
+                        submitted: stats.submitted.load(Ordering::Relaxed),
+                        committed: stats.committed.load(Ordering::Relaxed),
+                        expired: stats.expired.load(Ordering::Relaxed),
+                        latency: stats.latency.load(Ordering::Relaxed),
+                    };
+                    (*kind, stats)
+                })
+                .collect(),
+            target_tps: self.target_tps,
+        }
+    }
+
+    /// Records the commits of a batch of `(sender, kind)` transactions, those sent by
+    /// `uncommitted` accounts are counted as expired.
+    fn record_kinds(
+        &self,
+        kinds: &[(AccountAddress, TransactionKind)],
+        uncommitted: Option<&Vec<AccountAddress>>,
+        latency: u64,
+    ) {
+        let uncommitted: HashSet<_> = uncommitted.into_iter().flatten().collect();
+        for (sender, kind) in kinds {
+            let stats = &self.by_kind[kind];
+            if uncommitted.contains(sender) {
+                stats.expired.fetch_add(1, Ordering::Relaxed);
+            } else {
+                stats.committed.fetch_add(1, Ordering::Relaxed);
+                stats.latency.fetch_add(latency, Ordering::Relaxed);
+            }
         }
     }
 }
@@ -981,6 +1446,28 @@ impl TxnStats {
                 self.latency / self.committed
             },
             p99_latency: self.latency_buckets.percentile(99, 100),
+            by_kind: self
+                .by_kind
+                .iter()
+                .filter(|(_, stats)| stats.submitted > 0)
+                .map(|(kind, stats)| (*kind, stats.rate(window)))
+                .collect(),
+            target_tps: self.target_tps,
+        }
+    }
+}
+
+impl KindStats {
+    pub fn rate(&self, window: Duration) -> KindStatsRate {
+        KindStatsRate {
+            submitted: self.submitted / window.as_secs(),
+            committed: self.committed / window.as_secs(),
+            expired: self.expired / window.as_secs(),
+            latency: if self.committed == 0 {
+                0u64
+            } else {
+                self.latency / self.committed
+            },
         }
     }
 }
@@ -995,6 +1482,21 @@ impl std::ops::Sub for &TxnStats {
             expired: self.expired - other.expired,
             latency: self.latency - other.latency,
             latency_buckets: &self.latency_buckets - &other.latency_buckets,
+            by_kind: self
+                .by_kind
+                .iter()
+                .map(|(kind, stats)| {
+                    let other = other.by_kind.get(kind).cloned().unwrap_or_default();
+                    let stats = KindStats {
+                        submitted: stats.submitted - other.submitted,
+                        committed: stats.committed - other.committed,
+                        expired: stats.expired - other.expired,
+                        latency: stats.latency - other.latency,
+                    };
+                    (*kind, stats)
+                })
+                .collect(),
+            target_tps: self.target_tps,
         }
     }
 }
@@ -1005,7 +1507,15 @@ impl fmt::Display for TxnStats {
             f,
             "submitted: {}, committed: {}, expired: {}",
             self.submitted, self.committed, self.expired,
-        )
+        )?;
+        for (kind, stats) in self.by_kind.iter().filter(|(_, s)| s.submitted > 0) {
+            write!(
+                f,
+                "\n  {}: submitted: {}, committed: {}, expired: {}",
+                kind, stats.submitted, stats.committed, stats.expired,
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -1015,7 +1525,18 @@ impl fmt::Display for TxnStatsRate {
             f,
             "submitted: {} txn/s, committed: {} txn/s, expired: {} txn/s, latency: {} ms, p99 latency: {} ms",
             self.submitted, self.committed, self.expired, self.latency, self.p99_latency,
-        )
+        )?;
+        if let Some(target_tps) = self.target_tps {
+            write!(f, ", target: {} txn/s", target_tps)?;
+        }
+        for (kind, stats) in &self.by_kind {
+            write!(
+                f,
+                "\n  {}: submitted: {} txn/s, committed: {} txn/s, expired: {} txn/s, latency: {} ms",
+                kind, stats.submitted, stats.committed, stats.expired, stats.latency,
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -1085,3 +1606,111 @@ where
     }
     Ok(vasp_accounts)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_open_loop_schedule_rate() {
+        // Two workers sharing 200 TPS send one transaction every 10ms each.
+        let mut schedule = OpenLoopSchedule::new(2, 200);
+        let start = Instant::now();
+        let mut scheduled_times = vec![];
+        for _ in 0..21 {
+            scheduled_times.push(schedule.wait_next().await);
+        }
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        for (prev, next) in zip(&scheduled_times, &scheduled_times[1..]) {
+            assert_eq!(*next - *prev, Duration::from_millis(10));
+        }
+
+        // A worker falling behind gets the missed send times right away, so that their latency
+        // is measured from the time they should have been sent.
+        time::sleep(Duration::from_millis(100)).await;
+        let behind = Instant::now();
+        for _ in 0..5 {
+            schedule.wait_next().await;
+        }
+        assert!(behind.elapsed() < Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_open_loop_gap_recovery() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut account = LocalAccount::generate(&mut rng);
+        let sender = account.address();
+        let now = Instant::now();
+        let mut txns: VecDeque<_> = (0..5)
+            .map(|sequence_number| PendingTransaction {
+                sender,
+                sequence_number,
+                scheduled_time: now,
+                kind: TransactionKind::P2p,
+            })
+            .collect();
+        *account.sequence_number_mut() = 5;
+        let stats = StatsAccumulator::default();
+        let mut failed = HashSet::new();
+
+        // Nothing is refused yet, the transactions on chain are committed and the rest wait.
+        assert!(!settle_pending(&stats, &mut txns, &mut failed, 1, now));
+        assert_eq!(stats.committed.load(Ordering::Relaxed), 1);
+        assert_eq!(txns.len(), 4);
+
+        // The node refused transaction 2, transactions 3 and 4 can't commit after it.
+        failed.insert((sender, 2));
+        assert!(settle_pending(&stats, &mut txns, &mut failed, 2, now));
+        assert_eq!(stats.committed.load(Ordering::Relaxed), 2);
+        assert_eq!(stats.expired.load(Ordering::Relaxed), 3);
+        assert_eq!(
+            stats.by_kind[&TransactionKind::P2p]
+                .expired
+                .load(Ordering::Relaxed),
+            3
+        );
+        assert!(txns.is_empty());
+        assert!(failed.is_empty());
+
+        // The account creator is resynced along with the accounts.
+        let mut creator = LocalAccount::generate(&mut rng);
+        *creator.sequence_number_mut() = 7;
+        let resyncs = vec![(sender, 2), (creator.address(), 4)]
+            .into_iter()
+            .collect();
+        resync_sequence_numbers(
+            std::iter::once(&mut account).chain(Some(&mut creator)),
+            &resyncs,
+        );
+        assert_eq!(account.sequence_number(), 2);
+        assert_eq!(creator.sequence_number(), 4);
+    }
+
+    #[test]
+    fn test_open_loop_expiry_recovery() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sender = LocalAccount::generate(&mut rng).address();
+        let now = Instant::now();
+        let mut txns: VecDeque<_> = (0..2)
+            .map(|sequence_number| PendingTransaction {
+                sender,
+                sequence_number,
+                scheduled_time: now,
+                kind: TransactionKind::P2p,
+            })
+            .collect();
+        let stats = StatsAccumulator::default();
+        let mut failed = HashSet::new();
+
+        assert!(!settle_pending(&stats, &mut txns, &mut failed, 0, now));
+        assert!(settle_pending(
+            &stats,
+            &mut txns,
+            &mut failed,
+            0,
+            now + TXN_MAX_WAIT + Duration::from_secs(1)
+        ));
+        assert_eq!(stats.expired.load(Ordering::Relaxed), 2);
+        assert!(txns.is_empty());
+    }
+}
@@ -13,6 +13,7 @@ use rand::{rngs::StdRng, Rng, SeedableRng};
 use rand_core::OsRng;
 use std::{
     cmp::min,
+    num::NonZeroU64,
     process,
     time::{Duration, Instant},
 };
@@ -20,7 +21,7 @@ use structopt::StructOpt;
 use termion::color;
 use transaction_emitter::{
     cluster::Cluster, instance::Instance, query_sequence_numbers, EmitJobRequest, EmitThreadParams,
-    TxnEmitter,
+    TpsProfile, TxnEmitter, Workload,
 };
 
 #[derive(StructOpt, Debug)]
@@ -59,6 +60,22 @@ struct Args {
     duration: u64,
     #[structopt(long, help = "Percentage of invalid txs", default_value = "0")]
     invalid_tx: usize,
+    #[structopt(
+        long,
+        help = "Weighted mix of transaction types, e.g. p2p=60,zipf-p2p:1.2=20,create-account=10,hot-account:4=10,script-function:0x1::M::f=0",
+        default_value = "p2p=1"
+    )]
+    workload: Workload,
+    #[structopt(
+        long,
+        help = "Target TPS over time: constant:TPS, ramp:FROM:TO:SECS, step:TPS:SECS,TPS:SECS,... or burst:BASE:BURST:PERIOD_SECS:BURST_SECS"
+    )]
+    tps_profile: Option<TpsProfile>,
+    #[structopt(
+        long,
+        help = "Submit transactions at this rate without waiting for commits, measuring latency from the scheduled send time"
+    )]
+    open_loop_tps: Option<NonZeroU64>,
 }
 
 #[tokio::main]
@@ -114,7 +131,14 @@ async fn emit_tx(cluster: &Cluster, args: &Args) -> Result<()> {
         EmitJobRequest::new(cluster.all_instances().map(Instance::rest_client).collect())
             .accounts_per_client(args.accounts_per_client)
             .thread_params(thread_params)
-            .invalid_transaction_ratio(args.invalid_tx);
+            .invalid_transaction_ratio(args.invalid_tx)
+            .workload(args.workload.clone());
+    if let Some(tps_profile) = &args.tps_profile {
+        emit_job_request = emit_job_request.tps_profile(tps_profile.clone());
+    }
+    if let Some(target_tps) = args.open_loop_tps {
+        emit_job_request = emit_job_request.open_loop(target_tps);
+    }
     if let Some(workers_per_endpoint) = args.workers_per_ac {
         emit_job_request = emit_job_request.workers_per_endpoint(workers_per_endpoint);
     }
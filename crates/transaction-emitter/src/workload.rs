@@ -0,0 +1,504 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, format_err, Result};
+use diem_sdk::move_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+    parser::{parse_transaction_argument, parse_type_tag},
+    transaction_argument::convert_txn_args,
+};
+use rand::Rng;
+use std::{fmt, str::FromStr, time::Duration};
+
+/// The kind of a generated transaction, stats are reported per kind.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TransactionKind {
+    P2p,
+    ZipfP2p,
+    AccountCreation,
+    ScriptFunction,
+    HotAccount,
+    /// Transactions generated invalid on purpose, see `EmitJobRequest::invalid_transaction_ratio`.
+    Invalid,
+}
+
+impl TransactionKind {
+    pub const ALL: [TransactionKind; 6] = [
+        TransactionKind::P2p,
+        TransactionKind::ZipfP2p,
+        TransactionKind::AccountCreation,
+        TransactionKind::ScriptFunction,
+        TransactionKind::HotAccount,
+        TransactionKind::Invalid,
+    ];
+}
+
+impl fmt::Display for TransactionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TransactionKind::P2p => "p2p",
+            TransactionKind::ZipfP2p => "zipf-p2p",
+            TransactionKind::AccountCreation => "create-account",
+            TransactionKind::ScriptFunction => "script-function",
+            TransactionKind::HotAccount => "hot-account",
+            TransactionKind::Invalid => "invalid",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionType {
+    /// P2P transfer to a receiver picked uniformly among all accounts of the job.
+    P2p,
+    /// P2P transfer to a receiver picked with a Zipfian distribution of the given exponent, so
+    /// that a few accounts receive most of the transfers.
+    ZipfP2p { exponent: f64 },
+    /// Creation of a child VASP account with a zero balance. Each worker gets a parent VASP
+    /// account of its own to create them from.
+    AccountCreation,
+    /// Call to a script function, typically from a Move package published beforehand.
+    ScriptFunction {
+        module: ModuleId,
+        function: Identifier,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+    },
+    /// P2P transfer to one of the first `num_hot_accounts` accounts of the job, so that all
+    /// senders contend on the same few balances.
+    HotAccount { num_hot_accounts: usize },
+}
+
+impl TransactionType {
+    pub fn kind(&self) -> TransactionKind {
+        match self {
+            TransactionType::P2p => TransactionKind::P2p,
+            TransactionType::ZipfP2p { .. } => TransactionKind::ZipfP2p,
+            TransactionType::AccountCreation => TransactionKind::AccountCreation,
+            TransactionType::ScriptFunction { .. } => TransactionKind::ScriptFunction,
+            TransactionType::HotAccount { .. } => TransactionKind::HotAccount,
+        }
+    }
+}
+
+/// Parses `kind[:param]`, e.g. `zipf-p2p:1.2`, `hot-account:4` or
+/// `script-function:0x1::Module::function<TYPE;...>(ARG;...)`, where the type arguments and the
+/// arguments are optional and separated by `;`, e.g.
+/// `script-function:0x1::PaymentScripts::peer_to_peer_with_metadata<0x1::XUS::XUS>(0xa;1;x"";x"")`.
+impl FromStr for TransactionType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, param) = match s.split_once(':') {
+            Some((kind, param)) => (kind, Some(param)),
+            None => (s, None),
+        };
+        let param = |name: &str| param.ok_or_else(|| format_err!("{} requires a parameter", name));
+        Ok(match kind {
+            "p2p" => TransactionType::P2p,
+            "zipf-p2p" => {
+                let exponent: f64 = param(kind)?.parse()?;
+                if !exponent.is_finite() || exponent <= 0.0 {
+                    bail!("zipf-p2p requires a positive exponent, got {}", exponent);
+                }
+                TransactionType::ZipfP2p { exponent }
+            }
+            "create-account" => TransactionType::AccountCreation,
+            "script-function" => {
+                let call = param(kind)?;
+                let (function, args) = match call.split_once('(') {
+                    Some((function, args)) => (
+                        function,
+                        args.strip_suffix(')')
+                            .ok_or_else(|| format_err!("Invalid script function {}", call))?,
+                    ),
+                    None => (call, ""),
+                };
+                let (function, ty_args) = match function.split_once('<') {
+                    Some((function, ty_args)) => (
+                        function,
+                        ty_args
+                            .strip_suffix('>')
+                            .ok_or_else(|| format_err!("Invalid script function {}", call))?,
+                    ),
+                    None => (function, ""),
+                };
+                let (module, name) = function
+                    .rsplit_once("::")
+                    .ok_or_else(|| format_err!("Invalid script function {}", function))?;
+                let (address, module) = module
+                    .split_once("::")
+                    .ok_or_else(|| format_err!("Invalid script function {}", function))?;
+                TransactionType::ScriptFunction {
+                    module: ModuleId::new(
+                        AccountAddress::from_hex_literal(address)?,
+                        Identifier::new(module)?,
+                    ),
+                    function: Identifier::new(name)?,
+                    ty_args: split_list(ty_args)
+                        .map(parse_type_tag)
+                        .collect::<Result<_>>()?,
+                    args: convert_txn_args(
+                        &split_list(args)
+                            .map(parse_transaction_argument)
+                            .collect::<Result<Vec<_>>>()?,
+                    ),
+                }
+            }
+            "hot-account" => {
+                let num_hot_accounts = param(kind)?.parse()?;
+                if num_hot_accounts == 0 {
+                    bail!("hot-account requires at least one hot account");
+                }
+                TransactionType::HotAccount { num_hot_accounts }
+            }
+            _ => bail!("Unknown transaction type {}", kind),
+        })
+    }
+}
+
+/// Splits a `;` separated list, which is empty if `list` is.
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// A weighted mix of transaction types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Workload {
+    entries: Vec<(TransactionType, u64)>,
+    total_weight: u64,
+}
+
+impl Default for Workload {
+    fn default() -> Self {
+        Self::new(vec![(TransactionType::P2p, 1)]).expect("P2P workload is valid")
+    }
+}
+
+impl Workload {
+    pub fn new(entries: Vec<(TransactionType, u64)>) -> Result<Self> {
+        let total_weight = entries.iter().map(|(_, weight)| weight).sum();
+        if total_weight == 0 {
+            bail!("Workload needs at least one transaction type with a positive weight");
+        }
+        Ok(Self {
+            entries,
+            total_weight,
+        })
+    }
+
+    pub fn entries(&self) -> &[(TransactionType, u64)] {
+        &self.entries
+    }
+
+    pub fn has_account_creation(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|(txn_type, weight)| *txn_type == TransactionType::AccountCreation && *weight > 0)
+    }
+
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> &TransactionType {
+        let mut point = rng.gen_range(0..self.total_weight);
+        for (txn_type, weight) in &self.entries {
+            if point < *weight {
+                return txn_type;
+            }
+            point -= weight;
+        }
+        unreachable!("point is below the total weight")
+    }
+}
+
+/// Parses a comma separated list of `type=weight`, e.g. `p2p=70,hot-account:4=30`.
+impl FromStr for Workload {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let entries = s
+            .split(',')
+            .map(|entry| {
+                let (txn_type, weight) = entry
+                    .rsplit_once('=')
+                    .ok_or_else(|| format_err!("Expected type=weight, got {}", entry))?;
+                Ok((txn_type.parse()?, weight.parse()?))
+            })
+            .collect::<Result<_>>()?;
+        Self::new(entries)
+    }
+}
+
+/// Picks an index in `0..n` with a probability roughly proportional to `1 / (index + 1)^exponent`,
+/// using the inverse of the continuous approximation of the Zipfian distribution.
+pub fn zipf_index<R: Rng + ?Sized>(rng: &mut R, n: usize, exponent: f64) -> usize {
+    let u: f64 = rng.gen();
+    let n = n as f64;
+    let rank = if (exponent - 1.0).abs() < f64::EPSILON {
+        (n + 1.0).powf(u)
+    } else {
+        let e = 1.0 - exponent;
+        (((n + 1.0).powf(e) - 1.0) * u + 1.0).powf(1.0 / e)
+    };
+    (rank.floor() as usize)
+        .saturating_sub(1)
+        .min(n as usize - 1)
+}
+
+/// Target TPS of a job over time.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TpsProfile {
+    Constant(u64),
+    /// Linear ramp from `from` to `to` TPS over `duration`, then constant at `to`.
+    Ramp {
+        from: u64,
+        to: u64,
+        duration: Duration,
+    },
+    /// Each step holds its TPS for its duration, the last one until the end of the job.
+    Step(Vec<(u64, Duration)>),
+    /// `base` TPS, raised to `burst` TPS for the first `burst_duration` of every `period`.
+    Burst {
+        base: u64,
+        burst: u64,
+        period: Duration,
+        burst_duration: Duration,
+    },
+}
+
+impl TpsProfile {
+    pub fn target_tps(&self, elapsed: Duration) -> u64 {
+        match self {
+            TpsProfile::Constant(tps) => *tps,
+            TpsProfile::Ramp { from, to, duration } => {
+                if elapsed >= *duration {
+                    *to
+                } else {
+                    let progress = elapsed.as_secs_f64() / duration.as_secs_f64();
+                    (*from as f64 + (*to as f64 - *from as f64) * progress) as u64
+                }
+            }
+            TpsProfile::Step(steps) => {
+                let mut step_end = Duration::from_secs(0);
+                for (tps, duration) in steps {
+                    step_end += *duration;
+                    if elapsed < step_end {
+                        return *tps;
+                    }
+                }
+                steps.last().map_or(0, |(tps, _)| *tps)
+            }
+            TpsProfile::Burst {
+                base,
+                burst,
+                period,
+                burst_duration,
+            } => {
+                let offset = elapsed.as_millis() % period.as_millis();
+                if offset < burst_duration.as_millis() {
+                    *burst
+                } else {
+                    *base
+                }
+            }
+        }
+    }
+}
+
+/// Parses `constant:TPS`, `ramp:FROM:TO:SECS`, `step:TPS:SECS,TPS:SECS,...` or
+/// `burst:BASE:BURST:PERIOD_SECS:BURST_SECS`.
+impl FromStr for TpsProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, params) = s
+            .split_once(':')
+            .ok_or_else(|| format_err!("Invalid TPS profile {}", s))?;
+        let numbers = |params: &str| -> Result<Vec<u64>> {
+            params
+                .split(':')
+                .map(|n| Ok(n.parse()?))
+                .collect::<Result<_>>()
+        };
+        let profile = match (kind, numbers(params).as_deref()) {
+            ("constant", Ok([tps])) => TpsProfile::Constant(*tps),
+            ("ramp", Ok([from, to, secs])) => TpsProfile::Ramp {
+                from: *from,
+                to: *to,
+                duration: Duration::from_secs(*secs),
+            },
+            ("burst", Ok([base, burst, period, burst_duration]))
+                if *period > 0 && burst_duration <= period =>
+            {
+                TpsProfile::Burst {
+                    base: *base,
+                    burst: *burst,
+                    period: Duration::from_secs(*period),
+                    burst_duration: Duration::from_secs(*burst_duration),
+                }
+            }
+            ("step", _) => TpsProfile::Step(
+                params
+                    .split(',')
+                    .map(|step| match numbers(step)?.as_slice() {
+                        [tps, secs] => Ok((*tps, Duration::from_secs(*secs))),
+                        _ => bail!("Expected TPS:SECS, got {}", step),
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            _ => bail!("Invalid TPS profile {}", s),
+        };
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_parse_workload() {
+        let workload: Workload =
+            "p2p=70,hot-account:4=20,create-account=10,script-function:0x1::M::f=1"
+                .parse()
+                .unwrap();
+        assert_eq!(workload.entries().len(), 4);
+        assert_eq!(
+            workload.entries()[1],
+            (
+                TransactionType::HotAccount {
+                    num_hot_accounts: 4
+                },
+                20
+            )
+        );
+        assert!(workload.has_account_creation());
+        assert_eq!(
+            workload.entries()[3].0.kind(),
+            TransactionKind::ScriptFunction
+        );
+
+        assert!("p2p=0".parse::<Workload>().is_err());
+        assert!("zipf-p2p=1".parse::<Workload>().is_err());
+        assert!("p2p".parse::<Workload>().is_err());
+    }
+
+    #[test]
+    fn test_parse_zipf_exponent() {
+        assert_eq!(
+            "zipf-p2p:1.2".parse::<TransactionType>().unwrap(),
+            TransactionType::ZipfP2p { exponent: 1.2 }
+        );
+        for exponent in &["0", "-1", "inf", "NaN"] {
+            assert!(format!("zipf-p2p:{}", exponent)
+                .parse::<TransactionType>()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_script_function() {
+        let txn_type: TransactionType =
+            "script-function:0x1::PaymentScripts::peer_to_peer_with_metadata<0x1::XUS::XUS>(0xa; 1; x\"\"; x\"\")"
+                .parse()
+                .unwrap();
+        match txn_type {
+            TransactionType::ScriptFunction {
+                module,
+                function,
+                ty_args,
+                args,
+            } => {
+                assert_eq!(module.name().as_str(), "PaymentScripts");
+                assert_eq!(function.as_str(), "peer_to_peer_with_metadata");
+                assert_eq!(ty_args, vec![parse_type_tag("0x1::XUS::XUS").unwrap()]);
+                assert_eq!(
+                    args,
+                    convert_txn_args(&[
+                        parse_transaction_argument("0xa").unwrap(),
+                        parse_transaction_argument("1").unwrap(),
+                        parse_transaction_argument("x\"\"").unwrap(),
+                        parse_transaction_argument("x\"\"").unwrap(),
+                    ])
+                );
+            }
+            txn_type => panic!("Unexpected {:?}", txn_type),
+        }
+
+        assert!("script-function:0x1::M::f(1"
+            .parse::<TransactionType>()
+            .is_err());
+        assert!("script-function:0x1::M::f<u64(1)"
+            .parse::<TransactionType>()
+            .is_err());
+        assert!("script-function:0x1::M::f(not an argument)"
+            .parse::<TransactionType>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_sample_workload() {
+        let workload = Workload::new(vec![
+            (TransactionType::P2p, 3),
+            (TransactionType::AccountCreation, 0),
+            (
+                TransactionType::HotAccount {
+                    num_hot_accounts: 1,
+                },
+                1,
+            ),
+        ])
+        .unwrap();
+        let mut rng = StdRng::from_seed([0; 32]);
+        let mut p2p = 0;
+        for _ in 0..4000 {
+            match workload.sample(&mut rng) {
+                TransactionType::P2p => p2p += 1,
+                TransactionType::HotAccount { .. } => (),
+                txn_type => panic!("Sampled {:?} with a zero weight", txn_type),
+            }
+        }
+        assert!((2800..3200).contains(&p2p), "{}", p2p);
+    }
+
+    #[test]
+    fn test_zipf_index() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let mut counts = vec![0; 100];
+        for _ in 0..10000 {
+            counts[zipf_index(&mut rng, 100, 1.2)] += 1;
+        }
+        assert!(counts[0] > counts[1]);
+        assert!(counts[1] > counts[10]);
+        assert!(counts[0] > 10000 / 10);
+    }
+
+    #[test]
+    fn test_tps_profile() {
+        let secs = Duration::from_secs;
+        let ramp: TpsProfile = "ramp:100:200:10".parse().unwrap();
+        assert_eq!(ramp.target_tps(secs(0)), 100);
+        assert_eq!(ramp.target_tps(secs(5)), 150);
+        assert_eq!(ramp.target_tps(secs(20)), 200);
+
+        let step: TpsProfile = "step:10:5,20:5".parse().unwrap();
+        assert_eq!(step.target_tps(secs(4)), 10);
+        assert_eq!(step.target_tps(secs(5)), 20);
+        assert_eq!(step.target_tps(secs(100)), 20);
+
+        let burst: TpsProfile = "burst:10:100:60:5".parse().unwrap();
+        assert_eq!(burst.target_tps(secs(1)), 100);
+        assert_eq!(burst.target_tps(secs(30)), 10);
+        assert_eq!(burst.target_tps(secs(62)), 100);
+
+        assert_eq!(
+            "constant:50".parse::<TpsProfile>().unwrap(),
+            TpsProfile::Constant(50)
+        );
+        assert!("burst:10:100:5:60".parse::<TpsProfile>().is_err());
+        assert!("ramp:10".parse::<TpsProfile>().is_err());
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::DiemValidatorInterface;
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use diem_client::BlockingClient;
 use diem_types::{
     account_address::AccountAddress,
@@ -10,7 +10,7 @@ use diem_types::{
     account_state_blob::AccountStateBlob,
     contract_event::EventWithProof,
     event::EventKey,
-    transaction::{Transaction, Version},
+    transaction::{Transaction, TransactionInfo, TransactionOutput, Version},
 };
 use std::convert::TryFrom;
 
@@ -77,6 +77,28 @@ impl DiemValidatorInterface for JsonRpcDebuggerInterface {
         Ok(output)
     }
 
+    fn get_committed_transaction_infos(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<TransactionInfo>> {
+        let txns = self
+            .client
+            .get_transactions_with_proofs(start, limit, false)?
+            .into_inner()
+            .ok_or_else(|| anyhow!("No transactions found at version {}", start))?;
+
+        Ok(bcs::from_bytes(txns.proofs.transaction_infos.inner())?)
+    }
+
+    fn get_committed_transaction_outputs(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<Vec<TransactionOutput>> {
+        bail!("Committed write sets are not exposed through JSON-RPC, use a local DiemDB instead")
+    }
+
     fn get_latest_version(&self) -> Result<Version> {
         let metadata = self.client.get_metadata()?.into_inner();
 
@@ -17,7 +17,7 @@ use diem_types::{
     contract_event::EventWithProof,
     event::EventKey,
     on_chain_config::ValidatorSet,
-    transaction::{Transaction, Version},
+    transaction::{Transaction, TransactionInfo, TransactionOutput, Version},
 };
 use move_binary_format::file_format::CompiledModule;
 
@@ -30,6 +30,16 @@ pub trait DiemValidatorInterface: Sync {
     fn get_events(&self, key: &EventKey, start_seq: u64, limit: u64)
         -> Result<Vec<EventWithProof>>;
     fn get_committed_transactions(&self, start: Version, limit: u64) -> Result<Vec<Transaction>>;
+    fn get_committed_transaction_infos(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<TransactionInfo>>;
+    fn get_committed_transaction_outputs(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<TransactionOutput>>;
     fn get_latest_version(&self) -> Result<Version>;
     fn get_version_by_account_sequence(
         &self,
@@ -9,12 +9,15 @@ use diem_types::{
     account_state::AccountState,
     contract_event::EventWithProof,
     event::EventKey,
-    transaction::{Transaction, Version},
+    transaction::{Transaction, TransactionInfo, TransactionOutput, Version},
 };
 use diemdb::DiemDB;
 use std::{convert::TryFrom, path::Path, sync::Arc};
 use storage_interface::{DbReader, Order};
 
+/// The maximum number of transaction outputs DiemDB returns per request.
+const MAX_OUTPUTS_PER_REQUEST: u64 = 5000;
+
 pub struct DBDebuggerInterface(Arc<dyn DbReader>);
 
 impl DBDebuggerInterface {
@@ -24,7 +27,8 @@ impl DBDebuggerInterface {
             true,
             None,
             RocksdbConfig::default(),
-            true, /* account_count_migration, ignored anyway */
+            true,  /* account_count_migration, ignored anyway */
+            false, /* enable_resource_type_index */
         )?)))
     }
 }
@@ -58,6 +62,40 @@ impl DiemValidatorInterface for DBDebuggerInterface {
             .transactions)
     }
 
+    fn get_committed_transaction_infos(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<TransactionInfo>> {
+        Ok(self
+            .0
+            .get_transactions(start, limit, self.get_latest_version()?, false)?
+            .proof
+            .transaction_infos)
+    }
+
+    fn get_committed_transaction_outputs(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<TransactionOutput>> {
+        let ledger_version = self.get_latest_version()?;
+        let mut outputs = vec![];
+        while (outputs.len() as u64) < limit {
+            let version = start + outputs.len() as u64;
+            let batch_size = std::cmp::min(limit - outputs.len() as u64, MAX_OUTPUTS_PER_REQUEST);
+            let batch = self
+                .0
+                .get_transaction_outputs(version, batch_size, ledger_version)?
+                .transactions_and_outputs;
+            if batch.is_empty() {
+                break;
+            }
+            outputs.extend(batch.into_iter().map(|(_txn, output)| output));
+        }
+        Ok(outputs)
+    }
+
     fn get_latest_version(&self) -> Result<Version> {
         let (version, _) = self
             .0
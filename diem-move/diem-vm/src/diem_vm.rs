@@ -55,7 +55,6 @@ use std::{
     collections::HashSet,
     convert::{AsMut, AsRef},
 };
-use std::time::Instant;
 
 #[derive(Clone)]
 pub struct DiemVM(pub(crate) DiemVMImpl);
@@ -708,14 +707,28 @@ impl DiemVM {
         let mut state_view_cache = StateViewCache::new(state_view);
         let count = transactions.len();
         let vm = DiemVM::new(&state_view_cache);
-        let timer = Instant::now();
         let res = adapter_common::execute_block_impl(&vm, transactions, &mut state_view_cache)?;
-        let exec_t = timer.elapsed();
-        println!("Sequential TPS: {}, block_size: {}", (count * 1000 / exec_t.as_millis() as usize) as usize, count);
         // Record the histogram count for transactions per block.
         BLOCK_TRANSACTION_COUNT.observe(count as f64);
         Ok(res)
     }
+
+    /// Executes a user transaction without checking its signature. The output is not meant to be
+    /// committed: it tells what the transaction would do if it was signed and executed on top of
+    /// `state_view`.
+    pub fn simulate_signed_transaction(
+        txn: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        let state_view_cache = StateViewCache::new(state_view);
+        let vm = DiemVM::new(&state_view_cache);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        vm.execute_user_transaction(
+            &state_view_cache,
+            &SignatureCheckedTransaction::new_unchecked_for_simulation(txn.clone()),
+            &log_context,
+        )
+    }
 }
 
 // Executor external API
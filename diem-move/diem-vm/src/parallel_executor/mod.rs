@@ -9,8 +9,10 @@ use crate::{
     adapter_common::{preprocess_transaction, PreprocessedTransaction},
     diem_vm::DiemVM,
     parallel_executor::vm_wrapper::DiemVMWrapper,
+    VMExecutor,
 };
 use diem_parallel_executor::{
+    conflicts::ConflictStats,
     errors::Error,
     executor::ParallelTransactionExecutor,
     task::{Transaction as PTransaction, TransactionOutput as PTransactionOutput},
@@ -102,6 +104,32 @@ impl ParallelDiemVM {
         }
     }
 
+    /// Executes the block with the parallel executor and returns the conflicts between its
+    /// transactions along with the outputs. Unlike `execute_block`, there is no fallback to
+    /// sequential execution, so any error of the parallel executor is returned.
+    pub fn execute_block_with_conflicts<S: StateView>(
+        transactions: Vec<Transaction>,
+        state_view: &S,
+    ) -> Result<(Vec<TransactionOutput>, ConflictStats<AccessPath>), Error<VMStatus>> {
+        let signature_verified_block: Vec<PreprocessedTransaction> = transactions
+            .into_par_iter()
+            .map(preprocess_transaction::<DiemVM>)
+            .collect();
+
+        let (results, conflicts) = ParallelTransactionExecutor::<
+            PreprocessedTransaction,
+            DiemVMWrapper<S>,
+        >::new()
+        .execute_transactions_parallel_with_conflicts(state_view, signature_verified_block)?;
+        Ok((
+            results
+                .into_iter()
+                .map(DiemTransactionOutput::into)
+                .collect(),
+            conflicts,
+        ))
+    }
+
     pub fn execute_block_tps<S: StateView>(
         transactions: Vec<Transaction>,
         state_view: &S,
@@ -131,3 +159,17 @@ impl ParallelDiemVM {
         (transactions.len() * 1000 / exec_t.as_millis() as usize) as usize
     }
 }
+
+// Executor external API, for the nodes executing in parallel regardless of the on-chain config.
+impl VMExecutor for ParallelDiemVM {
+    /// Same as `DiemVM::execute_block`, except the transactions always go through the parallel
+    /// executor, even if `ParallelExecutionConfig` is not set on chain. The parallel executor
+    /// falls back to sequential execution when it fails to run the block in parallel.
+    fn execute_block(
+        transactions: Vec<Transaction>,
+        state_view: &impl StateView,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        let (outputs, _) = ParallelDiemVM::execute_block(transactions, state_view)?;
+        Ok(outputs)
+    }
+}
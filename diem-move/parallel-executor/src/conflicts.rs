@@ -0,0 +1,76 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_infallible::Mutex;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Conflicts between transactions encountered while executing a block in parallel. Every conflict
+/// is attributed to the key whose read caused it, so the keys with the most conflicts are the ones
+/// limiting the parallelism of the block.
+#[derive(Debug)]
+pub struct ConflictStats<K> {
+    /// Number of executions that were aborted because their read set failed validation.
+    pub num_aborts: usize,
+    /// Number of executions that were suspended to wait for a write of an earlier transaction.
+    pub num_dependencies: usize,
+    /// Number of aborts and dependencies caused by each key.
+    pub conflicts_by_key: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq> ConflictStats<K> {
+    /// Returns (up to) the `limit` keys causing the most conflicts, most conflicting first.
+    pub fn hot_keys(&self, limit: usize) -> Vec<(&K, usize)> {
+        let mut keys: Vec<_> = self
+            .conflicts_by_key
+            .iter()
+            .map(|(key, count)| (key, *count))
+            .collect();
+        keys.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+        keys.truncate(limit);
+        keys
+    }
+}
+
+/// Collects conflicts from all the executor threads. Conflicts are rare compared to reads, so
+/// only recording one takes a lock.
+pub(crate) struct ConflictCounter<K> {
+    num_aborts: AtomicUsize,
+    num_dependencies: AtomicUsize,
+    conflicts_by_key: Mutex<HashMap<K, usize>>,
+}
+
+impl<K: Hash + Eq + Clone> ConflictCounter<K> {
+    pub fn new() -> Self {
+        Self {
+            num_aborts: AtomicUsize::new(0),
+            num_dependencies: AtomicUsize::new(0),
+            conflicts_by_key: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record_abort(&self, key: &K) {
+        self.num_aborts.fetch_add(1, Ordering::Relaxed);
+        self.record_key(key);
+    }
+
+    pub fn record_dependency(&self, key: &K) {
+        self.num_dependencies.fetch_add(1, Ordering::Relaxed);
+        self.record_key(key);
+    }
+
+    fn record_key(&self, key: &K) {
+        *self.conflicts_by_key.lock().entry(key.clone()).or_insert(0) += 1;
+    }
+
+    pub fn into_stats(self) -> ConflictStats<K> {
+        ConflictStats {
+            num_aborts: self.num_aborts.into_inner(),
+            num_dependencies: self.num_dependencies.into_inner(),
+            conflicts_by_key: std::mem::take(&mut *self.conflicts_by_key.lock()),
+        }
+    }
+}
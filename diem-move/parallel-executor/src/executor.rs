@@ -1,6 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
+    conflicts::{ConflictCounter, ConflictStats},
     errors::*,
     outcome_array::OutcomeArray,
     scheduler::{Scheduler, SchedulerTask, TaskGuard, TxnIndex, Version},
@@ -34,6 +35,7 @@ pub struct MVHashMapView<'a, K, V> {
     versioned_map: &'a MVHashMap<K, V>,
     txn_idx: TxnIndex,
     scheduler: &'a Scheduler,
+    conflicts: Option<&'a ConflictCounter<K>>,
     read_dependency: AtomicBool,
     captured_reads: Mutex<Vec<ReadDescriptor<K>>>,
 }
@@ -68,6 +70,9 @@ impl<'a, K: PartialOrd + Send + Clone + Hash + Eq, V: Send + Sync> MVHashMapView
                     // Don't start execution transaction `self.txn_idx` until `dep_idx` is computed.
                     if self.scheduler.try_add_dependency(self.txn_idx, dep_idx) {
                         // dep_idx is already executed, push `self.txn_idx` to ready queue.
+                        if let Some(conflicts) = self.conflicts {
+                            conflicts.record_dependency(key);
+                        }
                         self.read_dependency.store(true, Ordering::Relaxed);
                         bail!("Read dependency is not computed, retry later")
                     } else {
@@ -119,6 +124,7 @@ where
         >,
         versioned_data_cache: &MVHashMap<<T as Transaction>::Key, <T as Transaction>::Value>,
         scheduler: &'a Scheduler,
+        conflicts: Option<&ConflictCounter<<T as Transaction>::Key>>,
         executor: &E,
     ) -> SchedulerTask<'a> {
         let (idx_to_execute, incarnation) = version_to_execute;
@@ -129,13 +135,16 @@ where
         // TODO (issue 10180): remove once we have a way to suspend VM execution (so partial
         // execution would not be discarded).
         if let Some(read_set) = last_input_output.read_set(idx_to_execute) {
-            if read_set.iter().any(
-                |r| match versioned_data_cache.read(r.path(), idx_to_execute) {
+            if let Some(r) = read_set.iter().find(|r| {
+                match versioned_data_cache.read(r.path(), idx_to_execute) {
                     Err(Some(dep_idx)) => scheduler.try_add_dependency(idx_to_execute, dep_idx),
                     Ok(_) | Err(None) => false,
-                },
-            ) {
+                }
+            }) {
                 // Transaction has a read dependency. Was not executed and thus nothing to validate.
+                if let Some(conflicts) = conflicts {
+                    conflicts.record_dependency(r.path());
+                }
                 return SchedulerTask::NoTask;
             }
         }
@@ -144,6 +153,7 @@ where
             versioned_map: versioned_data_cache,
             txn_idx: idx_to_execute,
             scheduler,
+            conflicts,
             read_dependency: AtomicBool::new(false),
             captured_reads: Mutex::new(Vec::new()),
         };
@@ -209,21 +219,31 @@ where
         >,
         versioned_data_cache: &MVHashMap<<T as Transaction>::Key, <T as Transaction>::Value>,
         scheduler: &'a Scheduler,
+        conflicts: Option<&ConflictCounter<<T as Transaction>::Key>>,
     ) -> SchedulerTask<'a> {
         let (idx_to_validate, incarnation) = version_to_validate;
         let read_set = last_input_output
             .read_set(idx_to_validate)
             .expect("Prior read-set must be recorded");
 
-        let valid = read_set.iter().all(|r| {
-            match versioned_data_cache.read(r.path(), idx_to_validate) {
+        let invalid_read = read_set.iter().find(|r| {
+            let valid = match versioned_data_cache.read(r.path(), idx_to_validate) {
                 Ok((version, _)) => r.validate_version(version),
                 Err(Some(_)) => false, // Dependency implies a validation failure.
                 Err(None) => r.validate_storage(),
-            }
+            };
+            !valid
         });
 
-        let aborted = !valid && scheduler.try_abort(idx_to_validate, incarnation);
+        let aborted = match invalid_read {
+            Some(r) if scheduler.try_abort(idx_to_validate, incarnation) => {
+                if let Some(conflicts) = conflicts {
+                    conflicts.record_abort(r.path());
+                }
+                true
+            }
+            _ => false,
+        };
 
         if aborted {
             // Not valid and successfully aborted, mark the latest write-set as estimates.
@@ -241,6 +261,37 @@ where
         &self,
         executor_initial_arguments: E::Argument,
         signature_verified_block: Vec<T>,
+    ) -> Result<Vec<E::Output>, E::Error> {
+        self.execute_transactions_parallel_impl(
+            executor_initial_arguments,
+            signature_verified_block,
+            None,
+        )
+    }
+
+    /// Same as `execute_transactions_parallel`, but also returns the conflicts between
+    /// transactions encountered during the execution.
+    pub fn execute_transactions_parallel_with_conflicts(
+        &self,
+        executor_initial_arguments: E::Argument,
+        signature_verified_block: Vec<T>,
+    ) -> Result<(Vec<E::Output>, ConflictStats<T::Key>), E::Error> {
+        let conflicts = ConflictCounter::new();
+        let outputs = self.execute_transactions_parallel_impl(
+            executor_initial_arguments,
+            signature_verified_block,
+            Some(&conflicts),
+        )?;
+        Ok((outputs, conflicts.into_stats()))
+    }
+
+    /// Conflicts are only tracked when a counter is passed, so that executions that don't report
+    /// them don't pay for the locking and key copies.
+    fn execute_transactions_parallel_impl(
+        &self,
+        executor_initial_arguments: E::Argument,
+        signature_verified_block: Vec<T>,
+        conflicts: Option<&ConflictCounter<T::Key>>,
     ) -> Result<Vec<E::Output>, E::Error> {
         if signature_verified_block.is_empty() {
             return Ok(vec![]);
@@ -275,6 +326,7 @@ where
                                     &last_input_output,
                                     &versioned_data_cache,
                                     &scheduler,
+                                    conflicts,
                                 ),
                             SchedulerTask::ExecutionTask(version_to_execute, guard) => self
                                 .execute(
@@ -284,6 +336,7 @@ where
                                     &last_input_output,
                                     &versioned_data_cache,
                                     &scheduler,
+                                    conflicts,
                                     &executor,
                                 ),
                             SchedulerTask::NoTask => scheduler.next_task(),
@@ -1,6 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod conflicts;
 pub mod errors;
 pub mod executor;
 mod outcome_array;
@@ -131,6 +131,55 @@ fn early_skips() {
     run_and_assert(transactions)
 }
 
+#[test]
+fn no_conflicts_between_independent_transactions() {
+    let transactions: Vec<_> = (0..TXN_PER_BLOCK * NUM_BLOCKS)
+        .map(|_| {
+            let key = random::<[u8; 32]>();
+            Transaction::Write {
+                reads: vec![key],
+                actual_writes: vec![(key, random::<u64>())],
+                skipped_writes: vec![],
+            }
+        })
+        .collect();
+
+    let (_, conflicts) = ParallelTransactionExecutor::<Transaction<_, u64>, Task<_, u64>>::new()
+        .execute_transactions_parallel_with_conflicts((), transactions)
+        .unwrap();
+    assert_eq!(conflicts.num_aborts, 0);
+    assert_eq!(conflicts.num_dependencies, 0);
+    assert!(conflicts.conflicts_by_key.is_empty());
+}
+
+#[test]
+fn conflicts_attributed_to_shared_key() {
+    let shared_key = random::<[u8; 32]>();
+    let mut transactions = vec![];
+    for _ in 0..TXN_PER_BLOCK * NUM_BLOCKS {
+        let key = random::<[u8; 32]>();
+        transactions.push(Transaction::Write {
+            reads: vec![shared_key, key],
+            actual_writes: vec![(shared_key, random::<u64>()), (key, random::<u64>())],
+            skipped_writes: vec![],
+        })
+    }
+
+    let (_, conflicts) = ParallelTransactionExecutor::<Transaction<_, u64>, Task<_, u64>>::new()
+        .execute_transactions_parallel_with_conflicts((), transactions)
+        .unwrap();
+    // Whether conflicts happen depends on the scheduling, but only the shared key can cause them.
+    assert!(conflicts
+        .conflicts_by_key
+        .keys()
+        .all(|key| *key == shared_key));
+    assert_eq!(
+        conflicts.conflicts_by_key.values().sum::<usize>(),
+        conflicts.num_aborts + conflicts.num_dependencies
+    );
+    assert!(conflicts.hot_keys(10).len() <= 1);
+}
+
 #[test]
 fn scheduler_tasks() {
     let s = Scheduler::new(6);
@@ -0,0 +1,224 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_resource_viewer::DiemValueAnnotator;
+use diem_types::{
+    access_path::{AccessPath, Path},
+    account_address::AccountAddress,
+    contract_event::ContractEvent,
+    transaction::{TransactionOutput, Version},
+    write_set::WriteOp,
+};
+use difference::Changeset;
+use move_core_types::resolver::MoveResolver;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// The differences between the committed and the replayed outputs of a range of transactions.
+#[derive(Debug, Serialize)]
+pub struct ReplayDiffReport {
+    pub start: Version,
+    pub limit: u64,
+    /// Only the transactions whose outputs differ are listed.
+    pub transactions: Vec<TransactionDiff>,
+}
+
+/// The differences between the committed and the replayed output of a transaction.
+#[derive(Debug, Serialize)]
+pub struct TransactionDiff {
+    pub version: Version,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ValueDiff<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<ValueDiff<u64>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<EventDiff>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<ResourceDiff>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValueDiff<T> {
+    pub committed: T,
+    pub replayed: T,
+}
+
+/// A differing event, `None` if the event was only emitted by one of the two executions.
+#[derive(Debug, Serialize)]
+pub struct EventDiff {
+    pub index: usize,
+    pub committed: Option<String>,
+    pub replayed: Option<String>,
+}
+
+/// A differing write to a resource or module, `None` if only one of the two executions wrote it.
+#[derive(Debug, Serialize)]
+pub struct ResourceDiff {
+    pub address: AccountAddress,
+    pub path: String,
+    pub committed: Option<String>,
+    pub replayed: Option<String>,
+}
+
+impl TransactionDiff {
+    fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.gas_used.is_none()
+            && self.events.is_empty()
+            && self.resources.is_empty()
+    }
+}
+
+/// Diffs the `committed` and `replayed` outputs of the transaction at `version`, annotating the
+/// differing events and resources with `annotator`, or printing their raw bytes if they can't be
+/// annotated. Returns `None` if the outputs are identical.
+pub(crate) fn diff_transaction_outputs<T: MoveResolver>(
+    annotator: &DiemValueAnnotator<T>,
+    version: Version,
+    committed: &TransactionOutput,
+    replayed: &TransactionOutput,
+) -> Option<TransactionDiff> {
+    let mut diff = TransactionDiff {
+        version,
+        status: None,
+        gas_used: None,
+        events: vec![],
+        resources: vec![],
+    };
+    if committed.status() != replayed.status() {
+        diff.status = Some(ValueDiff {
+            committed: format!("{:?}", committed.status()),
+            replayed: format!("{:?}", replayed.status()),
+        });
+    }
+    if committed.gas_used() != replayed.gas_used() {
+        diff.gas_used = Some(ValueDiff {
+            committed: committed.gas_used(),
+            replayed: replayed.gas_used(),
+        });
+    }
+
+    let num_events = std::cmp::max(committed.events().len(), replayed.events().len());
+    for index in 0..num_events {
+        let committed_event = committed.events().get(index);
+        let replayed_event = replayed.events().get(index);
+        if committed_event != replayed_event {
+            diff.events.push(EventDiff {
+                index,
+                committed: committed_event.map(|event| annotate_event(annotator, event)),
+                replayed: replayed_event.map(|event| annotate_event(annotator, event)),
+            });
+        }
+    }
+
+    let mut writes: BTreeMap<&AccessPath, (Option<&WriteOp>, Option<&WriteOp>)> = BTreeMap::new();
+    for (ap, op) in committed.write_set() {
+        writes.entry(ap).or_default().0 = Some(op);
+    }
+    for (ap, op) in replayed.write_set() {
+        writes.entry(ap).or_default().1 = Some(op);
+    }
+    for (ap, (committed_op, replayed_op)) in writes {
+        if committed_op == replayed_op {
+            continue;
+        }
+        let path = ap.get_path();
+        diff.resources.push(ResourceDiff {
+            address: ap.address,
+            path: match &path {
+                Path::Resource(tag) => tag.to_string(),
+                Path::Code(module_id) => format!("{}", module_id),
+            },
+            committed: committed_op.map(|op| annotate_write(annotator, &path, op)),
+            replayed: replayed_op.map(|op| annotate_write(annotator, &path, op)),
+        });
+    }
+
+    if diff.is_empty() {
+        None
+    } else {
+        Some(diff)
+    }
+}
+
+/// Annotates an event, falling back to its raw data if its type can't be resolved.
+fn annotate_event<T: MoveResolver>(
+    annotator: &DiemValueAnnotator<T>,
+    event: &ContractEvent,
+) -> String {
+    let data = match annotator.view_contract_event(event) {
+        Ok(value) => value.to_string(),
+        Err(_) => format!("0x{}", hex::encode(event.event_data())),
+    };
+    format!(
+        "key: {}, sequence number: {}, data: {}",
+        event.key(),
+        event.sequence_number(),
+        data
+    )
+}
+
+/// Annotates a write, falling back to the raw bytes if the resource type can't be resolved.
+fn annotate_write<T: MoveResolver>(
+    annotator: &DiemValueAnnotator<T>,
+    path: &Path,
+    op: &WriteOp,
+) -> String {
+    match (path, op) {
+        (_, WriteOp::Deletion) => "deleted".to_string(),
+        (Path::Resource(tag), WriteOp::Value(bytes)) => match annotator.view_resource(tag, bytes) {
+            Ok(value) => value.to_string(),
+            Err(_) => format!("0x{}", hex::encode(bytes)),
+        },
+        (Path::Code(_), WriteOp::Value(bytes)) => format!("module of {} bytes", bytes.len()),
+    }
+}
+
+fn print_value_diff(
+    f: &mut Formatter,
+    committed: &Option<String>,
+    replayed: &Option<String>,
+) -> std::fmt::Result {
+    let none = "<none>".to_string();
+    writeln!(
+        f,
+        "{}",
+        Changeset::new(
+            committed.as_ref().unwrap_or(&none),
+            replayed.as_ref().unwrap_or(&none),
+            "\n"
+        )
+    )
+}
+
+impl Display for TransactionDiff {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "Transaction at version {} differs:", self.version)?;
+        if let Some(status) = &self.status {
+            writeln!(
+                f,
+                "status: committed {}, replayed {}",
+                status.committed, status.replayed
+            )?;
+        }
+        if let Some(gas_used) = &self.gas_used {
+            writeln!(
+                f,
+                "gas used: committed {}, replayed {}",
+                gas_used.committed, gas_used.replayed
+            )?;
+        }
+        for event in &self.events {
+            writeln!(f, "event {}:", event.index)?;
+            print_value_diff(f, &event.committed, &event.replayed)?;
+        }
+        for resource in &self.resources {
+            writeln!(f, "{} under {}:", resource.path, resource.address)?;
+            print_value_diff(f, &resource.committed, &resource.replayed)?;
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{anyhow, bail, format_err, Result};
+use crate::diff::diff_transaction_outputs;
+use anyhow::{anyhow, bail, ensure, format_err, Result};
+use diem_crypto::hash::{CryptoHash, EventAccumulatorHasher};
 use diem_resource_viewer::{AnnotatedAccountStateBlob, AnnotatedMoveStruct, DiemValueAnnotator};
 use diem_state_view::StateView;
 use diem_types::{
@@ -11,15 +13,19 @@ use diem_types::{
     account_state::AccountState,
     contract_event::{ContractEvent, EventWithProof},
     event::EventKey,
-    transaction::{ChangeSet, Transaction, TransactionOutput, Version, WriteSetPayload},
+    proof::accumulator::InMemoryAccumulator,
+    transaction::{
+        ChangeSet, Transaction, TransactionInfo, TransactionOutput, TransactionStatus, Version,
+        WriteSetPayload,
+    },
     write_set::WriteOp,
 };
 use diem_validator_interface::{
     DBDebuggerInterface, DebuggerStateView, DiemValidatorInterface, JsonRpcDebuggerInterface,
 };
 use diem_vm::{
-    convert_changeset_and_events, data_cache::RemoteStorage, logging::AdapterLogSchema, DiemVM,
-    VMExecutor,
+    convert_changeset_and_events, data_cache::RemoteStorage, logging::AdapterLogSchema,
+    parallel_executor::ParallelDiemVM, DiemVM, VMExecutor,
 };
 use move_binary_format::{errors::VMResult, file_format::CompiledModule};
 use move_cli::sandbox::utils::on_disk_state_view::OnDiskStateView;
@@ -28,15 +34,62 @@ use move_core_types::{effects::ChangeSet as MoveChanges, language_storage::TypeT
 use move_vm_runtime::{move_vm::MoveVM, session::Session};
 use move_vm_test_utils::DeltaStorage;
 use move_vm_types::gas_schedule::GasStatus;
-use std::path::{Path, PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+mod diff;
 #[cfg(test)]
 mod unit_tests;
 
+pub use crate::diff::{EventDiff, ReplayDiffReport, ResourceDiff, TransactionDiff, ValueDiff};
+
+/// Selects the executor used to re-execute past transactions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExecutionMode {
+    /// Use `DiemVM::execute_block`, which picks the executor from the on-chain config at the
+    /// replayed version, like a validator would.
+    OnChainConfig,
+    /// Always execute transactions sequentially.
+    Sequential,
+    /// Always execute transactions with `ParallelDiemVM`.
+    Parallel,
+    /// Execute transactions both ways and check the outputs against each other, against the
+    /// committed `TransactionInfo`s and against the committed write sets. The committed write sets
+    /// are only available from a local DiemDB.
+    Both,
+}
+
+impl Default for ExecutionMode {
+    fn default() -> Self {
+        Self::OnChainConfig
+    }
+}
+
+impl FromStr for ExecutionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "on-chain-config" => Self::OnChainConfig,
+            "sequential" => Self::Sequential,
+            "parallel" => Self::Parallel,
+            "both" => Self::Both,
+            _ => bail!(
+                "Invalid execution mode {}, expected one of: sequential, parallel, both",
+                s
+            ),
+        })
+    }
+}
+
 pub struct DiemDebugger {
     debugger: Box<dyn DiemValidatorInterface>,
     build_dir: PathBuf,
     storage_dir: PathBuf,
+    execution_mode: ExecutionMode,
 }
 
 impl DiemDebugger {
@@ -45,9 +98,15 @@ impl DiemDebugger {
             debugger,
             build_dir: PathBuf::from(move_cli::DEFAULT_BUILD_DIR),
             storage_dir: PathBuf::from(move_cli::DEFAULT_STORAGE_DIR),
+            execution_mode: ExecutionMode::default(),
         }
     }
 
+    pub fn with_execution_mode(mut self, execution_mode: ExecutionMode) -> Self {
+        self.execution_mode = execution_mode;
+        self
+    }
+
     pub fn json_rpc(url: &str) -> Result<Self> {
         Ok(Self::new(Box::new(JsonRpcDebuggerInterface::new(url)?)))
     }
@@ -64,8 +123,18 @@ impl DiemDebugger {
         txns: Vec<Transaction>,
     ) -> Result<Vec<TransactionOutput>> {
         let state_view = DebuggerStateView::new(&*self.debugger, version.checked_sub(1));
-        DiemVM::execute_block(txns, &state_view)
-            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))
+        match self.execution_mode {
+            ExecutionMode::OnChainConfig => DiemVM::execute_block(txns, &state_view)
+                .map_err(|err| format_err!("Unexpected VM Error: {:?}", err)),
+            ExecutionMode::Sequential => execute_sequential(txns, &state_view),
+            ExecutionMode::Parallel => execute_parallel(txns, &state_view),
+            ExecutionMode::Both => {
+                let sequential_outputs = execute_sequential(txns.clone(), &state_view)?;
+                let parallel_outputs = execute_parallel(txns, &state_view)?;
+                compare_outputs(version, &sequential_outputs, &parallel_outputs)?;
+                Ok(sequential_outputs)
+            }
+        }
     }
 
     pub fn execute_past_transactions(
@@ -75,6 +144,16 @@ impl DiemDebugger {
         save_write_sets: bool,
     ) -> Result<Vec<TransactionOutput>> {
         let mut txns = self.debugger.get_committed_transactions(begin, limit)?;
+        let (mut txn_infos, mut committed_outputs) = if self.execution_mode == ExecutionMode::Both {
+            (
+                self.debugger
+                    .get_committed_transaction_infos(begin, limit)?,
+                self.debugger
+                    .get_committed_transaction_outputs(begin, limit)?,
+            )
+        } else {
+            (vec![], vec![])
+        };
         let mut ret = vec![];
         while limit != 0 {
             println!(
@@ -83,6 +162,12 @@ impl DiemDebugger {
             );
             let mut epoch_result =
                 self.execute_transactions_by_epoch(begin, txns.clone(), save_write_sets)?;
+            if self.execution_mode == ExecutionMode::Both {
+                verify_transaction_infos(begin, &epoch_result, &txn_infos)?;
+                verify_write_sets(begin, &epoch_result, &committed_outputs)?;
+                txn_infos = txn_infos.split_off(epoch_result.len());
+                committed_outputs = committed_outputs.split_off(epoch_result.len());
+            }
             begin += epoch_result.len() as u64;
             limit -= epoch_result.len() as u64;
             txns = txns.split_off(epoch_result.len());
@@ -91,6 +176,42 @@ impl DiemDebugger {
         Ok(ret)
     }
 
+    /// Replays the transactions from `begin` to `begin + limit` and diffs their outputs against the
+    /// committed ones.
+    pub fn replay_and_diff(&self, begin: Version, limit: u64) -> Result<ReplayDiffReport> {
+        let committed_outputs = self
+            .debugger
+            .get_committed_transaction_outputs(begin, limit)?;
+        let replayed_outputs = self.execute_past_transactions(begin, limit, false)?;
+        ensure!(
+            committed_outputs.len() == replayed_outputs.len(),
+            "Replayed {} transactions but {} are committed",
+            replayed_outputs.len(),
+            committed_outputs.len()
+        );
+
+        let mut transactions = vec![];
+        for (idx, (committed, replayed)) in committed_outputs
+            .iter()
+            .zip(replayed_outputs.iter())
+            .enumerate()
+        {
+            // the values are annotated with the modules as of the transaction
+            let version = begin + idx as u64;
+            let state_view = DebuggerStateView::new(&*self.debugger, Some(version));
+            let remote_storage = RemoteStorage::new(&state_view);
+            let annotator = DiemValueAnnotator::new(&remote_storage);
+            if let Some(diff) = diff_transaction_outputs(&annotator, version, committed, replayed) {
+                transactions.push(diff);
+            }
+        }
+        Ok(ReplayDiffReport {
+            start: begin,
+            limit,
+            transactions,
+        })
+    }
+
     pub fn execute_transactions_by_epoch(
         &self,
         begin: Version,
@@ -240,13 +361,14 @@ impl DiemDebugger {
         Ok(())
     }
 
+    /// Annotates each event with the modules as of the transaction that emitted it.
     pub fn annotate_events(&self, events: &[EventWithProof]) -> Result<Vec<AnnotatedMoveStruct>> {
-        let version = self.debugger.get_latest_version()?;
-        let state_view = DebuggerStateView::new(&*self.debugger, Some(version));
-        let remote_storage = RemoteStorage::new(&state_view);
-        let annotator = DiemValueAnnotator::new(&remote_storage);
         let mut events_data = vec![];
         for event in events {
+            let state_view =
+                DebuggerStateView::new(&*self.debugger, Some(event.transaction_version));
+            let remote_storage = RemoteStorage::new(&state_view);
+            let annotator = DiemValueAnnotator::new(&remote_storage);
             match &event.event {
                 ContractEvent::V0(event_v0) => match event_v0.type_tag() {
                     TypeTag::Struct(s) => {
@@ -406,6 +528,144 @@ fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
         .any(|event| *event.key() == new_epoch_event_key)
 }
 
+fn execute_sequential(
+    txns: Vec<Transaction>,
+    state_view: &impl StateView,
+) -> Result<Vec<TransactionOutput>> {
+    Ok(DiemVM::execute_block_and_keep_vm_status(txns, state_view)
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+        .into_iter()
+        .map(|(_vm_status, output)| output)
+        .collect())
+}
+
+fn execute_parallel(
+    txns: Vec<Transaction>,
+    state_view: &impl StateView,
+) -> Result<Vec<TransactionOutput>> {
+    let (outputs, fallback_err) = ParallelDiemVM::execute_block(txns, state_view)
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+    if let Some(err) = fallback_err {
+        println!(
+            "Parallel execution fell back to sequential execution: {:?}",
+            err
+        );
+    }
+    Ok(outputs)
+}
+
+/// Checks that the sequential and parallel outputs of a block starting at `begin` agree. Outputs
+/// after the first reconfiguration are ignored as they are re-executed in the next epoch anyway.
+fn compare_outputs(
+    begin: Version,
+    sequential_outputs: &[TransactionOutput],
+    parallel_outputs: &[TransactionOutput],
+) -> Result<()> {
+    ensure!(
+        sequential_outputs.len() == parallel_outputs.len(),
+        "Sequential execution returned {} outputs but parallel execution returned {}",
+        sequential_outputs.len(),
+        parallel_outputs.len()
+    );
+    for (idx, (sequential, parallel)) in sequential_outputs
+        .iter()
+        .zip(parallel_outputs.iter())
+        .enumerate()
+    {
+        ensure!(
+            sequential == parallel,
+            "Sequential and parallel outputs differ at version {}:\nsequential: {:#?}\nparallel: {:#?}",
+            begin + idx as u64,
+            sequential,
+            parallel
+        );
+        if is_reconfiguration(sequential) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Checks the outputs of transactions starting at `begin` against their committed
+/// `TransactionInfo`s. Only the fields that can be recomputed from a `TransactionOutput` are
+/// checked: the status, the gas used and the event root hash. The state root hash would require
+/// the full state tree, the write sets are checked by `verify_write_sets` instead.
+fn verify_transaction_infos(
+    begin: Version,
+    outputs: &[TransactionOutput],
+    txn_infos: &[TransactionInfo],
+) -> Result<()> {
+    ensure!(
+        outputs.len() <= txn_infos.len(),
+        "Replayed {} transactions but only {} transaction infos are committed",
+        outputs.len(),
+        txn_infos.len()
+    );
+    for (idx, (output, txn_info)) in outputs.iter().zip(txn_infos.iter()).enumerate() {
+        let version = begin + idx as u64;
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status,
+            status => bail!(
+                "Committed transaction at version {} got status {:?} on replay",
+                version,
+                status
+            ),
+        };
+        let event_hashes: Vec<_> = output.events().iter().map(CryptoHash::hash).collect();
+        let event_root_hash =
+            InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes).root_hash();
+        ensure!(
+            status == txn_info.status()
+                && output.gas_used() == txn_info.gas_used()
+                && event_root_hash == txn_info.event_root_hash(),
+            "Replayed transaction at version {} doesn't match the committed transaction info:\n\
+             replayed: status {:?}, gas used {}, event root hash {}\n\
+             committed: {:?}",
+            version,
+            status,
+            output.gas_used(),
+            event_root_hash,
+            txn_info
+        );
+    }
+    Ok(())
+}
+
+/// Checks the write sets of transactions starting at `begin` against the committed ones, reporting
+/// the first access path written differently.
+fn verify_write_sets(
+    begin: Version,
+    outputs: &[TransactionOutput],
+    committed_outputs: &[TransactionOutput],
+) -> Result<()> {
+    ensure!(
+        outputs.len() <= committed_outputs.len(),
+        "Replayed {} transactions but only {} write sets are committed",
+        outputs.len(),
+        committed_outputs.len()
+    );
+    for (idx, (output, committed)) in outputs.iter().zip(committed_outputs.iter()).enumerate() {
+        let replayed_ops: BTreeMap<_, _> = output.write_set().iter().cloned().collect();
+        let committed_ops: BTreeMap<_, _> = committed.write_set().iter().cloned().collect();
+        let access_paths: BTreeSet<_> = replayed_ops.keys().chain(committed_ops.keys()).collect();
+        for access_path in access_paths {
+            let replayed_op = replayed_ops.get(access_path);
+            let committed_op = committed_ops.get(access_path);
+            ensure!(
+                replayed_op == committed_op,
+                "Replayed transaction at version {} writes {} differently:\n\
+                 replayed: {:?}\n\
+                 committed: {:?}",
+                begin + idx as u64,
+                access_path,
+                replayed_op,
+                committed_op
+            );
+        }
+    }
+    Ok(())
+}
+
 fn compile_move_script(file_path: &str) -> Result<Vec<u8>> {
     let cur_path = file_path.to_owned();
     let targets = &vec![cur_path];
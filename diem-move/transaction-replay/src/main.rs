@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{bail, Result};
-use diem_transaction_replay::DiemDebugger;
+use diem_transaction_replay::{DiemDebugger, ExecutionMode};
 use diem_types::{
     account_address::AccountAddress,
     event::EventKey,
@@ -24,6 +24,11 @@ struct Opt {
     /// If true, persist the effects of replaying transactions via `cmd` to disk in a format understood by the Move CLI
     #[structopt(short = "s", global = true)]
     save_write_sets: bool,
+    /// Executor used to replay transactions: `sequential`, `parallel` or `both`. `both` also checks
+    /// the outputs against each other and against the committed transaction infos. If unset, the
+    /// executor is picked from the on-chain config like a validator would.
+    #[structopt(long, global = true)]
+    mode: Option<ExecutionMode>,
     #[structopt(subcommand)] // Note that we mark a field as a subcommand
     cmd: Command,
 }
@@ -33,6 +38,16 @@ enum Command {
     /// Replay transactions starting from version `start` to `start + limit`.
     #[structopt(name = "replay-transactions")]
     ReplayTransactions { start: Version, limit: u64 },
+    /// Replay transactions starting from version `start` to `start + limit` and diff their events,
+    /// gas and resources against the committed outputs. Requires a local DiemDB.
+    #[structopt(name = "replay-and-diff")]
+    ReplayAndDiff {
+        start: Version,
+        limit: u64,
+        /// Write the diff as a JSON report to this path
+        #[structopt(long, parse(from_os_str))]
+        json_report: Option<PathBuf>,
+    },
     /// Replay the last `txns` committed transactions.
     #[structopt(name = "replay-recent-transactions")]
     ReplayRecentTransactions { txns: u64 },
@@ -96,7 +111,8 @@ fn main() -> Result<()> {
         DiemDebugger::json_rpc(url.as_str())?
     } else {
         panic!("No debugger attached")
-    };
+    }
+    .with_execution_mode(opt.mode.unwrap_or_default());
 
     println!("Connection Succeeded");
 
@@ -107,6 +123,30 @@ fn main() -> Result<()> {
                 debugger.execute_past_transactions(start, limit, opt.save_write_sets)
             );
         }
+        Command::ReplayAndDiff {
+            start,
+            limit,
+            json_report,
+        } => {
+            let report = debugger.replay_and_diff(start, limit)?;
+            for txn_diff in &report.transactions {
+                println!("{}", txn_diff);
+            }
+            if let Some(path) = json_report {
+                fs::write(path, serde_json::to_string_pretty(&report)?)?;
+            }
+            if !report.transactions.is_empty() {
+                bail!(
+                    "{} out of {} replayed transactions differ from the committed outputs",
+                    report.transactions.len(),
+                    limit
+                );
+            }
+            println!(
+                "All {} replayed transactions match the committed outputs",
+                limit
+            );
+        }
         Command::ReplayRecentTransactions { txns } => {
             let latest_version = debugger
                 .get_latest_version()
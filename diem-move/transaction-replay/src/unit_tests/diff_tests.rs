@@ -0,0 +1,106 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{diff::diff_transaction_outputs, unit_tests::TestInterface};
+use diem_resource_viewer::DiemValueAnnotator;
+use diem_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::DiemAccountResource,
+    event::{EventHandle, EventKey},
+    transaction::{TransactionOutput, TransactionStatus},
+    vm_status::KeptVMStatus,
+    write_set::{WriteOp, WriteSetMut},
+};
+use diem_validator_interface::DebuggerStateView;
+use diem_vm::data_cache::RemoteStorage;
+use move_core_types::move_resource::MoveStructType;
+
+fn account_resource(address: AccountAddress, sequence_number: u64) -> Vec<u8> {
+    bcs::to_bytes(&DiemAccountResource::new(
+        sequence_number,
+        vec![],
+        None,
+        None,
+        EventHandle::new(EventKey::new_from_address(&address, 0), 0),
+        EventHandle::new(EventKey::new_from_address(&address, 1), 1),
+    ))
+    .unwrap()
+}
+
+fn output(writes: Vec<(AccessPath, WriteOp)>, gas_used: u64) -> TransactionOutput {
+    TransactionOutput::new(
+        WriteSetMut::new(writes).freeze().unwrap(),
+        vec![],
+        gas_used,
+        TransactionStatus::Keep(KeptVMStatus::Executed),
+    )
+}
+
+#[test]
+fn test_diff_transaction_outputs() {
+    let interface = TestInterface::genesis();
+    let state_view = DebuggerStateView::new(&interface, Some(0));
+    let remote_storage = RemoteStorage::new(&state_view);
+    let annotator = DiemValueAnnotator::new(&remote_storage);
+
+    let address = AccountAddress::random();
+    let resource_path = AccessPath::new(
+        address,
+        AccessPath::resource_access_vec(DiemAccountResource::struct_tag()),
+    );
+    let committed = output(
+        vec![(
+            resource_path.clone(),
+            WriteOp::Value(account_resource(address, 1)),
+        )],
+        10,
+    );
+    assert!(diff_transaction_outputs(&annotator, 5, &committed, &committed).is_none());
+
+    let replayed = output(
+        vec![(resource_path, WriteOp::Value(account_resource(address, 2)))],
+        12,
+    );
+    let diff = diff_transaction_outputs(&annotator, 5, &committed, &replayed).unwrap();
+    assert_eq!(diff.version, 5);
+    assert!(diff.status.is_none());
+    let gas_used = diff.gas_used.as_ref().unwrap();
+    assert_eq!((gas_used.committed, gas_used.replayed), (10, 12));
+    assert!(diff.events.is_empty());
+    assert_eq!(diff.resources.len(), 1);
+    assert_eq!(diff.resources[0].address, address);
+    assert_eq!(
+        diff.resources[0].path,
+        DiemAccountResource::struct_tag().to_string()
+    );
+    assert!(diff.resources[0]
+        .committed
+        .as_ref()
+        .unwrap()
+        .contains("sequence_number: 1"));
+    assert!(diff.resources[0]
+        .replayed
+        .as_ref()
+        .unwrap()
+        .contains("sequence_number: 2"));
+
+    let deleted = output(vec![], 10);
+    let diff = diff_transaction_outputs(&annotator, 5, &committed, &deleted).unwrap();
+    assert!(diff.gas_used.is_none());
+    assert!(diff.resources[0].replayed.is_none());
+
+    // values that can't be annotated are printed as raw bytes
+    let garbage = output(
+        vec![(
+            AccessPath::new(
+                address,
+                AccessPath::resource_access_vec(DiemAccountResource::struct_tag()),
+            ),
+            WriteOp::Value(vec![0xab, 0xcd]),
+        )],
+        10,
+    );
+    let diff = diff_transaction_outputs(&annotator, 5, &committed, &garbage).unwrap();
+    assert_eq!(diff.resources[0].replayed.as_deref(), Some("0xabcd"));
+}
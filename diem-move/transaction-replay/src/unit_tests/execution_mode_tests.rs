@@ -0,0 +1,143 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{compare_outputs, verify_transaction_infos, verify_write_sets, ExecutionMode};
+use diem_crypto::{hash::EventAccumulatorHasher, HashValue};
+use diem_types::{
+    access_path::AccessPath,
+    account_config::diem_root_address,
+    proof::accumulator::InMemoryAccumulator,
+    transaction::{TransactionInfo, TransactionOutput, TransactionStatus},
+    vm_status::KeptVMStatus,
+    write_set::{WriteOp, WriteSet, WriteSetMut},
+};
+
+fn output(gas_used: u64, status: TransactionStatus) -> TransactionOutput {
+    TransactionOutput::new(WriteSet::default(), vec![], gas_used, status)
+}
+
+fn output_with_writes(writes: Vec<(u8, WriteOp)>) -> TransactionOutput {
+    let write_set = WriteSetMut::new(
+        writes
+            .into_iter()
+            .map(|(path, op)| (AccessPath::new(diem_root_address(), vec![path]), op))
+            .collect(),
+    )
+    .freeze()
+    .unwrap();
+    TransactionOutput::new(
+        write_set,
+        vec![],
+        0,
+        TransactionStatus::Keep(KeptVMStatus::Executed),
+    )
+}
+
+fn txn_info(gas_used: u64, status: KeptVMStatus) -> TransactionInfo {
+    TransactionInfo::new(
+        HashValue::zero(),
+        HashValue::zero(),
+        InMemoryAccumulator::<EventAccumulatorHasher>::default().root_hash(),
+        gas_used,
+        status,
+    )
+}
+
+#[test]
+fn test_parse_execution_mode() {
+    assert_eq!(
+        "sequential".parse::<ExecutionMode>().unwrap(),
+        ExecutionMode::Sequential
+    );
+    assert_eq!(
+        "parallel".parse::<ExecutionMode>().unwrap(),
+        ExecutionMode::Parallel
+    );
+    assert_eq!(
+        "both".parse::<ExecutionMode>().unwrap(),
+        ExecutionMode::Both
+    );
+    assert!("fast".parse::<ExecutionMode>().is_err());
+}
+
+#[test]
+fn test_compare_outputs() {
+    let executed = output(10, TransactionStatus::Keep(KeptVMStatus::Executed));
+    let out_of_gas = output(10, TransactionStatus::Keep(KeptVMStatus::OutOfGas));
+
+    compare_outputs(0, &[executed.clone()], &[executed.clone()]).unwrap();
+    assert!(compare_outputs(0, &[executed.clone()], &[out_of_gas]).is_err());
+    assert!(compare_outputs(0, &[executed], &[]).is_err());
+}
+
+#[test]
+fn test_verify_transaction_infos() {
+    let outputs = vec![
+        output(10, TransactionStatus::Keep(KeptVMStatus::Executed)),
+        output(20, TransactionStatus::Keep(KeptVMStatus::OutOfGas)),
+    ];
+    let txn_infos = vec![
+        txn_info(10, KeptVMStatus::Executed),
+        txn_info(20, KeptVMStatus::OutOfGas),
+    ];
+    verify_transaction_infos(0, &outputs, &txn_infos).unwrap();
+
+    // Gas mismatch.
+    assert!(verify_transaction_infos(
+        0,
+        &outputs,
+        &[
+            txn_info(10, KeptVMStatus::Executed),
+            txn_info(21, KeptVMStatus::OutOfGas)
+        ]
+    )
+    .is_err());
+    // Status mismatch.
+    assert!(
+        verify_transaction_infos(0, &outputs[..1], &[txn_info(10, KeptVMStatus::OutOfGas)])
+            .is_err()
+    );
+    // Committed transactions can't be discarded on replay.
+    assert!(
+        verify_transaction_infos(0, &[output(0, TransactionStatus::Retry)], &txn_infos).is_err()
+    );
+    // Missing transaction infos.
+    assert!(verify_transaction_infos(0, &outputs, &txn_infos[..1]).is_err());
+}
+
+#[test]
+fn test_verify_write_sets() {
+    let outputs = vec![
+        output_with_writes(vec![(0, WriteOp::Value(vec![1])), (1, WriteOp::Deletion)]),
+        output_with_writes(vec![]),
+    ];
+    verify_write_sets(0, &outputs, &outputs).unwrap();
+
+    // Value mismatch.
+    let err = verify_write_sets(
+        0,
+        &outputs[..1],
+        &[output_with_writes(vec![
+            (0, WriteOp::Value(vec![2])),
+            (1, WriteOp::Deletion),
+        ])],
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("at version 0"));
+    // Access path only written on replay.
+    assert!(verify_write_sets(
+        1,
+        &outputs[1..],
+        &[output_with_writes(vec![(0, WriteOp::Deletion)])]
+    )
+    .is_err());
+    // Access path only committed.
+    assert!(verify_write_sets(
+        0,
+        &outputs[..1],
+        &[output_with_writes(vec![(1, WriteOp::Deletion)])]
+    )
+    .is_err());
+    // Missing write sets.
+    assert!(verify_write_sets(0, &outputs, &outputs[..1]).is_err());
+}
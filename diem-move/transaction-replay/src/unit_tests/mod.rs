@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod bisection_tests;
+mod diff_tests;
+mod execution_mode_tests;
 
 use crate::DiemValidatorInterface;
 use anyhow::{bail, Result};
@@ -11,7 +13,7 @@ use diem_types::{
     account_state_blob::AccountStateBlob,
     contract_event::EventWithProof,
     event::EventKey,
-    transaction::{Transaction, Version, WriteSetPayload},
+    transaction::{Transaction, TransactionInfo, TransactionOutput, Version, WriteSetPayload},
     write_set::WriteOp,
 };
 use std::{collections::HashMap, convert::TryFrom};
@@ -93,6 +95,22 @@ impl DiemValidatorInterface for TestInterface {
         Ok(result)
     }
 
+    fn get_committed_transaction_infos(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<Vec<TransactionInfo>> {
+        unimplemented!()
+    }
+
+    fn get_committed_transaction_outputs(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<Vec<TransactionOutput>> {
+        unimplemented!()
+    }
+
     fn get_latest_version(&self) -> Result<Version> {
         Ok(self.latest_version)
     }
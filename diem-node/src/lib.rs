@@ -1,8 +1,11 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use backup_service::start_backup_service;
-use consensus::consensus_provider::start_consensus;
+use backup_service::{
+    checkpoint::{self, DbCheckpointer},
+    start_backup_service_with_checkpointer,
+};
+use consensus::consensus_provider::{start_consensus, ConsensusDbHandle};
 use consensus_notifications::ConsensusNotificationListener;
 use data_streaming_service::{
     streaming_client::{new_streaming_service_client_listener_pair, StreamingServiceClient},
@@ -32,7 +35,7 @@ use diem_types::{
     on_chain_config::{VMPublishingOption, ON_CHAIN_CONFIG_REGISTRY},
     waypoint::Waypoint,
 };
-use diem_vm::DiemVM;
+use diem_vm::{parallel_executor::ParallelDiemVM, DiemVM, VMExecutor};
 use diemdb::DiemDB;
 use event_notifications::EventSubscriptionService;
 use executor::{chunk_executor::ChunkExecutor, db_bootstrapper::maybe_bootstrap};
@@ -50,7 +53,7 @@ use std::{
     convert::TryFrom,
     io::Write,
     net::ToSocketAddrs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -279,13 +282,62 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
         diem_data_client.clone(),
     );
 
-    // Create the chunk executor
+    // Create the state sync multiplexer
+    let state_sync_multiplexer = if node_config.execution.parallel_chunk_execution {
+        create_state_sync_multiplexer::<ParallelDiemVM, M>(
+            node_config,
+            state_sync_network_handles,
+            mempool_notifier,
+            consensus_listener,
+            db_rw,
+            waypoint,
+            event_subscription_service,
+            diem_data_client,
+            streaming_service_client,
+        )
+    } else {
+        create_state_sync_multiplexer::<DiemVM, M>(
+            node_config,
+            state_sync_network_handles,
+            mempool_notifier,
+            consensus_listener,
+            db_rw,
+            waypoint,
+            event_subscription_service,
+            diem_data_client,
+            streaming_service_client,
+        )
+    };
+
+    // Create and return the new state sync handle
+    StateSyncRuntimes::new(
+        diem_data_client_runtime,
+        state_sync_multiplexer,
+        storage_service_runtime,
+        streaming_service_runtime,
+    )
+}
+
+/// Creates the state sync multiplexer, with a chunk executor re-executing the transactions with
+/// the given VM.
+fn create_state_sync_multiplexer<
+    V: VMExecutor + 'static,
+    M: MempoolNotificationSender + 'static,
+>(
+    node_config: &NodeConfig,
+    state_sync_network_handles: Vec<(NetworkId, StateSyncSender, StateSyncEvents)>,
+    mempool_notifier: M,
+    consensus_listener: ConsensusNotificationListener,
+    db_rw: DbReaderWriter,
+    waypoint: Waypoint,
+    event_subscription_service: EventSubscriptionService,
+    diem_data_client: DiemNetDataClient,
+    streaming_service_client: StreamingServiceClient,
+) -> StateSyncMultiplexer {
     let chunk_executor = Arc::new(
-        ChunkExecutor::<DiemVM>::new(db_rw.clone()).expect("Unable to create the chunk executor!"),
+        ChunkExecutor::<V>::new(db_rw.clone()).expect("Unable to create the chunk executor!"),
     );
-
-    // Create the state sync multiplexer
-    let state_sync_multiplexer = StateSyncMultiplexer::new(
+    StateSyncMultiplexer::new(
         state_sync_network_handles,
         mempool_notifier,
         consensus_listener,
@@ -296,14 +348,6 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
         event_subscription_service,
         diem_data_client,
         streaming_service_client,
-    );
-
-    // Create and return the new state sync handle
-    StateSyncRuntimes::new(
-        diem_data_client_runtime,
-        state_sync_multiplexer,
-        storage_service_runtime,
-        streaming_service_runtime,
     )
 }
 
@@ -450,13 +494,38 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             node_config.storage.prune_window,
             node_config.storage.rocksdb_config,
             node_config.storage.account_count_migration,
+            node_config.storage.enable_resource_type_index,
         )
         .expect("DB should open."),
     );
     let _simple_storage_service = start_storage_service_with_db(node_config, Arc::clone(&diem_db));
-    let backup_service = start_backup_service(
+
+    // Consensus DB is opened upfront so that the checkpoint endpoint can package it too.
+    let consensus_db = node_config
+        .validator_network
+        .as_ref()
+        .map(|_| ConsensusDbHandle::open(node_config));
+    let checkpointer = node_config
+        .storage
+        .checkpoint
+        .auth_token
+        .clone()
+        .map(|auth_token| {
+            let consensus_db_checkpointer = consensus_db.clone().map(|consensus_db| {
+                Box::new(move |path: &Path| consensus_db.create_checkpoint(path))
+                    as checkpoint::AuxDbCheckpointFn
+            });
+            DbCheckpointer::new(
+                Arc::clone(&diem_db),
+                consensus_db_checkpointer,
+                node_config.storage.checkpoint_dir(),
+                auth_token,
+            )
+        });
+    let backup_service = start_backup_service_with_checkpointer(
         node_config.storage.backup_service_address,
         Arc::clone(&diem_db),
+        checkpointer,
     );
 
     let genesis_waypoint = node_config.base.waypoint.genesis_waypoint();
@@ -657,6 +726,7 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         instant = Instant::now();
         consensus_runtime = Some(start_consensus(
             node_config,
+            consensus_db.expect("Consensus DB should be opened for validators."),
             consensus_network_sender,
             consensus_network_events,
             Arc::new(consensus_notifier),
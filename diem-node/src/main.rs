@@ -53,6 +53,13 @@ struct Args {
         requires("test")
     )]
     lazy: bool,
+
+    #[structopt(
+        long,
+        help = "Populate the empty storage dir from a DB checkpoint made by the backup service, after verifying it against the configured waypoint",
+        conflicts_with("test")
+    )]
+    bootstrap_from_checkpoint: Option<PathBuf>,
 }
 
 #[global_allocator]
@@ -88,6 +95,10 @@ fn main() {
     } else {
         let config = NodeConfig::load(args.config.unwrap()).expect("Failed to load node config");
         println!("Using node config {:?}", &config);
+        if let Some(checkpoint_dir) = args.bootstrap_from_checkpoint {
+            backup_service::checkpoint::bootstrap_from_checkpoint(&config, &checkpoint_dir)
+                .expect("Failed to bootstrap from DB checkpoint");
+        }
         diem_node::start(&config, None);
     };
 }
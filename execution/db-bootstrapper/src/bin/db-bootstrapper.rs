@@ -57,6 +57,7 @@ fn main() -> Result<()> {
             None, /* pruner */
             RocksdbConfig::default(),
             opt.account_count_migration,
+            false, /* enable_resource_type_index */
         )
     } else {
         // When not committing, we open the DB as secondary so the tool is usable along side a
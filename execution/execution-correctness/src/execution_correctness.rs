@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use consensus_types::block::Block;
+use consensus_types::{block::Block, common::Payload};
 use diem_crypto::HashValue;
 use diem_types::ledger_info::LedgerInfoWithSignatures;
 use executor_types::{Error, StateComputeResult};
@@ -13,13 +13,32 @@ pub trait ExecutionCorrectness: Send {
 
     fn reset(&self) -> Result<(), Error>;
 
-    /// Executes a block.
+    /// Executes a block. The transactions of a block carrying proofs of store are resolved by the
+    /// caller and passed as `resolved_payload`.
     fn execute_block(
         &self,
         block: Block,
+        resolved_payload: Option<Payload>,
         parent_block_id: HashValue,
     ) -> Result<StateComputeResult, Error>;
 
+    /// Executes consecutive blocks, the first one being the child of `parent_block_id`, and
+    /// returns one result per block.
+    fn execute_blocks(
+        &self,
+        blocks: Vec<(Block, Option<Payload>)>,
+        parent_block_id: HashValue,
+    ) -> Result<Vec<StateComputeResult>, Error> {
+        let mut parent_block_id = parent_block_id;
+        let mut results = Vec::with_capacity(blocks.len());
+        for (block, resolved_payload) in blocks {
+            let block_id = block.id();
+            results.push(self.execute_block(block, resolved_payload, parent_block_id)?);
+            parent_block_id = block_id;
+        }
+        Ok(results)
+    }
+
     fn commit_blocks(
         &self,
         block_ids: Vec<HashValue>,
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::execution_correctness::ExecutionCorrectness;
-use consensus_types::{block::Block, vote_proposal::VoteProposal};
+use consensus_types::{block::Block, common::Payload, vote_proposal::VoteProposal};
 use diem_crypto::{ed25519::Ed25519PrivateKey, traits::SigningKey, HashValue};
 use diem_types::ledger_info::LedgerInfoWithSignatures;
 use executor_types::{BlockExecutorTrait, Error, StateComputeResult};
@@ -23,6 +23,19 @@ impl LocalService {
             prikey,
         }
     }
+
+    fn sign(&self, block: Block, result: &mut StateComputeResult) {
+        if let Some(prikey) = self.prikey.as_ref() {
+            let vote_proposal = VoteProposal::new(
+                result.extension_proof(),
+                block,
+                result.epoch_state().clone(),
+                false,
+            );
+            let signature = prikey.sign(&vote_proposal);
+            result.set_signature(signature);
+        }
+    }
 }
 
 /// A local interface into ExecutionCorrectness. Constructed in such a way that the container / caller
@@ -50,26 +63,45 @@ impl ExecutionCorrectness for LocalClient {
     fn execute_block(
         &self,
         block: Block,
+        resolved_payload: Option<Payload>,
         parent_block_id: HashValue,
     ) -> Result<StateComputeResult, Error> {
         let local = &self.internal;
         let mut result = local.block_executor.execute_block(
-            (block.id(), block.transactions_to_execute()),
+            (
+                block.id(),
+                block.transactions_to_execute(resolved_payload.as_ref())?,
+            ),
             parent_block_id,
         )?;
-        if let Some(prikey) = local.prikey.as_ref() {
-            let vote_proposal = VoteProposal::new(
-                result.extension_proof(),
-                block,
-                result.epoch_state().clone(),
-                false,
-            );
-            let signature = prikey.sign(&vote_proposal);
-            result.set_signature(signature);
-        }
+        local.sign(block, &mut result);
         Ok(result)
     }
 
+    fn execute_blocks(
+        &self,
+        blocks: Vec<(Block, Option<Payload>)>,
+        parent_block_id: HashValue,
+    ) -> Result<Vec<StateComputeResult>, Error> {
+        let local = &self.internal;
+        let mut results = local.block_executor.execute_blocks(
+            blocks
+                .iter()
+                .map(|(block, resolved_payload)| {
+                    Ok((
+                        block.id(),
+                        block.transactions_to_execute(resolved_payload.as_ref())?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            parent_block_id,
+        )?;
+        for ((block, _), result) in blocks.into_iter().zip(results.iter_mut()) {
+            local.sign(block, result);
+        }
+        Ok(results)
+    }
+
     fn commit_blocks(
         &self,
         block_ids: Vec<HashValue>,
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::execution_correctness::ExecutionCorrectness;
-use consensus_types::{block::Block, vote_proposal::VoteProposal};
+use consensus_types::{block::Block, common::Payload, vote_proposal::VoteProposal};
 use diem_crypto::{ed25519::Ed25519PrivateKey, traits::SigningKey, HashValue};
 use diem_types::ledger_info::LedgerInfoWithSignatures;
 use executor_types::{BlockExecutorTrait, Error, StateComputeResult};
@@ -13,8 +13,9 @@ use std::sync::Arc;
 pub enum ExecutionCorrectnessInput {
     CommittedBlockId,
     Reset,
-    ExecuteBlock(Box<(Block, HashValue)>),
+    ExecuteBlock(Box<(Block, Option<Payload>, HashValue)>),
     CommitBlocks(Box<(Vec<HashValue>, LedgerInfoWithSignatures)>),
+    ExecuteBlocks(Box<(Vec<(Block, Option<Payload>)>, HashValue)>),
 }
 
 pub struct SerializerService {
@@ -35,30 +36,43 @@ impl SerializerService {
                 bcs::to_bytes(&Result::<_, Error>::Ok(self.internal.committed_block_id()))
             }
             ExecutionCorrectnessInput::Reset => bcs::to_bytes(&self.internal.reset()),
-            ExecutionCorrectnessInput::ExecuteBlock(block_with_parent_id) => bcs::to_bytes(
-                &self
-                    .internal
-                    .execute_block(
-                        (
-                            block_with_parent_id.0.id(),
-                            block_with_parent_id.0.transactions_to_execute(),
-                        ),
-                        block_with_parent_id.1,
-                    )
-                    .map(|mut result| {
-                        if let Some(prikey) = self.prikey.as_ref() {
-                            let vote_proposal = VoteProposal::new(
-                                result.extension_proof(),
-                                block_with_parent_id.0.clone(),
-                                result.epoch_state().clone(),
-                                false,
-                            );
-                            let signature = prikey.sign(&vote_proposal);
-                            result.set_signature(signature);
-                        }
-                        result
-                    }),
-            ),
+            ExecutionCorrectnessInput::ExecuteBlock(block_with_parent_id) => {
+                let (block, resolved_payload, parent_block_id) = *block_with_parent_id;
+                bcs::to_bytes(
+                    &block
+                        .transactions_to_execute(resolved_payload.as_ref())
+                        .map_err(Error::from)
+                        .and_then(|txns| {
+                            self.internal
+                                .execute_block((block.id(), txns), parent_block_id)
+                        })
+                        .map(|mut result| {
+                            self.sign(block, &mut result);
+                            result
+                        }),
+                )
+            }
+            ExecutionCorrectnessInput::ExecuteBlocks(blocks_with_parent_id) => {
+                let (blocks, parent_block_id) = *blocks_with_parent_id;
+                bcs::to_bytes(
+                    &blocks
+                        .iter()
+                        .map(|(block, resolved_payload)| {
+                            Ok((
+                                block.id(),
+                                block.transactions_to_execute(resolved_payload.as_ref())?,
+                            ))
+                        })
+                        .collect::<Result<_, Error>>()
+                        .and_then(|txns| self.internal.execute_blocks(txns, parent_block_id))
+                        .map(|mut results| {
+                            for ((block, _), result) in blocks.into_iter().zip(results.iter_mut()) {
+                                self.sign(block, result);
+                            }
+                            results
+                        }),
+                )
+            }
             ExecutionCorrectnessInput::CommitBlocks(blocks_with_li) => bcs::to_bytes(
                 &self
                     .internal
@@ -67,6 +81,19 @@ impl SerializerService {
         };
         Ok(output?)
     }
+
+    fn sign(&self, block: Block, result: &mut StateComputeResult) {
+        if let Some(prikey) = self.prikey.as_ref() {
+            let vote_proposal = VoteProposal::new(
+                result.extension_proof(),
+                block,
+                result.epoch_state().clone(),
+                false,
+            );
+            let signature = prikey.sign(&vote_proposal);
+            result.set_signature(signature);
+        }
+    }
 }
 
 pub struct SerializerClient {
@@ -102,10 +129,24 @@ impl ExecutionCorrectness for SerializerClient {
     fn execute_block(
         &self,
         block: Block,
+        resolved_payload: Option<Payload>,
         parent_block_id: HashValue,
     ) -> Result<StateComputeResult, Error> {
         let response = self.request(ExecutionCorrectnessInput::ExecuteBlock(Box::new((
             block,
+            resolved_payload,
+            parent_block_id,
+        ))))?;
+        bcs::from_bytes(&response)?
+    }
+
+    fn execute_blocks(
+        &self,
+        blocks: Vec<(Block, Option<Payload>)>,
+        parent_block_id: HashValue,
+    ) -> Result<Vec<StateComputeResult>, Error> {
+        let response = self.request(ExecutionCorrectnessInput::ExecuteBlocks(Box::new((
+            blocks,
             parent_block_id,
         ))))?;
         bcs::from_bytes(&response)?
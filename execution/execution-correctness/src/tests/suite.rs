@@ -16,7 +16,7 @@ pub fn run_test_suite(executor_pair: (Box<dyn ExecutionCorrectness>, Option<Ed25
     let block_id = block.id();
 
     let result = executor
-        .execute_block(block.clone(), parent_block_id)
+        .execute_block(block.clone(), None, parent_block_id)
         .unwrap();
 
     if let Some(sig) = result.signature().as_ref() {
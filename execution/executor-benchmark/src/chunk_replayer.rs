@@ -0,0 +1,105 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_config::config::RocksdbConfig;
+use diem_logger::prelude::*;
+use diem_types::transaction::Version;
+use diem_vm::{DiemVM, VMExecutor};
+use diemdb::DiemDB;
+use executor::{
+    chunk_executor::ChunkExecutor,
+    db_bootstrapper::{generate_waypoint, maybe_bootstrap},
+    metrics::{
+        DIEM_EXECUTOR_COMMIT_CHUNK_SECONDS, DIEM_EXECUTOR_EXECUTE_CHUNK_SECONDS,
+        DIEM_EXECUTOR_VM_EXECUTE_CHUNK_SECONDS,
+    },
+};
+use executor_types::ChunkExecutorTrait;
+use std::{fs, path::Path, time::Instant};
+use storage_interface::{DbReader, DbReaderWriter};
+
+/// Syncs an empty DB to the ledger of the DB in `source_dir` the way a fullnode does in the
+/// executing mode of state sync: chunks of `chunk_size` transactions with their proofs are
+/// re-executed with the VM `V`, checked against the transaction infos and committed. Returns the
+/// number of transactions synced per second.
+pub fn run<V: VMExecutor>(
+    chunk_size: u64,
+    source_dir: impl AsRef<Path>,
+    target_dir: impl AsRef<Path>,
+) -> f64 {
+    let source_db = DiemDB::open(
+        &source_dir,
+        true, /* readonly */
+        None, /* pruner */
+        RocksdbConfig::default(),
+        true,  /* account_count_migration */
+        false, /* enable_resource_type_index */
+    )
+    .expect("db open failure.");
+    let target_li = source_db
+        .get_latest_ledger_info()
+        .expect("Source DB should have a ledger info.");
+    let target_version = target_li.ledger_info().version();
+
+    if target_dir.as_ref().exists() {
+        fs::remove_dir_all(target_dir.as_ref().join("diemdb")).unwrap_or(());
+    }
+    fs::create_dir_all(target_dir.as_ref()).unwrap();
+    let (_, target_db_rw) = DbReaderWriter::wrap(
+        DiemDB::open(
+            &target_dir,
+            false, /* readonly */
+            None,  /* pruner */
+            RocksdbConfig::default(),
+            true,  /* account_count_migration */
+            false, /* enable_resource_type_index */
+        )
+        .expect("DB should open."),
+    );
+
+    // Bootstrap the target db with the genesis of the source db
+    let genesis = source_db
+        .get_transactions(0, 1, target_version, false)
+        .expect("Source DB should have a genesis.")
+        .transactions
+        .pop()
+        .expect("Source DB should have a genesis.");
+    let waypoint = generate_waypoint::<DiemVM>(&target_db_rw, &genesis).unwrap();
+    maybe_bootstrap::<DiemVM>(&target_db_rw, &genesis, waypoint).unwrap();
+
+    let chunk_executor = ChunkExecutor::<V>::new(target_db_rw).unwrap();
+    // the metrics accumulate across the runs in the same process
+    let vm_time_before = DIEM_EXECUTOR_VM_EXECUTE_CHUNK_SECONDS.get_sample_sum();
+    let execute_time_before = DIEM_EXECUTOR_EXECUTE_CHUNK_SECONDS.get_sample_sum();
+    let commit_time_before = DIEM_EXECUTOR_COMMIT_CHUNK_SECONDS.get_sample_sum();
+    let start_time = Instant::now();
+    let mut version: Version = 1;
+    while version <= target_version {
+        let num_txns = std::cmp::min(chunk_size, target_version - version + 1);
+        let txn_list_with_proof = source_db
+            .get_transactions(version, num_txns, target_version, false)
+            .unwrap();
+        chunk_executor
+            .execute_and_commit_chunk(txn_list_with_proof, &target_li, None)
+            .unwrap();
+        version += num_txns;
+        info!(
+            "Version: {}. Accumulative TPS: {:.0}",
+            version - 1,
+            (version - 1) as f64 / start_time.elapsed().as_secs_f64(),
+        );
+    }
+
+    let tps = target_version as f64 / start_time.elapsed().as_secs_f64();
+    let vm_time = DIEM_EXECUTOR_VM_EXECUTE_CHUNK_SECONDS.get_sample_sum() - vm_time_before;
+    let execute_time = DIEM_EXECUTOR_EXECUTE_CHUNK_SECONDS.get_sample_sum() - execute_time_before;
+    info!(
+        "Synced {} transactions. TPS: {:.0}. Total: VM time: {:.0} secs, executor time: {:.0} secs, commit time: {:.0} secs",
+        target_version,
+        tps,
+        vm_time,
+        execute_time - vm_time,
+        DIEM_EXECUTOR_COMMIT_CHUNK_SECONDS.get_sample_sum() - commit_time_before,
+    );
+    tps
+}
@@ -49,7 +49,8 @@ pub fn run(
             false,        /* readonly */
             prune_window, /* pruner */
             RocksdbConfig::default(),
-            true, /* account_count_migration */
+            true,  /* account_count_migration */
+            false, /* enable_resource_type_index */
         )
         .expect("DB should open."),
     );
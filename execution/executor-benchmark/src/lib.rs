@@ -1,6 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod chunk_replayer;
 pub mod db_generator;
 pub mod transaction_committer;
 pub mod transaction_executor;
@@ -13,7 +14,7 @@ use crate::{
 use diem_config::config::{NodeConfig, RocksdbConfig};
 use diem_logger::prelude::*;
 
-use diem_vm::DiemVM;
+use diem_vm::{parallel_executor::ParallelDiemVM, DiemVM};
 use diemdb::DiemDB;
 use executor::block_executor::BlockExecutor;
 use executor_types::BlockExecutorTrait;
@@ -31,7 +32,8 @@ pub fn init_db_and_executor(config: &NodeConfig) -> (Arc<dyn DbReader>, BlockExe
             false, /* readonly */
             None,  /* pruner */
             RocksdbConfig::default(),
-            true, /* account_count_migration */
+            true,  /* account_count_migration */
+            false, /* enable_resource_type_index */
         )
         .expect("DB should open."),
     );
@@ -60,7 +62,8 @@ pub fn run_benchmark(
         true, /* readonly */
         None, /* pruner */
         RocksdbConfig::default(),
-        true, /* account_count_migration */
+        true,  /* account_count_migration */
+        false, /* enable_resource_type_index */
     )
     .expect("db open failure.")
     .create_checkpoint(checkpoint_dir.as_ref().join("diemdb"))
@@ -126,6 +129,23 @@ pub fn run_benchmark(
     }
 }
 
+/// Syncs the ledger in `source_dir` to `target_dir` by re-executing chunks of transactions, first
+/// with the sequential VM and then with the parallel one, and reports the throughput of both.
+pub fn run_sync_benchmark(
+    chunk_size: u64,
+    source_dir: impl AsRef<Path>,
+    target_dir: impl AsRef<Path>,
+) {
+    let sequential_tps = chunk_replayer::run::<DiemVM>(chunk_size, &source_dir, &target_dir);
+    let parallel_tps = chunk_replayer::run::<ParallelDiemVM>(chunk_size, &source_dir, &target_dir);
+    info!(
+        "Sync TPS: sequential: {:.0}, parallel: {:.0}, speedup: {:.2}x",
+        sequential_tps,
+        parallel_tps,
+        parallel_tps / sequential_tps,
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use diem_temppath::TempPath;
@@ -153,4 +173,26 @@ mod tests {
             false,
         );
     }
+
+    #[test]
+    fn test_sync_benchmark() {
+        let storage_dir = TempPath::new();
+        let target_dir = TempPath::new();
+        storage_dir.create_as_dir().unwrap();
+        target_dir.create_as_dir().unwrap();
+
+        crate::db_generator::run(
+            25, /* num_accounts */
+            10, /* init_account_balance */
+            5,  /* block_size */
+            storage_dir.as_ref(),
+            None, /* prune_window */
+        );
+
+        super::run_sync_benchmark(
+            7, /* chunk_size */
+            storage_dir.as_ref(),
+            target_dir.as_ref(),
+        );
+    }
 }
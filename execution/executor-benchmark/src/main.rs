@@ -52,6 +52,20 @@ enum Command {
         )]
         verify: bool,
     },
+    SyncChunks {
+        #[structopt(
+            long,
+            default_value = "1000",
+            about = "number of transactions in a chunk"
+        )]
+        chunk_size: u64,
+
+        #[structopt(long, parse(from_os_str))]
+        data_dir: PathBuf,
+
+        #[structopt(long, parse(from_os_str))]
+        target_dir: PathBuf,
+    },
 }
 
 fn main() {
@@ -93,5 +107,13 @@ fn main() {
                 verify,
             );
         }
+        Command::SyncChunks {
+            chunk_size,
+            data_dir,
+            target_dir,
+        } => {
+            diem_logger::Logger::new().init();
+            executor_benchmark::run_sync_benchmark(chunk_size, data_dir, target_dir);
+        }
     }
 }
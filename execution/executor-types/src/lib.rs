@@ -97,6 +97,25 @@ pub trait BlockExecutorTrait: Send + Sync {
         parent_block_id: HashValue,
     ) -> Result<StateComputeResult, Error>;
 
+    /// Executes consecutive blocks, each one the child of the previous one and the first one the
+    /// child of `parent_block_id`, returning one result per block. The results are the same as
+    /// executing the blocks one by one, which is what the default implementation does, but an
+    /// implementation may execute the transactions of all blocks in one run of the VM.
+    fn execute_blocks(
+        &self,
+        blocks: Vec<(HashValue, Vec<Transaction>)>,
+        parent_block_id: HashValue,
+    ) -> Result<Vec<StateComputeResult>, Error> {
+        let mut parent_block_id = parent_block_id;
+        let mut results = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let block_id = block.0;
+            results.push(self.execute_block(block, parent_block_id)?);
+            parent_block_id = block_id;
+        }
+        Ok(results)
+    }
+
     /// Saves eligible blocks to persistent storage.
     /// If we have multiple blocks and not all of them have signatures, we may send them to storage
     /// in a few batches. For example, if we have
@@ -44,6 +44,29 @@ where
     }
 }
 
+impl<V> BlockExecutor<V> {
+    /// Whether executing the block needs the VM, i.e. it's neither a retry nor a descendant of a
+    /// reconfiguration block.
+    fn needs_vm_execution(
+        &self,
+        block_id: HashValue,
+        parent_block_id: HashValue,
+        committed_block_id: HashValue,
+    ) -> Result<bool, Error> {
+        let mut block_vec = self
+            .block_tree
+            .get_blocks_opt(&[block_id, parent_block_id])?;
+        let parent_block = block_vec
+            .pop()
+            .expect("Must exist.")
+            .ok_or(Error::BlockNotFound(parent_block_id))?;
+        let is_retry = block_vec.pop().expect("Must exist").is_some();
+        let is_reconfig_suffix =
+            parent_block_id != committed_block_id && parent_block.output.has_reconfiguration();
+        Ok(!is_retry && !is_reconfig_suffix)
+    }
+}
+
 impl<V> BlockExecutorTrait for BlockExecutor<V>
 where
     V: VMExecutor,
@@ -119,6 +142,92 @@ where
         Ok(block.output.as_state_compute_result(parent_accumulator))
     }
 
+    fn execute_blocks(
+        &self,
+        blocks: Vec<(HashValue, Vec<Transaction>)>,
+        parent_block_id: HashValue,
+    ) -> Result<Vec<StateComputeResult>, Error> {
+        let committed_block = self.block_tree.root_block();
+        let mut parent_block_id = parent_block_id;
+        let mut results = Vec::with_capacity(blocks.len());
+
+        // Retries and descendants of a reconfiguration don't run the VM, leave them to
+        // `execute_block`.
+        let mut blocks = blocks.into_iter().peekable();
+        while let Some((block_id, _)) = blocks.peek() {
+            let block_id = *block_id;
+            if self.needs_vm_execution(block_id, parent_block_id, committed_block.id)? {
+                break;
+            }
+            let block = blocks.next().expect("Must exist.");
+            results.push(self.execute_block(block, parent_block_id)?);
+            parent_block_id = block_id;
+        }
+        let blocks: Vec<_> = blocks.collect();
+        if blocks.len() < 2 {
+            for block in blocks {
+                let block_id = block.0;
+                results.push(self.execute_block(block, parent_block_id)?);
+                parent_block_id = block_id;
+            }
+            return Ok(results);
+        }
+
+        let parent_block = self.block_tree.get_block(parent_block_id)?;
+        let parent_view = &parent_block.output.result_view;
+        let mut parent_accumulator = parent_view.txn_accumulator().clone();
+        let first_block_id = blocks[0].0;
+        info!(
+            LogSchema::new(LogEntry::BlockExecutor)
+                .block_id(first_block_id)
+                .num(blocks.len() as u64),
+            "execute_blocks"
+        );
+
+        let mut block_ids = Vec::with_capacity(blocks.len());
+        let mut block_sizes = Vec::with_capacity(blocks.len());
+        let mut transactions = Vec::new();
+        for (block_id, block_transactions) in blocks {
+            block_ids.push(block_id);
+            block_sizes.push(block_transactions.len());
+            transactions.extend(block_transactions);
+        }
+
+        let outputs = {
+            let _timer = DIEM_EXECUTOR_EXECUTE_BLOCK_SECONDS.start_timer();
+            let state_view = parent_view.state_view(
+                &committed_block.output.result_view,
+                StateViewId::BlockExecution {
+                    block_id: first_block_id,
+                },
+                self.db.reader.clone(),
+            );
+
+            let chunk_output = {
+                let _timer = DIEM_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.start_timer();
+                fail_point!("executor::vm_execute_block", |_| {
+                    Err(Error::from(anyhow::anyhow!(
+                        "Injected error in vm_execute_block"
+                    )))
+                });
+                ChunkOutput::by_transaction_execution::<V>(transactions, state_view)?
+            };
+            chunk_output.trace_log_transaction_status();
+
+            chunk_output.apply_blocks_to_ledger(&block_sizes, &parent_accumulator)?
+        };
+
+        for (block_id, output) in itertools::zip_eq(block_ids, outputs) {
+            let block = self
+                .block_tree
+                .add_block(parent_block_id, block_id, output)?;
+            results.push(block.output.as_state_compute_result(&parent_accumulator));
+            parent_accumulator = block.output.result_view.txn_accumulator().clone();
+            parent_block_id = block_id;
+        }
+        Ok(results)
+    }
+
     fn commit_blocks(
         &self,
         block_ids: Vec<HashValue>,
@@ -28,7 +28,7 @@ use diem_types::{
 use executor_types::{ExecutedChunk, ExecutedTrees, ProofReader, TransactionData};
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
-use scratchpad::SparseMerkleTree;
+use scratchpad::FrozenSparseMerkleTree;
 use std::{
     collections::{hash_map, HashMap, HashSet},
     convert::TryFrom,
@@ -50,14 +50,101 @@ impl ApplyChunkOutput {
             transactions,
             transaction_outputs,
         } = chunk_output;
+        let StateCache {
+            frozen_base,
+            mut accounts,
+            proofs,
+        } = state_cache;
 
+        let (executed_chunk, to_discard, to_retry, _) = Self::apply_segment(
+            transactions,
+            transaction_outputs,
+            frozen_base,
+            &mut accounts,
+            &ProofReader::new(proofs),
+            base_accumulator,
+        )?;
+        Ok((executed_chunk, to_discard, to_retry))
+    }
+
+    /// Applies the output of executing consecutive blocks as one chunk, and splits it back into
+    /// one `ExecutedChunk` per block, `block_sizes` being the number of transactions in each.
+    /// Blocks after the one ending the epoch get its reconfiguration suffix, the same as they
+    /// would when executed one by one.
+    pub fn apply_blocks(
+        chunk_output: ChunkOutput,
+        block_sizes: &[usize],
+        base_accumulator: &Arc<InMemoryAccumulator<TransactionAccumulatorHasher>>,
+    ) -> Result<Vec<ExecutedChunk>> {
+        let ChunkOutput {
+            state_cache,
+            transactions,
+            transaction_outputs,
+        } = chunk_output;
+        let StateCache {
+            frozen_base,
+            mut accounts,
+            proofs,
+        } = state_cache;
+        ensure!(
+            block_sizes.iter().sum::<usize>() == transactions.len(),
+            "Block sizes don't add up to the number of transactions. {:?} vs {}",
+            block_sizes,
+            transactions.len(),
+        );
+
+        let proof_reader = ProofReader::new(proofs);
+        let mut transactions = transactions.into_iter();
+        let mut transaction_outputs = transaction_outputs.into_iter();
+        let mut base_state = frozen_base;
+        let mut base_accumulator = base_accumulator.clone();
+        let mut executed_chunks: Vec<ExecutedChunk> = Vec::with_capacity(block_sizes.len());
+        for size in block_sizes {
+            let block_transactions: Vec<_> = transactions.by_ref().take(*size).collect();
+            let block_outputs: Vec<_> = transaction_outputs.by_ref().take(*size).collect();
+            if let Some(last) = executed_chunks.last() {
+                if last.has_reconfiguration() {
+                    // The outputs after the epoch ending transaction are all Retry.
+                    executed_chunks.push(last.reconfig_suffix());
+                    continue;
+                }
+            }
+
+            let (executed_chunk, _, _, result_state) = Self::apply_segment(
+                block_transactions,
+                block_outputs,
+                base_state,
+                &mut accounts,
+                &proof_reader,
+                &base_accumulator,
+            )?;
+            base_state = result_state;
+            base_accumulator = executed_chunk.result_view.txn_accumulator().clone();
+            executed_chunks.push(executed_chunk);
+        }
+        Ok(executed_chunks)
+    }
+
+    fn apply_segment(
+        transactions: Vec<Transaction>,
+        transaction_outputs: Vec<TransactionOutput>,
+        base_state: FrozenSparseMerkleTree<AccountStateBlob>,
+        accounts: &mut HashMap<AccountAddress, AccountState>,
+        proof_reader: &ProofReader,
+        base_accumulator: &Arc<InMemoryAccumulator<TransactionAccumulatorHasher>>,
+    ) -> Result<(
+        ExecutedChunk,
+        Vec<Transaction>,
+        Vec<Transaction>,
+        FrozenSparseMerkleTree<AccountStateBlob>,
+    )> {
         // Separate transactions with different VM statuses.
         let (new_epoch, status, to_keep, to_discard, to_retry) =
             Self::sort_transactions(transactions, transaction_outputs)?;
 
         // Apply the write set, get the latest state.
         let (account_blobs, roots_with_node_hashes, result_state, next_epoch_state) =
-            Self::apply_write_set(state_cache, new_epoch, &to_keep)?;
+            Self::apply_write_set(base_state, accounts, proof_reader, new_epoch, &to_keep)?;
 
         // Calculate TransactionData and TransactionInfo, i.e. the ledger history diff.
         let (to_commit, transaction_info_hashes) =
@@ -68,7 +155,7 @@ impl ApplyChunkOutput {
                 status,
                 to_commit,
                 result_view: ExecutedTrees::new_copy(
-                    result_state,
+                    result_state.clone().unfreeze(),
                     Arc::new(base_accumulator.append(&transaction_info_hashes)),
                 ),
                 next_epoch_state,
@@ -76,6 +163,7 @@ impl ApplyChunkOutput {
             },
             to_discard,
             to_retry,
+            result_state,
         ))
     }
 
@@ -154,25 +242,21 @@ impl ApplyChunkOutput {
     }
 
     fn apply_write_set(
-        state_cache: StateCache,
+        base_state: FrozenSparseMerkleTree<AccountStateBlob>,
+        accounts: &mut HashMap<AccountAddress, AccountState>,
+        proof_reader: &ProofReader,
         new_epoch: bool,
         to_keep: &[(Transaction, ParsedTransactionOutput)],
     ) -> Result<(
         Vec<HashMap<AccountAddress, AccountStateBlob>>,
         Vec<(HashValue, HashMap<NibblePath, HashValue>)>,
-        SparseMerkleTree<AccountStateBlob>,
+        FrozenSparseMerkleTree<AccountStateBlob>,
         Option<EpochState>,
     )> {
-        let StateCache {
-            frozen_base,
-            mut accounts,
-            proofs,
-        } = state_cache;
-
         // Apply write sets to account states in the AccountCache, resulting in new account states.
         let account_states = to_keep
             .iter()
-            .map(|(t, o)| process_write_set(t, &mut accounts, o.write_set().clone()))
+            .map(|(t, o)| process_write_set(t, accounts, o.write_set().clone()))
             .collect::<Result<Vec<_>>>()?;
         let account_blobs = account_states
             .par_iter()
@@ -186,19 +270,16 @@ impl ApplyChunkOutput {
             .collect::<Result<Vec<_>>>()?;
 
         // Apply new account states to the base state tree, resulting in updated state tree.
-        let (roots_with_node_hashes, result_state) = frozen_base
+        let (roots_with_node_hashes, result_state) = base_state
             .serial_update(
                 Self::account_blobs_to_smt_updates(&account_blobs),
-                &ProofReader::new(proofs),
+                proof_reader,
             )
             .map_err(|e| anyhow!("Failed to update state tree. err: {:?}", e))?;
-        // Release ASAP the ref to the base SMT to allow old in-mem nodes to be dropped,
-        // now that we don't require access to them.
-        let result_state = result_state.unfreeze();
 
         // Get the updated validator set from updated account state.
         let next_epoch_state = if new_epoch {
-            Some(Self::parse_validator_set(&accounts)?)
+            Some(Self::parse_validator_set(accounts)?)
         } else {
             None
         };
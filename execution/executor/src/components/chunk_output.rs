@@ -81,6 +81,19 @@ impl ChunkOutput {
         ApplyChunkOutput::apply(self, base_accumulator)
     }
 
+    /// Like `apply_to_ledger`, for the output of executing several consecutive blocks together,
+    /// returning one `ExecutedChunk` per block.
+    pub fn apply_blocks_to_ledger(
+        self,
+        block_sizes: &[usize],
+        base_accumulator: &Arc<InMemoryAccumulator<TransactionAccumulatorHasher>>,
+    ) -> Result<Vec<ExecutedChunk>> {
+        fail_point!("executor::vm_execute_chunk", |_| {
+            Err(anyhow::anyhow!("Injected error in apply_blocks_to_ledger."))
+        });
+        ApplyChunkOutput::apply_blocks(self, block_sizes, base_accumulator)
+    }
+
     pub fn trace_log_transaction_status(&self) {
         let status: Vec<_> = self
             .transaction_outputs
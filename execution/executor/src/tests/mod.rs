@@ -21,7 +21,9 @@ use diem_types::{
     transaction::{Transaction, TransactionListWithProof, TransactionStatus, Version},
 };
 use diemdb::DiemDB;
-use executor_types::{BlockExecutorTrait, ChunkExecutorTrait, ExecutedTrees, TransactionReplayer};
+use executor_types::{
+    BlockExecutorTrait, ChunkExecutorTrait, ExecutedTrees, StateComputeResult, TransactionReplayer,
+};
 use proptest::prelude::*;
 use std::collections::BTreeMap;
 use storage_interface::DbReaderWriter;
@@ -249,6 +251,100 @@ fn test_executor_execute_same_block_multiple_times() {
     assert_eq!(responses.len(), 1);
 }
 
+fn gen_consecutive_blocks(reconfig_block: Option<u64>) -> Vec<(HashValue, Vec<Transaction>)> {
+    (0..5)
+        .map(|i| {
+            let mut txns: Vec<_> = (0..20)
+                .map(|j| {
+                    if j % 3 == 2 {
+                        encode_transfer_transaction(gen_address(j), gen_address(j + 1), 150)
+                    } else {
+                        encode_mint_transaction(gen_address(j), 100)
+                    }
+                })
+                .collect();
+            if reconfig_block == Some(i) {
+                txns.insert(10, encode_reconfiguration_transaction(gen_address(i)));
+            }
+            (gen_block_id(i + 1), txns)
+        })
+        .collect()
+}
+
+fn execute_blocks_one_by_one(
+    blocks: Vec<(HashValue, Vec<Transaction>)>,
+) -> Vec<StateComputeResult> {
+    let executor = TestExecutor::new();
+    let mut parent_block_id = executor.committed_block_id();
+    blocks
+        .into_iter()
+        .map(|block| {
+            let block_id = block.0;
+            let output = executor.execute_block(block, parent_block_id).unwrap();
+            parent_block_id = block_id;
+            output
+        })
+        .collect()
+}
+
+#[test]
+fn test_executor_execute_blocks() {
+    let blocks = gen_consecutive_blocks(None);
+    let expected = execute_blocks_one_by_one(blocks.clone());
+
+    let executor = TestExecutor::new();
+    let parent_block_id = executor.committed_block_id();
+    let outputs = executor
+        .execute_blocks(blocks.clone(), parent_block_id)
+        .unwrap();
+    assert_eq!(outputs, expected);
+
+    // a retry returns the same results
+    let outputs = executor
+        .execute_blocks(blocks.clone(), parent_block_id)
+        .unwrap();
+    assert_eq!(outputs, expected);
+
+    let last = outputs.last().unwrap();
+    let block_ids: Vec<_> = blocks.iter().map(|(id, _)| *id).collect();
+    let ledger_info = gen_ledger_info(
+        last.version(),
+        last.root_hash(),
+        *block_ids.last().unwrap(),
+        1,
+    );
+    executor.commit_blocks(block_ids, ledger_info).unwrap();
+}
+
+#[test]
+fn test_executor_execute_blocks_with_reconfiguration() {
+    let blocks = gen_consecutive_blocks(Some(2));
+    let expected = execute_blocks_one_by_one(blocks.clone());
+
+    let executor = TestExecutor::new();
+    let parent_block_id = executor.committed_block_id();
+    let outputs = executor.execute_blocks(blocks, parent_block_id).unwrap();
+    assert_eq!(outputs, expected);
+    assert!(outputs[2].has_reconfiguration());
+    assert_eq!(outputs[3].root_hash(), outputs[2].root_hash());
+    assert_eq!(outputs[4].root_hash(), outputs[2].root_hash());
+}
+
+#[test]
+fn test_executor_execute_blocks_partially_executed() {
+    let blocks = gen_consecutive_blocks(None);
+    let expected = execute_blocks_one_by_one(blocks.clone());
+
+    // the first two blocks were executed before
+    let executor = TestExecutor::new();
+    let parent_block_id = executor.committed_block_id();
+    executor
+        .execute_blocks(blocks[..2].to_vec(), parent_block_id)
+        .unwrap();
+    let outputs = executor.execute_blocks(blocks, parent_block_id).unwrap();
+    assert_eq!(outputs, expected);
+}
+
 /// Generates a list of `TransactionListWithProof`s according to the given ranges.
 fn create_transaction_chunks(
     chunk_ranges: Vec<std::ops::Range<Version>>,
@@ -1842,6 +1842,85 @@ impl PublicUsageTest for GetResourcesTest {
     }
 }
 
+pub struct GetDecodedResourcesTest;
+impl Test for GetDecodedResourcesTest {
+    fn name(&self) -> &'static str {
+        "jsonrpc::get-decoded-resources-test"
+    }
+}
+
+impl PublicUsageTest for GetDecodedResourcesTest {
+    fn run<'t>(&self, ctx: &mut PublicUsageContext<'t>) -> Result<()> {
+        let env = JsonRpcTestHelper::new(ctx.url().to_owned());
+        let address = format!("{:x}", diem_sdk::types::account_config::diem_root_address());
+        let find_resource = |resources: &serde_json::Value, typ: &str| {
+            resources
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|resource| resource["type"] == typ)
+                .unwrap_or_else(|| panic!("resource {} not found", typ))
+                .clone()
+        };
+
+        let response = env.send("get_resources", json!([address, null, true]));
+        let resources = response.result.unwrap();
+        let currency_info = find_resource(&resources, "0x1::Diem::CurrencyInfo<0x1::XDX::XDX>");
+        let fields = &currency_info["value"];
+        assert_eq!(fields["total_value"], json!({"type": "u128", "value": "0"}));
+        assert_eq!(
+            fields["scaling_factor"],
+            json!({"type": "u64", "value": 1000000})
+        );
+        assert_eq!(
+            fields["is_synthetic"],
+            json!({"type": "bool", "value": true})
+        );
+        assert_eq!(
+            fields["currency_code"],
+            json!({"type": "vector<u8>", "value": hex::encode("XDX")})
+        );
+        assert_eq!(
+            fields["mint_events"]["type"],
+            "0x1::Event::EventHandle<0x1::Diem::MintEvent>"
+        );
+        assert_eq!(
+            fields["mint_events"]["value"]["counter"],
+            json!({"type": "u64", "value": 0})
+        );
+
+        // vectors of structs are decoded element by element
+        let diem_system = find_resource(
+            &resources,
+            "0x1::DiemConfig::DiemConfig<0x1::DiemSystem::DiemSystem>",
+        );
+        let validators = &diem_system["value"]["payload"]["value"]["validators"];
+        assert_eq!(validators["type"], "vector<0x1::DiemSystem::ValidatorInfo>");
+        let validator = &validators["value"][0];
+        assert_eq!(validator["type"], "0x1::DiemSystem::ValidatorInfo");
+        assert_eq!(validator["value"]["addr"]["type"], "address");
+
+        // the same resources come along with the account state
+        let response = env.send(
+            "get_account_state_with_proof",
+            json!([address, null, null, true]),
+        );
+        let account_state = response.result.unwrap();
+        assert_eq!(
+            find_resource(
+                &account_state["resources"],
+                "0x1::Diem::CurrencyInfo<0x1::XDX::XDX>"
+            ),
+            currency_info
+        );
+
+        let response = env.send("get_account_state_with_proof", json!([address]));
+        assert!(response.result.unwrap().get("resources").is_none());
+
+        Ok(())
+    }
+}
+
 pub struct MultiAgentPaymentOverDualAttestationLimit;
 
 impl Test for MultiAgentPaymentOverDualAttestationLimit {
@@ -31,6 +31,7 @@ fn main() -> Result<()> {
             &GetEventsWithProofs,
             &GetEventByVersionWithProofTest,
             &GetResourcesTest,
+            &GetDecodedResourcesTest,
             &MultiAgentPaymentOverDualAttestationLimit,
             &GetAccumulatorConsistencyProof,
             &NoUnknownEvents,
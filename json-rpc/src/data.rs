@@ -6,17 +6,18 @@ use crate::{
     views::{
         AccountStateWithProofView, AccountTransactionsWithProofView, AccountView,
         AccumulatorConsistencyProofView, CurrencyInfoView, EventByVersionWithProofView, EventView,
-        EventWithProofView, MetadataView, StateProofView, TransactionListView, TransactionView,
-        TransactionsWithProofsView,
+        EventWithProofView, MetadataView, MoveValueDataView, MoveValueView, StateProofView,
+        TransactionListView, TransactionView, TransactionsWithProofsView,
     },
 };
 use anyhow::Result;
+use diem_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue, DiemValueAnnotator};
 use diem_types::{
     account_address::AccountAddress, account_config::diem_root_address,
-    account_state::AccountState, chain_id::ChainId, event::EventKey,
-    ledger_info::LedgerInfoWithSignatures,
+    account_state::AccountState, account_state_blob::AccountStateBlob, chain_id::ChainId,
+    event::EventKey, ledger_info::LedgerInfoWithSignatures,
 };
-use move_resource_viewer::{AnnotatedMoveStruct, MoveValueAnnotator};
+use move_core_types::language_storage::TypeTag;
 use std::{
     collections::BTreeMap,
     convert::{TryFrom, TryInto},
@@ -276,12 +277,14 @@ pub fn get_accumulator_consistency_proof(
 
 /// Returns the account state to the client, alongside a proof relative to the version and
 /// ledger_version specified by the client. If version or ledger_version are not specified,
-/// the latest known versions will be used.
+/// the latest known versions will be used. With `decode_resources`, the resources of the account
+/// are also returned decoded into typed Move values.
 pub fn get_account_state_with_proof(
     db: &dyn MoveDbReader,
     ledger_version: u64,
     account_address: AccountAddress,
     version: u64,
+    decode_resources: bool,
 ) -> Result<AccountStateWithProofView, JsonRpcError> {
     if version > ledger_version {
         return Err(JsonRpcError::invalid_request_with_msg(format!(
@@ -291,9 +294,17 @@ pub fn get_account_state_with_proof(
     }
     let account_state_with_proof =
         db.get_account_state_with_proof(account_address, version, ledger_version)?;
-    Ok(AccountStateWithProofView::try_from(
-        account_state_with_proof,
-    )?)
+    let resources = if decode_resources {
+        match &account_state_with_proof.blob {
+            Some(blob) => Some(decode_account_resources(db, blob)?),
+            None => Some(vec![]),
+        }
+    } else {
+        None
+    };
+    let mut view = AccountStateWithProofView::try_from(account_state_with_proof)?;
+    view.resources = resources;
+    Ok(view)
 }
 
 /// Get all resources stored under `account_address` at `version`
@@ -309,7 +320,7 @@ pub fn get_resources(
     if let Some(account_state_blob) = account_state_with_proof.blob {
         let account_state = AccountState::try_from(&account_state_blob)
             .map_err(|e| JsonRpcError::internal_error(format!("{:?}", e)))?;
-        let annotator = MoveValueAnnotator::new(&db);
+        let annotator = DiemValueAnnotator::new(&db);
         for (typ, bytes) in account_state.get_resources() {
             let resource = annotator.view_resource(&typ, bytes)?;
             resources.insert(format!("{}", typ), resource);
@@ -317,3 +328,66 @@ pub fn get_resources(
     }
     Ok(resources)
 }
+
+/// Get all resources stored under `account_address` at `version`, decoded into typed Move values
+pub fn get_decoded_resources(
+    db: &dyn MoveDbReader,
+    ledger_version: u64,
+    account_address: AccountAddress,
+    version: u64,
+) -> Result<Vec<MoveValueView>, JsonRpcError> {
+    let account_state_with_proof =
+        db.get_account_state_with_proof(account_address, version, ledger_version)?;
+    match account_state_with_proof.blob {
+        Some(blob) => decode_account_resources(db, &blob),
+        None => Ok(vec![]),
+    }
+}
+
+/// Decodes the resources of an account with the ABIs of the modules loaded from `db`.
+fn decode_account_resources(
+    db: &dyn MoveDbReader,
+    blob: &AccountStateBlob,
+) -> Result<Vec<MoveValueView>, JsonRpcError> {
+    let account_state = AccountState::try_from(blob)
+        .map_err(|e| JsonRpcError::internal_error(format!("{:?}", e)))?;
+    let annotator = DiemValueAnnotator::new(&db);
+    account_state
+        .get_resources()
+        .map(|(typ, bytes)| Ok(move_struct_view(annotator.view_resource(&typ, bytes)?)))
+        .collect()
+}
+
+fn move_struct_view(value: AnnotatedMoveStruct) -> MoveValueView {
+    let fields = value
+        .value
+        .into_iter()
+        .map(|(name, field)| (name.to_string(), move_value_view(field)))
+        .collect();
+    MoveValueView::new(
+        &TypeTag::Struct(value.type_),
+        MoveValueDataView::Struct(fields),
+    )
+}
+
+fn move_value_view(value: AnnotatedMoveValue) -> MoveValueView {
+    let (type_tag, data) = match value {
+        AnnotatedMoveValue::U8(v) => (TypeTag::U8, MoveValueDataView::Number(v.into())),
+        AnnotatedMoveValue::U64(v) => (TypeTag::U64, MoveValueDataView::Number(v)),
+        AnnotatedMoveValue::U128(v) => (TypeTag::U128, MoveValueDataView::String(v.to_string())),
+        AnnotatedMoveValue::Bool(v) => (TypeTag::Bool, MoveValueDataView::Bool(v)),
+        AnnotatedMoveValue::Address(v) => {
+            (TypeTag::Address, MoveValueDataView::String(hex::encode(v)))
+        }
+        AnnotatedMoveValue::Bytes(v) => (
+            TypeTag::Vector(Box::new(TypeTag::U8)),
+            MoveValueDataView::String(hex::encode(v)),
+        ),
+        AnnotatedMoveValue::Vector(element_type, values) => (
+            TypeTag::Vector(Box::new(element_type)),
+            MoveValueDataView::Vector(values.into_iter().map(move_value_view).collect()),
+        ),
+        AnnotatedMoveValue::Struct(v) => return move_struct_view(v),
+    };
+    MoveValueView::new(&type_tag, data)
+}
@@ -154,7 +154,7 @@ pub fn request_fuzzer(json_request: serde_json::Value) {
         .unwrap();
 
     rt.spawn(async move {
-        if let Some(MempoolClientRequest::SubmitTransaction(_, cb)) = mp_events.next().await {
+        if let Some(MempoolClientRequest::SubmitTransaction(_, _, cb)) = mp_events.next().await {
             cb.send(Ok((
                 diem_types::mempool_status::MempoolStatus::new(
                     diem_types::mempool_status::MempoolStatusCode::Accepted,
@@ -8,8 +8,8 @@ use crate::{
     views::{
         AccountStateWithProofView, AccountTransactionsWithProofView, AccountView,
         AccumulatorConsistencyProofView, CurrencyInfoView, EventByVersionWithProofView, EventView,
-        EventWithProofView, MetadataView, StateProofView, TransactionListView, TransactionView,
-        TransactionsWithProofsView,
+        EventWithProofView, MetadataView, MoveValueView, StateProofView, TransactionListView,
+        TransactionView, TransactionsWithProofsView,
     },
 };
 use anyhow::Result;
@@ -22,14 +22,14 @@ use diem_json_rpc_types::request::{
     GetResourcesParams, GetStateProofParams, GetTransactionsParams,
     GetTransactionsWithProofsParams, MethodRequest, SubmitParams,
 };
-use diem_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use diem_mempool::{ClientSource, MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use diem_resource_viewer::AnnotatedMoveStruct;
 use diem_types::{
     chain_id::ChainId, ledger_info::LedgerInfoWithSignatures, mempool_status::MempoolStatusCode,
     transaction::SignedTransaction,
 };
 use fail::fail_point;
 use futures::{channel::oneshot, SinkExt};
-use move_resource_viewer::AnnotatedMoveStruct;
 use serde_json::Value;
 use std::{borrow::Borrow, collections::BTreeMap, sync::Arc};
 use storage_interface::MoveDbReader;
@@ -42,6 +42,7 @@ pub(crate) struct JsonRpcService {
     chain_id: ChainId,
     batch_size_limit: u16,
     page_size_limit: u16,
+    client_source: ClientSource,
 }
 
 impl JsonRpcService {
@@ -60,9 +61,16 @@ impl JsonRpcService {
             chain_id,
             batch_size_limit,
             page_size_limit,
+            client_source: ClientSource::InProcess,
         }
     }
 
+    /// Sets where the requests handled by this service come from, for mempool admission.
+    pub fn with_client_source(mut self, client_source: ClientSource) -> Self {
+        self.client_source = client_source;
+        self
+    }
+
     pub async fn mempool_request(
         &self,
         transaction: SignedTransaction,
@@ -73,6 +81,7 @@ impl JsonRpcService {
             .clone()
             .send(MempoolClientRequest::SubmitTransaction(
                 transaction,
+                self.client_source.clone(),
                 req_sender,
             ))
             .await?;
@@ -173,7 +182,11 @@ impl<'a> Handler<'a> {
                 serde_json::to_value(self.get_network_status(params).await?)?
             }
             MethodRequest::GetResources(params) => {
-                serde_json::to_value(self.get_resources(params).await?)?
+                if params.decode_resources {
+                    serde_json::to_value(self.get_decoded_resources(params).await?)?
+                } else {
+                    serde_json::to_value(self.get_resources(params).await?)?
+                }
             }
             MethodRequest::GetStateProof(params) => {
                 serde_json::to_value(self.get_state_proof(params).await?)?
@@ -409,6 +422,20 @@ impl<'a> Handler<'a> {
         )
     }
 
+    /// Returns all resources in the account specified by `params`, decoded into typed Move values
+    async fn get_decoded_resources(
+        &self,
+        params: GetResourcesParams,
+    ) -> Result<Vec<MoveValueView>, JsonRpcError> {
+        let version = self.version_param(params.version, "version")?;
+        data::get_decoded_resources(
+            self.service.db.borrow(),
+            self.version(),
+            params.account,
+            version,
+        )
+    }
+
     /// Returns proof of new state relative to version known to client
     async fn get_state_proof(
         &self,
@@ -446,6 +473,7 @@ impl<'a> Handler<'a> {
             ledger_version,
             params.account,
             version,
+            params.decode_resources,
         )
     }
 }
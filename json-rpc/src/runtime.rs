@@ -12,7 +12,7 @@ use anyhow::{ensure, Result};
 use diem_config::config::{JsonRpcConfig, NodeConfig, RoleType};
 use diem_json_rpc_types::Method;
 use diem_logger::{debug, Schema};
-use diem_mempool::MempoolClientSender;
+use diem_mempool::{ClientSource, MempoolClientSender};
 use diem_types::{chain_id::ChainId, ledger_info::LedgerInfoWithSignatures};
 use futures::future::{join_all, Either};
 use rand::{rngs::OsRng, RngCore};
@@ -169,6 +169,7 @@ pub fn jsonrpc_routes(
         config.page_size_limit,
     );
 
+    let api_keys = config.api_keys.clone();
     let base_route = warp::any()
         .and(warp::post())
         .and(warp::header::exact("content-type", "application/json"))
@@ -176,7 +177,17 @@ pub fn jsonrpc_routes(
             config.content_length_limit as u64,
         ))
         .and(warp::body::json())
-        .and(warp::any().map(move || service.clone()))
+        .and(
+            warp::header::optional::<String>(ClientSource::API_KEY_HEADER)
+                .and(warp::addr::remote())
+                .map(move |api_key, remote_addr| {
+                    service.clone().with_client_source(ClientSource::new(
+                        api_key,
+                        remote_addr,
+                        &api_keys,
+                    ))
+                }),
+        )
         .and(warp::filters::header::optional::<String>("user-agent"))
         .and_then(rpc_endpoint)
         .with(warp::log::custom(|info| {
@@ -197,12 +208,15 @@ pub fn jsonrpc_routes(
         // CORS is required for full node server to accept requests from different domain web pages.
         // It needs to be configured for the json-rpc request accepting method and headers.
         // Technically it's fine for any headers, but for simplicity we only set must have header
-        // content-type.
+        // content-type, and the optional API key header.
         .with(
             warp::cors()
                 .allow_any_origin()
                 .allow_methods(vec!["POST"])
-                .allow_headers(vec![header::CONTENT_TYPE]),
+                .allow_headers(vec![
+                    header::CONTENT_TYPE.as_str(),
+                    ClientSource::API_KEY_HEADER,
+                ]),
         );
 
     // For now we still allow user to use "/", but user should start to move to "/v1" soon
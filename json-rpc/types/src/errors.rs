@@ -51,6 +51,7 @@ pub enum ServerCode {
     MempoolInvalidUpdate = -32010,
     MempoolVmError = -32011,
     MempoolUnknownError = -32012,
+    MempoolRateLimited = -32013,
 }
 
 /// JSON RPC server error codes for invalid request
@@ -203,6 +204,7 @@ impl JsonRpcError {
             MempoolStatusCode::InvalidUpdate => ServerCode::MempoolInvalidUpdate,
             MempoolStatusCode::VmError => ServerCode::MempoolVmError,
             MempoolStatusCode::UnknownStatus => ServerCode::MempoolUnknownError,
+            MempoolStatusCode::RateLimited => ServerCode::MempoolRateLimited,
             MempoolStatusCode::Accepted => {
                 return Err(anyhow::format_err!(
                     "[JSON RPC] cannot create mempool error for mempool accepted status"
@@ -316,6 +318,10 @@ mod tests {
             MempoolStatusCode::UnknownStatus,
             ServerCode::MempoolUnknownError,
         );
+        assert_map_code(
+            MempoolStatusCode::RateLimited,
+            ServerCode::MempoolRateLimited,
+        );
     }
 
     #[test]
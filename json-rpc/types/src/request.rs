@@ -328,6 +328,8 @@ pub struct GetResourcesParams {
     pub account: AccountAddress,
     #[serde(default)]
     pub version: Option<u64>,
+    #[serde(default)]
+    pub decode_resources: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -350,6 +352,8 @@ pub struct GetAccountStateWithProofParams {
     pub version: Option<u64>,
     #[serde(default)]
     pub ledger_version: Option<u64>,
+    #[serde(default)]
+    pub decode_resources: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -837,13 +841,18 @@ mod test {
         let account = "1668f6be25668c1a17cd8caf6b8d2f25";
 
         // Array with all params
-        parse_ok(json!([account, 11, 12]));
+        parse_ok(json!([account, 11, 12, true]));
 
         // Array without optional params
         parse_ok(json!([account]));
         parse_ok(json!([account, 12]));
+        parse_ok(json!([account, 11, 12]));
+        assert!(parse_ok(json!([account, null, null, true])).decode_resources);
 
         // Array with too many params
+        parse_err(json!([account, 11, 12, true, "foo"]));
+
+        // Array with wrong optional param
         parse_err(json!([account, 11, 12, "foo"]));
 
         // Array with wrong param
@@ -1465,6 +1465,9 @@ pub struct AccountStateWithProofView {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub blob: Option<BytesView>,
     pub proof: AccountStateProofView,
+    /// The resources of the account, only present if they are requested decoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Vec<MoveValueView>>,
 }
 
 impl TryFrom<AccountStateWithProof> for AccountStateWithProofView {
@@ -1482,6 +1485,7 @@ impl TryFrom<AccountStateWithProof> for AccountStateWithProofView {
             version: account_state_with_proof.version,
             blob,
             proof: AccountStateProofView::try_from(account_state_with_proof.proof)?,
+            resources: None,
         })
     }
 }
@@ -1559,11 +1563,45 @@ impl TryFrom<&AccountStateProofView> for AccountStateProof {
     }
 }
 
+/// A Move value decoded with the ABIs of the modules it's defined in.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MoveValueView {
+    /// The Move type of the value with its type arguments, e.g. `u64`, `vector<address>` or
+    /// `0x1::DiemAccount::Balance<0x1::XUS::XUS>`.
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub value: MoveValueDataView,
+}
+
+impl MoveValueView {
+    pub fn new(type_tag: &TypeTag, value: MoveValueDataView) -> Self {
+        Self {
+            type_: type_tag.to_string(),
+            value,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum MoveValueDataView {
+    Bool(bool),
+    /// A `u8` or `u64`.
+    Number(u64),
+    /// A `u128` in decimal, or an `address` or `vector<u8>` hex-encoded.
+    String(String),
+    /// The elements of any other vector.
+    Vector(Vec<MoveValueView>),
+    /// The fields of a struct by name.
+    Struct(BTreeMap<String, MoveValueView>),
+}
+
 #[cfg(test)]
 mod tests {
     use crate::views::{
         AccountRoleView, AccountStateWithProofView, AmountView, EventDataView, MetadataView,
-        PreburnWithMetadataView, TransactionDataView, VMStatusView,
+        MoveValueDataView, MoveValueView, PreburnWithMetadataView, TransactionDataView,
+        VMStatusView,
     };
     use diem_types::{contract_event::ContractEvent, event::EventKey};
     use move_core_types::language_storage::TypeTag;
@@ -1607,6 +1645,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_serialize_deserialize_move_value_view() {
+        let json = json!({
+            "type": "0x1::M::S<u64>",
+            "value": {
+                "flag": {"type": "bool", "value": true},
+                "amount": {"type": "u64", "value": 10},
+                "total": {"type": "u128", "value": "340282366920938463463374607431768211455"},
+                "nested": {
+                    "type": "vector<vector<u64>>",
+                    "value": [{"type": "vector<u64>", "value": [{"type": "u64", "value": 1}]}]
+                },
+            }
+        });
+        let view: MoveValueView = serde_json::from_value(json.clone()).unwrap();
+        match &view.value {
+            MoveValueDataView::Struct(fields) => {
+                assert_eq!(fields["flag"].value, MoveValueDataView::Bool(true));
+                assert_eq!(fields["amount"].value, MoveValueDataView::Number(10));
+                assert_eq!(
+                    fields["total"].value,
+                    MoveValueDataView::String(u128::MAX.to_string())
+                );
+                assert!(matches!(
+                    fields["nested"].value,
+                    MoveValueDataView::Vector(_)
+                ));
+            }
+            _ => panic!("expect struct value"),
+        }
+        assert_eq!(serde_json::to_value(&view).unwrap(), json);
+    }
+
     #[test]
     fn account_role_view_unknown() {
         let json = json!({
@@ -6,12 +6,13 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
-        transaction::{MempoolTransaction, TimelineState},
+        transaction::{inferred_write_keys, MempoolTransaction, TimelineState},
         transaction_store::TransactionStore,
         ttl_cache::TtlCache,
     },
     counters,
     logging::{LogEntry, LogSchema, TxnsLog},
+    mempooldb::MempoolDbOp,
 };
 use diem_config::config::NodeConfig;
 use diem_crypto::HashValue;
@@ -24,7 +25,7 @@ use diem_types::{
 };
 use std::{
     cmp::max,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     time::{Duration, SystemTime},
 };
 
@@ -38,6 +39,9 @@ pub struct Mempool {
     // takes to pick it up by consensus.
     pub(crate) metrics_cache: TtlCache<(AccountAddress, u64), SystemTime>,
     pub system_transaction_timeout: Duration,
+    // Max number of txns in a block touching the same account, see `HotKeyTracker`.
+    max_txns_per_hot_key_in_block: Option<usize>,
+    max_conflict_deferral: Duration,
 }
 
 impl Mempool {
@@ -49,6 +53,8 @@ impl Mempool {
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
+            max_txns_per_hot_key_in_block: config.mempool.max_txns_per_hot_key_in_block,
+            max_conflict_deferral: Duration::from_millis(config.mempool.max_conflict_deferral_ms),
         }
     }
 
@@ -183,6 +189,12 @@ impl Mempool {
     /// `batch_size` - size of requested block.
     /// `seen_txns` - transactions that were sent to Consensus but were not committed yet,
     ///  mempool should filter out such transactions.
+    ///
+    /// If `max_txns_per_hot_key_in_block` is configured, transactions that would push the number
+    /// of transactions touching any single account in the block over the cap are deferred. They
+    /// are still used to fill the block if there aren't enough other transactions, and a transaction
+    /// that has been waiting in mempool for longer than `max_conflict_deferral_ms` is never
+    /// deferred.
     #[allow(clippy::explicit_counter_loop)]
     pub(crate) fn get_block(
         &self,
@@ -197,6 +209,13 @@ impl Mempool {
         // but can't be executed before first txn. Once observed, such txn will be saved in
         // `skipped` DS and rechecked once it's ancestor becomes available
         let mut skipped = HashSet::new();
+        // Ready txns held back because of hot keys, in the order they were observed.
+        let mut deferred = vec![];
+        let mut hot_keys = HotKeyTracker::new(
+            self.max_txns_per_hot_key_in_block,
+            self.max_conflict_deferral,
+            self.system_transaction_timeout,
+        );
         let seen_size = seen.len();
         let mut txn_walked = 0usize;
         // iterate over the queue of transactions based on gas price
@@ -217,6 +236,10 @@ impl Mempool {
                 || matches!(account_seqtype, AccountSequenceInfo::CRSN { .. })
             {
                 let ptr = TxnPointer::from(txn);
+                if !self.admit(&mut hot_keys, ptr, false) {
+                    deferred.push(ptr);
+                    continue;
+                }
                 seen.insert(ptr);
                 result.push(ptr);
                 if (result.len() as u64) == batch_size {
@@ -227,6 +250,10 @@ impl Mempool {
                 // that were skipped before for given account
                 let mut skipped_txn = (txn.address, tx_seq + 1);
                 while skipped.contains(&skipped_txn) {
+                    if !self.admit(&mut hot_keys, skipped_txn, false) {
+                        deferred.push(skipped_txn);
+                        break;
+                    }
                     seen.insert(skipped_txn);
                     result.push(skipped_txn);
                     if (result.len() as u64) == batch_size {
@@ -238,7 +265,32 @@ impl Mempool {
                 skipped.insert(TxnPointer::from(txn));
             }
         }
+        // fill the rest of the block with deferred txns, and whatever they unblock, rather than
+        // leaving it underfull
+        'deferred: for &ptr in &deferred {
+            if (result.len() as u64) == batch_size {
+                break;
+            }
+            if seen.contains(&ptr) {
+                continue;
+            }
+            let mut next = ptr;
+            loop {
+                self.admit(&mut hot_keys, next, true);
+                seen.insert(next);
+                result.push(next);
+                if (result.len() as u64) == batch_size {
+                    break 'deferred;
+                }
+                next = (next.0, next.1 + 1);
+                if !skipped.contains(&next) {
+                    break;
+                }
+            }
+        }
         let result_size = result.len();
+        let left_out = deferred.iter().filter(|ptr| !seen.contains(ptr)).count();
+        hot_keys.observe_block(result_size, left_out);
         // convert transaction pointers to real values
         let mut block_log = TxnsLog::new();
         let block: Vec<_> = result
@@ -255,7 +307,9 @@ impl Mempool {
             walked = txn_walked,
             seen_after = seen.len(),
             result_size = result_size,
-            block_size = block.len()
+            block_size = block.len(),
+            deferred = deferred.len(),
+            deferred_left_out = left_out
         );
         for transaction in &block {
             self.log_latency(
@@ -267,6 +321,16 @@ impl Mempool {
         block
     }
 
+    /// Checks the txn against the hot key cap and records its write keys if it goes into the
+    /// block. `force` bypasses the cap.
+    fn admit(&self, hot_keys: &mut HotKeyTracker, ptr: TxnPointer, force: bool) -> bool {
+        match self.transactions.get_mempool_txn_ref(&ptr.0, ptr.1) {
+            Some(txn) => hot_keys.admit(txn, force),
+            // the pointer is dropped when converting the block anyway
+            None => true,
+        }
+    }
+
     /// Periodic core mempool garbage collection.
     /// Removes all expired transactions and clears expired entries in metrics
     /// cache and sequence number cache.
@@ -298,6 +362,27 @@ impl Mempool {
         self.transactions.timeline_range(start_id, end_id)
     }
 
+    /// Deletes a transaction reloaded from MempoolDB from it, if it was not re-admitted.
+    pub(crate) fn forget_persisted(&mut self, sender: &AccountAddress, sequence_number: u64) {
+        self.transactions.forget_persisted(sender, sequence_number)
+    }
+
+    /// Promotes a transaction reloaded from MempoolDB to the timeline if it is ready.
+    pub(crate) fn process_reloaded_transaction(
+        &mut self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) {
+        self.transactions
+            .process_reloaded_transaction(sender, sequence_number)
+    }
+
+    /// Takes the writes to MempoolDB recorded since the last call, empty if persistence is
+    /// disabled.
+    pub(crate) fn take_persistence_ops(&mut self) -> Vec<MempoolDbOp> {
+        self.transactions.take_persistence_ops()
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
@@ -307,3 +392,67 @@ impl Mempool {
         self.transactions.get_parking_lot_size()
     }
 }
+
+/// Tracks how many txns in the block being built touch each account, and decides which txns
+/// to defer when a cap is configured.
+struct HotKeyTracker {
+    cap: Option<usize>,
+    max_deferral: Duration,
+    system_transaction_timeout: Duration,
+    now: Duration,
+    touches: HashMap<AccountAddress, usize>,
+}
+
+impl HotKeyTracker {
+    fn new(
+        cap: Option<usize>,
+        max_deferral: Duration,
+        system_transaction_timeout: Duration,
+    ) -> Self {
+        Self {
+            cap,
+            max_deferral,
+            system_transaction_timeout,
+            now: diem_infallible::duration_since_epoch(),
+            touches: HashMap::new(),
+        }
+    }
+
+    fn admit(&mut self, txn: &MempoolTransaction, force: bool) -> bool {
+        let keys = inferred_write_keys(&txn.txn);
+        if let (Some(cap), false) = (self.cap, force) {
+            let over_cap = keys
+                .iter()
+                .any(|key| self.touches.get(key).copied().unwrap_or(0) >= cap);
+            if over_cap && !self.waited_too_long(txn) {
+                return false;
+            }
+        }
+        for key in keys {
+            *self.touches.entry(key).or_insert(0) += 1;
+        }
+        true
+    }
+
+    fn waited_too_long(&self, txn: &MempoolTransaction) -> bool {
+        // the system TTL is set when the txn enters mempool
+        let inserted_at = txn
+            .expiration_time
+            .checked_sub(self.system_transaction_timeout)
+            .unwrap_or_default();
+        self.now.saturating_sub(inserted_at) >= self.max_deferral
+    }
+
+    fn observe_block(&self, block_size: usize, deferred: usize) {
+        if block_size == 0 {
+            return;
+        }
+        let max_touches = self.touches.values().copied().max().unwrap_or(0);
+        // share of the txns in the block that touch an account already touched by another one
+        let conflicting: usize = self.touches.values().map(|count| count - 1).sum();
+        counters::CORE_MEMPOOL_BLOCK_MAX_KEY_TOUCHES.observe(max_touches as f64);
+        counters::CORE_MEMPOOL_BLOCK_CONFLICT_DENSITY
+            .observe((conflicting as f64 / block_size as f64).min(1.0));
+        counters::CORE_MEMPOOL_CONFLICT_DEFERRED_TXNS.inc_by(deferred as u64);
+    }
+}
@@ -5,7 +5,7 @@ use diem_crypto::HashValue;
 use diem_types::{
     account_address::AccountAddress,
     account_config::AccountSequenceInfo,
-    transaction::{GovernanceRole, SignedTransaction},
+    transaction::{GovernanceRole, SignedTransaction, TransactionArgument, TransactionPayload},
 };
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
@@ -56,6 +56,32 @@ impl MempoolTransaction {
     }
 }
 
+/// Accounts the transaction is expected to write to, inferred without execution: the sender,
+/// plus the address arguments of a script, or the first argument of a script function if it
+/// is an address (e.g. the payee of a P2P transfer).
+pub(crate) fn inferred_write_keys(txn: &SignedTransaction) -> Vec<AccountAddress> {
+    let mut keys = vec![txn.sender()];
+    match txn.payload() {
+        TransactionPayload::Script(script) => {
+            keys.extend(script.args().iter().filter_map(|arg| match arg {
+                TransactionArgument::Address(address) => Some(*address),
+                _ => None,
+            }))
+        }
+        TransactionPayload::ScriptFunction(script_function) => keys.extend(
+            script_function
+                .args()
+                .first()
+                .filter(|arg| arg.len() == AccountAddress::LENGTH)
+                .and_then(|arg| bcs::from_bytes::<AccountAddress>(arg).ok()),
+        ),
+        TransactionPayload::WriteSet(_) | TransactionPayload::ModuleBundle(_) => (),
+    }
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Hash, Serialize)]
 pub enum TimelineState {
     // The transaction is ready for broadcast.
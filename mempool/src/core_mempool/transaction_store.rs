@@ -12,6 +12,7 @@ use crate::{
     },
     counters,
     logging::{LogEntry, LogEvent, LogSchema, TxnsLog},
+    mempooldb::MempoolDbOp,
 };
 use diem_config::config::MempoolConfig;
 use diem_crypto::HashValue;
@@ -52,6 +53,9 @@ pub struct TransactionStore {
     // one valid hash.
     hash_index: HashMap<HashValue, (AccountAddress, u64)>,
 
+    // Writes to MempoolDB not flushed yet, None if persistence is disabled.
+    persistence_ops: Option<Vec<MempoolDbOp>>,
+
     // configuration
     capacity: usize,
     capacity_per_user: usize,
@@ -72,6 +76,11 @@ impl TransactionStore {
             timeline_index: TimelineIndex::new(),
             parking_lot_index: ParkingLotIndex::new(),
             hash_index: HashMap::new(),
+            persistence_ops: if config.enable_persistence {
+                Some(vec![])
+            } else {
+                None
+            },
 
             // configuration
             capacity: config.capacity,
@@ -114,6 +123,17 @@ impl TransactionStore {
             .cloned()
     }
 
+    /// Fetch a reference to the mempool transaction by account address + sequence_number.
+    pub(crate) fn get_mempool_txn_ref(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<&MempoolTransaction> {
+        self.transactions
+            .get(address)
+            .and_then(|txns| txns.get(&sequence_number))
+    }
+
     /// Insert transaction into TransactionStore. Performs validation checks and updates indexes.
     pub(crate) fn insert(&mut self, txn: MempoolTransaction) -> MempoolStatus {
         let address = txn.get_sender();
@@ -188,6 +208,9 @@ impl TransactionStore {
                     sequence_number.transaction_sequence_number,
                 ),
             );
+            if let Some(ops) = &mut self.persistence_ops {
+                ops.push(MempoolDbOp::Put(txn.txn.clone()));
+            }
             txns.insert(sequence_number.transaction_sequence_number, txn);
             self.track_indices();
         }
@@ -410,9 +433,51 @@ impl TransactionStore {
         self.timeline_index.remove(txn);
         self.parking_lot_index.remove(txn);
         self.hash_index.remove(&txn.get_committed_hash());
+        if let Some(ops) = &mut self.persistence_ops {
+            ops.push(MempoolDbOp::Delete(
+                txn.get_sender(),
+                txn.sequence_info.transaction_sequence_number,
+            ));
+        }
         self.track_indices();
     }
 
+    /// Records the deletion of a transaction reloaded from MempoolDB that was not re-admitted,
+    /// unless another transaction with the same sequence number has been admitted since.
+    pub(crate) fn forget_persisted(&mut self, address: &AccountAddress, sequence_number: u64) {
+        if self.get(address, sequence_number).is_some() {
+            return;
+        }
+        if let Some(ops) = &mut self.persistence_ops {
+            ops.push(MempoolDbOp::Delete(*address, sequence_number));
+        }
+    }
+
+    /// Re-runs the readiness check of a transaction reloaded from MempoolDB, with the account
+    /// sequence number it was admitted with, so that it is promoted to the timeline and broadcast
+    /// if it is ready.
+    pub(crate) fn process_reloaded_transaction(
+        &mut self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) {
+        if let Some(crsn_or_seqno) = self
+            .transactions
+            .get(address)
+            .and_then(|txns| txns.get(&sequence_number))
+            .map(|txn| txn.sequence_info.account_sequence_number_type)
+        {
+            self.process_ready_transactions(address, crsn_or_seqno);
+        }
+    }
+
+    /// Takes the writes to MempoolDB recorded since the last call.
+    pub(crate) fn take_persistence_ops(&mut self) -> Vec<MempoolDbOp> {
+        self.persistence_ops
+            .as_mut()
+            .map_or_else(Vec::new, std::mem::take)
+    }
+
     /// Read `count` transactions from timeline since `timeline_id`.
     /// Returns block of transactions and new last_timeline_id.
     pub(crate) fn read_timeline(
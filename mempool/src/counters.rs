@@ -4,7 +4,7 @@
 use diem_config::network_id::{NetworkId, PeerNetworkId};
 use diem_metrics::{
     register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
-    register_int_gauge_vec, DurationHistogram, HistogramTimer, HistogramVec, IntCounter,
+    register_int_gauge_vec, DurationHistogram, Histogram, HistogramTimer, HistogramVec, IntCounter,
     IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
@@ -49,6 +49,10 @@ pub const VM_VALIDATION_LABEL: &str = "vm_validation";
 pub const CLIENT_LABEL: &str = "client";
 pub const SUCCESS_LABEL: &str = "success";
 
+// Admission rate limit source labels
+pub const PEER_LABEL: &str = "peer";
+pub const ACCOUNT_LABEL: &str = "account";
+
 // Bounded executor task labels
 pub const CLIENT_EVENT_LABEL: &str = "client_event";
 pub const CLIENT_EVENT_GET_TXN_LABEL: &str = "client_event_get_txn";
@@ -112,6 +116,36 @@ pub static CORE_MEMPOOL_TXN_COMMIT_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Histogram of the max number of txns in a block pulled by consensus touching the same account
+pub static CORE_MEMPOOL_BLOCK_MAX_KEY_TOUCHES: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "diem_core_mempool_block_max_key_touches",
+        "Max number of txns in a block pulled from core mempool touching the same account",
+        vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0, 512.0, 1024.0]
+    )
+    .unwrap()
+});
+
+/// Histogram of the share of txns in a block pulled by consensus touching an account that another
+/// txn in the block touches as well
+pub static CORE_MEMPOOL_BLOCK_CONFLICT_DENSITY: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "diem_core_mempool_block_conflict_density",
+        "Share of txns in a block pulled from core mempool conflicting with another txn in the block",
+        vec![0.0, 0.05, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0]
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of txns deferred to a later block because of hot accounts
+pub static CORE_MEMPOOL_CONFLICT_DEFERRED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_core_mempool_conflict_deferred_txns_count",
+        "Number of txns deferred to a later block because of hot accounts"
+    )
+    .unwrap()
+});
+
 /// Counter for number of periodic garbage-collection (=GC) events that happen, regardless of
 /// how many txns were actually cleaned up in this GC event
 pub static CORE_MEMPOOL_GC_EVENT_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
@@ -132,6 +166,51 @@ pub static CORE_MEMPOOL_GC_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Histogram of the time it takes to flush a batch of writes to MempoolDB
+pub static PERSISTENCE_FLUSH_LATENCY: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "diem_mempool_persistence_flush_latency",
+        "Time it takes to flush a batch of writes to MempoolDB"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of writes (puts and deletes) flushed to MempoolDB
+pub static PERSISTENCE_OPS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_mempool_persistence_ops_count",
+        "Number of writes flushed to MempoolDB"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of failed flushes to MempoolDB
+pub static PERSISTENCE_FLUSH_FAILURES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_mempool_persistence_flush_failures_count",
+        "Number of failed flushes to MempoolDB"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of txns rejected by the admission rate limits
+static RATE_LIMITED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_mempool_rate_limited_txns_count",
+        "Number of txns rejected by the admission rate limits, by source",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub fn rate_limited_txns_inc(source: &'static str, num_txns: usize) {
+    if num_txns > 0 {
+        RATE_LIMITED_TXNS
+            .with_label_values(&[source])
+            .inc_by(num_txns as u64);
+    }
+}
+
 /// Counter of pending network events to Mempool
 pub static PENDING_MEMPOOL_NETWORK_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -60,8 +60,8 @@ mod tests;
 pub use shared_mempool::{
     bootstrap, network,
     types::{
-        ConsensusRequest, ConsensusResponse, MempoolClientRequest, MempoolClientSender,
-        MempoolEventsReceiver, SubmissionStatus, TransactionSummary,
+        ClientSource, ConsensusRequest, ConsensusResponse, MempoolClientRequest,
+        MempoolClientSender, MempoolEventsReceiver, SubmissionStatus, TransactionSummary,
     },
 };
 #[cfg(any(test, feature = "fuzzing"))]
@@ -70,4 +70,5 @@ pub use tests::{fuzzing, mocks};
 mod core_mempool;
 mod counters;
 mod logging;
+mod mempooldb;
 mod shared_mempool;
@@ -0,0 +1,72 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional local persistence of the transactions accepted by mempool, so that they survive a
+//! restart. Core mempool records the writes in memory and `persistence_job` flushes them in
+//! batches, off the submission path.
+
+mod schema;
+
+use crate::mempooldb::schema::{transaction::TransactionSchema, TRANSACTION_CF_NAME};
+use anyhow::Result;
+use diem_logger::prelude::*;
+use diem_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use schemadb::{Options, ReadOptions, SchemaBatch, DB, DEFAULT_CF_NAME};
+use std::{path::Path, time::Instant};
+
+/// A write to `MempoolDB`, recorded by core mempool as transactions enter and leave it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum MempoolDbOp {
+    Put(SignedTransaction),
+    Delete(AccountAddress, u64),
+}
+
+pub(crate) struct MempoolDB {
+    db: DB,
+}
+
+impl MempoolDB {
+    pub(crate) fn new<P: AsRef<Path>>(db_root_path: P) -> Self {
+        let column_families = vec![/* UNUSED CF = */ DEFAULT_CF_NAME, TRANSACTION_CF_NAME];
+
+        let path = db_root_path.as_ref().join("mempooldb");
+        let instant = Instant::now();
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open(path.clone(), "mempool", column_families, &opts)
+            .expect("MempoolDB open failed; unable to continue");
+
+        info!(
+            "Opened MempoolDB at {:?} in {} ms",
+            path,
+            instant.elapsed().as_millis()
+        );
+
+        Self { db }
+    }
+
+    /// Applies the ops in a single batch. Later ops on the same transaction win.
+    pub(crate) fn write(&self, ops: Vec<MempoolDbOp>) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        for op in ops {
+            match op {
+                MempoolDbOp::Put(txn) => {
+                    batch.put::<TransactionSchema>(&(txn.sender(), txn.sequence_number()), &txn)?
+                }
+                MempoolDbOp::Delete(address, sequence_number) => {
+                    batch.delete::<TransactionSchema>(&(address, sequence_number))?
+                }
+            }
+        }
+        self.db.write_schemas(batch)
+    }
+
+    /// Returns all the persisted transactions, in (sender, sequence number) order. They stay in
+    /// the DB until they are re-admitted to mempool (and overwritten) or rejected (and deleted).
+    pub(crate) fn read_all(&self) -> Result<Vec<SignedTransaction>> {
+        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        iter.map(|res| res.map(|(_key, txn)| txn)).collect()
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for transactions accepted by mempool.
+//!
+//! Serialized signed transaction bytes identified by sender and sequence number.
+//! ```text
+//! |<-----------key------------>|<----value---->|
+//! | sender | sequence_number   |  signed txn   |
+//! ```
+
+use super::{ensure_slice_len_eq, TRANSACTION_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use diem_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use schemadb::schema::{KeyCodec, Schema, ValueCodec};
+use std::{convert::TryFrom, mem::size_of};
+
+pub struct TransactionSchema;
+
+impl Schema for TransactionSchema {
+    const COLUMN_FAMILY_NAME: schemadb::ColumnFamilyName = TRANSACTION_CF_NAME;
+    type Key = (AccountAddress, u64);
+    type Value = SignedTransaction;
+}
+
+impl KeyCodec<TransactionSchema> for (AccountAddress, u64) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (address, sequence_number) = self;
+        let mut encoded = address.to_vec();
+        encoded.extend_from_slice(&sequence_number.to_be_bytes());
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, AccountAddress::LENGTH + size_of::<u64>())?;
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let sequence_number = (&data[AccountAddress::LENGTH..]).read_u64::<BigEndian>()?;
+        Ok((address, sequence_number))
+    }
+}
+
+impl ValueCodec<TransactionSchema> for SignedTransaction {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
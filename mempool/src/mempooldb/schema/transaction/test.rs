@@ -0,0 +1,19 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        address in any::<AccountAddress>(),
+        sequence_number in any::<u64>(),
+        txn in any::<SignedTransaction>(),
+    ) {
+        assert_encode_decode::<TransactionSchema>(&(address, sequence_number), &txn);
+    }
+}
+
+test_no_panic_decoding!(TransactionSchema);
@@ -0,0 +1,101 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admission rate limiting of txns entering shared mempool, per client, per network peer and per
+//! sender account. Txns over a client or peer limit are rejected with
+//! `MempoolStatusCode::RateLimited` before they reach VM validation. The sender account limit is
+//! only charged for txns passing validation, as anyone can name any account as the sender.
+
+use crate::{counters, shared_mempool::types::ClientSource};
+use diem_config::{
+    config::{MempoolConfig, MempoolRateLimitConfig},
+    network_id::PeerNetworkId,
+};
+use diem_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use diem_types::account_address::AccountAddress;
+use std::{fmt::Debug, hash::Hash};
+
+pub(crate) struct AdmissionControl {
+    clients: TokenBucketRateLimiter<ClientSource>,
+    peers: TokenBucketRateLimiter<PeerNetworkId>,
+    accounts: TokenBucketRateLimiter<AccountAddress>,
+}
+
+impl AdmissionControl {
+    pub(crate) fn new(config: &MempoolConfig) -> Self {
+        Self {
+            clients: rate_limiter(counters::CLIENT_LABEL, config.client_rate_limit),
+            peers: rate_limiter(counters::PEER_LABEL, config.peer_rate_limit),
+            accounts: rate_limiter(counters::ACCOUNT_LABEL, config.account_rate_limit),
+        }
+    }
+
+    /// Whether a txn submitted by the client is admitted. In-process clients are not limited.
+    pub(crate) fn admit_client_txn(&self, source: &ClientSource) -> bool {
+        if *source == ClientSource::InProcess {
+            return true;
+        }
+        let admitted = self
+            .clients
+            .bucket(source.clone())
+            .lock()
+            .acquire_all_tokens(1)
+            .is_ok();
+        if !admitted {
+            counters::rate_limited_txns_inc(counters::CLIENT_LABEL, 1);
+        }
+        admitted
+    }
+
+    /// Returns how many of the `num_txns` txns broadcast by the peer are admitted.
+    pub(crate) fn admit_peer_txns(&self, peer: PeerNetworkId, num_txns: usize) -> usize {
+        let admitted = self
+            .peers
+            .bucket(peer)
+            .lock()
+            .acquire_tokens(num_txns)
+            .unwrap_or(0);
+        counters::rate_limited_txns_inc(counters::PEER_LABEL, num_txns - admitted);
+        admitted
+    }
+
+    /// Whether a txn sent by the account is admitted. Only call this for txns whose signature was
+    /// checked.
+    pub(crate) fn admit_account_txn(&self, sender: AccountAddress) -> bool {
+        let admitted = self
+            .accounts
+            .bucket(sender)
+            .lock()
+            .acquire_all_tokens(1)
+            .is_ok();
+        if !admitted {
+            counters::rate_limited_txns_inc(counters::ACCOUNT_LABEL, 1);
+        }
+        admitted
+    }
+
+    /// Drops the state of the sources that haven't submitted anything for a while, so that it
+    /// doesn't grow with the number of clients and accounts ever seen.
+    pub(crate) fn gc(&self) {
+        self.clients.garbage_collect_full_buckets();
+        self.peers.garbage_collect_full_buckets();
+        self.accounts.garbage_collect_full_buckets();
+    }
+}
+
+fn rate_limiter<Key: Eq + Hash + Clone + Debug>(
+    label: &'static str,
+    config: Option<MempoolRateLimitConfig>,
+) -> TokenBucketRateLimiter<Key> {
+    match config {
+        Some(config) => TokenBucketRateLimiter::new(
+            label,
+            "mempool".to_string(),
+            100,
+            config.bucket_size,
+            config.fill_rate,
+            None,
+        ),
+        None => TokenBucketRateLimiter::open(label),
+    }
+}
@@ -6,8 +6,10 @@ use crate::{
     core_mempool::{CoreMempool, TimelineState},
     counters,
     logging::{LogEntry, LogEvent, LogSchema},
+    mempooldb::MempoolDB,
     network::{MempoolNetworkEvents, MempoolSyncMsg},
     shared_mempool::{
+        admission::AdmissionControl,
         tasks,
         tasks::process_committed_transactions,
         types::{notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification},
@@ -105,7 +107,7 @@ async fn handle_client_request<V>(
     V: TransactionValidation,
 {
     match request {
-        MempoolClientRequest::SubmitTransaction(txn, callback) => {
+        MempoolClientRequest::SubmitTransaction(txn, source, callback) => {
             // This timer measures how long it took for the bounded executor to *schedule* the
             // task.
             let _timer = counters::task_spawn_latency_timer(
@@ -121,6 +123,7 @@ async fn handle_client_request<V>(
                 .spawn(tasks::process_client_transaction_submission(
                     smp.clone(),
                     txn,
+                    source,
                     callback,
                     task_start_timer,
                 ))
@@ -325,8 +328,13 @@ async fn handle_network_event<V>(
     }
 }
 
-/// Garbage collect all expired transactions by SystemTTL.
-pub(crate) async fn gc_coordinator(mempool: Arc<Mutex<CoreMempool>>, gc_interval_ms: u64) {
+/// Garbage collect all expired transactions by SystemTTL, and the admission rate limit state of
+/// idle sources.
+pub(crate) async fn gc_coordinator(
+    mempool: Arc<Mutex<CoreMempool>>,
+    admission: Arc<AdmissionControl>,
+    gc_interval_ms: u64,
+) {
     info!(LogSchema::event_log(LogEntry::GCRuntime, LogEvent::Start));
     let mut interval = IntervalStream::new(interval(Duration::from_millis(gc_interval_ms)));
     while let Some(_interval) = interval.next().await {
@@ -335,6 +343,7 @@ pub(crate) async fn gc_coordinator(mempool: Arc<Mutex<CoreMempool>>, gc_interval
             info!(LogSchema::event_log(LogEntry::GCRuntime, LogEvent::Live))
         );
         mempool.lock().gc();
+        admission.gc();
     }
 
     error!(LogSchema::event_log(
@@ -343,6 +352,40 @@ pub(crate) async fn gc_coordinator(mempool: Arc<Mutex<CoreMempool>>, gc_interval
     ));
 }
 
+/// Periodically flushes the writes to MempoolDB recorded by core mempool in a single batch, so
+/// that persistence stays off the submission path.
+/// A failed flush is only logged: the transactions left behind in MempoolDB are revalidated on
+/// startup anyway.
+pub(crate) async fn persistence_job(
+    mempool: Arc<Mutex<CoreMempool>>,
+    mempool_db: Arc<MempoolDB>,
+    flush_interval_ms: u64,
+) {
+    let mut interval = IntervalStream::new(interval(Duration::from_millis(flush_interval_ms)));
+    while let Some(_interval) = interval.next().await {
+        let ops = mempool.lock().take_persistence_ops();
+        if ops.is_empty() {
+            continue;
+        }
+        let num_ops = ops.len();
+        let db = mempool_db.clone();
+        let timer = counters::PERSISTENCE_FLUSH_LATENCY.start_timer();
+        let result = tokio::task::spawn_blocking(move || db.write(ops)).await;
+        timer.stop_and_record();
+        match result {
+            Ok(Ok(())) => counters::PERSISTENCE_OPS.inc_by(num_ops as u64),
+            Ok(Err(e)) => {
+                counters::PERSISTENCE_FLUSH_FAILURES.inc();
+                error!(LogSchema::new(LogEntry::DBError).error(&e));
+            }
+            Err(e) => {
+                counters::PERSISTENCE_FLUSH_FAILURES.inc();
+                error!(error = ?e, "MempoolDB flush task failed.");
+            }
+        }
+    }
+}
+
 /// Periodically logs a snapshot of transactions in core mempool.
 /// In the future we may want an interactive way to directly query mempool's internal state.
 /// For now, we will rely on this periodic snapshot to observe the internal state.
@@ -2,16 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::CoreMempool,
+    core_mempool::{CoreMempool, TimelineState},
+    mempooldb::MempoolDB,
     network::{MempoolNetworkEvents, MempoolNetworkSender},
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator, snapshot_job},
+        coordinator::{coordinator, gc_coordinator, persistence_job, snapshot_job},
+        tasks,
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
     ConsensusRequest,
 };
 use diem_config::{config::NodeConfig, network_id::NetworkId};
 use diem_infallible::{Mutex, RwLock};
+use diem_logger::prelude::*;
+use diem_types::mempool_status::MempoolStatusCode;
 
 use event_notifications::ReconfigNotificationListener;
 use futures::channel::mpsc::{self, Receiver, UnboundedSender};
@@ -27,6 +31,7 @@ use vm_validator::vm_validator::{TransactionValidation, VMValidator};
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers).
 ///   - inbound_network_task (task that handles inbound mempool messages and network events).
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL).
+///   - persistence_task (task that flushes accepted transactions to MempoolDB), if enabled.
 pub(crate) fn start_shared_mempool<V>(
     executor: &Handle,
     config: &NodeConfig,
@@ -63,6 +68,49 @@ pub(crate) fn start_shared_mempool<V>(
         peer_metadata_storage,
     );
 
+    if config.mempool.enable_persistence {
+        let mempool_db = Arc::new(MempoolDB::new(config.storage.dir()));
+        // Reload the transactions persisted before the restart. They go through validation
+        // again: the ones still valid are persisted again on insertion and promoted to the
+        // timeline if ready, the others are deleted.
+        // Until then they stay in MempoolDB, so a crash while reloading loses nothing.
+        match mempool_db.read_all() {
+            Ok(txns) if !txns.is_empty() => {
+                let smp = smp.clone();
+                executor.spawn_blocking(move || {
+                    let num_txns = txns.len();
+                    let statuses =
+                        tasks::process_incoming_transactions(&smp, txns, TimelineState::NotReady);
+                    let mut num_accepted = 0;
+                    let mut mempool = smp.mempool.lock();
+                    for (txn, (status, _)) in &statuses {
+                        if status.code == MempoolStatusCode::Accepted {
+                            num_accepted += 1;
+                            mempool
+                                .process_reloaded_transaction(&txn.sender(), txn.sequence_number());
+                        } else {
+                            mempool.forget_persisted(&txn.sender(), txn.sequence_number());
+                        }
+                    }
+                    drop(mempool);
+                    info!(
+                        num_txns = num_txns,
+                        num_accepted = num_accepted,
+                        "Reloaded transactions from MempoolDB."
+                    );
+                });
+            }
+            Ok(_) => (),
+            Err(e) => error!(error = ?e, "Failed to reload transactions from MempoolDB."),
+        }
+        executor.spawn(persistence_job(
+            mempool.clone(),
+            mempool_db,
+            config.mempool.persistence_flush_interval_ms,
+        ));
+    }
+
+    let admission = smp.admission.clone();
     executor.spawn(coordinator(
         smp,
         executor.clone(),
@@ -75,6 +123,7 @@ pub(crate) fn start_shared_mempool<V>(
 
     executor.spawn(gc_coordinator(
         mempool.clone(),
+        admission,
         config.mempool.system_transaction_gc_interval_ms,
     ));
 
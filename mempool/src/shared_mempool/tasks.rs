@@ -8,8 +8,8 @@ use crate::{
     logging::{LogEntry, LogEvent, LogSchema},
     network::{BroadcastError, MempoolSyncMsg},
     shared_mempool::types::{
-        notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification,
-        SubmissionStatusBundle, TransactionSummary,
+        notify_subscribers, ClientSource, ScheduledBroadcast, SharedMempool,
+        SharedMempoolNotification, SubmissionStatusBundle, TransactionSummary,
     },
     ConsensusRequest, ConsensusResponse, SubmissionStatus,
 };
@@ -98,6 +98,7 @@ pub(crate) async fn execute_broadcast<V>(
 pub(crate) async fn process_client_transaction_submission<V>(
     smp: SharedMempool<V>,
     transaction: SignedTransaction,
+    source: ClientSource,
     callback: oneshot::Sender<Result<SubmissionStatus>>,
     timer: HistogramTimer,
 ) where
@@ -105,7 +106,11 @@ pub(crate) async fn process_client_transaction_submission<V>(
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer_client();
-    let statuses = process_incoming_transactions(&smp, vec![transaction], TimelineState::NotReady);
+    let statuses = if smp.admission.admit_client_txn(&source) {
+        process_admitted_transactions(&smp, vec![transaction], TimelineState::NotReady)
+    } else {
+        vec![rate_limited(transaction)]
+    };
     log_txn_process_results(&statuses, None);
 
     if let Some(status) = statuses.get(0) {
@@ -144,7 +149,7 @@ pub(crate) async fn process_client_get_transaction<V>(
 /// Processes transactions from other nodes.
 pub(crate) async fn process_transaction_broadcast<V>(
     smp: SharedMempool<V>,
-    transactions: Vec<SignedTransaction>,
+    mut transactions: Vec<SignedTransaction>,
     request_id: Vec<u8>,
     timeline_state: TimelineState,
     peer: PeerNetworkId,
@@ -154,7 +159,10 @@ pub(crate) async fn process_transaction_broadcast<V>(
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer(peer.network_id());
-    let results = process_incoming_transactions(&smp, transactions, timeline_state);
+    let num_admitted = smp.admission.admit_peer_txns(peer, transactions.len());
+    let over_limit = transactions.split_off(num_admitted);
+    let mut results = process_admitted_transactions(&smp, transactions, timeline_state);
+    results.extend(over_limit.into_iter().map(rate_limited));
     log_txn_process_results(&results, Some(peer));
 
     let ack_response = gen_ack_response(request_id, results, &peer);
@@ -171,7 +179,8 @@ pub(crate) async fn process_transaction_broadcast<V>(
     notify_subscribers(SharedMempoolNotification::ACK, &smp.subscribers);
 }
 
-/// If `MempoolIsFull` on any of the transactions, provide backpressure to the downstream peer.
+/// If `MempoolIsFull` or `RateLimited` on any of the transactions, provide backpressure to the
+/// downstream peer.
 fn gen_ack_response(
     request_id: Vec<u8>,
     results: Vec<SubmissionStatusBundle>,
@@ -179,7 +188,10 @@ fn gen_ack_response(
 ) -> MempoolSyncMsg {
     let mut backoff_and_retry = false;
     for (_, (mempool_status, _)) in results.into_iter() {
-        if mempool_status.code == MempoolStatusCode::MempoolIsFull {
+        if matches!(
+            mempool_status.code,
+            MempoolStatusCode::MempoolIsFull | MempoolStatusCode::RateLimited
+        ) {
             backoff_and_retry = true;
             break;
         }
@@ -220,6 +232,30 @@ pub(crate) fn update_ack_counter(
     }
 }
 
+/// Submits the txns admitted from their client or peer to the local mempool. The sender account
+/// admission rate limit is only charged for txns passing signature and VM validation, otherwise
+/// anyone could use up the allowance of an account by sending forged txns in its name.
+pub(crate) fn process_admitted_transactions<V>(
+    smp: &SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+    timeline_state: TimelineState,
+) -> Vec<SubmissionStatusBundle>
+where
+    V: TransactionValidation,
+{
+    if transactions.is_empty() {
+        return vec![];
+    }
+    validate_and_add_transactions(smp, transactions, timeline_state, true)
+}
+
+fn rate_limited(transaction: SignedTransaction) -> SubmissionStatusBundle {
+    (
+        transaction,
+        (MempoolStatus::new(MempoolStatusCode::RateLimited), None),
+    )
+}
+
 /// Submits a list of SignedTransaction to the local mempool
 /// and returns a vector containing AdmissionControlStatus.
 pub(crate) fn process_incoming_transactions<V>(
@@ -227,6 +263,20 @@ pub(crate) fn process_incoming_transactions<V>(
     transactions: Vec<SignedTransaction>,
     timeline_state: TimelineState,
 ) -> Vec<SubmissionStatusBundle>
+where
+    V: TransactionValidation,
+{
+    validate_and_add_transactions(smp, transactions, timeline_state, false)
+}
+
+/// Validates the txns and adds the valid ones to the local mempool, within the sender account
+/// admission rate limit if `limit_accounts` is set.
+fn validate_and_add_transactions<V>(
+    smp: &SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+    timeline_state: TimelineState,
+    limit_accounts: bool,
+) -> Vec<SubmissionStatusBundle>
 where
     V: TransactionValidation,
 {
@@ -295,6 +345,11 @@ where
         for (idx, (transaction, crsn_or_seqno)) in transactions.into_iter().enumerate() {
             if let Ok(validation_result) = &validation_results[idx] {
                 match validation_result.status() {
+                    None if limit_accounts
+                        && !smp.admission.admit_account_txn(transaction.sender()) =>
+                    {
+                        statuses.push(rate_limited(transaction));
+                    }
                     None => {
                         let gas_amount = transaction.max_gas_amount();
                         let ranking_score = validation_result.score();
@@ -3,8 +3,9 @@
 
 //! Objects used by/related to shared mempool
 use crate::{
-    core_mempool::CoreMempool, network::MempoolNetworkInterface,
-    shared_mempool::network::MempoolNetworkSender,
+    core_mempool::CoreMempool,
+    network::MempoolNetworkInterface,
+    shared_mempool::{admission::AdmissionControl, network::MempoolNetworkSender},
 };
 use anyhow::Result;
 use diem_config::{
@@ -27,6 +28,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     fmt,
+    net::{IpAddr, SocketAddr},
     pin::Pin,
     sync::Arc,
     task::Waker,
@@ -48,6 +50,7 @@ where
     pub db: Arc<dyn DbReader>,
     pub validator: Arc<RwLock<V>>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
+    pub(crate) admission: Arc<AdmissionControl>,
 }
 
 impl<V: TransactionValidation + 'static> SharedMempool<V> {
@@ -67,6 +70,7 @@ impl<V: TransactionValidation + 'static> SharedMempool<V> {
             role,
             config.clone(),
         );
+        let admission = Arc::new(AdmissionControl::new(&config));
         SharedMempool {
             mempool,
             config,
@@ -74,6 +78,7 @@ impl<V: TransactionValidation + 'static> SharedMempool<V> {
             db,
             validator,
             subscribers,
+            admission,
         }
     }
 }
@@ -214,10 +219,46 @@ pub type SubmissionStatus = (MempoolStatus, Option<DiscardedVMStatus>);
 pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 
 pub enum MempoolClientRequest {
-    SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
+    SubmitTransaction(
+        SignedTransaction,
+        ClientSource,
+        oneshot::Sender<Result<SubmissionStatus>>,
+    ),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
 }
 
+/// Where a client submission comes from, the key of the client admission rate limit.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ClientSource {
+    /// Requests carrying one of the configured API keys are limited per key.
+    ApiKey(String),
+    /// Other requests are limited per remote IP address.
+    Ip(IpAddr),
+    /// Requests whose remote address is unknown share a single limit.
+    Anonymous,
+    /// In-process clients are not limited.
+    InProcess,
+}
+
+impl ClientSource {
+    /// HTTP header clients put their API key in.
+    pub const API_KEY_HEADER: &'static str = "x-api-key";
+
+    /// Identifies the client of an HTTP request. The API key is only trusted if it is one of the
+    /// `api_keys` configured, otherwise anyone could dodge the limit by sending random keys.
+    pub fn new(
+        api_key: Option<String>,
+        remote_addr: Option<SocketAddr>,
+        api_keys: &[String],
+    ) -> Self {
+        match (api_key, remote_addr) {
+            (Some(api_key), _) if api_keys.contains(&api_key) => ClientSource::ApiKey(api_key),
+            (_, Some(addr)) => ClientSource::Ip(addr.ip()),
+            (_, None) => ClientSource::Anonymous,
+        }
+    }
+}
+
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
 pub type MempoolEventsReceiver = mpsc::Receiver<MempoolClientRequest>;
 
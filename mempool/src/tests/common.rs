@@ -10,7 +10,7 @@ use diem_types::{
     account_config::{AccountSequenceInfo, XUS_NAME},
     chain_id::ChainId,
     mempool_status::MempoolStatusCode,
-    transaction::{GovernanceRole, RawTransaction, Script, SignedTransaction},
+    transaction::{GovernanceRole, RawTransaction, Script, SignedTransaction, TransactionArgument},
 };
 use once_cell::sync::Lazy;
 use rand::{rngs::StdRng, SeedableRng};
@@ -39,6 +39,7 @@ pub struct TestTransaction {
     pub(crate) gas_price: u64,
     pub(crate) governance_role: GovernanceRole,
     pub(crate) account_seqno_type: AccountSequenceInfo,
+    pub(crate) receiver: Option<usize>,
 }
 
 impl TestTransaction {
@@ -49,9 +50,15 @@ impl TestTransaction {
             gas_price,
             governance_role: GovernanceRole::NonGovernanceRole,
             account_seqno_type: AccountSequenceInfo::Sequential(0),
+            receiver: None,
         }
     }
 
+    pub(crate) fn receiver(mut self, receiver: usize) -> Self {
+        self.receiver = Some(receiver);
+        self
+    }
+
     pub(crate) fn crsn(mut self, min_nonce: u64) -> Self {
         // Default CRSN size to 128
         self.account_seqno_type = AccountSequenceInfo::CRSN {
@@ -87,7 +94,16 @@ impl TestTransaction {
         let raw_txn = RawTransaction::new_script(
             TestTransaction::get_address(self.address),
             self.sequence_number,
-            Script::new(vec![], vec![], vec![]),
+            Script::new(
+                vec![],
+                vec![],
+                self.receiver
+                    .map(|receiver| {
+                        TransactionArgument::Address(TestTransaction::get_address(receiver))
+                    })
+                    .into_iter()
+                    .collect(),
+            ),
             max_gas_amount,
             self.gas_price,
             XUS_NAME.to_owned(),
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TtlCache},
+    core_mempool::{CoreMempool, TimelineState, TtlCache, TxnPointer},
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
         TestTransaction,
@@ -662,6 +662,72 @@ fn test_clean_stuck_transactions() {
     assert_eq!(block[0].sequence_number(), 10);
 }
 
+fn setup_hot_key_mempool(max_conflict_deferral_ms: u64) -> (CoreMempool, Vec<SignedTransaction>) {
+    let mut config = NodeConfig::random();
+    config.mempool.max_txns_per_hot_key_in_block = Some(2);
+    config.mempool.max_conflict_deferral_ms = max_conflict_deferral_ms;
+    let mut pool = CoreMempool::new(&config);
+    // Three payments to account 3, plus unrelated txns from two of the payers.
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 10).receiver(3),
+            TestTransaction::new(1, 0, 9).receiver(3),
+            TestTransaction::new(2, 0, 8).receiver(3),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(1, 1, 1),
+        ],
+    );
+    (pool, txns)
+}
+
+fn txn_pointers(txns: &[SignedTransaction]) -> HashSet<TxnPointer> {
+    txns.iter()
+        .map(|txn| (txn.sender(), txn.sequence_number()))
+        .collect()
+}
+
+#[test]
+fn test_hot_key_cap_defers_txns() {
+    let (pool, txns) = setup_hot_key_mempool(60_000);
+
+    // The third payment to account 3 goes over the cap and is deferred.
+    let block = pool.get_block(4, HashSet::new());
+    assert_eq!(
+        txn_pointers(&block),
+        txn_pointers(&[
+            txns[0].clone(),
+            txns[1].clone(),
+            txns[3].clone(),
+            txns[4].clone()
+        ])
+    );
+
+    // It goes into the next block.
+    let block = pool.get_block(4, txn_pointers(&block));
+    assert_eq!(block, vec![txns[2].clone()]);
+}
+
+#[test]
+fn test_hot_key_cap_fills_underfull_block() {
+    let (pool, txns) = setup_hot_key_mempool(60_000);
+
+    // There aren't enough other txns, so the deferred payment still fills the block.
+    let block = pool.get_block(10, HashSet::new());
+    assert_eq!(block.len(), 5);
+    assert_eq!(txn_pointers(&block), txn_pointers(&txns));
+    assert_eq!(block.last(), Some(&txns[2]));
+}
+
+#[test]
+fn test_hot_key_cap_fairness_bound() {
+    // Every txn has been waiting long enough to never be deferred.
+    let (pool, txns) = setup_hot_key_mempool(0);
+
+    let block = pool.get_block(3, HashSet::new());
+    assert_eq!(block, txns[..3].to_vec());
+}
+
 #[test]
 fn test_ttl_cache() {
     let mut cache = TtlCache::new(2, Duration::from_secs(1));
@@ -0,0 +1,120 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    core_mempool::CoreMempool,
+    mempooldb::{MempoolDB, MempoolDbOp},
+    tests::common::{add_signed_txn, add_txn, add_txns_to_mempool, TestTransaction},
+};
+use diem_config::config::NodeConfig;
+use diem_temppath::TempPath;
+use std::time::Duration;
+
+#[test]
+fn test_write_and_read_all() {
+    let tmp_dir = TempPath::new();
+    let db = MempoolDB::new(&tmp_dir);
+    let txns: Vec<_> = (0..3)
+        .map(|seq| TestTransaction::new(0, seq, 1).make_signed_transaction())
+        .collect();
+
+    let mut ops: Vec<_> = txns.iter().cloned().map(MempoolDbOp::Put).collect();
+    ops.push(MempoolDbOp::Delete(TestTransaction::get_address(0), 1));
+    db.write(ops).unwrap();
+
+    assert_eq!(
+        db.read_all().unwrap(),
+        vec![txns[0].clone(), txns[2].clone()]
+    );
+    // Reading doesn't drop the transactions from the DB.
+    assert_eq!(db.read_all().unwrap().len(), 2);
+}
+
+#[test]
+fn test_persistence_ops() {
+    let mut config = NodeConfig::random();
+    config.mempool.enable_persistence = true;
+    let mut pool = CoreMempool::new(&config);
+
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(1, 0, 1),
+        ],
+    );
+    assert_eq!(
+        pool.take_persistence_ops(),
+        txns.iter()
+            .cloned()
+            .map(MempoolDbOp::Put)
+            .collect::<Vec<_>>()
+    );
+    assert!(pool.take_persistence_ops().is_empty());
+
+    // Committed transactions are dropped.
+    pool.remove_transaction(&TestTransaction::get_address(0), 0, false);
+    assert_eq!(
+        pool.take_persistence_ops(),
+        vec![MempoolDbOp::Delete(TestTransaction::get_address(0), 0)]
+    );
+
+    // Expired transactions are dropped.
+    let txn = TestTransaction::new(1, 1, 1).make_signed_transaction_with_expiration_time(0);
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+    pool.gc_by_expiration_time(Duration::from_secs(1));
+    assert_eq!(
+        pool.take_persistence_ops(),
+        vec![
+            MempoolDbOp::Put(txn),
+            MempoolDbOp::Delete(TestTransaction::get_address(1), 1)
+        ]
+    );
+}
+
+#[test]
+fn test_forget_persisted() {
+    let mut config = NodeConfig::random();
+    config.mempool.enable_persistence = true;
+    let mut pool = CoreMempool::new(&config);
+
+    // A reloaded transaction that was not re-admitted is dropped.
+    pool.forget_persisted(&TestTransaction::get_address(0), 0);
+    assert_eq!(
+        pool.take_persistence_ops(),
+        vec![MempoolDbOp::Delete(TestTransaction::get_address(0), 0)]
+    );
+
+    // Unless another transaction took its place.
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    pool.take_persistence_ops();
+    pool.forget_persisted(&TestTransaction::get_address(1), 0);
+    assert!(pool.take_persistence_ops().is_empty());
+}
+
+#[test]
+fn test_persistence_disabled() {
+    let mut pool = CoreMempool::new(&NodeConfig::random());
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert!(pool.take_persistence_ops().is_empty());
+}
+
+#[test]
+fn test_process_reloaded_transaction() {
+    let mut config = NodeConfig::random();
+    config.mempool.enable_persistence = true;
+    let mut pool = CoreMempool::new(&config);
+
+    // Reloaded out of order, the first transaction is parked until its predecessor is back.
+    add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(0, 1, 1), TestTransaction::new(0, 0, 1)],
+    );
+    for sequence_number in 0..3 {
+        pool.process_reloaded_transaction(&TestTransaction::get_address(0), sequence_number);
+    }
+    let (timeline, _) = pool.read_timeline(0, 10);
+    assert_eq!(timeline.len(), 2);
+    assert_eq!(pool.get_parking_lot_size(), 0);
+}
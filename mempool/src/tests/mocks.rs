@@ -108,10 +108,14 @@ impl MockSharedMempool {
         let (_network_notifs_tx, network_notifs_rx) = diem_channel::new(QueueStyle::FIFO, 8, None);
         let (_, conn_notifs_rx) = conn_notifs_channel::new();
         let network_sender = MempoolNetworkSender::new(
-            PeerManagerRequestSender::new(network_reqs_tx),
+            PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
             ConnectionRequestSender::new(connection_reqs_tx),
         );
-        let network_events = MempoolNetworkEvents::new(network_notifs_rx, conn_notifs_rx);
+        let network_events = MempoolNetworkEvents::new(
+            network_notifs_rx,
+            conn_notifs_rx,
+            network::constants::MAX_FRAME_SIZE,
+        );
         let (ac_client, client_events) = mpsc::channel(1_024);
         let (consensus_sender, consensus_events) = mpsc::channel(1_024);
         let (mempool_notifier, mempool_listener) =
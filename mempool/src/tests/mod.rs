@@ -8,6 +8,8 @@ mod core_mempool_test;
 #[cfg(test)]
 mod integration_tests;
 #[cfg(test)]
+mod mempooldb_test;
+#[cfg(test)]
 mod multi_node_test;
 #[cfg(test)]
 mod node;
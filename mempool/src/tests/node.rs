@@ -545,10 +545,14 @@ fn setup_node_network_interface(
         diem_channel::new(QueueStyle::FIFO, MAX_QUEUE_SIZE, None);
     let (network_conn_event_notifs_tx, conn_status_rx) = conn_notifs_channel::new();
     let network_sender = MempoolNetworkSender::new(
-        PeerManagerRequestSender::new(network_reqs_tx),
+        PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
         ConnectionRequestSender::new(connection_reqs_tx),
     );
-    let network_events = MempoolNetworkEvents::new(network_notifs_rx, conn_status_rx);
+    let network_events = MempoolNetworkEvents::new(
+        network_notifs_rx,
+        conn_status_rx,
+        network::constants::MAX_FRAME_SIZE,
+    );
 
     (
         NodeNetworkInterface {
@@ -2,15 +2,33 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    core_mempool::{CoreMempool, TimelineState},
     mocks::MockSharedMempool,
-    shared_mempool::types::TransactionSummary,
+    shared_mempool::{
+        admission::AdmissionControl,
+        tasks,
+        types::{ClientSource, SharedMempool, TransactionSummary},
+    },
     tests::common::{batch_add_signed_txn, TestTransaction},
     ConsensusRequest,
 };
-use diem_types::transaction::Transaction;
+use diem_config::{
+    config::{MempoolConfig, MempoolRateLimitConfig, NodeConfig},
+    network_id::{NetworkId, PeerNetworkId},
+};
+use diem_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use diem_infallible::{Mutex, RwLock};
+use diem_types::{
+    account_address::AccountAddress, mempool_status::MempoolStatusCode, transaction::Transaction,
+};
 use futures::{channel::oneshot, executor::block_on, sink::SinkExt};
 use mempool_notifications::MempoolNotificationSender;
+use network::application::storage::PeerMetadataStorage;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use storage_interface::mock::MockDbReaderWriter;
 use tokio::runtime::Builder;
+use vm_validator::mocks::mock_vm_validator::MockVMValidator;
 
 #[test]
 fn test_consensus_events_rejected_txns() {
@@ -93,3 +111,125 @@ fn test_mempool_notify_committed_txns() {
     assert_eq!(timeline.len(), 1);
     assert_eq!(timeline.get(0).unwrap(), &kept_txn);
 }
+
+#[test]
+fn test_admission_control() {
+    let limit = Some(MempoolRateLimitConfig {
+        bucket_size: 2,
+        fill_rate: 1,
+    });
+    let config = MempoolConfig {
+        client_rate_limit: limit,
+        peer_rate_limit: limit,
+        account_rate_limit: limit,
+        ..MempoolConfig::default()
+    };
+    let admission = AdmissionControl::new(&config);
+
+    // Each client gets its own bucket, and in-process submissions are never limited.
+    let client = ClientSource::ApiKey("key".to_string());
+    assert!(admission.admit_client_txn(&client));
+    assert!(admission.admit_client_txn(&client));
+    assert!(!admission.admit_client_txn(&client));
+    assert!(admission.admit_client_txn(&ClientSource::ApiKey("other".to_string())));
+    for _ in 0..10 {
+        assert!(admission.admit_client_txn(&ClientSource::InProcess));
+    }
+
+    // A peer broadcast is admitted partially.
+    let peer = PeerNetworkId::random();
+    assert_eq!(admission.admit_peer_txns(peer, 5), 2);
+    assert_eq!(admission.admit_peer_txns(peer, 5), 0);
+
+    let sender = AccountAddress::random();
+    assert!(admission.admit_account_txn(sender));
+    assert!(admission.admit_account_txn(sender));
+    assert!(!admission.admit_account_txn(sender));
+    assert!(admission.admit_account_txn(AccountAddress::random()));
+}
+
+#[test]
+fn test_forged_txns_dont_use_up_account_limit() {
+    let mut config = NodeConfig::default();
+    config.mempool.account_rate_limit = Some(MempoolRateLimitConfig {
+        bucket_size: 1,
+        fill_rate: 1,
+    });
+    let smp = SharedMempool::new(
+        Arc::new(Mutex::new(CoreMempool::new(&config))),
+        config.mempool.clone(),
+        HashMap::new(),
+        Arc::new(MockDbReaderWriter),
+        Arc::new(RwLock::new(MockVMValidator)),
+        vec![],
+        config.base.role,
+        PeerMetadataStorage::new(&[NetworkId::Validator]),
+    );
+
+    // Txns naming the account as their sender, but not signed by the key they carry.
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let forger_key = Ed25519PrivateKey::generate(&mut rng);
+    let other_key = Ed25519PrivateKey::generate(&mut rng);
+    let forged_txns: Vec<_> = (0..10)
+        .map(|seq_num| {
+            TestTransaction::new(0, seq_num, 1)
+                .make_signed_transaction()
+                .into_raw_transaction()
+                .sign(&forger_key, other_key.public_key())
+                .unwrap()
+                .into_inner()
+        })
+        .collect();
+    let statuses = tasks::process_admitted_transactions(&smp, forged_txns, TimelineState::NotReady);
+    assert!(statuses
+        .iter()
+        .all(|(_, (status, _))| status.code == MempoolStatusCode::VmError));
+
+    // The account's own txns are still admitted, up to its limit.
+    let txns = vec![
+        TestTransaction::new(0, 0, 1).make_signed_transaction(),
+        TestTransaction::new(0, 1, 1).make_signed_transaction(),
+    ];
+    let statuses = tasks::process_admitted_transactions(&smp, txns, TimelineState::NotReady);
+    assert_eq!(statuses[0].1 .0.code, MempoolStatusCode::Accepted);
+    assert_eq!(statuses[1].1 .0.code, MempoolStatusCode::RateLimited);
+}
+
+#[test]
+fn test_client_source() {
+    let api_keys = vec!["key".to_string()];
+    let addr: SocketAddr = "1.2.3.4:5678".parse().unwrap();
+
+    assert_eq!(
+        ClientSource::new(Some("key".to_string()), Some(addr), &api_keys),
+        ClientSource::ApiKey("key".to_string())
+    );
+    // Unknown API keys don't get their own limit.
+    assert_eq!(
+        ClientSource::new(Some("random".to_string()), Some(addr), &api_keys),
+        ClientSource::Ip(addr.ip())
+    );
+    assert_eq!(
+        ClientSource::new(None, Some(addr), &api_keys),
+        ClientSource::Ip(addr.ip())
+    );
+    assert_eq!(
+        ClientSource::new(Some("random".to_string()), None, &api_keys),
+        ClientSource::Anonymous
+    );
+}
+
+#[test]
+fn test_admission_control_disabled() {
+    let admission = AdmissionControl::new(&MempoolConfig::default());
+    let client = ClientSource::ApiKey("key".to_string());
+    let sender = AccountAddress::random();
+    for _ in 0..1000 {
+        assert!(admission.admit_client_txn(&client));
+        assert!(admission.admit_account_txn(sender));
+    }
+    assert_eq!(
+        admission.admit_peer_txns(PeerNetworkId::random(), 1000),
+        1000
+    );
+}
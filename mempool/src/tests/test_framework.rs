@@ -6,7 +6,7 @@ use crate::{
     network::{MempoolNetworkEvents, MempoolNetworkSender, MempoolSyncMsg},
     shared_mempool::start_shared_mempool,
     tests::common::TestTransaction,
-    ConsensusRequest, MempoolClientRequest, MempoolClientSender,
+    ClientSource, ConsensusRequest, MempoolClientRequest, MempoolClientSender,
 };
 use diem_config::{
     config::NodeConfig,
@@ -141,7 +141,11 @@ impl MempoolNode {
             let (sender, receiver) = oneshot::channel();
 
             self.mempool_client_sender
-                .send(MempoolClientRequest::SubmitTransaction(txn, sender))
+                .send(MempoolClientRequest::SubmitTransaction(
+                    txn,
+                    ClientSource::InProcess,
+                    sender,
+                ))
                 .await
                 .unwrap();
             let status = receiver.await.unwrap().unwrap();
@@ -272,12 +276,16 @@ impl MempoolNode {
 
         let response: MempoolSyncMsg = if let Some(res_rx) = maybe_receiver {
             let response = res_rx.await.unwrap().unwrap();
-            protocol_id.from_bytes(&response).unwrap()
+            protocol_id
+                .from_bytes(&response, network::constants::MAX_FRAME_SIZE)
+                .unwrap()
         } else {
             match self.get_outbound_handle(network_id).next().await.unwrap() {
                 PeerManagerRequest::SendDirectSend(peer_id, msg) => {
                     assert_eq!(peer_id, remote_peer_id);
-                    msg.protocol_id.from_bytes(&msg.mdata).unwrap()
+                    msg.protocol_id
+                        .from_bytes(&msg.mdata, network::constants::MAX_FRAME_SIZE)
+                        .unwrap()
                 }
                 _ => panic!("Should not be getting an RPC response"),
             }
@@ -0,0 +1,89 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares the plain and LZ4 compressed BCS encodings used by DiemNet
+//! protocols on payloads shaped like large state-sync chunks.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use diem_types::account_address::AccountAddress;
+use network::{constants::MAX_FRAME_SIZE, ProtocolId};
+use serde::{Deserialize, Serialize};
+
+const NUM_TXNS: &[usize] = &[100, 1_000, 10_000];
+
+/// A stand-in for a signed transaction: a handful of senders, increasing
+/// sequence numbers and a script payload that repeats across transactions.
+#[derive(Deserialize, Serialize)]
+struct BenchTxn {
+    sender: AccountAddress,
+    sequence_number: u64,
+    payload: Vec<u8>,
+    max_gas_amount: u64,
+    gas_unit_price: u64,
+    expiration_timestamp_secs: u64,
+}
+
+fn chunk(num_txns: usize) -> Vec<BenchTxn> {
+    let senders: Vec<_> = (0..16).map(|_| AccountAddress::random()).collect();
+    let payload: Vec<u8> = (0..256).map(|i| (i % 64) as u8).collect();
+    (0..num_txns)
+        .map(|i| BenchTxn {
+            sender: senders[i % senders.len()],
+            sequence_number: (i / senders.len()) as u64,
+            payload: payload.clone(),
+            max_gas_amount: 1_000_000,
+            gas_unit_price: 0,
+            expiration_timestamp_secs: 1_600_000_000 + i as u64,
+        })
+        .collect()
+}
+
+fn encoding_bench(c: &mut Criterion) {
+    let protocols = [
+        ProtocolId::StateSyncDirectSend,
+        ProtocolId::StateSyncDirectSendCompressed,
+    ];
+
+    let mut group = c.benchmark_group("encode");
+    for num_txns in NUM_TXNS {
+        let value = chunk(*num_txns);
+        for protocol in protocols {
+            let encoded_len = protocol.to_bytes(&value).unwrap().len();
+            group.throughput(Throughput::Bytes(encoded_len as u64));
+            group.bench_with_input(
+                BenchmarkId::new(protocol.as_str(), num_txns),
+                &value,
+                |b, value| b.iter(|| protocol.to_bytes(value).unwrap()),
+            );
+        }
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("decode");
+    for num_txns in NUM_TXNS {
+        let value = chunk(*num_txns);
+        for protocol in protocols {
+            let encoded = protocol.to_bytes(&value).unwrap();
+            group.throughput(Throughput::Bytes(encoded.len() as u64));
+            group.bench_with_input(
+                BenchmarkId::new(protocol.as_str(), num_txns),
+                &encoded,
+                |b, encoded| {
+                    b.iter(|| {
+                        protocol
+                            .from_bytes::<Vec<BenchTxn>>(encoded, MAX_FRAME_SIZE)
+                            .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    name = compression_benches;
+    config = Criterion::default();
+    targets = encoding_bench
+);
+criterion_main!(compression_benches);
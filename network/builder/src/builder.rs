@@ -11,10 +11,10 @@
 //! long as the latter is in its trusted peers set.
 use diem_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RateLimitConfig, RoleType,
-        CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS,
-        MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS,
-        MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
+        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, ProtocolRateLimitConfig,
+        RateLimitConfig, RoleType, CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS,
+        MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE,
+        MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -36,6 +36,7 @@ use network::{
         health_checker::{self, builder::HealthCheckerBuilder},
         network::{AppConfig, NewNetworkEvents, NewNetworkSender},
     },
+    ProtocolId,
 };
 use network_discovery::DiscoveryChangeListener;
 use std::{
@@ -88,6 +89,7 @@ impl NetworkBuilder {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        outbound_protocol_rate_limits: HashMap<ProtocolId, ProtocolRateLimitConfig>,
     ) -> Self {
         // A network cannot exist without a PeerManager
         // TODO:  construct this in create and pass it to new() as a parameter. The complication is manual construction of NetworkBuilder in various tests.
@@ -106,6 +108,7 @@ impl NetworkBuilder {
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
+            outbound_protocol_rate_limits,
         );
 
         NetworkBuilder {
@@ -148,6 +151,7 @@ impl NetworkBuilder {
             MAX_INBOUND_CONNECTIONS,
             None,
             None,
+            HashMap::new(),
         );
 
         builder.add_connectivity_manager(
@@ -202,6 +206,7 @@ impl NetworkBuilder {
             config.max_inbound_connections,
             config.inbound_rate_limit_config,
             config.outbound_rate_limit_config,
+            outbound_protocol_rate_limits(config),
         );
 
         network_builder.add_connection_monitoring(
@@ -453,10 +458,33 @@ impl NetworkBuilder {
     pub fn add_service<EventsT: NewNetworkEvents>(&mut self, config: &AppConfig) -> EventsT {
         let (peer_mgr_reqs_rx, connection_notifs_rx) =
             self.peer_manager_builder.add_service(config);
-        EventsT::new(peer_mgr_reqs_rx, connection_notifs_rx)
+        EventsT::new(
+            peer_mgr_reqs_rx,
+            connection_notifs_rx,
+            self.peer_manager_builder.max_frame_size(),
+        )
     }
 }
 
+/// Resolve the configured outbound protocol rate limits to their `ProtocolId`s
+fn outbound_protocol_rate_limits(
+    config: &NetworkConfig,
+) -> HashMap<ProtocolId, ProtocolRateLimitConfig> {
+    config
+        .verify_outbound_protocol_rate_limits()
+        .expect("Outbound protocol rate limits must be well formed");
+    config
+        .outbound_protocol_rate_limits
+        .iter()
+        .map(|(protocol, limit)| {
+            let protocol_id = protocol
+                .parse::<ProtocolId>()
+                .expect("Protocol names are verified with the config");
+            (protocol_id, *limit)
+        })
+        .collect()
+}
+
 /// Retrieve and merge seeds so that they have all keys associated
 fn merge_seeds(config: &NetworkConfig) -> PeerSet {
     config.verify_seeds().expect("Seeds must be well formed");
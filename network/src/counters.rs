@@ -303,6 +303,77 @@ pub fn direct_send_bytes(
     ])
 }
 
+pub static DIEM_NETWORK_PROTOCOL_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_protocol_bytes",
+        "Number of application bytes transferred per protocol and remote peer",
+        &[
+            "role_type",
+            "network_id",
+            "peer_id",
+            "remote_peer_id",
+            "protocol_id",
+            "state"
+        ]
+    )
+    .unwrap()
+});
+
+pub fn protocol_bytes(
+    network_context: &NetworkContext,
+    remote_peer_id: &PeerId,
+    protocol_id: ProtocolId,
+    state_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_PROTOCOL_BYTES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        remote_peer_id.short_str().as_str(),
+        protocol_id.as_str(),
+        state_label,
+    ])
+}
+
+pub static DIEM_NETWORK_THROTTLED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_throttled_messages",
+        "Number of outbound messages delayed by a per-protocol rate limit",
+        &["role_type", "network_id", "peer_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+pub fn throttled_messages(network_context: &NetworkContext, protocol_id: ProtocolId) -> IntCounter {
+    DIEM_NETWORK_THROTTLED_MESSAGES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_id.as_str(),
+    ])
+}
+
+pub static DIEM_NETWORK_DROPPED_OUTBOUND_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_dropped_outbound_messages",
+        "Number of outbound messages dropped because their priority's queue was full",
+        &["role_type", "network_id", "peer_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+pub fn dropped_outbound_messages(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+) -> IntCounter {
+    DIEM_NETWORK_DROPPED_OUTBOUND_MESSAGES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_id.as_str(),
+    ])
+}
+
 /// Counters(queued,dequeued,dropped) related to inbound network notifications for RPCs and
 /// DirectSends.
 pub static PENDING_NETWORK_NOTIFICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
@@ -17,6 +17,7 @@
 use crate::{
     counters::{self, RECEIVED_LABEL, SENT_LABEL},
     logging::NetworkSchema,
+    peer::outbound_queue::{message_len, OutboundQueue},
     peer_manager::{PeerManagerError, TransportNotification},
     protocols::{
         direct_send::Message,
@@ -40,17 +41,18 @@ use futures::{
     self,
     channel::oneshot,
     io::{AsyncRead, AsyncWrite},
-    stream::StreamExt,
+    stream::{FusedStream, StreamExt},
     FutureExt, SinkExt, TryFutureExt,
 };
 use serde::Serialize;
 use short_hex_str::AsShortHexStr;
-use std::{fmt, panic, time::Duration};
+use std::{collections::HashMap, fmt, panic, time::Duration};
 use tokio::runtime::Handle;
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
 };
 
+mod outbound_queue;
 #[cfg(test)]
 mod test;
 
@@ -66,6 +68,16 @@ pub enum PeerRequest {
     SendDirectSend(Message),
 }
 
+/// A message for the [`Peer`]'s writer task, tagged with the application protocol it
+/// belongs to so the writer can prioritize and throttle it.
+#[derive(Debug)]
+pub struct WriteRequest {
+    /// `None` for messages that don't belong to any protocol, like wire errors.
+    pub protocol_id: Option<ProtocolId>,
+    pub message: NetworkMessage,
+    pub ack_tx: oneshot::Sender<Result<(), PeerManagerError>>,
+}
+
 /// Notifications that [`Peer`] sends to the [`PeerManager`](crate::peer_manager::PeerManager).
 #[derive(Debug, PartialEq)]
 pub enum PeerNotification {
@@ -134,6 +146,8 @@ pub struct Peer<TSocket> {
     inbound_rate_limiter: Option<SharedBucket>,
     /// Optional outbound rate limiter
     outbound_rate_limiter: Option<SharedBucket>,
+    /// Outbound rate limiters for individual protocols
+    outbound_protocol_rate_limiters: HashMap<ProtocolId, SharedBucket>,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -154,6 +168,7 @@ where
         max_frame_size: usize,
         inbound_rate_limiter: Option<SharedBucket>,
         outbound_rate_limiter: Option<SharedBucket>,
+        outbound_protocol_rate_limiters: HashMap<ProtocolId, SharedBucket>,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
@@ -186,6 +201,7 @@ where
             max_frame_size,
             inbound_rate_limiter,
             outbound_rate_limiter,
+            outbound_protocol_rate_limiters,
         }
     }
 
@@ -229,6 +245,7 @@ where
             self.connection_metadata.clone(),
             self.network_context,
             writer,
+            self.outbound_protocol_rate_limiters.clone(),
         );
 
         // Start main Peer event loop.
@@ -299,53 +316,96 @@ where
     // 2. The second channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
+    //
+    // Queued messages are written in priority order and held back by their protocol's rate
+    // limiter, if it has one. Lower priority messages are dropped when their queue is full. See
+    // [`OutboundQueue`].
     fn start_writer_task(
         executor: &Handle,
         time_service: TimeService,
         connection_metadata: ConnectionMetadata,
         network_context: NetworkContext,
         mut writer: NetworkMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
-    ) -> (
-        channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
-        oneshot::Sender<()>,
-    ) {
+        outbound_protocol_rate_limiters: HashMap<ProtocolId, SharedBucket>,
+    ) -> (channel::Sender<WriteRequest>, oneshot::Sender<()>) {
         let remote_peer_id = connection_metadata.remote_peer_id;
-        let (write_reqs_tx, mut write_reqs_rx): (
-            channel::Sender<(
-                NetworkMessage,
-                oneshot::Sender<Result<(), PeerManagerError>>,
-            )>,
-            _,
-        ) = channel::new(1024, &counters::PENDING_WIRE_MESSAGES);
+        let (write_reqs_tx, mut write_reqs_rx): (channel::Sender<WriteRequest>, _) =
+            channel::new(1024, &counters::PENDING_WIRE_MESSAGES);
         let (close_tx, close_rx) = oneshot::channel();
         let writer_task = async move {
             let mut close_rx = close_rx.into_stream();
+            let mut outbound_queue =
+                OutboundQueue::new(network_context, outbound_protocol_rate_limiters);
             loop {
-                futures::select! {
-                    (message, ack_ch) = write_reqs_rx.select_next_some() => {
-                        if let Err(err) = writer
-                            .send(&message)
-                            .map_ok(|_| ack_ch.send(Ok(())))
-                            .await
-                        {
-                            warn!(
-                                NetworkSchema::new(&network_context)
-                                    .connection_metadata(&connection_metadata),
-                                error = %err,
-                                "{} Error in sending message to peer: {}, error: {}",
-                                network_context,
-                                remote_peer_id.short_str(),
-                                err
-                            );
-                            break;
+                if close_rx.next().now_or_never().is_some() {
+                    break;
+                }
+
+                // Pull in everything that's already waiting, so the next message
+                // written is the highest priority one.
+                while !outbound_queue.is_full() {
+                    match write_reqs_rx.next().now_or_never() {
+                        Some(Some(request)) => outbound_queue.push(request),
+                        _ => break,
+                    }
+                }
+
+                let request = match outbound_queue.pop() {
+                    Ok(request) => request,
+                    Err(ready_at) => {
+                        // Nothing can be written yet, so wait for a new message or
+                        // for a throttled protocol to have tokens again.
+                        let throttle = match ready_at {
+                            Some(ready_at) => {
+                                tokio::time::sleep_until(tokio::time::Instant::from_std(ready_at))
+                                    .boxed()
+                            }
+                            None => futures::future::pending().boxed(),
+                        };
+                        // Stop reading requests while the high priority queue is full,
+                        // which pushes back on the Peer.
+                        let next_request =
+                            if outbound_queue.is_full() || write_reqs_rx.is_terminated() {
+                                futures::future::pending().boxed()
+                            } else {
+                                write_reqs_rx.select_next_some().boxed()
+                            };
+                        futures::select! {
+                            request = next_request.fuse() => outbound_queue.push(request),
+                            _ = throttle.fuse() => (),
+                            _ = close_rx.select_next_some() => break,
                         }
-                    },
-                    _ = close_rx.select_next_some() => {
-                        break;
+                        continue;
                     }
+                };
+
+                let protocol_id = request.protocol_id;
+                let bytes = message_len(&request.message);
+                let ack_ch = request.ack_tx;
+                if let Err(err) = writer
+                    .send(&request.message)
+                    .map_ok(|_| ack_ch.send(Ok(())))
+                    .await
+                {
+                    warn!(
+                        NetworkSchema::new(&network_context)
+                            .connection_metadata(&connection_metadata),
+                        error = %err,
+                        "{} Error in sending message to peer: {}, error: {}",
+                        network_context,
+                        remote_peer_id.short_str(),
+                        err
+                    );
+                    break;
+                }
+                if let Some(protocol_id) = protocol_id {
+                    counters::protocol_bytes(
+                        &network_context,
+                        &remote_peer_id,
+                        protocol_id,
+                        SENT_LABEL,
+                    )
+                    .inc_by(bytes as u64);
                 }
             }
             info!(
@@ -401,10 +461,7 @@ where
     async fn handle_inbound_message(
         &mut self,
         message: Result<NetworkMessage, ReadError>,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut channel::Sender<WriteRequest>,
     ) -> Result<(), PeerManagerError> {
         trace!(
             NetworkSchema::new(&self.network_context)
@@ -427,7 +484,12 @@ where
                     let message = NetworkMessage::Error(error_code);
 
                     let (ack_tx, _) = oneshot::channel();
-                    write_reqs_tx.send((message, ack_tx)).await?;
+                    let request = WriteRequest {
+                        protocol_id: None,
+                        message,
+                        ack_tx,
+                    };
+                    write_reqs_tx.send(request).await?;
                     return Err(err.into());
                 }
                 ReadError::IoError(_) => {
@@ -493,6 +555,8 @@ where
         counters::direct_send_messages(&self.network_context, RECEIVED_LABEL).inc();
         counters::direct_send_bytes(&self.network_context, RECEIVED_LABEL)
             .inc_by(data.len() as u64);
+        counters::protocol_bytes(&self.network_context, &peer_id, protocol_id, RECEIVED_LABEL)
+            .inc_by(data.len() as u64);
 
         let notif = PeerNotification::RecvMessage(Message {
             protocol_id,
@@ -513,10 +577,7 @@ where
     async fn handle_outbound_request(
         &mut self,
         request: PeerRequest,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut channel::Sender<WriteRequest>,
     ) {
         trace!(
             "Peer {} PeerRequest::{:?}",
@@ -535,8 +596,13 @@ where
                     raw_msg: Vec::from(message.mdata.as_ref()),
                 });
                 let (ack_tx, _ack_rx) = oneshot::channel();
+                let request = WriteRequest {
+                    protocol_id: Some(protocol_id),
+                    message,
+                    ack_tx,
+                };
 
-                match write_reqs_tx.send((message, ack_tx)).await {
+                match write_reqs_tx.send(request).await {
                     Ok(_) => {
                         counters::direct_send_messages(&self.network_context, SENT_LABEL).inc();
                         counters::direct_send_bytes(&self.network_context, SENT_LABEL)
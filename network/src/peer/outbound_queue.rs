@@ -0,0 +1,322 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! [`OutboundQueue`] orders the messages waiting for a [`Peer`](crate::peer::Peer)'s
+//! writer task.
+//!
+//! Messages are written in [`OutboundPriority`] order, so consensus messages are never
+//! stuck behind a backlog of mempool or state-sync messages. Protocols with an outbound
+//! rate limit are additionally held back until their token bucket has room; this only
+//! delays messages of the throttled protocol, and messages of the same protocol are
+//! always written in the order they were queued.
+//!
+//! Each priority has a queue of its own. Once the high priority queue is full, the writer
+//! task stops reading new requests, which pushes back on the `Peer`. Lower priority
+//! messages are dropped instead when their queue is full, so that a backlog of throttled
+//! messages can't keep the writer from reading consensus messages.
+
+use crate::{
+    counters, peer::WriteRequest, peer_manager::PeerManagerError,
+    protocols::wire::messaging::v1::NetworkMessage, ProtocolId,
+};
+use anyhow::format_err;
+use diem_config::network_id::NetworkContext;
+use diem_rate_limiter::rate_limit::SharedBucket;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Instant,
+};
+
+/// The maximum number of messages the writer task buffers per priority.
+pub const MAX_QUEUED_MESSAGES: usize = 1024;
+
+/// The order in which queued messages are written, from first to last.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum OutboundPriority {
+    High = 0,
+    Medium = 1,
+    Low = 2,
+}
+
+const NUM_PRIORITIES: usize = 3;
+
+impl OutboundPriority {
+    /// Messages that don't belong to a protocol, like wire errors, are small
+    /// and share the highest priority.
+    pub fn for_protocol(protocol_id: Option<ProtocolId>) -> Self {
+        use ProtocolId::*;
+        match protocol_id {
+            None => OutboundPriority::High,
+            Some(protocol_id) => match protocol_id {
+                ConsensusRpcBcs
+                | ConsensusDirectSendBcs
+                | ConsensusDirectSendJson
+                | ConsensusRpcJson
+                | ConsensusRpcCompressed
+                | ConsensusDirectSendCompressed
+                | HealthCheckerRpc => OutboundPriority::High,
+                MempoolDirectSend | MempoolRpc | DiscoveryDirectSend => OutboundPriority::Medium,
+                StateSyncDirectSend | StateSyncDirectSendCompressed | StorageServiceRpc => {
+                    OutboundPriority::Low
+                }
+            },
+        }
+    }
+}
+
+struct QueuedRequest {
+    request: WriteRequest,
+    /// The number of rate limit tokens this message needs.
+    len: usize,
+    /// Whether this message has already been counted as throttled.
+    throttled: bool,
+}
+
+pub struct OutboundQueue {
+    network_context: NetworkContext,
+    queues: [VecDeque<QueuedRequest>; NUM_PRIORITIES],
+    rate_limiters: HashMap<ProtocolId, SharedBucket>,
+}
+
+impl OutboundQueue {
+    pub fn new(
+        network_context: NetworkContext,
+        rate_limiters: HashMap<ProtocolId, SharedBucket>,
+    ) -> Self {
+        Self {
+            network_context,
+            queues: Default::default(),
+            rate_limiters,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    /// Whether the high priority queue is full, in which case the writer task
+    /// should stop reading new requests.
+    pub fn is_full(&self) -> bool {
+        self.queues[OutboundPriority::High as usize].len() >= MAX_QUEUED_MESSAGES
+    }
+
+    /// Queue a message. Lower priority messages are dropped if their queue is
+    /// full; high priority messages are always queued.
+    pub fn push(&mut self, request: WriteRequest) {
+        let priority = OutboundPriority::for_protocol(request.protocol_id);
+        let queue = &mut self.queues[priority as usize];
+        if priority != OutboundPriority::High && queue.len() >= MAX_QUEUED_MESSAGES {
+            if let Some(protocol_id) = request.protocol_id {
+                counters::dropped_outbound_messages(&self.network_context, protocol_id).inc();
+            }
+            let _ = request.ack_tx.send(Err(PeerManagerError::Error(format_err!(
+                "Outbound queue is full"
+            ))));
+            return;
+        }
+        let len = message_len(&request.message);
+        queue.push_back(QueuedRequest {
+            request,
+            len,
+            throttled: false,
+        });
+    }
+
+    /// Remove the highest priority message that can be written now. If every
+    /// queued message is throttled, returns the earliest time at which one of
+    /// them may be allowed through, or `None` if the queue is empty.
+    pub fn pop(&mut self) -> Result<WriteRequest, Option<Instant>> {
+        let mut next_ready: Option<Instant> = None;
+        for queue in self.queues.iter_mut() {
+            // Protocols whose oldest message is throttled; none of their later
+            // messages may overtake it.
+            let mut blocked = HashSet::new();
+            for idx in 0..queue.len() {
+                let protocol_id = match queue[idx].request.protocol_id {
+                    Some(protocol_id) => protocol_id,
+                    None => return Ok(queue.remove(idx).unwrap().request),
+                };
+                if blocked.contains(&protocol_id) {
+                    continue;
+                }
+                let bucket = match self.rate_limiters.get(&protocol_id) {
+                    Some(bucket) => bucket,
+                    None => return Ok(queue.remove(idx).unwrap().request),
+                };
+
+                let result = bucket.lock().acquire_all_tokens(queue[idx].len);
+                match result {
+                    Ok(()) => return Ok(queue.remove(idx).unwrap().request),
+                    // The config requires buckets to hold a full frame, so this
+                    // can't happen; send the message rather than stall forever.
+                    Err(None) => return Ok(queue.remove(idx).unwrap().request),
+                    Err(Some(ready_at)) => {
+                        let queued = &mut queue[idx];
+                        if !queued.throttled {
+                            queued.throttled = true;
+                            counters::throttled_messages(&self.network_context, protocol_id).inc();
+                        }
+                        blocked.insert(protocol_id);
+                        next_ready =
+                            Some(next_ready.map_or(ready_at, |earliest| earliest.min(ready_at)));
+                    }
+                }
+            }
+        }
+        Err(next_ready)
+    }
+}
+
+/// The number of application bytes in a message.
+pub fn message_len(message: &NetworkMessage) -> usize {
+    match message {
+        NetworkMessage::DirectSendMsg(message) => message.raw_msg.len(),
+        NetworkMessage::RpcRequest(request) => request.raw_request.len(),
+        NetworkMessage::RpcResponse(response) => response.raw_response.len(),
+        NetworkMessage::Error(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::{DirectSendMsg, ErrorCode};
+    use diem_infallible::Mutex;
+    use diem_rate_limiter::rate_limit::Bucket;
+    use futures::channel::oneshot;
+    use std::sync::Arc;
+
+    fn direct_send(protocol_id: ProtocolId, len: usize) -> WriteRequest {
+        let (ack_tx, _) = oneshot::channel();
+        WriteRequest {
+            protocol_id: Some(protocol_id),
+            message: NetworkMessage::DirectSendMsg(DirectSendMsg {
+                protocol_id,
+                priority: 0,
+                raw_msg: vec![0; len],
+            }),
+            ack_tx,
+        }
+    }
+
+    fn popped_protocol(queue: &mut OutboundQueue) -> Option<ProtocolId> {
+        queue.pop().ok().unwrap().protocol_id
+    }
+
+    fn full_bucket(size: usize) -> SharedBucket {
+        Arc::new(Mutex::new(Bucket::new(
+            "test".to_string(),
+            "test".to_string(),
+            "test".to_string(),
+            size,
+            size,
+            size,
+            None,
+        )))
+    }
+
+    #[test]
+    fn pops_in_priority_order() {
+        let mut queue = OutboundQueue::new(NetworkContext::mock(), HashMap::new());
+        queue.push(direct_send(ProtocolId::StateSyncDirectSend, 10));
+        queue.push(direct_send(ProtocolId::MempoolDirectSend, 10));
+        queue.push(direct_send(ProtocolId::StateSyncDirectSend, 10));
+        queue.push(direct_send(ProtocolId::ConsensusDirectSendBcs, 10));
+        let (ack_tx, _) = oneshot::channel();
+        queue.push(WriteRequest {
+            protocol_id: None,
+            message: NetworkMessage::Error(ErrorCode::parsing_error(0, 0)),
+            ack_tx,
+        });
+        assert_eq!(queue.len(), 5);
+
+        // The error and consensus message come first, in the order they were queued
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::ConsensusDirectSendBcs)
+        );
+        assert_eq!(popped_protocol(&mut queue), None);
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::MempoolDirectSend)
+        );
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::StateSyncDirectSend)
+        );
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::StateSyncDirectSend)
+        );
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop().unwrap_err(), None);
+    }
+
+    #[test]
+    fn throttles_only_the_limited_protocol() {
+        let rate_limiters = vec![(ProtocolId::StateSyncDirectSend, full_bucket(100))]
+            .into_iter()
+            .collect();
+        let mut queue = OutboundQueue::new(NetworkContext::mock(), rate_limiters);
+        queue.push(direct_send(ProtocolId::StateSyncDirectSend, 80));
+        queue.push(direct_send(ProtocolId::StateSyncDirectSend, 80));
+        queue.push(direct_send(ProtocolId::StateSyncDirectSend, 10));
+        queue.push(direct_send(ProtocolId::StorageServiceRpc, 80));
+
+        // The first message fits in the bucket, the second doesn't. The small
+        // third message must not overtake it, but the unlimited storage service
+        // message can go ahead.
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::StateSyncDirectSend)
+        );
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::StorageServiceRpc)
+        );
+        let ready_at = queue.pop().unwrap_err().unwrap();
+        assert!(ready_at > Instant::now());
+        assert_eq!(queue.len(), 2);
+
+        // Higher priority messages are never throttled by another protocol's limit
+        queue.push(direct_send(ProtocolId::ConsensusDirectSendBcs, 1000));
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::ConsensusDirectSendBcs)
+        );
+    }
+
+    #[test]
+    fn drops_low_priority_messages_when_full() {
+        let rate_limiters = vec![(ProtocolId::StateSyncDirectSend, full_bucket(100))]
+            .into_iter()
+            .collect();
+        let mut queue = OutboundQueue::new(NetworkContext::mock(), rate_limiters);
+        for _ in 0..MAX_QUEUED_MESSAGES + 10 {
+            queue.push(direct_send(ProtocolId::StateSyncDirectSend, 80));
+        }
+        assert_eq!(queue.len(), MAX_QUEUED_MESSAGES);
+        // A backlog of low priority messages doesn't stop the writer from reading
+        assert!(!queue.is_full());
+
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::StateSyncDirectSend)
+        );
+        assert!(queue.pop().unwrap_err().is_some());
+        queue.push(direct_send(ProtocolId::ConsensusDirectSendBcs, 80));
+        assert_eq!(
+            popped_protocol(&mut queue),
+            Some(ProtocolId::ConsensusDirectSendBcs)
+        );
+
+        for _ in 0..MAX_QUEUED_MESSAGES {
+            queue.push(direct_send(ProtocolId::ConsensusDirectSendBcs, 80));
+        }
+        assert!(queue.is_full());
+    }
+}
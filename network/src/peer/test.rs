@@ -25,6 +25,8 @@ use crate::{
 use bytes::Bytes;
 use channel::{self, diem_channel, message_queues::QueueStyle};
 use diem_config::{config::PeerRole, network_id::NetworkContext};
+use diem_infallible::Mutex;
+use diem_rate_limiter::rate_limit::{Bucket, SharedBucket};
 use diem_time_service::{MockTimeService, TimeService};
 use diem_types::{network_address::NetworkAddress, PeerId};
 use futures::{
@@ -36,7 +38,12 @@ use futures::{
 };
 use memsocket::MemorySocket;
 use netcore::transport::ConnectionOrigin;
-use std::{collections::HashSet, str::FromStr, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 use tokio::runtime::{Handle, Runtime};
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
@@ -54,6 +61,21 @@ fn build_test_peer(
     MemorySocket,
     channel::Receiver<TransportNotification<MemorySocket>>,
     diem_channel::Receiver<ProtocolId, PeerNotification>,
+) {
+    build_test_peer_with_rate_limits(executor, time_service, origin, HashMap::new())
+}
+
+fn build_test_peer_with_rate_limits(
+    executor: Handle,
+    time_service: TimeService,
+    origin: ConnectionOrigin,
+    outbound_protocol_rate_limiters: HashMap<ProtocolId, SharedBucket>,
+) -> (
+    Peer<MemorySocket>,
+    PeerHandle,
+    MemorySocket,
+    channel::Receiver<TransportNotification<MemorySocket>>,
+    diem_channel::Receiver<ProtocolId, PeerNotification>,
 ) {
     let (a, b) = MemorySocket::new_pair();
     let peer_id = PeerId::random();
@@ -90,6 +112,7 @@ fn build_test_peer(
         MAX_FRAME_SIZE,
         None,
         None,
+        outbound_protocol_rate_limiters,
     );
     let peer_handle = PeerHandle(peer_reqs_tx);
 
@@ -237,6 +260,119 @@ fn peer_send_message() {
     rt.block_on(future::join3(peer.start(), server, client));
 }
 
+// A rate limited protocol should be held back without delaying other protocols.
+#[test]
+fn peer_send_message_throttled() {
+    ::diem_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    // Room for one state-sync message, and far too slow a refill for a second.
+    let bucket = Arc::new(Mutex::new(Bucket::new(
+        "test".to_string(),
+        "test".to_string(),
+        "test".to_string(),
+        100,
+        100,
+        1,
+        None,
+    )));
+    let (peer, mut peer_handle, mut connection, _connection_notifs_rx, _peer_notifs_rx) =
+        build_test_peer_with_rate_limits(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+            vec![(ProtocolId::StateSyncDirectSend, bucket)]
+                .into_iter()
+                .collect(),
+        );
+    let (mut client_sink, mut client_stream) = build_network_sink_stream(&mut connection);
+
+    let message = |protocol_id| Message {
+        protocol_id,
+        mdata: Bytes::from(vec![0; 80]),
+    };
+    let wire_message = |protocol_id| {
+        NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: 0,
+            raw_msg: vec![0; 80],
+        })
+    };
+
+    let client = async {
+        peer_handle.send_direct_send(message(ProtocolId::StateSyncDirectSend));
+        peer_handle.send_direct_send(message(ProtocolId::StateSyncDirectSend));
+        let msg = client_stream.next().await.unwrap().unwrap();
+        assert_eq!(msg, wire_message(ProtocolId::StateSyncDirectSend));
+
+        // The second state-sync message is throttled, but consensus gets through.
+        peer_handle.send_direct_send(message(ProtocolId::ConsensusDirectSendBcs));
+        let msg = client_stream.next().await.unwrap().unwrap();
+        assert_eq!(msg, wire_message(ProtocolId::ConsensusDirectSendBcs));
+
+        client_sink.close().await.unwrap();
+    };
+    rt.block_on(future::join(peer.start(), client));
+}
+
+// A backlog of throttled low priority messages must not keep consensus messages from being sent.
+#[test]
+fn peer_send_message_not_starved_by_throttled_backlog() {
+    ::diem_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    // No room for any state-sync message for a long time.
+    let bucket = || {
+        Arc::new(Mutex::new(Bucket::new(
+            "test".to_string(),
+            "test".to_string(),
+            "test".to_string(),
+            0,
+            100,
+            1,
+            None,
+        )))
+    };
+    let (peer, mut peer_handle, mut connection, _connection_notifs_rx, _peer_notifs_rx) =
+        build_test_peer_with_rate_limits(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+            vec![
+                (ProtocolId::StateSyncDirectSend, bucket()),
+                (ProtocolId::StateSyncDirectSendCompressed, bucket()),
+            ]
+            .into_iter()
+            .collect(),
+        );
+    let (mut client_sink, mut client_stream) = build_network_sink_stream(&mut connection);
+
+    let message = |protocol_id| Message {
+        protocol_id,
+        mdata: Bytes::from(vec![0; 80]),
+    };
+
+    let client = async {
+        // More throttled messages than the writer's queue and channel hold together.
+        for _ in 0..NETWORK_CHANNEL_SIZE {
+            peer_handle.send_direct_send(message(ProtocolId::StateSyncDirectSend));
+            peer_handle.send_direct_send(message(ProtocolId::StateSyncDirectSendCompressed));
+        }
+        peer_handle.send_direct_send(message(ProtocolId::ConsensusDirectSendBcs));
+
+        let msg = client_stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            msg,
+            NetworkMessage::DirectSendMsg(DirectSendMsg {
+                protocol_id: ProtocolId::ConsensusDirectSendBcs,
+                priority: 0,
+                raw_msg: vec![0; 80],
+            })
+        );
+
+        client_sink.close().await.unwrap();
+    };
+    rt.block_on(future::join(peer.start(), client));
+}
+
 // Reading an inbound DirectSendMsg off the wire should notify the PeerManager of
 // an inbound DirectSend.
 #[test]
@@ -16,7 +16,7 @@ use crate::{
 };
 use channel::{self, diem_channel, message_queues::QueueStyle};
 use diem_config::{
-    config::{PeerSet, RateLimitConfig, HANDSHAKE_VERSION},
+    config::{PeerSet, ProtocolRateLimitConfig, RateLimitConfig, HANDSHAKE_VERSION},
     network_id::NetworkContext,
 };
 use diem_crypto::x25519;
@@ -81,6 +81,7 @@ struct PeerManagerContext {
     inbound_connection_limit: usize,
     inbound_rate_limit_config: Option<RateLimitConfig>,
     outbound_rate_limit_config: Option<RateLimitConfig>,
+    outbound_protocol_rate_limits: HashMap<ProtocolId, ProtocolRateLimitConfig>,
 }
 
 impl PeerManagerContext {
@@ -105,6 +106,7 @@ impl PeerManagerContext {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        outbound_protocol_rate_limits: HashMap<ProtocolId, ProtocolRateLimitConfig>,
     ) -> Self {
         Self {
             pm_reqs_tx,
@@ -123,6 +125,7 @@ impl PeerManagerContext {
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
+            outbound_protocol_rate_limits,
         }
     }
 
@@ -182,6 +185,7 @@ impl PeerManagerBuilder {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        outbound_protocol_rate_limits: HashMap<ProtocolId, ProtocolRateLimitConfig>,
     ) -> Self {
         // Setup channel to send requests to peer manager.
         let (pm_reqs_tx, pm_reqs_rx) = diem_channel::new(
@@ -218,6 +222,7 @@ impl PeerManagerBuilder {
                 inbound_connection_limit,
                 inbound_rate_limit_config,
                 outbound_rate_limit_config,
+                outbound_protocol_rate_limits,
             )),
             peer_manager: None,
             listen_address,
@@ -340,6 +345,21 @@ impl PeerManagerBuilder {
             "outbound",
             pm_context.outbound_rate_limit_config,
         );
+        let outbound_protocol_rate_limiters = pm_context
+            .outbound_protocol_rate_limits
+            .into_iter()
+            .map(|(protocol_id, config)| {
+                let rate_limiters = TokenBucketRateLimiter::new(
+                    "outbound_protocol",
+                    format!("{}:{}", self.network_context, protocol_id),
+                    100,
+                    config.byte_bucket_size,
+                    config.byte_rate,
+                    Some(NETWORK_RATE_LIMIT_METRICS.clone()),
+                );
+                (protocol_id, rate_limiters)
+            })
+            .collect();
         let peer_mgr = PeerManager::new(
             executor.clone(),
             self.time_service.clone(),
@@ -360,6 +380,7 @@ impl PeerManagerBuilder {
             pm_context.inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            outbound_protocol_rate_limiters,
         );
 
         // PeerManager constructor appends a public key to the listen_address.
@@ -424,11 +445,19 @@ impl PeerManagerBuilder {
         self.transport_context().add_protocols(&config.protocols);
         let pm_context = self.peer_manager_context();
         (
-            PeerManagerRequestSender::new(pm_context.pm_reqs_tx.clone()),
+            PeerManagerRequestSender::new(pm_context.pm_reqs_tx.clone(), pm_context.max_frame_size),
             ConnectionRequestSender::new(pm_context.connection_reqs_tx.clone()),
         )
     }
 
+    /// Limit on the size of the messages exchanged with peers.
+    pub fn max_frame_size(&self) -> usize {
+        self.peer_manager_context
+            .as_ref()
+            .expect("Cannot get the frame size once PeerManager has been built")
+            .max_frame_size
+    }
+
     /// Register a service for handling some protocols.
     pub fn add_service(
         &mut self,
@@ -65,6 +65,7 @@ pub use senders::*;
 pub use types::*;
 
 pub type IpAddrTokenBucketLimiter = TokenBucketRateLimiter<IpAddr>;
+pub type PeerIdTokenBucketLimiter = TokenBucketRateLimiter<PeerId>;
 
 /// Responsible for handling and maintaining connections to other Peers
 pub struct PeerManager<TTransport, TSocket>
@@ -126,6 +127,8 @@ where
     inbound_rate_limiters: IpAddrTokenBucketLimiter,
     /// Keyed storage of all outbound rate limiters
     outbound_rate_limiters: IpAddrTokenBucketLimiter,
+    /// Per protocol keyed storage of outbound bandwidth limiters
+    outbound_protocol_rate_limiters: HashMap<ProtocolId, PeerIdTokenBucketLimiter>,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -156,6 +159,7 @@ where
         inbound_connection_limit: usize,
         inbound_rate_limiters: IpAddrTokenBucketLimiter,
         outbound_rate_limiters: IpAddrTokenBucketLimiter,
+        outbound_protocol_rate_limiters: HashMap<ProtocolId, PeerIdTokenBucketLimiter>,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = channel::new(
             channel_size,
@@ -200,6 +204,7 @@ where
             inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            outbound_protocol_rate_limiters,
         }
     }
 
@@ -425,6 +430,9 @@ where
                 self.inbound_rate_limiters.try_garbage_collect_key(&ip_addr);
                 self.outbound_rate_limiters
                     .try_garbage_collect_key(&ip_addr);
+                for rate_limiters in self.outbound_protocol_rate_limiters.values() {
+                    rate_limiters.try_garbage_collect_key(&peer_id);
+                }
             }
         }
     }
@@ -657,6 +665,11 @@ where
             .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         let inbound_rate_limiter = self.inbound_rate_limiters.bucket(ip_addr);
         let outbound_rate_limiter = self.outbound_rate_limiters.bucket(ip_addr);
+        let outbound_protocol_rate_limiters = self
+            .outbound_protocol_rate_limiters
+            .iter()
+            .map(|(protocol_id, rate_limiters)| (*protocol_id, rate_limiters.bucket(peer_id)))
+            .collect();
 
         // TODO: Add label for peer.
         let (peer_reqs_tx, peer_reqs_rx) = diem_channel::new(
@@ -686,6 +699,7 @@ where
             self.max_frame_size,
             Some(inbound_rate_limiter),
             Some(outbound_rate_limiter),
+            outbound_protocol_rate_limiters,
         );
         self.executor.spawn(peer.start());
 
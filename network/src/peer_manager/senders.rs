@@ -21,6 +21,7 @@ use crate::peer_manager::{types::PeerManagerRequest, ConnectionRequest, PeerMana
 #[derive(Clone, Debug)]
 pub struct PeerManagerRequestSender {
     inner: diem_channel::Sender<(PeerId, ProtocolId), PeerManagerRequest>,
+    max_message_size: usize,
 }
 
 /// Convenience wrapper which makes it easy to issue connection requests and await the responses
@@ -31,9 +32,21 @@ pub struct ConnectionRequestSender {
 }
 
 impl PeerManagerRequestSender {
-    /// Construct a new PeerManagerRequestSender with a raw channel::Sender
-    pub fn new(inner: diem_channel::Sender<(PeerId, ProtocolId), PeerManagerRequest>) -> Self {
-        Self { inner }
+    /// Construct a new PeerManagerRequestSender with a raw channel::Sender, `max_message_size`
+    /// is the `max_frame_size` of the network.
+    pub fn new(
+        inner: diem_channel::Sender<(PeerId, ProtocolId), PeerManagerRequest>,
+        max_message_size: usize,
+    ) -> Self {
+        Self {
+            inner,
+            max_message_size,
+        }
+    }
+
+    /// Limit on the decompressed size of the rpc responses received from remote peers.
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
     }
 
     /// Send a fire-and-forget direct-send message to remote peer.
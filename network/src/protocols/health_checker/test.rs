@@ -40,11 +40,14 @@ impl TestHarness {
         let (connection_notifs_tx, connection_notifs_rx) = conn_notifs_channel::new();
 
         let hc_network_tx = HealthCheckerNetworkSender::new(
-            PeerManagerRequestSender::new(peer_mgr_reqs_tx),
+            PeerManagerRequestSender::new(peer_mgr_reqs_tx, crate::constants::MAX_FRAME_SIZE),
             ConnectionRequestSender::new(connection_reqs_tx),
         );
-        let hc_network_rx =
-            HealthCheckerNetworkEvents::new(peer_mgr_notifs_rx, connection_notifs_rx);
+        let hc_network_rx = HealthCheckerNetworkEvents::new(
+            peer_mgr_notifs_rx,
+            connection_notifs_rx,
+            crate::constants::MAX_FRAME_SIZE,
+        );
         let health_checker = HealthChecker::new(
             NetworkContext::mock(),
             mock_time.clone(),
@@ -25,7 +25,7 @@ use futures::{
     task::{Context, Poll},
 };
 use pin_project::pin_project;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Serialize};
 use short_hex_str::AsShortHexStr;
 use std::{cmp::min, iter::FromIterator, marker::PhantomData, pin::Pin, time::Duration};
 
@@ -144,7 +144,7 @@ pub struct NetworkEvents<TMessage> {
         FilterMap<
             diem_channel::Receiver<(PeerId, ProtocolId), PeerManagerNotification>,
             future::Ready<Option<Event<TMessage>>>,
-            fn(PeerManagerNotification) -> future::Ready<Option<Event<TMessage>>>,
+            NotificationToEvent<TMessage>,
         >,
         Map<
             diem_channel::Receiver<PeerId, ConnectionNotification>,
@@ -154,23 +154,30 @@ pub struct NetworkEvents<TMessage> {
     _marker: PhantomData<TMessage>,
 }
 
+/// Deserializes an inbound notification into an `Event`, see `peer_mgr_notif_to_event`.
+type NotificationToEvent<TMessage> =
+    Box<dyn FnMut(PeerManagerNotification) -> future::Ready<Option<Event<TMessage>>> + Send + Sync>;
+
 /// Trait specifying the signature for `new()` `NetworkEvents`
 pub trait NewNetworkEvents {
+    /// `max_message_size` bounds the size of decompressed inbound messages, it is the
+    /// `max_frame_size` of the network.
     fn new(
         peer_mgr_notifs_rx: diem_channel::Receiver<(PeerId, ProtocolId), PeerManagerNotification>,
         connection_notifs_rx: diem_channel::Receiver<PeerId, ConnectionNotification>,
+        max_message_size: usize,
     ) -> Self;
 }
 
-impl<TMessage: Message> NewNetworkEvents for NetworkEvents<TMessage> {
+impl<TMessage: Message + 'static> NewNetworkEvents for NetworkEvents<TMessage> {
     fn new(
         peer_mgr_notifs_rx: diem_channel::Receiver<(PeerId, ProtocolId), PeerManagerNotification>,
         connection_notifs_rx: diem_channel::Receiver<PeerId, ConnectionNotification>,
+        max_message_size: usize,
     ) -> Self {
-        let data_event_stream = peer_mgr_notifs_rx.filter_map(
-            peer_mgr_notif_to_event
-                as fn(PeerManagerNotification) -> future::Ready<Option<Event<TMessage>>>,
-        );
+        let to_event: NotificationToEvent<TMessage> =
+            Box::new(move |notif| peer_mgr_notif_to_event(notif, max_message_size));
+        let data_event_stream = peer_mgr_notifs_rx.filter_map(to_event);
         let control_event_stream = connection_notifs_rx
             .map(control_msg_to_event as fn(ConnectionNotification) -> Event<TMessage>);
         Self {
@@ -196,14 +203,16 @@ impl<TMessage> Stream for NetworkEvents<TMessage> {
 /// type, logging and dropping messages that fail to deserialize.
 fn peer_mgr_notif_to_event<TMessage: Message>(
     notif: PeerManagerNotification,
+    max_message_size: usize,
 ) -> future::Ready<Option<Event<TMessage>>> {
     let maybe_event = match notif {
         PeerManagerNotification::RecvRpc(peer_id, rpc_req) => {
-            request_to_network_event(peer_id, &rpc_req)
+            request_to_network_event(peer_id, &rpc_req, max_message_size)
                 .map(|msg| Event::RpcRequest(peer_id, msg, rpc_req.protocol_id, rpc_req.res_tx))
         }
         PeerManagerNotification::RecvMessage(peer_id, request) => {
-            request_to_network_event(peer_id, &request).map(|msg| Event::Message(peer_id, msg))
+            request_to_network_event(peer_id, &request, max_message_size)
+                .map(|msg| Event::Message(peer_id, msg))
         }
     };
     future::ready(maybe_event)
@@ -213,8 +222,9 @@ fn peer_mgr_notif_to_event<TMessage: Message>(
 fn request_to_network_event<TMessage: Message, Request: SerializedRequest>(
     peer_id: PeerId,
     request: &Request,
+    max_message_size: usize,
 ) -> Option<TMessage> {
-    match request.to_message() {
+    match request.to_message(max_message_size) {
         Ok(msg) => Some(msg),
         Err(err) => {
             let data = &request.data();
@@ -346,7 +356,8 @@ impl<TMessage: Message> NetworkSender<TMessage> {
             .peer_mgr_reqs_tx
             .send_rpc(recipient, protocol, req_data, timeout)
             .await?;
-        let res_msg: TMessage = protocol.from_bytes(&res_data)?;
+        let res_msg: TMessage =
+            protocol.from_bytes(&res_data, self.peer_mgr_reqs_tx.max_message_size())?;
         Ok(res_msg)
     }
 }
@@ -382,7 +393,10 @@ pub trait SerializedRequest {
 
     /// Converts the `SerializedMessage` into its deserialized version of `TMessage` based on the
     /// `ProtocolId`.  See: [`ProtocolId::from_bytes`]
-    fn to_message<'a, TMessage: Deserialize<'a>>(&'a self) -> anyhow::Result<TMessage> {
-        self.protocol_id().from_bytes(self.data())
+    fn to_message<TMessage: DeserializeOwned>(
+        &self,
+        max_message_size: usize,
+    ) -> anyhow::Result<TMessage> {
+        self.protocol_id().from_bytes(self.data(), max_message_size)
     }
 }
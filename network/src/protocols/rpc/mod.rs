@@ -49,8 +49,7 @@ use crate::{
         RESPONSE_LABEL, SENT_LABEL,
     },
     logging::NetworkSchema,
-    peer::PeerNotification,
-    peer_manager::PeerManagerError,
+    peer::{PeerNotification, WriteRequest},
     protocols::{
         network::SerializedRequest,
         wire::messaging::v1::{NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse},
@@ -174,7 +173,9 @@ pub struct InboundRpcs {
     remote_peer_id: PeerId,
     /// The core async queue of pending inbound rpc tasks. The tasks are driven
     /// to completion by the `InboundRpcs::next_completed_response()` method.
-    inbound_rpc_tasks: FuturesUnordered<BoxFuture<'static, Result<RpcResponse, RpcError>>>,
+    /// Each response is tagged with the protocol of its request.
+    inbound_rpc_tasks:
+        FuturesUnordered<BoxFuture<'static, Result<(ProtocolId, RpcResponse), RpcError>>>,
     /// A blanket timeout on all inbound rpc requests. If the application handler
     /// doesn't respond to the request before this timeout, the request will be
     /// dropped.
@@ -234,6 +235,13 @@ impl InboundRpcs {
         // Collect counters for received request.
         counters::rpc_messages(network_context, REQUEST_LABEL, RECEIVED_LABEL).inc();
         counters::rpc_bytes(network_context, REQUEST_LABEL, RECEIVED_LABEL).inc_by(req_len);
+        counters::protocol_bytes(
+            network_context,
+            &self.remote_peer_id,
+            protocol_id,
+            RECEIVED_LABEL,
+        )
+        .inc_by(req_len);
         let timer =
             counters::inbound_rpc_handler_latency(network_context, protocol_id).start_timer();
 
@@ -256,11 +264,14 @@ impl InboundRpcs {
             .map(move |result| {
                 // Flatten the errors
                 let maybe_response = match result {
-                    Ok(Ok(Ok(response_bytes))) => Ok(RpcResponse {
-                        request_id,
-                        priority,
-                        raw_response: Vec::from(response_bytes.as_ref()),
-                    }),
+                    Ok(Ok(Ok(response_bytes))) => Ok((
+                        protocol_id,
+                        RpcResponse {
+                            request_id,
+                            priority,
+                            raw_response: Vec::from(response_bytes.as_ref()),
+                        },
+                    )),
                     Ok(Ok(Err(err))) => Err(err),
                     Ok(Err(oneshot::Canceled)) => Err(RpcError::UnexpectedResponseChannelCancel),
                     Err(timeout::Elapsed) => Err(RpcError::TimedOut),
@@ -286,7 +297,7 @@ impl InboundRpcs {
     /// `futures::select!`.
     pub fn next_completed_response(
         &mut self,
-    ) -> impl Future<Output = Result<RpcResponse, RpcError>> + FusedFuture + '_ {
+    ) -> impl Future<Output = Result<(ProtocolId, RpcResponse), RpcError>> + FusedFuture + '_ {
         self.inbound_rpc_tasks.select_next_some()
     }
 
@@ -295,14 +306,11 @@ impl InboundRpcs {
     /// the outbound write queue.
     pub async fn send_outbound_response(
         &mut self,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
-        maybe_response: Result<RpcResponse, RpcError>,
+        write_reqs_tx: &mut channel::Sender<WriteRequest>,
+        maybe_response: Result<(ProtocolId, RpcResponse), RpcError>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
-        let response = match maybe_response {
+        let (protocol_id, response) = match maybe_response {
             Ok(response) => response,
             Err(err) => {
                 counters::rpc_messages(network_context, RESPONSE_LABEL, FAILED_LABEL).inc();
@@ -321,7 +329,12 @@ impl InboundRpcs {
         );
         let message = NetworkMessage::RpcResponse(response);
         let (ack_tx, _) = oneshot::channel();
-        write_reqs_tx.send((message, ack_tx)).await?;
+        let request = WriteRequest {
+            protocol_id: Some(protocol_id),
+            message,
+            ack_tx,
+        };
+        write_reqs_tx.send(request).await?;
 
         // Collect counters for sent response.
         counters::rpc_messages(network_context, RESPONSE_LABEL, SENT_LABEL).inc();
@@ -382,10 +395,7 @@ impl OutboundRpcs {
     pub async fn handle_outbound_request(
         &mut self,
         request: OutboundRpcRequest,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut channel::Sender<WriteRequest>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
         let peer_id = &self.remote_peer_id;
@@ -437,7 +447,12 @@ impl OutboundRpcs {
             raw_request: Vec::from(request_data.as_ref()),
         });
         let (ack_tx, _) = oneshot::channel();
-        write_reqs_tx.send((message, ack_tx)).await?;
+        let request = WriteRequest {
+            protocol_id: Some(protocol_id),
+            message,
+            ack_tx,
+        };
+        write_reqs_tx.send(request).await?;
 
         // Collect counters for requests sent.
         counters::rpc_messages(network_context, REQUEST_LABEL, SENT_LABEL).inc();
@@ -489,12 +504,21 @@ impl OutboundRpcs {
             }
         };
 
+        let network_context = *network_context;
+        let peer_id = *peer_id;
         let outbound_rpc_task = async move {
             // Always return the request_id so we can garbage collect the
             // pending_outbound_rpcs map.
             match notify_application.await {
                 Ok(response_len) => {
                     let latency = timer.stop_and_record();
+                    counters::protocol_bytes(
+                        &network_context,
+                        &peer_id,
+                        protocol_id,
+                        RECEIVED_LABEL,
+                    )
+                    .inc_by(response_len);
                     (request_id, Ok((latency, response_len)))
                 }
                 Err(err) => {
@@ -0,0 +1,103 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! LZ4 block compression for DiemNet application messages.
+//!
+//! A compressed payload is the little-endian `u32` length of the decompressed
+//! bytes followed by a raw LZ4 block. The length prefix lets the receiver reject
+//! a payload before allocating anything if it would decompress past the caller's
+//! limit, so a small frame cannot be used as a compression bomb.
+
+use std::convert::{TryFrom, TryInto};
+use thiserror::Error;
+
+/// The number of bytes in the decompressed length prefix.
+const SIZE_PREFIX_LEN: usize = 4;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum CompressionError {
+    #[error("compression: payload of {0} bytes is too large to compress")]
+    PayloadTooLarge(usize),
+    #[error("compression: compressed payload is missing its size prefix")]
+    MissingSizePrefix,
+    #[error("compression: decompressed size {0} exceeds the limit of {1} bytes")]
+    DecompressedSizeTooLarge(usize, usize),
+    #[error("compression: decompressed {0} bytes, but the size prefix claimed {1}")]
+    SizeMismatch(usize, usize),
+    #[error("compression: lz4 decompression failed: {0}")]
+    Lz4Error(String),
+}
+
+/// Compress `raw` into a size-prefixed LZ4 block.
+pub fn compress(raw: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let size =
+        u32::try_from(raw.len()).map_err(|_| CompressionError::PayloadTooLarge(raw.len()))?;
+    let block = lz4_flex::block::compress(raw);
+    let mut compressed = Vec::with_capacity(SIZE_PREFIX_LEN + block.len());
+    compressed.extend_from_slice(&size.to_le_bytes());
+    compressed.extend_from_slice(&block);
+    Ok(compressed)
+}
+
+/// Decompress a size-prefixed LZ4 block, refusing to produce more than
+/// `max_size` bytes.
+pub fn decompress(compressed: &[u8], max_size: usize) -> Result<Vec<u8>, CompressionError> {
+    if compressed.len() < SIZE_PREFIX_LEN {
+        return Err(CompressionError::MissingSizePrefix);
+    }
+    let (prefix, block) = compressed.split_at(SIZE_PREFIX_LEN);
+    let size = u32::from_le_bytes(prefix.try_into().expect("prefix is exactly 4 bytes")) as usize;
+    if size > max_size {
+        return Err(CompressionError::DecompressedSizeTooLarge(size, max_size));
+    }
+
+    // The output buffer is bounded by `size`, so a lying prefix can only make
+    // decompression fail, never allocate past the limit.
+    let raw = lz4_flex::block::decompress(block, size)
+        .map_err(|e| CompressionError::Lz4Error(e.to_string()))?;
+    if raw.len() != size {
+        return Err(CompressionError::SizeMismatch(raw.len(), size));
+    }
+    Ok(raw)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let raw = b"diem diem diem diem diem diem diem diem".repeat(100);
+        let compressed = compress(&raw).unwrap();
+        assert!(compressed.len() < raw.len());
+        assert_eq!(decompress(&compressed, raw.len()).unwrap(), raw);
+
+        let empty = compress(&[]).unwrap();
+        assert_eq!(decompress(&empty, 0).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn rejects_payloads_over_the_limit() {
+        let raw = vec![0u8; 1 << 20];
+        let compressed = compress(&raw).unwrap();
+        assert_eq!(
+            decompress(&compressed, raw.len() - 1).unwrap_err(),
+            CompressionError::DecompressedSizeTooLarge(raw.len(), raw.len() - 1),
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_payloads() {
+        assert_eq!(
+            decompress(&[0, 1], 1024).unwrap_err(),
+            CompressionError::MissingSizePrefix,
+        );
+
+        // A prefix that understates the real size must not let the block
+        // decompress past it.
+        let raw = vec![7u8; 4096];
+        let mut compressed = compress(&raw).unwrap();
+        compressed[..SIZE_PREFIX_LEN].copy_from_slice(&16u32.to_le_bytes());
+        assert!(decompress(&compressed, raw.len()).is_err());
+    }
+}
@@ -10,17 +10,23 @@
 //! supported over that messaging protocol. On receipt, both ends will determine the highest
 //! intersecting messaging protocol version and use that for the remainder of the session.
 //!
+//! Each [`ProtocolId`] also fixes how its messages are encoded on the wire. The `*Compressed`
+//! protocols carry LZ4-compressed BCS; since they are ordinary protocol ids, a peer only uses
+//! them once both ends advertise them in the handshake.
+//!
 //! [DiemNet Handshake v1 Specification]: https://github.com/diem/diem/blob/main/specifications/network/handshake-v1.md
 
+use crate::protocols::wire::compression;
 use anyhow::anyhow;
 use diem_config::network_id::NetworkId;
 use diem_types::chain_id::ChainId;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
     fmt,
     iter::{FromIterator, Iterator},
     ops::{BitAnd, BitOr},
+    str::FromStr,
 };
 use thiserror::Error;
 
@@ -51,12 +57,18 @@ pub enum ProtocolId {
     ConsensusRpcJson = 7,
     StorageServiceRpc = 8,
     MempoolRpc = 9,
+    // lz4 compressed bcs for large consensus proposals and state-sync chunks
+    ConsensusRpcCompressed = 10,
+    ConsensusDirectSendCompressed = 11,
+    StateSyncDirectSendCompressed = 12,
 }
 
 /// The encoding types for Protocols
 enum Encoding {
     Bcs,
     Json,
+    /// LZ4 compressed BCS
+    CompressedBcs,
 }
 
 impl ProtocolId {
@@ -73,6 +85,9 @@ impl ProtocolId {
             ConsensusRpcJson => "ConsensusRpcJson",
             StorageServiceRpc => "StorageServiceRpc",
             MempoolRpc => "MempoolRpc",
+            ConsensusRpcCompressed => "ConsensusRpcCompressed",
+            ConsensusDirectSendCompressed => "ConsensusDirectSendCompressed",
+            StateSyncDirectSendCompressed => "StateSyncDirectSendCompressed",
         }
     }
 
@@ -88,6 +103,9 @@ impl ProtocolId {
             ProtocolId::ConsensusRpcJson,
             ProtocolId::StorageServiceRpc,
             ProtocolId::MempoolRpc,
+            ProtocolId::ConsensusRpcCompressed,
+            ProtocolId::ConsensusDirectSendCompressed,
+            ProtocolId::StateSyncDirectSendCompressed,
         ]
     }
 
@@ -95,6 +113,9 @@ impl ProtocolId {
     fn encoding(self) -> Encoding {
         match self {
             ProtocolId::ConsensusDirectSendJson | ProtocolId::ConsensusRpcJson => Encoding::Json,
+            ProtocolId::ConsensusRpcCompressed
+            | ProtocolId::ConsensusDirectSendCompressed
+            | ProtocolId::StateSyncDirectSendCompressed => Encoding::CompressedBcs,
             _ => Encoding::Bcs,
         }
     }
@@ -108,13 +129,29 @@ impl ProtocolId {
         match self.encoding() {
             Encoding::Json => serde_json::to_vec(value).map_err(|e| anyhow!("{:?}", e)),
             Encoding::Bcs => bcs::to_bytes(value).map_err(|e| anyhow! {"{:?}", e}),
+            Encoding::CompressedBcs => {
+                let raw = bcs::to_bytes(value).map_err(|e| anyhow! {"{:?}", e})?;
+                compression::compress(&raw).map_err(|e| anyhow!("{:?}", e))
+            }
         }
     }
 
-    pub fn from_bytes<'a, T: Deserialize<'a>>(&self, bytes: &'a [u8]) -> anyhow::Result<T> {
+    /// Deserializes a message of this protocol. Compressed messages that would decompress to
+    /// more than `max_message_size` bytes are rejected, this should be the `max_frame_size` of
+    /// the network the message was received from.
+    pub fn from_bytes<T: DeserializeOwned>(
+        &self,
+        bytes: &[u8],
+        max_message_size: usize,
+    ) -> anyhow::Result<T> {
         match self.encoding() {
             Encoding::Json => serde_json::from_slice(bytes).map_err(|e| anyhow!("{:?}", e)),
             Encoding::Bcs => bcs::from_bytes(bytes).map_err(|e| anyhow! {"{:?}", e}),
+            Encoding::CompressedBcs => {
+                let raw = compression::decompress(bytes, max_message_size)
+                    .map_err(|e| anyhow!("{:?}", e))?;
+                bcs::from_bytes(&raw).map_err(|e| anyhow! {"{:?}", e})
+            }
         }
     }
 }
@@ -131,6 +168,18 @@ impl fmt::Display for ProtocolId {
     }
 }
 
+impl FromStr for ProtocolId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        ProtocolId::all()
+            .iter()
+            .copied()
+            .find(|protocol| protocol.as_str() == s)
+            .ok_or_else(|| anyhow!("Unknown protocol: {}", s))
+    }
+}
+
 //
 // ProtocolIdSet
 //
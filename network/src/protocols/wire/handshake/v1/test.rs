@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
+use crate::constants::MAX_FRAME_SIZE;
 use std::iter::FromIterator;
 
 // Ensure serialization of MessagingProtocolVersion enum takes 1 byte.
@@ -182,3 +183,101 @@ fn ignore_unknown_protocols() {
         ProtocolIdSet::empty(),
     );
 }
+
+#[test]
+fn compressed_encoding_round_trip() {
+    let value: Vec<u64> = (0..10_000).map(|i| i % 16).collect();
+    let compressed = ProtocolId::StateSyncDirectSendCompressed
+        .to_bytes(&value)
+        .unwrap();
+    let uncompressed = ProtocolId::StateSyncDirectSend.to_bytes(&value).unwrap();
+    assert!(compressed.len() < uncompressed.len());
+
+    let decoded: Vec<u64> = ProtocolId::StateSyncDirectSendCompressed
+        .from_bytes(&compressed, MAX_FRAME_SIZE)
+        .unwrap();
+    assert_eq!(decoded, value);
+
+    // The compressed and uncompressed encodings are not interchangeable.
+    ProtocolId::StateSyncDirectSendCompressed
+        .from_bytes::<Vec<u64>>(&uncompressed, MAX_FRAME_SIZE)
+        .unwrap_err();
+}
+
+#[test]
+fn compressed_encoding_rejects_oversized_payloads() {
+    // A tiny payload claiming to decompress past the limit is rejected before
+    // anything is allocated.
+    let size = (MAX_FRAME_SIZE + 1) as u32;
+    let mut bomb = size.to_le_bytes().to_vec();
+    bomb.extend_from_slice(&[0u8; 16]);
+    ProtocolId::ConsensusDirectSendCompressed
+        .from_bytes::<Vec<u8>>(&bomb, MAX_FRAME_SIZE)
+        .unwrap_err();
+
+    // The limit is the one of the receiving network, a message within the default frame size
+    // is too large for a network configured with smaller frames.
+    let value = vec![0u8; 4096];
+    let compressed = ProtocolId::ConsensusDirectSendCompressed
+        .to_bytes(&value)
+        .unwrap();
+    ProtocolId::ConsensusDirectSendCompressed
+        .from_bytes::<Vec<u8>>(&compressed, 1024)
+        .unwrap_err();
+    let decoded: Vec<u8> = ProtocolId::ConsensusDirectSendCompressed
+        .from_bytes(&compressed, MAX_FRAME_SIZE)
+        .unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[test]
+fn compressed_protocols_are_negotiated() {
+    let compressed_hs = HandshakeMsg::from_supported(ProtocolIdSet::from_iter([
+        ProtocolId::ConsensusDirectSendCompressed,
+        ProtocolId::ConsensusDirectSendBcs,
+    ]));
+    let legacy_hs = HandshakeMsg::from_supported(ProtocolIdSet::from_iter([
+        ProtocolId::ConsensusDirectSendBcs,
+    ]));
+
+    // Both ends support compression.
+    let (_, common_protos) = compressed_hs.perform_handshake(&compressed_hs).unwrap();
+    assert!(common_protos.contains(ProtocolId::ConsensusDirectSendCompressed));
+
+    // An older peer that doesn't know the compressed protocol falls back to bcs.
+    let (_, common_protos) = compressed_hs.perform_handshake(&legacy_hs).unwrap();
+    assert_eq!(
+        common_protos,
+        ProtocolIdSet::from_iter([ProtocolId::ConsensusDirectSendBcs])
+    );
+}
+
+#[test]
+fn protocol_from_str() {
+    for protocol in ProtocolId::all() {
+        assert_eq!(protocol.as_str().parse::<ProtocolId>().unwrap(), *protocol);
+    }
+    "NotAProtocol".parse::<ProtocolId>().unwrap_err();
+}
+
+#[test]
+fn protocol_names_match_config() {
+    let names: Vec<_> = ProtocolId::all().iter().map(|p| p.as_str()).collect();
+    assert_eq!(names, diem_config::config::PROTOCOL_NAMES);
+
+    // Rate limits naming an unknown protocol fail the config verification instead of the
+    // network setup.
+    let mut config = diem_config::config::NetworkConfig::default();
+    let limit = diem_config::config::ProtocolRateLimitConfig {
+        byte_rate: config.max_frame_size,
+        byte_bucket_size: config.max_frame_size,
+    };
+    config
+        .outbound_protocol_rate_limits
+        .insert(ProtocolId::StateSyncDirectSend.as_str().to_string(), limit);
+    config.verify_outbound_protocol_rate_limits().unwrap();
+    config
+        .outbound_protocol_rate_limits
+        .insert("NotAProtocol".to_string(), limit);
+    config.verify_outbound_protocol_rate_limits().unwrap_err();
+}
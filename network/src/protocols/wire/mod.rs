@@ -8,5 +8,6 @@
 //! handshake protocol on an end-point, and that is advertised as part of its discovery
 //! NetworkAddress.
 
+pub mod compression;
 pub mod handshake;
 pub mod messaging;
@@ -80,10 +80,14 @@ fn setup_network<NetworkSender: NewNetworkSender, NetworkEvents: NewNetworkEvent
     let (connection_inbound_sender, connection_inbound_receiver) =
         crate::peer_manager::conn_notifs_channel::new();
     let network_sender = NetworkSender::new(
-        PeerManagerRequestSender::new(reqs_outbound_sender),
+        PeerManagerRequestSender::new(reqs_outbound_sender, crate::constants::MAX_FRAME_SIZE),
         ConnectionRequestSender::new(connection_outbound_sender),
     );
-    let network_events = NetworkEvents::new(reqs_inbound_receiver, connection_inbound_receiver);
+    let network_events = NetworkEvents::new(
+        reqs_inbound_receiver,
+        connection_inbound_receiver,
+        crate::constants::MAX_FRAME_SIZE,
+    );
 
     (
         (network_id, network_sender, network_events),
@@ -0,0 +1,130 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_transaction_builder::error_explain;
+use diem_types::{
+    transaction::TransactionStatus,
+    vm_status::{AbortLocation, KeptVMStatus, StatusCode, VMStatus},
+};
+use std::fmt;
+
+/// Why a simulated transaction didn't execute successfully.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FailureExplanation {
+    /// The transaction would be rejected without being committed, e.g., because its sequence
+    /// number is stale or the sender can't pay for the max gas amount.
+    Discarded(StatusCode),
+    /// The transaction would have to be retried, e.g., after a reconfiguration.
+    Retry,
+    /// The transaction ran out of gas.
+    OutOfGas,
+    /// Move code aborted. The reason is known for aborts raised by the Diem Framework.
+    MoveAbort {
+        location: AbortLocation,
+        abort_code: u64,
+        reason: Option<AbortReason>,
+    },
+    /// The execution failed with an arithmetic error, a failed cast and the like.
+    ExecutionFailure {
+        location: AbortLocation,
+        function: u16,
+        code_offset: u16,
+    },
+    MiscellaneousError(StatusCode),
+}
+
+/// The human-readable explanation of a Diem Framework abort code.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AbortReason {
+    pub category: String,
+    pub category_description: String,
+    pub reason: String,
+    pub reason_description: String,
+}
+
+impl FailureExplanation {
+    /// Explains the status of a transaction output, returning None if it executed successfully.
+    pub(crate) fn from_status(status: &TransactionStatus, vm_status: &VMStatus) -> Option<Self> {
+        let kept_status = match status {
+            TransactionStatus::Keep(kept_status) => kept_status,
+            TransactionStatus::Discard(status_code) => return Some(Self::Discarded(*status_code)),
+            TransactionStatus::Retry => return Some(Self::Retry),
+        };
+
+        Some(match kept_status {
+            KeptVMStatus::Executed => return None,
+            KeptVMStatus::OutOfGas => Self::OutOfGas,
+            KeptVMStatus::MoveAbort(location, abort_code) => {
+                let reason = match location {
+                    AbortLocation::Module(module_id) => {
+                        error_explain::get_explanation(module_id, *abort_code).map(|context| {
+                            AbortReason {
+                                category: context.category.code_name,
+                                category_description: context.category.code_description,
+                                reason: context.reason.code_name,
+                                reason_description: context.reason.code_description,
+                            }
+                        })
+                    }
+                    AbortLocation::Script => None,
+                };
+                Self::MoveAbort {
+                    location: location.clone(),
+                    abort_code: *abort_code,
+                    reason,
+                }
+            }
+            KeptVMStatus::ExecutionFailure {
+                location,
+                function,
+                code_offset,
+            } => Self::ExecutionFailure {
+                location: location.clone(),
+                function: *function,
+                code_offset: *code_offset,
+            },
+            KeptVMStatus::MiscellaneousError => Self::MiscellaneousError(vm_status.status_code()),
+        })
+    }
+}
+
+impl fmt::Display for FailureExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Discarded(status_code) => write!(f, "Discarded: {:?}", status_code),
+            Self::Retry => write!(f, "Needs to be retried"),
+            Self::OutOfGas => write!(f, "Out of gas"),
+            Self::MoveAbort {
+                location,
+                abort_code,
+                reason: Some(reason),
+            } => write!(
+                f,
+                "Move abort by {} - {} (code {} at {})\n{}\n{}",
+                reason.category,
+                reason.reason,
+                abort_code,
+                location,
+                reason.category_description,
+                reason.reason_description
+            ),
+            Self::MoveAbort {
+                location,
+                abort_code,
+                reason: None,
+            } => write!(f, "Move abort: code {} at {}", abort_code, location),
+            Self::ExecutionFailure {
+                location,
+                function,
+                code_offset,
+            } => write!(
+                f,
+                "Execution failure: {} function {} at code offset {}",
+                location, function, code_offset
+            ),
+            Self::MiscellaneousError(status_code) => {
+                write!(f, "Miscellaneous error: {:?}", status_code)
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Simulates transactions built with the Diem SDK before they are submitted.
+//!
+//! A [`Simulator`] executes a transaction with the `DiemVM` on top of a [`StateView`] without
+//! committing it, which tells whether the transaction would succeed, how much gas it would use
+//! and, if it fails, why (see [`FailureExplanation`]). When a
+//! [`TransactionFactory`](diem_sdk::transaction_builder::TransactionFactory) is configured with
+//! a [`GasEstimation`](diem_sdk::transaction_builder::GasEstimation), the simulator also
+//! replaces the guessed gas parameters with estimates.
+//!
+//! The state usually comes from a node through a [`RemoteStateView`], which checks every account
+//! state it fetches against a ledger info verified from a trusted waypoint. Tests can simulate
+//! offline on the state of a `FakeExecutor` instead.
+//!
+//! [`StateView`]: diem_state_view::StateView
+
+mod explanation;
+mod remote_state_view;
+mod simulator;
+
+#[cfg(test)]
+mod tests;
+
+pub use crate::{
+    explanation::{AbortReason, FailureExplanation},
+    remote_state_view::RemoteStateView,
+    simulator::{PreparedTransaction, SimulationResult, Simulator},
+};
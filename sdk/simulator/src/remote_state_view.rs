@@ -0,0 +1,127 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use diem_client::BlockingClient;
+use diem_infallible::RwLock;
+use diem_state_view::StateView;
+use diem_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_state::AccountState,
+    account_state_blob::AccountStateWithProof,
+    ledger_info::LedgerInfo,
+    proof::{AccumulatorConsistencyProof, TransactionAccumulatorSummary},
+    state_proof::StateProof,
+    transaction::Version,
+    trusted_state::TrustedState,
+    waypoint::Waypoint,
+};
+use std::{collections::HashMap, convert::TryFrom};
+
+/// A view of the state of a node at its latest ledger info, as of when the view was created. The
+/// ledger info is verified by ratcheting a trusted state forward, and every account state is
+/// fetched with a proof that is checked against it. Account states are cached, so each one is
+/// only fetched once.
+pub struct RemoteStateView {
+    client: BlockingClient,
+    trusted_state: TrustedState,
+    ledger_info: LedgerInfo,
+    account_states: RwLock<HashMap<AccountAddress, Option<AccountState>>>,
+}
+
+impl RemoteStateView {
+    /// Creates a view trusting only the given epoch waypoint.
+    pub fn from_waypoint(client: BlockingClient, waypoint: Waypoint) -> Result<Self> {
+        Self::new(client, TrustedState::from_epoch_waypoint(waypoint))
+    }
+
+    pub fn new(client: BlockingClient, trusted_state: TrustedState) -> Result<Self> {
+        let mut trusted_state = trusted_state;
+        loop {
+            let state_proof_view = client
+                .get_state_proof(trusted_state.version())?
+                .into_inner();
+            let state_proof = StateProof::try_from(&state_proof_view)?;
+
+            // Verifying from a waypoint needs an (untrusted) accumulator to start from
+            let initial_accumulator = if trusted_state.need_accumulator() {
+                let consistency_proof_view = client
+                    .get_accumulator_consistency_proof(None, Some(trusted_state.version()))?
+                    .into_inner();
+                let consistency_proof =
+                    AccumulatorConsistencyProof::try_from(&consistency_proof_view)?;
+                Some(TransactionAccumulatorSummary::try_from_genesis_proof(
+                    consistency_proof,
+                    trusted_state.version(),
+                )?)
+            } else {
+                None
+            };
+
+            let change =
+                trusted_state.verify_and_ratchet(&state_proof, initial_accumulator.as_ref())?;
+            if let Some(new_state) = change.new_state() {
+                trusted_state = new_state;
+            }
+
+            // The node returns a limited number of epoch changes at a time
+            if !state_proof.epoch_changes().more {
+                return Ok(Self {
+                    client,
+                    trusted_state,
+                    ledger_info: state_proof.latest_ledger_info().clone(),
+                    account_states: RwLock::new(HashMap::new()),
+                });
+            }
+        }
+    }
+
+    /// The version of the state this view reads.
+    pub fn version(&self) -> Version {
+        self.ledger_info.version()
+    }
+
+    /// The trusted state after verifying the latest ledger info, which can be persisted to
+    /// create later views without verifying the same epoch changes again.
+    pub fn trusted_state(&self) -> &TrustedState {
+        &self.trusted_state
+    }
+
+    fn fetch_account_state(&self, address: AccountAddress) -> Result<Option<AccountState>> {
+        let version = self.version();
+        let account_state_view = self
+            .client
+            .get_account_state_with_proof(address, Some(version), Some(version))?
+            .into_inner();
+        let account_state_with_proof = AccountStateWithProof::try_from(&account_state_view)?;
+        account_state_with_proof.verify(&self.ledger_info, version, address)?;
+
+        account_state_with_proof
+            .blob
+            .map(|blob| AccountState::try_from(&blob))
+            .transpose()
+    }
+}
+
+impl StateView for RemoteStateView {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        let address = access_path.address;
+        if let Some(account_state) = self.account_states.read().get(&address) {
+            return Ok(account_state
+                .as_ref()
+                .and_then(|state| state.get(&access_path.path).cloned()));
+        }
+
+        let account_state = self.fetch_account_state(address)?;
+        let value = account_state
+            .as_ref()
+            .and_then(|state| state.get(&access_path.path).cloned());
+        self.account_states.write().insert(address, account_state);
+        Ok(value)
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::FailureExplanation;
+use anyhow::{format_err, Result};
+use diem_sdk::{
+    transaction_builder::{GasEstimation, TransactionBuilder, TransactionFactory},
+    types::LocalAccount,
+};
+use diem_state_view::StateView;
+use diem_types::{
+    on_chain_config::{OnChainConfig, VMConfig},
+    transaction::{SignedTransaction, TransactionOutput, TransactionStatus},
+    vm_status::{KeptVMStatus, VMStatus},
+};
+use diem_vm::{data_cache::RemoteStorage, DiemVM};
+
+/// Executes transactions on top of a state view without committing them.
+pub struct Simulator<S> {
+    state_view: S,
+}
+
+impl<S: StateView> Simulator<S> {
+    pub fn new(state_view: S) -> Self {
+        Self { state_view }
+    }
+
+    pub fn state_view(&self) -> &S {
+        &self.state_view
+    }
+
+    /// Simulates the transaction `account` would sign with `builder`. The sequence number of
+    /// `account` is left untouched.
+    pub fn simulate(
+        &self,
+        account: &LocalAccount,
+        builder: TransactionBuilder,
+    ) -> SimulationResult {
+        self.simulate_signed_transaction(&sign(account, builder))
+    }
+
+    /// Simulates a signed transaction. Its signature is not checked.
+    pub fn simulate_signed_transaction(&self, txn: &SignedTransaction) -> SimulationResult {
+        let (vm_status, output) = DiemVM::simulate_signed_transaction(txn, &self.state_view);
+        SimulationResult { vm_status, output }
+    }
+
+    /// Simulates the transaction `account` would sign with `builder` and, if `factory` has gas
+    /// estimation enabled, replaces its gas parameters with estimates.
+    pub fn prepare(
+        &self,
+        factory: &TransactionFactory,
+        account: &LocalAccount,
+        builder: TransactionBuilder,
+    ) -> Result<PreparedTransaction> {
+        match factory.gas_estimation() {
+            Some(gas_estimation) => self.estimate_gas(account, builder, gas_estimation),
+            None => {
+                let simulation = self.simulate(account, builder.clone());
+                Ok(PreparedTransaction {
+                    builder,
+                    simulation,
+                })
+            }
+        }
+    }
+
+    /// Raises the gas unit price of the transaction to the on-chain minimum, then simulates it
+    /// with its max gas amount (capped by the on-chain maximum) and sets the max gas amount to
+    /// the gas used plus the safety margin of `gas_estimation`. The max gas amount is left
+    /// untouched if the simulation fails.
+    pub fn estimate_gas(
+        &self,
+        account: &LocalAccount,
+        builder: TransactionBuilder,
+        gas_estimation: GasEstimation,
+    ) -> Result<PreparedTransaction> {
+        let gas_constants = VMConfig::fetch_config(&RemoteStorage::new(&self.state_view))
+            .ok_or_else(|| format_err!("Unable to read the gas schedule from the state view"))?
+            .gas_schedule
+            .gas_constants;
+
+        let txn = sign(account, builder.clone());
+        let gas_unit_price = txn
+            .gas_unit_price()
+            .max(gas_constants.min_price_per_gas_unit.get());
+        let max_gas_amount = txn
+            .max_gas_amount()
+            .min(gas_constants.maximum_number_of_gas_units.get());
+        let builder = builder
+            .gas_unit_price(gas_unit_price)
+            .max_gas_amount(max_gas_amount);
+
+        let simulation = self.simulate(account, builder.clone());
+        let builder = if simulation.is_success() {
+            let estimate = gas_estimation.max_gas_amount(simulation.gas_used());
+            builder.max_gas_amount(estimate.min(max_gas_amount))
+        } else {
+            builder
+        };
+        Ok(PreparedTransaction {
+            builder,
+            simulation,
+        })
+    }
+}
+
+fn sign(account: &LocalAccount, builder: TransactionBuilder) -> SignedTransaction {
+    account.sign_transaction(
+        builder
+            .sender(account.address())
+            .sequence_number(account.sequence_number())
+            .build(),
+    )
+}
+
+/// The output of a simulated transaction.
+#[derive(Debug)]
+pub struct SimulationResult {
+    vm_status: VMStatus,
+    output: TransactionOutput,
+}
+
+impl SimulationResult {
+    pub fn vm_status(&self) -> &VMStatus {
+        &self.vm_status
+    }
+
+    pub fn output(&self) -> &TransactionOutput {
+        &self.output
+    }
+
+    pub fn status(&self) -> &TransactionStatus {
+        self.output.status()
+    }
+
+    pub fn gas_used(&self) -> u64 {
+        self.output.gas_used()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status() == &TransactionStatus::Keep(KeptVMStatus::Executed)
+    }
+
+    /// Explains why the transaction failed, returning None if it succeeded.
+    pub fn explain(&self) -> Option<FailureExplanation> {
+        FailureExplanation::from_status(self.status(), &self.vm_status)
+    }
+}
+
+/// A transaction builder whose gas parameters have been estimated, along with the simulation
+/// the estimates are based on.
+#[derive(Debug)]
+pub struct PreparedTransaction {
+    pub builder: TransactionBuilder,
+    pub simulation: SimulationResult,
+}
@@ -0,0 +1,165 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{FailureExplanation, Simulator};
+use diem_sdk::{
+    transaction_builder::{Currency, GasEstimation, TransactionFactory},
+    types::{chain_id::ChainId, LocalAccount},
+};
+use diem_types::{
+    transaction::TransactionStatus,
+    vm_status::{KeptVMStatus, StatusCode},
+};
+use language_e2e_tests::{account::AccountData, data_store::FakeDataStore, executor::FakeExecutor};
+
+const BALANCE: u64 = 1_000_000;
+
+struct TestEnv {
+    executor: FakeExecutor,
+    sender: LocalAccount,
+    receiver: AccountData,
+    factory: TransactionFactory,
+}
+
+impl TestEnv {
+    fn new() -> Self {
+        let mut executor = FakeExecutor::from_genesis_file();
+        let sender = executor.create_raw_account_data(BALANCE, 10);
+        let receiver = executor.create_raw_account_data(BALANCE, 10);
+        executor.add_account_data(&sender);
+        executor.add_account_data(&receiver);
+
+        let sender = LocalAccount::new(
+            *sender.address(),
+            sender.account().privkey.clone(),
+            sender.sequence_number(),
+        );
+        Self {
+            executor,
+            sender,
+            receiver,
+            factory: TransactionFactory::new(ChainId::test()),
+        }
+    }
+
+    /// Simulates offline on a snapshot of the executor's state.
+    fn simulator(&self) -> Simulator<FakeDataStore> {
+        Simulator::new(self.executor.get_state_view().clone())
+    }
+}
+
+#[test]
+fn simulation_matches_execution() {
+    let mut env = TestEnv::new();
+    let builder = env
+        .factory
+        .peer_to_peer(Currency::XUS, *env.receiver.address(), 100);
+
+    let simulation = env.simulator().simulate(&env.sender, builder.clone());
+    assert!(simulation.is_success());
+    assert_eq!(simulation.explain(), None);
+    assert!(simulation.gas_used() > 0);
+
+    let output = env
+        .executor
+        .execute_transaction(env.sender.sign_with_transaction_builder(builder));
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(KeptVMStatus::Executed)
+    );
+    assert_eq!(output.gas_used(), simulation.gas_used());
+}
+
+#[test]
+fn gas_estimation() {
+    let mut env = TestEnv::new();
+    let factory = env
+        .factory
+        .clone()
+        .with_gas_estimation(GasEstimation::default());
+    let builder = factory.peer_to_peer(Currency::XUS, *env.receiver.address(), 100);
+
+    let prepared = env
+        .simulator()
+        .prepare(&factory, &env.sender, builder)
+        .unwrap();
+    assert!(prepared.simulation.is_success());
+    let gas_used = prepared.simulation.gas_used();
+
+    let txn = env
+        .sender
+        .sign_with_transaction_builder(prepared.builder.clone());
+    assert_eq!(
+        txn.max_gas_amount(),
+        GasEstimation::default().max_gas_amount(gas_used)
+    );
+    assert!(txn.max_gas_amount() < factory.max_gas_amount());
+
+    // The estimate is enough for the transaction to execute
+    let output = env.executor.execute_transaction(txn);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(KeptVMStatus::Executed)
+    );
+    assert_eq!(output.gas_used(), gas_used);
+}
+
+#[test]
+fn explain_framework_abort() {
+    let env = TestEnv::new();
+    let factory = env
+        .factory
+        .clone()
+        .with_gas_estimation(GasEstimation::default());
+    let builder = factory.peer_to_peer(Currency::XUS, *env.receiver.address(), BALANCE + 1);
+
+    let prepared = env
+        .simulator()
+        .prepare(&factory, &env.sender, builder)
+        .unwrap();
+    assert!(!prepared.simulation.is_success());
+    match prepared.simulation.explain() {
+        Some(FailureExplanation::MoveAbort {
+            reason: Some(reason),
+            ..
+        }) => assert_eq!(reason.reason, "EINSUFFICIENT_BALANCE"),
+        explanation => panic!("Unexpected explanation: {:?}", explanation),
+    }
+
+    // The max gas amount isn't estimated from a failed simulation
+    let txn = env.sender.sign_transaction(
+        prepared
+            .builder
+            .sender(env.sender.address())
+            .sequence_number(env.sender.sequence_number())
+            .build(),
+    );
+    assert_eq!(txn.max_gas_amount(), factory.max_gas_amount());
+}
+
+#[test]
+fn explain_discarded_transaction() {
+    let mut env = TestEnv::new();
+    *env.sender.sequence_number_mut() = 0;
+    let builder = env
+        .factory
+        .peer_to_peer(Currency::XUS, *env.receiver.address(), 100);
+
+    let simulation = env.simulator().simulate(&env.sender, builder);
+    assert_eq!(
+        simulation.explain(),
+        Some(FailureExplanation::Discarded(
+            StatusCode::SEQUENCE_NUMBER_TOO_OLD
+        ))
+    );
+}
+
+#[test]
+fn gas_estimation_margin() {
+    let gas_estimation = GasEstimation::new(20);
+    assert_eq!(gas_estimation.max_gas_amount(100), 120);
+    assert_eq!(gas_estimation.max_gas_amount(101), 122);
+    assert_eq!(gas_estimation.max_gas_amount(0), 1);
+    assert_eq!(GasEstimation::new(0).max_gas_amount(7), 7);
+    assert_eq!(gas_estimation.max_gas_amount(u64::MAX), u64::MAX);
+}
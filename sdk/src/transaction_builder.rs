@@ -10,11 +10,12 @@ use crate::{
     },
 };
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{convert::TryFrom, fmt};
 
 pub use diem_transaction_builder::{experimental_stdlib, stdlib};
 use diem_types::transaction::{ChangeSet, ModuleBundle, Script, ScriptFunction, WriteSetPayload};
 
+#[derive(Clone, Debug)]
 pub struct TransactionBuilder {
     sender: Option<AccountAddress>,
     sequence_number: Option<u64>,
@@ -85,6 +86,7 @@ pub struct TransactionFactory {
     transaction_expiration_time: u64,
     chain_id: ChainId,
     diem_version: u64,
+    gas_estimation: Option<GasEstimation>,
 }
 
 impl TransactionFactory {
@@ -96,6 +98,7 @@ impl TransactionFactory {
             transaction_expiration_time: 100,
             chain_id,
             diem_version: 2,
+            gas_estimation: None,
         }
     }
 
@@ -129,6 +132,34 @@ impl TransactionFactory {
         self
     }
 
+    /// Asks simulators to replace the gas parameters of transactions built by this factory with
+    /// estimates from a simulated execution. The max gas amount configured on the factory is
+    /// then only used as an upper bound.
+    pub fn with_gas_estimation(mut self, gas_estimation: GasEstimation) -> Self {
+        self.gas_estimation = Some(gas_estimation);
+        self
+    }
+
+    pub fn max_gas_amount(&self) -> u64 {
+        self.max_gas_amount
+    }
+
+    pub fn gas_unit_price(&self) -> u64 {
+        self.gas_unit_price
+    }
+
+    pub fn gas_currency(&self) -> Currency {
+        self.gas_currency
+    }
+
+    pub fn chain_id(&self) -> ChainId {
+        self.chain_id
+    }
+
+    pub fn gas_estimation(&self) -> Option<GasEstimation> {
+        self.gas_estimation
+    }
+
     pub fn payload(&self, payload: TransactionPayload) -> TransactionBuilder {
         self.transaction_builder(payload)
     }
@@ -582,6 +613,39 @@ impl TransactionFactory {
     }
 }
 
+/// How a simulator picks the max gas amount of a transaction from the gas it used when simulated.
+/// The safety margin covers state changes between the simulation and the execution, e.g., a
+/// resource growing in the meantime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasEstimation {
+    safety_margin_percent: u64,
+}
+
+impl GasEstimation {
+    pub fn new(safety_margin_percent: u64) -> Self {
+        Self {
+            safety_margin_percent,
+        }
+    }
+
+    pub fn safety_margin_percent(&self) -> u64 {
+        self.safety_margin_percent
+    }
+
+    /// Returns `gas_used` increased by the safety margin, rounded up.
+    pub fn max_gas_amount(&self, gas_used: u64) -> u64 {
+        let percent = 100 + self.safety_margin_percent as u128;
+        let with_margin = (gas_used as u128 * percent + 99) / 100;
+        u64::try_from(with_margin).unwrap_or(u64::MAX).max(1)
+    }
+}
+
+impl Default for GasEstimation {
+    fn default() -> Self {
+        Self::new(20)
+    }
+}
+
 pub struct DualAttestationMessage {
     message: Box<[u8]>,
 }
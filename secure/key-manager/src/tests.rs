@@ -417,7 +417,8 @@ fn setup_diem_interface_and_json_server(
 
     // Provide a VMValidator to the runtime.
     server.spawn(async move {
-        while let Some(MempoolClientRequest::SubmitTransaction(txn, cb)) = mp_events.next().await {
+        while let Some(MempoolClientRequest::SubmitTransaction(txn, _, cb)) = mp_events.next().await
+        {
             let vm_status = MockVMValidator.validate_transaction(txn).unwrap().status();
             let result = if vm_status.is_some() {
                 (MempoolStatus::new(MempoolStatusCode::VmError), vm_status)
@@ -0,0 +1,278 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{from_base64, to_base64, CryptoKVStorage, Error, GetResponse, KVStorage};
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead, Payload},
+    Aes256Gcm,
+};
+use diem_temppath::TempPath;
+use diem_time_service::{TimeService, TimeServiceTrait};
+use hmac::Hmac;
+use rand::{rngs::OsRng, RngCore};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// The version of the sealed file format written by this implementation.
+const FORMAT_VERSION: u32 = 1;
+/// The number of PBKDF2 rounds used to derive the encryption key of new files.
+const KEY_DERIVATION_ROUNDS: u32 = 100_000;
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const SALT_LENGTH: usize = 32;
+/// Authenticated alongside every ciphertext so that it can't be mistaken for another format.
+const ASSOCIATED_DATA: &[u8] = b"diem-secure-storage::encrypted-on-disk";
+
+/// EncryptedOnDiskStorage is a key value store persisted to a single file on the local filesystem,
+/// like OnDiskStorage, except that the file is sealed with AES-256-GCM under a key derived from a
+/// passphrase using PBKDF2-HMAC-SHA256. The passphrase can just as well be the contents of a key
+/// file. Every write re-encrypts the whole store under a fresh nonce and atomically replaces the
+/// file, syncing it to disk first. Like OnDiskStorage, it is intended for single threads (or must
+/// be wrapped by a Arc<RwLock<>>), provides no permission checks and copies key material in memory.
+/// It protects secrets at rest for deployments without Vault.
+pub struct EncryptedOnDiskStorage {
+    file_path: PathBuf,
+    file_dir: PathBuf,
+    temp_path: TempPath,
+    passphrase: Vec<u8>,
+    key: SealingKey,
+    time_service: TimeService,
+}
+
+impl EncryptedOnDiskStorage {
+    /// Opens the store at `file_path`, creating it if it doesn't exist. Fails if an existing file
+    /// can't be read or isn't a sealed store, or if a new one can't be written.
+    pub fn new(file_path: PathBuf, passphrase: Vec<u8>) -> Result<Self, Error> {
+        Self::new_with_time_service(file_path, passphrase, TimeService::real())
+    }
+
+    fn new_with_time_service(
+        file_path: PathBuf,
+        passphrase: Vec<u8>,
+        time_service: TimeService,
+    ) -> Result<Self, Error> {
+        // The parent will be one when only a filename is supplied. Therefore use the current
+        // working directory provided by PathBuf::new().
+        let file_dir = file_path
+            .parent()
+            .map_or(PathBuf::new(), |p| p.to_path_buf());
+
+        // Keep the salt of an existing store, so the key only has to be derived once
+        let existing = read_sealed(&file_path)?;
+        let key = match &existing {
+            Some(sealed) => SealingKey::derive(&passphrase, sealed.salt.clone(), sealed.rounds),
+            None => SealingKey::generate(&passphrase),
+        };
+
+        let storage = Self {
+            file_path,
+            temp_path: TempPath::new_with_temp_dir(file_dir.clone()),
+            file_dir,
+            passphrase,
+            key,
+            time_service,
+        };
+        if existing.is_none() {
+            storage.write(&HashMap::new())?;
+        }
+        Ok(storage)
+    }
+
+    fn read(&self) -> Result<HashMap<String, Value>, Error> {
+        let sealed = match read_sealed(&self.file_path)? {
+            Some(sealed) => sealed,
+            None => return Ok(HashMap::new()),
+        };
+
+        // Another writer may have replaced the file with one sealed under a different salt
+        let plaintext = if sealed.salt == self.key.salt && sealed.rounds == self.key.rounds {
+            self.key.open(&sealed)?
+        } else {
+            SealingKey::derive(&self.passphrase, sealed.salt.clone(), sealed.rounds)
+                .open(&sealed)?
+        };
+        let data = serde_json::from_slice(&plaintext)?;
+        Ok(data)
+    }
+
+    fn write(&self, data: &HashMap<String, Value>) -> Result<(), Error> {
+        let plaintext = serde_json::to_vec(data)?;
+        let contents = serde_json::to_vec(&self.key.seal(&plaintext)?)?;
+
+        let mut file = create_private_file(self.temp_path.path())?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        fs::rename(&self.temp_path, &self.file_path)?;
+        sync_dir(&self.file_dir)?;
+        Ok(())
+    }
+}
+
+impl KVStorage for EncryptedOnDiskStorage {
+    fn available(&self) -> Result<(), Error> {
+        // Verifies that the file can be decrypted with the configured passphrase
+        self.read().map(|_| ())
+    }
+
+    fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        let mut data = self.read()?;
+        data.remove(key)
+            .ok_or_else(|| Error::KeyNotSet(key.to_string()))
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.into()))
+    }
+
+    fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        let now = self.time_service.now_secs();
+        let mut data = self.read()?;
+        data.insert(
+            key.to_string(),
+            serde_json::to_value(&GetResponse::new(value, now))?,
+        );
+        self.write(&data)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.write(&HashMap::new())
+    }
+}
+
+impl CryptoKVStorage for EncryptedOnDiskStorage {}
+
+/// The on-disk representation of the store.
+#[derive(Deserialize, Serialize)]
+struct SealedStorage {
+    version: u32,
+    rounds: u32,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    salt: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    nonce: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    ciphertext: Vec<u8>,
+}
+
+/// An encryption key along with the salt and number of rounds it was derived with.
+struct SealingKey {
+    cipher: Aes256Gcm,
+    salt: Vec<u8>,
+    rounds: u32,
+}
+
+impl SealingKey {
+    fn generate(passphrase: &[u8]) -> Self {
+        let mut salt = vec![0; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        Self::derive(passphrase, salt, KEY_DERIVATION_ROUNDS)
+    }
+
+    fn derive(passphrase: &[u8], salt: Vec<u8>, rounds: u32) -> Self {
+        let mut key = [0u8; KEY_LENGTH];
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase, &salt, rounds, &mut key);
+        Self {
+            cipher: Aes256Gcm::new(GenericArray::from_slice(&key)),
+            salt,
+            rounds,
+        }
+    }
+
+    fn seal(&self, plaintext: &[u8]) -> Result<SealedStorage, Error> {
+        let mut nonce = vec![0; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut nonce);
+        let payload = Payload {
+            msg: plaintext,
+            aad: ASSOCIATED_DATA,
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(GenericArray::from_slice(&nonce), payload)
+            .map_err(|_| Error::InternalError("Unable to encrypt storage".into()))?;
+
+        Ok(SealedStorage {
+            version: FORMAT_VERSION,
+            rounds: self.rounds,
+            salt: self.salt.clone(),
+            nonce,
+            ciphertext,
+        })
+    }
+
+    fn open(&self, sealed: &SealedStorage) -> Result<Vec<u8>, Error> {
+        if sealed.nonce.len() != NONCE_LENGTH {
+            return Err(Error::SerializationError(format!(
+                "Invalid nonce length: {}",
+                sealed.nonce.len()
+            )));
+        }
+        let payload = Payload {
+            msg: &sealed.ciphertext,
+            aad: ASSOCIATED_DATA,
+        };
+        self.cipher
+            .decrypt(GenericArray::from_slice(&sealed.nonce), payload)
+            .map_err(|_| {
+                Error::InternalError(
+                    "Unable to decrypt storage, the passphrase may be incorrect".into(),
+                )
+            })
+    }
+}
+
+/// Reads the sealed store, returning None if the file doesn't exist or is empty.
+fn read_sealed(file_path: &Path) -> Result<Option<SealedStorage>, Error> {
+    let mut file = match File::open(file_path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    if contents.is_empty() {
+        return Ok(None);
+    }
+
+    let sealed: SealedStorage = serde_json::from_slice(&contents)?;
+    if sealed.version != FORMAT_VERSION {
+        return Err(Error::SerializationError(format!(
+            "Unsupported storage format version: {}",
+            sealed.version
+        )));
+    }
+    Ok(Some(sealed))
+}
+
+/// Creates (or truncates) a file that only its owner can read and write.
+fn create_private_file(path: &Path) -> Result<File, Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    Ok(options.open(path)?)
+}
+
+/// Syncs a directory so that a rename within it survives a crash.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<(), Error> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<(), Error> {
+    Ok(())
+}
@@ -0,0 +1,55 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{tests::suite, EncryptedOnDiskStorage, Error, KVStorage, Storage};
+use diem_temppath::TempPath;
+use std::fs;
+
+const PASSPHRASE: &[u8] = b"correct horse battery staple";
+
+#[test]
+fn encrypted_on_disk() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage =
+        Storage::from(EncryptedOnDiskStorage::new(path_buf, PASSPHRASE.to_vec()).unwrap());
+    suite::execute_all_storage_tests(&mut storage);
+}
+
+#[test]
+fn encrypted_on_disk_persistence() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE.to_vec()).unwrap();
+    storage
+        .set("secret", "plaintext value".to_string())
+        .unwrap();
+
+    // The value must not be readable from the file itself
+    let contents = fs::read_to_string(&path_buf).unwrap();
+    assert!(!contents.contains("secret"));
+    assert!(!contents.contains("plaintext value"));
+
+    // Reopening the file with the same passphrase recovers the value
+    let storage = EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE.to_vec()).unwrap();
+    storage.available().unwrap();
+    assert_eq!(
+        storage.get::<String>("secret").unwrap().value,
+        "plaintext value"
+    );
+
+    // But a different passphrase can't decrypt it
+    let storage = EncryptedOnDiskStorage::new(path_buf, b"wrong passphrase".to_vec()).unwrap();
+    assert!(matches!(storage.available(), Err(Error::InternalError(_))));
+    assert!(matches!(
+        storage.get::<String>("secret"),
+        Err(Error::InternalError(_))
+    ));
+}
+
+#[test]
+fn encrypted_on_disk_rejects_invalid_files() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    fs::write(&path_buf, "not a sealed store").unwrap();
+    assert!(EncryptedOnDiskStorage::new(path_buf, PASSPHRASE.to_vec()).is_err());
+}
@@ -1,6 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+mod encrypted_on_disk;
 mod github;
 mod in_memory;
 mod on_disk;
@@ -10,7 +10,11 @@ use crate::{
     Capability, CryptoStorage, Error, Identity, KVStorage, Namespaced, Permission, Policy, Storage,
 };
 use diem_crypto::{test_utils::TestDiemCrypto, Signature};
-use diem_vault_client::dev::{self, ROOT_TOKEN};
+use diem_vault_client::{
+    dev::{self, ROOT_TOKEN},
+    mock::{self, Failure, MockVault},
+};
+use std::time::Duration;
 
 /// VaultStorage namespace constants
 const VAULT_NAMESPACE_1: &str = "namespace_1";
@@ -49,13 +53,11 @@ const VAULT_TESTS: &[fn()] = &[
 ];
 
 /// A test for verifying VaultStorage properly implements the DiemSecureStorage API and enforces
-/// strict separation between unique namespaces. This test depends on running Vault, which can be
-/// done by using the provided docker run script in `docker/vault/run.sh`
+/// strict separation between unique namespaces. This test runs against Vault if it is installed
+/// (or started by the provided docker run script in `docker/vault/run.sh`) and otherwise against
+/// the mock Vault.
 #[test]
 fn execute_storage_tests_vault() {
-    if dev::test_host_safe().is_none() {
-        return;
-    }
     let mut storage = create_vault();
     storage.reset_and_clear().unwrap();
 
@@ -65,6 +67,68 @@ fn execute_storage_tests_vault() {
     }
 }
 
+/// Verifies how VaultStorage surfaces Vault failures. This needs to seal Vault, inject errors and
+/// move time forward, so it always runs against its own mock Vault.
+#[test]
+fn test_vault_failures() {
+    let vault = MockVault::start().unwrap();
+    let storage =
+        create_vault_storage_with_host(vault.host().into(), ROOT_TOKEN.into(), None, true);
+    let mut policy = VaultPolicy::new(storage, None);
+    policy.set(ANYONE, 1).unwrap();
+    policy.available().unwrap();
+
+    // A sealed Vault is unavailable and rejects all requests
+    vault.seal();
+    assert!(matches!(policy.available(), Err(Error::InternalError(_))));
+    assert!(matches!(
+        policy.get::<u64>(ANYONE),
+        Err(Error::InternalError(_))
+    ));
+    vault.unseal();
+    assert_eq!(policy.get::<u64>(ANYONE).unwrap().value, 1);
+
+    // A 403 is a permission error, while server errors are internal and transient
+    vault.fail_requests_to("secret/", Failure::PermissionDenied, 1);
+    assert_eq!(policy.get::<u64>(ANYONE), Err(Error::PermissionDenied));
+    vault.fail_requests(Failure::ServerError(500), 2);
+    assert!(matches!(policy.available(), Err(Error::InternalError(_))));
+    assert!(matches!(
+        policy.set(ANYONE, 2),
+        Err(Error::InternalError(_))
+    ));
+    policy.set(ANYONE, 2).unwrap();
+    assert_eq!(policy.get::<u64>(ANYONE).unwrap().value, 2);
+
+    // Tokens expire unless VaultStorage is configured to renew them
+    let reader = Policy::new(vec![Permission::new(
+        Identity::User(READER.into()),
+        vec![Capability::Read],
+    )]);
+    policy
+        .set_policies(ANYONE, &VaultEngine::KVSecrets, &reader)
+        .unwrap();
+    vault.set_token_ttl(Duration::from_secs(60));
+    let expiring = create_vault_storage_with_host(
+        vault.host().into(),
+        policy.create_token(vec![READER]).unwrap(),
+        None,
+        true,
+    );
+    let renewing = create_vault_storage_with_host(
+        vault.host().into(),
+        policy.create_token(vec![READER]).unwrap(),
+        Some(3600),
+        true,
+    );
+    assert_eq!(expiring.get::<u64>(ANYONE).unwrap().value, 2);
+    assert_eq!(renewing.get::<u64>(ANYONE).unwrap().value, 2);
+
+    vault.advance_time(Duration::from_secs(120));
+    assert_eq!(expiring.get::<u64>(ANYONE), Err(Error::PermissionDenied));
+    assert_eq!(renewing.get::<u64>(ANYONE).unwrap().value, 2);
+}
+
 /// Runs the test suite on a VaultStorage instance that does not use distinct namespaces
 fn test_suite_no_namespaces() {
     let mut storage = Storage::from(create_vault());
@@ -93,16 +157,22 @@ fn create_vault() -> VaultStorage {
     create_vault_storage(ROOT_TOKEN.into(), None, true)
 }
 
+/// Returns the host of a real Vault if one is available, or of the shared mock Vault otherwise.
+fn vault_host() -> String {
+    dev::test_host_safe().unwrap_or_else(mock::test_host)
+}
+
 fn create_vault_storage(token: String, renew_ttl_secs: Option<u32>, use_cas: bool) -> VaultStorage {
-    VaultStorage::new(
-        dev::test_host(),
-        token,
-        None,
-        renew_ttl_secs,
-        use_cas,
-        None,
-        None,
-    )
+    create_vault_storage_with_host(vault_host(), token, renew_ttl_secs, use_cas)
+}
+
+fn create_vault_storage_with_host(
+    host: String,
+    token: String,
+    renew_ttl_secs: Option<u32>,
+    use_cas: bool,
+) -> VaultStorage {
+    VaultStorage::new(host, token, None, renew_ttl_secs, use_cas, None, None)
 }
 
 fn create_vault_policy_with_namespace(namespace: Option<String>) -> VaultPolicy {
@@ -22,6 +22,8 @@ use ureq::Response;
 
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
 
 /// The max number of key versions held in vault at any one time.
 /// Keys are trimmed in FIFO order.
@@ -0,0 +1,1139 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An in-process mock of the subset of the Vault HTTP API used by [`Client`]: the KV secrets
+//! engine (version 2), the Transit secrets engine for ed25519 keys, ACL policies and tokens. It
+//! lets the Vault tests run without a Vault binary or network access.
+//!
+//! Beyond modeling Vault's behavior (policy enforcement, check-and-set, key versions and token
+//! TTLs), the mock can simulate failures: it can be sealed, and it can fail requests with a 403
+//! or a 5xx status. Token expiry is driven by a virtual clock, see [`MockVault::advance_time`].
+
+use crate::{dev::ROOT_TOKEN, Capability, Client, KeyBackup, PathPolicy, PolicyPaths};
+use diem_crypto::{
+    ed25519::{Ed25519PrivateKey, ED25519_PRIVATE_KEY_LENGTH},
+    PrivateKey, SigningKey, Uniform,
+};
+use diem_infallible::Mutex;
+use once_cell::sync::Lazy;
+use rand::rngs::OsRng;
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::TryFrom,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Vault's default token TTL (768 hours).
+const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(768 * 60 * 60);
+const DEFAULT_POLICY: &str = "default";
+const ROOT_POLICY: &str = "root";
+
+static MOCK_VAULT: Lazy<MockVault> =
+    Lazy::new(|| MockVault::start().expect("Unable to start the mock Vault"));
+
+/// Returns the host of a mock Vault shared by all tests in the process, starting it on first use.
+/// Tests that inject failures or change the clock should start their own [`MockVault`].
+pub fn test_host() -> String {
+    MOCK_VAULT.host().to_string()
+}
+
+/// A failure the mock returns instead of handling a request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Failure {
+    /// Responds with 403, as Vault does for missing, expired or unauthorized tokens.
+    PermissionDenied,
+    /// Responds with the given 5xx status code.
+    ServerError(u16),
+}
+
+/// An in-process Vault server. The server is stopped when this is dropped.
+pub struct MockVault {
+    addr: SocketAddr,
+    host: String,
+    shutdown: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockVault {
+    /// Starts a new, unsealed mock Vault on an ephemeral local port, with `ROOT_TOKEN` as its
+    /// root token.
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))?;
+        let addr = listener.local_addr()?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let state = Arc::new(Mutex::new(State::new()));
+
+        let accept_shutdown = shutdown.clone();
+        let accept_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accept_shutdown.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let shutdown = accept_shutdown.clone();
+                    let state = accept_state.clone();
+                    thread::spawn(move || serve_connection(stream, state, shutdown));
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            host: format!("http://{}", addr),
+            shutdown,
+            state,
+        })
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn root_token(&self) -> &str {
+        ROOT_TOKEN
+    }
+
+    pub fn client(&self) -> Client {
+        Client::new(
+            self.host().to_string(),
+            self.root_token().to_string(),
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Seals the vault: the seal status reports it as sealed and every other request fails with
+    /// a 503 until it is unsealed.
+    pub fn seal(&self) {
+        self.state.lock().sealed = true;
+    }
+
+    pub fn unseal(&self) {
+        self.state.lock().sealed = false;
+    }
+
+    /// Sets the TTL of tokens created (or renewed without an increment) from now on.
+    pub fn set_token_ttl(&self, ttl: Duration) {
+        self.state.lock().token_ttl = ttl;
+    }
+
+    /// Moves the mock's clock forward, expiring any tokens whose TTL has run out.
+    pub fn advance_time(&self, duration: Duration) {
+        self.state.lock().clock_offset += duration;
+    }
+
+    /// Fails the next `count` requests with the given failure.
+    pub fn fail_requests(&self, failure: Failure, count: usize) {
+        self.inject_failure(None, failure, count);
+    }
+
+    /// Fails the next `count` requests whose path (without the `/v1/` prefix) starts with
+    /// `path_prefix`, e.g., `auth/token/renew-self` or `secret/data/`.
+    pub fn fail_requests_to(&self, path_prefix: &str, failure: Failure, count: usize) {
+        self.inject_failure(Some(path_prefix.to_string()), failure, count);
+    }
+
+    fn inject_failure(&self, path_prefix: Option<String>, failure: Failure, count: usize) {
+        if count == 0 {
+            return;
+        }
+        self.state.lock().failures.push(InjectedFailure {
+            path_prefix,
+            failure,
+            remaining: count,
+        });
+    }
+}
+
+impl Drop for MockVault {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        // Wake up the accept loop so that it notices the shutdown
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+struct InjectedFailure {
+    path_prefix: Option<String>,
+    failure: Failure,
+    remaining: usize,
+}
+
+struct Token {
+    policies: Vec<String>,
+    /// None for tokens that never expire, like the root token.
+    expires_at: Option<Instant>,
+}
+
+struct Secret {
+    data: Value,
+    created_time: String,
+    version: u32,
+}
+
+struct KeyVersion {
+    private_key: Ed25519PrivateKey,
+    creation_time: String,
+}
+
+struct TransitKey {
+    versions: BTreeMap<u32, KeyVersion>,
+    latest_version: u32,
+    min_decryption_version: u32,
+    min_available_version: u32,
+    exportable: bool,
+    deletion_allowed: bool,
+}
+
+impl TransitKey {
+    fn new(exportable: bool) -> Self {
+        Self {
+            versions: BTreeMap::new(),
+            latest_version: 0,
+            min_decryption_version: 1,
+            min_available_version: 0,
+            exportable,
+            deletion_allowed: false,
+        }
+    }
+
+    fn add_version(&mut self, private_key: Ed25519PrivateKey, creation_time: String) {
+        self.latest_version += 1;
+        self.versions.insert(
+            self.latest_version,
+            KeyVersion {
+                private_key,
+                creation_time,
+            },
+        );
+    }
+}
+
+/// An HTTP request, reduced to the parts the mock looks at.
+struct Request {
+    method: String,
+    /// The path without the `/v1/` prefix and query string.
+    path: String,
+    token: Option<String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: Option<Value>,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self {
+            status: 200,
+            body: Some(body),
+        }
+    }
+
+    fn no_content() -> Self {
+        Self {
+            status: 204,
+            body: None,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: Some(json!({ "errors": [message] })),
+        }
+    }
+
+    fn bad_request(message: &str) -> Self {
+        Self::error(400, message)
+    }
+
+    fn permission_denied() -> Self {
+        Self::error(403, "permission denied")
+    }
+
+    fn not_found() -> Self {
+        Self {
+            status: 404,
+            body: Some(json!({ "errors": [] })),
+        }
+    }
+}
+
+struct State {
+    sealed: bool,
+    clock_offset: Duration,
+    token_ttl: Duration,
+    next_token: u64,
+    tokens: HashMap<String, Token>,
+    policies: BTreeMap<String, PolicyPaths>,
+    secrets: BTreeMap<String, Secret>,
+    keys: BTreeMap<String, TransitKey>,
+    failures: Vec<InjectedFailure>,
+}
+
+impl State {
+    fn new() -> Self {
+        let mut tokens = HashMap::new();
+        tokens.insert(
+            ROOT_TOKEN.to_string(),
+            Token {
+                policies: vec![ROOT_POLICY.to_string()],
+                expires_at: None,
+            },
+        );
+
+        // Like Vault's default policy, let every token manage itself
+        let mut default_policy = PolicyPaths::default();
+        for path in &["auth/token/renew-self", "auth/token/revoke-self"] {
+            default_policy.path.insert(
+                path.to_string(),
+                PathPolicy {
+                    capabilities: vec![Capability::Update],
+                },
+            );
+        }
+        let mut policies = BTreeMap::new();
+        policies.insert(DEFAULT_POLICY.to_string(), default_policy);
+
+        Self {
+            sealed: false,
+            clock_offset: Duration::from_secs(0),
+            token_ttl: DEFAULT_TOKEN_TTL,
+            next_token: 0,
+            tokens,
+            policies,
+            secrets: BTreeMap::new(),
+            keys: BTreeMap::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    fn now(&self) -> Instant {
+        Instant::now() + self.clock_offset
+    }
+
+    fn timestamp(&self) -> String {
+        let offset = chrono::Duration::from_std(self.clock_offset).expect("Offset is too large");
+        (chrono::Utc::now() + offset).to_rfc3339()
+    }
+
+    fn handle(&mut self, request: Request) -> Response {
+        if let Some(response) = self.injected_failure(&request.path) {
+            return response;
+        }
+        if request.path == "sys/seal-status" {
+            // Seal status is the one request that doesn't need a token
+            return Response::ok(json!({ "type": "shamir", "sealed": self.sealed }));
+        }
+        if self.sealed {
+            return Response::error(503, "Vault is sealed");
+        }
+
+        let policies = match self.token_policies(request.token.as_deref()) {
+            Some(policies) => policies,
+            None => return Response::permission_denied(),
+        };
+        let capability = self.required_capability(&request);
+        if !self.allowed(&policies, &request.path, capability) {
+            return Response::permission_denied();
+        }
+
+        let body = if request.body.is_empty() {
+            Value::Null
+        } else {
+            match serde_json::from_slice(&request.body) {
+                Ok(body) => body,
+                Err(e) => return Response::bad_request(&e.to_string()),
+            }
+        };
+        self.route(&request, body)
+    }
+
+    fn injected_failure(&mut self, path: &str) -> Option<Response> {
+        let index = self.failures.iter().position(|injected| {
+            injected
+                .path_prefix
+                .as_ref()
+                .map_or(true, |prefix| path.starts_with(prefix.as_str()))
+        })?;
+        let injected = &mut self.failures[index];
+        let failure = injected.failure;
+        injected.remaining -= 1;
+        if injected.remaining == 0 {
+            self.failures.remove(index);
+        }
+
+        Some(match failure {
+            Failure::PermissionDenied => Response::permission_denied(),
+            Failure::ServerError(status) => Response::error(status, "injected failure"),
+        })
+    }
+
+    /// Returns the policies of a valid token, removing it if it has expired.
+    fn token_policies(&mut self, token: Option<&str>) -> Option<Vec<String>> {
+        let token = token?;
+        let now = self.now();
+        let expired = self
+            .tokens
+            .get(token)?
+            .expires_at
+            .map_or(false, |expires_at| expires_at <= now);
+        if expired {
+            self.tokens.remove(token);
+            return None;
+        }
+        self.tokens.get(token).map(|token| token.policies.clone())
+    }
+
+    fn required_capability(&self, request: &Request) -> Capability {
+        match request.method.as_str() {
+            "GET" => Capability::Read,
+            "LIST" => Capability::List,
+            "DELETE" => Capability::Delete,
+            _ => {
+                // Writes to something that doesn't exist yet need create rather than update
+                let segments: Vec<_> = request.path.split('/').collect();
+                let exists = match segments.as_slice() {
+                    ["secret", "data", ..] => self
+                        .secrets
+                        .contains_key(&request.path["secret/data/".len()..]),
+                    ["transit", "keys", name] => self.keys.contains_key(*name),
+                    _ => true,
+                };
+                if exists {
+                    Capability::Update
+                } else {
+                    Capability::Create
+                }
+            }
+        }
+    }
+
+    /// Applies Vault's ACL rules: the most specific matching path wins (an exact match over any
+    /// glob, and longer globs over shorter ones), and deny overrides all other capabilities.
+    fn allowed(&self, policies: &[String], path: &str, capability: Capability) -> bool {
+        if policies.iter().any(|policy| policy == ROOT_POLICY) {
+            return true;
+        }
+
+        let mut best_match: Option<(usize, Vec<Capability>)> = None;
+        let rules = policies
+            .iter()
+            .filter_map(|policy| self.policies.get(policy))
+            .flat_map(|policy| policy.path.iter());
+        for (pattern, rule) in rules {
+            let specificity = if pattern == path {
+                usize::MAX
+            } else if let Some(prefix) = pattern.strip_suffix('*') {
+                if !path.starts_with(prefix) {
+                    continue;
+                }
+                prefix.len()
+            } else {
+                continue;
+            };
+
+            match &mut best_match {
+                Some((best, capabilities)) if *best == specificity => {
+                    capabilities.extend(rule.capabilities.iter().cloned())
+                }
+                Some((best, _)) if *best > specificity => (),
+                _ => best_match = Some((specificity, rule.capabilities.clone())),
+            }
+        }
+
+        best_match.map_or(false, |(_, capabilities)| {
+            !capabilities.contains(&Capability::Deny) && capabilities.contains(&capability)
+        })
+    }
+
+    fn route(&mut self, request: &Request, body: Value) -> Response {
+        let method = request.method.as_str();
+        let segments: Vec<_> = request.path.split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["sys", "policy"]) | ("LIST", ["sys", "policy"]) => self.list_policies(),
+            ("GET", ["sys", "policy", ..]) => self.read_policy(&segments[2..].join("/")),
+            ("POST", ["sys", "policy", ..]) | ("PUT", ["sys", "policy", ..]) => {
+                self.write_policy(&segments[2..].join("/"), body)
+            }
+            ("DELETE", ["sys", "policy", ..]) => self.delete_policy(&segments[2..].join("/")),
+
+            ("POST", ["auth", "token", "create"]) => self.create_token(body),
+            ("POST", ["auth", "token", "renew-self"]) => {
+                self.renew_token(request.token.as_deref().unwrap_or_default(), body)
+            }
+            ("POST", ["auth", "token", "revoke-self"]) => {
+                self.tokens
+                    .remove(request.token.as_deref().unwrap_or_default());
+                Response::no_content()
+            }
+
+            ("GET", ["secret", "data", ..]) => self.read_secret(&segments[2..].join("/")),
+            ("POST", ["secret", "data", ..]) | ("PUT", ["secret", "data", ..]) => {
+                self.write_secret(&segments[2..].join("/"), body)
+            }
+            ("LIST", ["secret", "metadata", ..]) => self.list_secrets(&segments[2..].join("/")),
+            ("DELETE", ["secret", "metadata", ..]) => {
+                self.secrets.remove(&segments[2..].join("/"));
+                Response::no_content()
+            }
+
+            ("LIST", ["transit", "keys"]) | ("LIST", ["transit", "keys", ""]) => self.list_keys(),
+            ("GET", ["transit", "keys", name]) => self.read_key(name),
+            ("POST", ["transit", "keys", name]) => self.create_key(name, body),
+            ("DELETE", ["transit", "keys", name]) => self.delete_key(name),
+            ("POST", ["transit", "keys", name, "config"]) => self.configure_key(name, body),
+            ("POST", ["transit", "keys", name, "rotate"]) => self.rotate_key(name),
+            ("POST", ["transit", "keys", name, "trim"]) => self.trim_key(name, body),
+            ("GET", ["transit", "export", "signing-key", name]) => self.export_key(name),
+            ("POST", ["transit", "restore", name]) => self.restore_key(name, body),
+            ("POST", ["transit", "sign", name]) => self.sign(name, body),
+
+            _ => Response::error(
+                405,
+                &format!("unsupported operation: {} {}", method, request.path),
+            ),
+        }
+    }
+
+    fn list_policies(&self) -> Response {
+        let mut policies: Vec<_> = self.policies.keys().cloned().collect();
+        policies.push(ROOT_POLICY.to_string());
+        Response::ok(json!({ "policies": policies, "keys": policies }))
+    }
+
+    fn read_policy(&self, name: &str) -> Response {
+        match self.policies.get(name) {
+            Some(policy) => Response::ok(json!({
+                "name": name,
+                "rules": serde_json::to_string(policy).expect("Policies always serialize"),
+            })),
+            None => Response::not_found(),
+        }
+    }
+
+    fn write_policy(&mut self, name: &str, body: Value) -> Response {
+        if name == ROOT_POLICY {
+            return Response::bad_request("cannot update \"root\" policy");
+        }
+        let policy = match body["rules"]
+            .as_str()
+            .map(serde_json::from_str::<PolicyPaths>)
+        {
+            Some(Ok(policy)) => policy,
+            Some(Err(e)) => {
+                return Response::bad_request(&format!("failed to parse policy: {}", e))
+            }
+            None => return Response::bad_request("'policy' parameter not supplied or empty"),
+        };
+        self.policies.insert(name.to_string(), policy);
+        Response::no_content()
+    }
+
+    fn delete_policy(&mut self, name: &str) -> Response {
+        if name == ROOT_POLICY || name == DEFAULT_POLICY {
+            return Response::bad_request(&format!("cannot delete \"{}\" policy", name));
+        }
+        self.policies.remove(name);
+        Response::no_content()
+    }
+
+    fn create_token(&mut self, body: Value) -> Response {
+        let mut policies: Vec<String> = body["policies"]
+            .as_array()
+            .map(|policies| {
+                policies
+                    .iter()
+                    .filter_map(|policy| policy.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        policies.push(DEFAULT_POLICY.to_string());
+
+        self.next_token += 1;
+        let client_token = format!("s.mock{:020}", self.next_token);
+        self.tokens.insert(
+            client_token.clone(),
+            Token {
+                policies: policies.clone(),
+                expires_at: Some(self.now() + self.token_ttl),
+            },
+        );
+
+        Response::ok(json!({
+            "auth": {
+                "client_token": client_token,
+                "policies": policies,
+                "token_policies": policies,
+                "lease_duration": self.token_ttl.as_secs(),
+                "renewable": true,
+            }
+        }))
+    }
+
+    fn renew_token(&mut self, token: &str, body: Value) -> Response {
+        let ttl = body["increment"]
+            .as_u64()
+            .map_or(self.token_ttl, Duration::from_secs);
+        let now = self.now();
+        match self.tokens.get_mut(token) {
+            Some(Token {
+                expires_at: Some(expires_at),
+                ..
+            }) => {
+                *expires_at = now + ttl;
+                Response::ok(json!({
+                    "auth": { "lease_duration": ttl.as_secs(), "renewable": true }
+                }))
+            }
+            _ => Response::bad_request("lease is not renewable"),
+        }
+    }
+
+    fn read_secret(&self, path: &str) -> Response {
+        match self.secrets.get(path) {
+            Some(secret) => Response::ok(json!({
+                "data": {
+                    "data": secret.data,
+                    "metadata": secret_metadata(secret),
+                }
+            })),
+            None => Response::not_found(),
+        }
+    }
+
+    fn write_secret(&mut self, path: &str, body: Value) -> Response {
+        let data = body["data"].clone();
+        if !data.is_object() {
+            return Response::bad_request("no data provided");
+        }
+
+        let current_version = self.secrets.get(path).map_or(0, |secret| secret.version);
+        if let Some(cas) = body["options"]["cas"].as_u64() {
+            if cas != current_version as u64 {
+                return Response::bad_request(
+                    "check-and-set parameter did not match the current version",
+                );
+            }
+        }
+
+        let secret = Secret {
+            data,
+            created_time: self.timestamp(),
+            version: current_version + 1,
+        };
+        let response = Response::ok(json!({ "data": secret_metadata(&secret) }));
+        self.secrets.insert(path.to_string(), secret);
+        response
+    }
+
+    fn list_secrets(&self, prefix: &str) -> Response {
+        // Like a directory listing: nested secrets are returned as their next path segment
+        let keys: BTreeSet<_> = self
+            .secrets
+            .keys()
+            .filter_map(|path| path.strip_prefix(prefix))
+            .map(|rest| match rest.find('/') {
+                Some(index) => rest[..=index].to_string(),
+                None => rest.to_string(),
+            })
+            .collect();
+        if keys.is_empty() {
+            Response::not_found()
+        } else {
+            Response::ok(json!({ "data": { "keys": keys } }))
+        }
+    }
+
+    fn list_keys(&self) -> Response {
+        if self.keys.is_empty() {
+            Response::not_found()
+        } else {
+            let keys: Vec<_> = self.keys.keys().collect();
+            Response::ok(json!({ "data": { "keys": keys } }))
+        }
+    }
+
+    fn read_key(&self, name: &str) -> Response {
+        let key = match self.keys.get(name) {
+            Some(key) => key,
+            None => return Response::not_found(),
+        };
+        let versions: BTreeMap<_, _> = key
+            .versions
+            .iter()
+            .map(|(version, key_version)| {
+                let public_key = key_version.private_key.public_key().to_bytes();
+                let value = json!({
+                    "creation_time": key_version.creation_time,
+                    "name": "ed25519",
+                    "public_key": base64::encode(public_key),
+                });
+                (version.to_string(), value)
+            })
+            .collect();
+
+        Response::ok(json!({
+            "data": {
+                "name": name,
+                "type": "ed25519",
+                "keys": versions,
+                "latest_version": key.latest_version,
+                "min_available_version": key.min_available_version,
+                "min_decryption_version": key.min_decryption_version,
+                "exportable": key.exportable,
+                "deletion_allowed": key.deletion_allowed,
+                "supports_signing": true,
+            }
+        }))
+    }
+
+    fn create_key(&mut self, name: &str, body: Value) -> Response {
+        if body["type"].as_str() != Some("ed25519") {
+            return Response::bad_request("the mock only supports ed25519 keys");
+        }
+        if !self.keys.contains_key(name) {
+            let mut key = TransitKey::new(body["exportable"].as_bool().unwrap_or(false));
+            key.add_version(Ed25519PrivateKey::generate(&mut OsRng), self.timestamp());
+            self.keys.insert(name.to_string(), key);
+        }
+        Response::no_content()
+    }
+
+    fn delete_key(&mut self, name: &str) -> Response {
+        match self.keys.get(name) {
+            Some(key) if !key.deletion_allowed => {
+                Response::bad_request("deletion is not allowed for this key")
+            }
+            _ => {
+                self.keys.remove(name);
+                Response::no_content()
+            }
+        }
+    }
+
+    fn configure_key(&mut self, name: &str, body: Value) -> Response {
+        let key = match self.keys.get_mut(name) {
+            Some(key) => key,
+            None => return Response::not_found(),
+        };
+        if let Some(deletion_allowed) = body["deletion_allowed"].as_bool() {
+            key.deletion_allowed = deletion_allowed;
+        }
+        if let Some(min_decryption_version) = body["min_decryption_version"].as_u64() {
+            key.min_decryption_version = min_decryption_version as u32;
+        }
+        Response::no_content()
+    }
+
+    fn rotate_key(&mut self, name: &str) -> Response {
+        let timestamp = self.timestamp();
+        match self.keys.get_mut(name) {
+            Some(key) => {
+                key.add_version(Ed25519PrivateKey::generate(&mut OsRng), timestamp);
+                Response::no_content()
+            }
+            None => Response::not_found(),
+        }
+    }
+
+    fn trim_key(&mut self, name: &str, body: Value) -> Response {
+        let key = match self.keys.get_mut(name) {
+            Some(key) => key,
+            None => return Response::not_found(),
+        };
+        let min_available_version = match body["min_available_version"].as_u64() {
+            Some(version) if version > 0 => version as u32,
+            _ => return Response::bad_request("invalid min_available_version"),
+        };
+        if min_available_version > key.min_decryption_version {
+            return Response::bad_request(
+                "minimum available version cannot be greater than minimum decryption version",
+            );
+        }
+        key.versions = key.versions.split_off(&min_available_version);
+        key.min_available_version = min_available_version;
+        Response::no_content()
+    }
+
+    fn export_key(&self, name: &str) -> Response {
+        let key = match self.keys.get(name) {
+            Some(key) => key,
+            None => return Response::not_found(),
+        };
+        if !key.exportable {
+            return Response::bad_request("private key material is not exportable");
+        }
+        let versions: BTreeMap<_, _> = key
+            .versions
+            .iter()
+            .map(|(version, key_version)| {
+                let mut bytes = key_version.private_key.to_bytes().to_vec();
+                bytes.extend(&key_version.private_key.public_key().to_bytes());
+                (version.to_string(), base64::encode(bytes))
+            })
+            .collect();
+        Response::ok(json!({
+            "data": { "name": name, "type": "ed25519", "keys": versions }
+        }))
+    }
+
+    fn restore_key(&mut self, name: &str, body: Value) -> Response {
+        if self.keys.contains_key(name) {
+            return Response::bad_request("key already exists");
+        }
+        let backup = match parse_backup(&body) {
+            Some(backup) => backup,
+            None => return Response::bad_request("invalid backup"),
+        };
+
+        let mut key = TransitKey::new(backup.policy.exportable);
+        for info in backup.policy.keys.values() {
+            let private_key = match info
+                .key
+                .as_ref()
+                .and_then(|key| base64::decode(key).ok())
+                .and_then(|bytes| {
+                    let bytes = bytes.get(..ED25519_PRIVATE_KEY_LENGTH)?;
+                    Ed25519PrivateKey::try_from(bytes).ok()
+                }) {
+                Some(private_key) => private_key,
+                None => return Response::bad_request("invalid key in backup"),
+            };
+            key.add_version(private_key, self.timestamp());
+        }
+        self.keys.insert(name.to_string(), key);
+        Response::no_content()
+    }
+
+    fn sign(&self, name: &str, body: Value) -> Response {
+        let key = match self.keys.get(name) {
+            Some(key) => key,
+            None => return Response::bad_request("signing key not found"),
+        };
+        let input = match body["input"].as_str().map(base64::decode) {
+            Some(Ok(input)) => input,
+            _ => return Response::bad_request("unable to decode input"),
+        };
+        let version = body["key_version"]
+            .as_u64()
+            .map_or(key.latest_version, |version| version as u32);
+        let key_version = match key.versions.get(&version) {
+            Some(key_version) => key_version,
+            None => return Response::bad_request("requested version for signing is not found"),
+        };
+
+        let signature = key_version.private_key.sign_arbitrary_message(&input);
+        Response::ok(json!({
+            "data": {
+                "signature": format!("vault:v{}:{}", version, base64::encode(signature.to_bytes())),
+                "key_version": version,
+            }
+        }))
+    }
+}
+
+fn secret_metadata(secret: &Secret) -> Value {
+    json!({
+        "created_time": secret.created_time,
+        "deletion_time": "",
+        "destroyed": false,
+        "version": secret.version,
+    })
+}
+
+fn parse_backup(body: &Value) -> Option<KeyBackup> {
+    let backup = base64::decode(body["backup"].as_str()?).ok()?;
+    serde_json::from_slice(&backup).ok()
+}
+
+/// Serves requests on a (keep-alive) connection until the client closes it.
+fn serve_connection(stream: TcpStream, state: Arc<Mutex<State>>, shutdown: Arc<AtomicBool>) {
+    let mut reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(_) => return,
+    };
+    let mut writer = stream;
+    while !shutdown.load(Ordering::Relaxed) {
+        let request = match read_request(&mut reader) {
+            Ok(Some(request)) => request,
+            _ => return,
+        };
+        let response = state.lock().handle(request);
+        if write_response(&mut writer, response).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "bad request")),
+    };
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target, None),
+    };
+    // Vault also accepts `GET ...?list=true` in place of the LIST method
+    let method = if method == "GET" && query == Some("list=true") {
+        "LIST".to_string()
+    } else {
+        method
+    };
+    let path = path.strip_prefix("/v1/").unwrap_or(path).to_string();
+
+    let mut token = None;
+    let mut content_length = 0;
+    let mut chunked = false;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "x-vault-token" => token = Some(value.to_string()),
+                "content-length" => {
+                    content_length = value
+                        .parse()
+                        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad length"))?
+                }
+                "transfer-encoding" => chunked = value.eq_ignore_ascii_case("chunked"),
+                _ => (),
+            }
+        }
+    }
+
+    let body = if chunked {
+        read_chunked_body(reader)?
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        body
+    };
+
+    Ok(Some(Request {
+        method,
+        path,
+        token,
+        body,
+    }))
+}
+
+fn read_chunked_body(reader: &mut impl BufRead) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let mut size_line = String::new();
+        reader.read_line(&mut size_line)?;
+        let size_str = size_line.trim().split(';').next().unwrap_or_default();
+        let size = usize::from_str_radix(size_str, 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad chunk size"))?;
+        let mut chunk = vec![0; size + 2];
+        if size == 0 {
+            // Skip the (empty) trailer
+            let mut trailer = String::new();
+            reader.read_line(&mut trailer)?;
+            return Ok(body);
+        }
+        reader.read_exact(&mut chunk)?;
+        body.extend_from_slice(&chunk[..size]);
+    }
+}
+
+fn write_response(writer: &mut impl Write, response: Response) -> io::Result<()> {
+    let body = response
+        .body
+        .map(|body| body.to_string().into_bytes())
+        .unwrap_or_default();
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+        response.status,
+        reason_phrase(response.status),
+        body.len()
+    )?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Policy};
+    use diem_crypto::{ed25519::Ed25519Signature, traits::Signature};
+
+    fn status(result: Result<impl std::fmt::Debug, Error>) -> u16 {
+        match result.unwrap_err() {
+            Error::HttpError(status, _, _) => status,
+            error => panic!("Unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn secrets() {
+        let vault = MockVault::start().unwrap();
+        let client = vault.client();
+        assert!(client.unsealed().unwrap());
+
+        assert_eq!(client.write_secret("a/b", "b", &json!(1), None).unwrap(), 1);
+        assert_eq!(client.write_secret("c", "c", &json!(2), None).unwrap(), 1);
+        assert_eq!(
+            client.write_secret("c", "c", &json!(3), Some(1)).unwrap(),
+            2
+        );
+        assert_eq!(
+            status(client.write_secret("c", "c", &json!(4), Some(1))),
+            400
+        );
+
+        let secret = client.read_secret("c", "c").unwrap();
+        assert_eq!((secret.value, secret.version), (json!(3), 2));
+        assert_eq!(
+            client.read_secret("d", "d").unwrap_err(),
+            Error::NotFound("d".into(), "d".into())
+        );
+        assert_eq!(client.list_secrets("").unwrap(), vec!["a/", "c"]);
+        assert_eq!(client.list_secrets("a/").unwrap(), vec!["b"]);
+
+        client.delete_secret("c").unwrap();
+        assert_eq!(client.list_secrets("").unwrap(), vec!["a/"]);
+    }
+
+    #[test]
+    fn transit() {
+        let vault = MockVault::start().unwrap();
+        let client = vault.client();
+        assert!(client.list_keys().is_err());
+
+        client.create_ed25519_key("key", true).unwrap();
+        client.rotate_key("key").unwrap();
+        let public_keys = client.read_ed25519_key("key").unwrap();
+        assert_eq!(public_keys.len(), 2);
+
+        // Signatures and exports use the latest version unless asked otherwise
+        let message = b"message";
+        let signature: Ed25519Signature = client.sign_ed25519("key", message, Some(1)).unwrap();
+        let first = public_keys.iter().find(|key| key.version == 1).unwrap();
+        signature
+            .verify_arbitrary_msg(message, &first.value)
+            .unwrap();
+        let exported = client.export_ed25519_key("key", None).unwrap();
+        let second = public_keys.iter().find(|key| key.version == 2).unwrap();
+        assert_eq!(exported.public_key(), second.value);
+
+        // Importing a key keeps its key material
+        let private_key = Ed25519PrivateKey::generate(&mut OsRng);
+        client.import_ed25519_key("imported", &private_key).unwrap();
+        assert_eq!(
+            client.export_ed25519_key("imported", None).unwrap(),
+            private_key
+        );
+        assert_eq!(client.list_keys().unwrap(), vec!["imported", "key"]);
+
+        // Keys can only be deleted once allowed, which delete_key takes care of
+        client.delete_key("key").unwrap();
+        assert_eq!(client.list_keys().unwrap(), vec!["imported"]);
+    }
+
+    #[test]
+    fn policies_and_tokens() {
+        let vault = MockVault::start().unwrap();
+        let root = vault.client();
+        root.write_secret("open", "open", &json!(1), None).unwrap();
+        root.write_secret("closed", "closed", &json!(2), None)
+            .unwrap();
+
+        let mut policy = Policy::new();
+        policy.add_policy("secret/data/open", vec![Capability::Read]);
+        policy.add_policy("secret/data/*", vec![Capability::Deny]);
+        root.set_policy("reader", &policy).unwrap();
+        assert_eq!(
+            root.list_policies().unwrap(),
+            vec!["default", "reader", "root"]
+        );
+
+        let token = root.create_token(vec!["reader"]).unwrap();
+        let reader = Client::new(vault.host().into(), token, None, None, None);
+        reader.read_secret("open", "open").unwrap();
+        assert_eq!(status(reader.read_secret("closed", "closed")), 403);
+        assert_eq!(
+            status(reader.write_secret("open", "open", &json!(3), None)),
+            403
+        );
+
+        // Tokens expire unless renewed
+        vault.set_token_ttl(Duration::from_secs(60));
+        let token = root.create_token(vec!["reader"]).unwrap();
+        let expiring = Client::new(vault.host().into(), token, None, None, None);
+        vault.advance_time(Duration::from_secs(45));
+        assert_eq!(expiring.renew_token_self(None).unwrap(), 60);
+        vault.advance_time(Duration::from_secs(45));
+        expiring.read_secret("open", "open").unwrap();
+        vault.advance_time(Duration::from_secs(45));
+        assert_eq!(status(expiring.read_secret("open", "open")), 403);
+
+        // The root token can't be renewed, and revoked tokens are gone
+        assert_eq!(status(root.renew_token_self(None)), 400);
+        reader.revoke_token_self().unwrap();
+        assert_eq!(status(reader.read_secret("open", "open")), 403);
+    }
+
+    #[test]
+    fn failure_injection() {
+        let vault = MockVault::start().unwrap();
+        let client = vault.client();
+
+        vault.seal();
+        assert!(!client.unsealed().unwrap());
+        assert_eq!(status(client.list_keys()), 503);
+        vault.unseal();
+        assert!(client.unsealed().unwrap());
+
+        vault.fail_requests(Failure::ServerError(500), 2);
+        assert_eq!(status(client.unsealed()), 500);
+        assert_eq!(status(client.list_secrets("")), 500);
+        assert!(client.unsealed().unwrap());
+
+        vault.fail_requests_to("secret/", Failure::PermissionDenied, 1);
+        assert!(client.unsealed().unwrap());
+        assert_eq!(status(client.write_secret("s", "s", &json!(1), None)), 403);
+        client.write_secret("s", "s", &json!(1), None).unwrap();
+    }
+}
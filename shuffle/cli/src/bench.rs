@@ -0,0 +1,436 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::shared::Home;
+use anyhow::{anyhow, bail, format_err, Result};
+use diem_api_types::ScriptFunctionId;
+use diem_parallel_executor::conflicts::ConflictStats;
+use diem_sdk::{
+    client::BlockingClient,
+    transaction_builder::{Currency, TransactionFactory},
+    types::LocalAccount,
+};
+use diem_sdk_simulator::RemoteStateView;
+use diem_state_view::StateView;
+use diem_types::{
+    access_path::{AccessPath, Path as AccessPathKind},
+    account_address::AccountAddress,
+    account_config::{self, AccountResource, ChainIdResource},
+    chain_id::ChainId,
+    transaction::{
+        ScriptFunction, Transaction, TransactionArgument, TransactionOutput, TransactionStatus,
+    },
+    vm_status::KeptVMStatus,
+    waypoint::Waypoint,
+    write_set::{WriteOp, WriteSet},
+};
+use diem_vm::{parallel_executor::ParallelDiemVM, DiemVM};
+use generate_key::load_key;
+use move_core_types::{
+    language_storage::TypeTag, move_resource::MoveResource, transaction_argument::convert_txn_args,
+};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::HashMap,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// XUS (in micro units) every benchmark account is funded with.
+const FUNDING_AMOUNT: u64 = 1_000_000_000;
+
+pub const DEFAULT_NUM_TXNS: &str = "1000";
+pub const DEFAULT_NUM_HOT_KEYS: &str = "10";
+
+/// Builds the script function called by every transaction of the benchmark.
+pub fn script_function(
+    function: ScriptFunctionId,
+    type_args: Vec<TypeTag>,
+    args: Vec<TransactionArgument>,
+) -> ScriptFunction {
+    ScriptFunction::new(
+        function.module.into(),
+        function.name,
+        type_args,
+        convert_txn_args(&args),
+    )
+}
+
+/// Executes `num_txns` calls to `function`, sent from `num_accounts` new accounts, as a single
+/// block with `ParallelDiemVM` on top of a snapshot of the network state. Nothing is submitted
+/// to the network: the accounts are created and funded by transactions executed on the snapshot
+/// too, signed with the treasury compliance key at `root_key_path`.
+#[allow(clippy::too_many_arguments)]
+pub fn handle(
+    home: &Home,
+    json_rpc_url: Url,
+    waypoint: Option<Waypoint>,
+    root_key_path: &Path,
+    function: ScriptFunction,
+    num_txns: usize,
+    num_accounts: Option<usize>,
+    num_hot_keys: usize,
+) -> Result<()> {
+    let num_accounts = num_accounts.unwrap_or(num_txns);
+    if num_txns == 0 || num_accounts == 0 {
+        bail!("The benchmark needs at least one transaction and one account");
+    }
+    let waypoint = match waypoint {
+        Some(waypoint) => waypoint,
+        None => Waypoint::from_str(home.read_genesis_waypoint()?.trim())?,
+    };
+    if !root_key_path.exists() {
+        return Err(anyhow!(
+            "A node hasn't been created yet! Run shuffle node first"
+        ));
+    }
+
+    println!("Connecting to {}...", json_rpc_url);
+    let snapshot =
+        RemoteStateView::from_waypoint(BlockingClient::new(json_rpc_url.as_str()), waypoint)?;
+    println!(
+        "Taking a snapshot of the state at version {}",
+        snapshot.version()
+    );
+    let mut state_view = OverlayStateView::new(&snapshot);
+    let factory = TransactionFactory::new(chain_id(&state_view)?);
+
+    println!("Creating and funding {} accounts...", num_accounts);
+    let mut accounts: Vec<_> = (0..num_accounts)
+        .map(|_| LocalAccount::generate(&mut rand::rngs::OsRng))
+        .collect();
+    let setup_txns = setup_transactions(&state_view, &factory, root_key_path, &accounts)?;
+    let (setup_outputs, _) = execute_sequential(setup_txns, &state_view)?;
+    for output in &setup_outputs {
+        if output.status() != &TransactionStatus::Keep(KeptVMStatus::Executed) {
+            bail!(
+                "Failed to set up the benchmark accounts: {:?}",
+                output.status()
+            );
+        }
+        state_view.apply(output.write_set());
+    }
+
+    let txns: Vec<_> = (0..num_txns)
+        .map(|idx| {
+            Transaction::UserTransaction(
+                accounts[idx % num_accounts]
+                    .sign_with_transaction_builder(factory.script_function(function.clone())),
+            )
+        })
+        .collect();
+
+    // The snapshot is fetched lazily, so a first (sequential) run caches all the state read by
+    // the block before anything is timed.
+    println!("Executing {} transactions...", num_txns);
+    let (expected_outputs, _) = execute_sequential(txns.clone(), &state_view)?;
+    let (parallel_outputs, conflicts, parallel_time) = execute_parallel(txns.clone(), &state_view)?;
+    let (_, sequential_time) = execute_sequential(txns, &state_view)?;
+    for (idx, (expected, actual)) in expected_outputs
+        .iter()
+        .zip(parallel_outputs.iter())
+        .enumerate()
+    {
+        if expected != actual {
+            bail!(
+                "Sequential and parallel outputs differ for transaction {}:\nsequential: {:#?}\nparallel: {:#?}",
+                idx,
+                expected,
+                actual
+            );
+        }
+    }
+
+    print_report(
+        &parallel_outputs,
+        &conflicts,
+        parallel_time,
+        sequential_time,
+        num_hot_keys,
+    );
+    Ok(())
+}
+
+/// Transactions creating the benchmark accounts from the treasury compliance account, then
+/// funding them from the testnet designated dealer, which uses the same key.
+fn setup_transactions(
+    state_view: &impl StateView,
+    factory: &TransactionFactory,
+    root_key_path: &Path,
+    accounts: &[LocalAccount],
+) -> Result<Vec<Transaction>> {
+    let mut treasury_account = load_account(
+        state_view,
+        account_config::treasury_compliance_account_address(),
+        root_key_path,
+    )?;
+    let mut dd_account = load_account(
+        state_view,
+        account_config::testnet_dd_account_address(),
+        root_key_path,
+    )?;
+
+    let create_accounts = accounts.iter().map(|account| {
+        treasury_account.sign_with_transaction_builder(factory.create_parent_vasp_account(
+            Currency::XUS,
+            0,
+            account.authentication_key(),
+            "",
+            false,
+        ))
+    });
+    let mut txns: Vec<_> = create_accounts.collect();
+    txns.extend(accounts.iter().map(|account| {
+        dd_account.sign_with_transaction_builder(factory.peer_to_peer(
+            Currency::XUS,
+            account.address(),
+            FUNDING_AMOUNT,
+        ))
+    }));
+    Ok(txns.into_iter().map(Transaction::UserTransaction).collect())
+}
+
+fn load_account(
+    state_view: &impl StateView,
+    address: AccountAddress,
+    key_path: &Path,
+) -> Result<LocalAccount> {
+    let account_resource: AccountResource = get_resource(state_view, address)?;
+    Ok(LocalAccount::new(
+        address,
+        load_key(key_path),
+        account_resource.sequence_number(),
+    ))
+}
+
+fn chain_id(state_view: &impl StateView) -> Result<ChainId> {
+    let chain_id: ChainIdResource = get_resource(state_view, account_config::diem_root_address())?;
+    Ok(chain_id.chain_id())
+}
+
+fn get_resource<T: MoveResource + DeserializeOwned>(
+    state_view: &impl StateView,
+    address: AccountAddress,
+) -> Result<T> {
+    let bytes = state_view
+        .get(&AccessPath::new(address, T::resource_path()))?
+        .ok_or_else(|| {
+            format_err!(
+                "Resource {} doesn't exist under {}",
+                T::struct_tag(),
+                address
+            )
+        })?;
+    Ok(bcs::from_bytes(&bytes)?)
+}
+
+fn execute_sequential(
+    txns: Vec<Transaction>,
+    state_view: &impl StateView,
+) -> Result<(Vec<TransactionOutput>, Duration)> {
+    // `DiemVM::execute_block` may itself run the block with the parallel executor, depending on
+    // the on-chain config, so sequential execution has to go through this variant.
+    let timer = Instant::now();
+    let outputs = DiemVM::execute_block_and_keep_vm_status(txns, state_view)
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+        .into_iter()
+        .map(|(_vm_status, output)| output)
+        .collect();
+    Ok((outputs, timer.elapsed()))
+}
+
+fn execute_parallel(
+    txns: Vec<Transaction>,
+    state_view: &impl StateView,
+) -> Result<(Vec<TransactionOutput>, ConflictStats<AccessPath>, Duration)> {
+    let timer = Instant::now();
+    let (outputs, conflicts) = ParallelDiemVM::execute_block_with_conflicts(txns, state_view)
+        .map_err(|err| format_err!("Parallel execution failed: {:?}", err))?;
+    Ok((outputs, conflicts, timer.elapsed()))
+}
+
+fn print_report(
+    outputs: &[TransactionOutput],
+    conflicts: &ConflictStats<AccessPath>,
+    parallel_time: Duration,
+    sequential_time: Duration,
+    num_hot_keys: usize,
+) {
+    let mut statuses: HashMap<String, usize> = HashMap::new();
+    for output in outputs {
+        let status = match output.status() {
+            TransactionStatus::Keep(KeptVMStatus::Executed) => continue,
+            TransactionStatus::Keep(KeptVMStatus::MoveAbort(location, code)) => {
+                format!("Move abort: code {} at {}", code, location)
+            }
+            status => format!("{:?}", status),
+        };
+        *statuses.entry(status).or_insert(0) += 1;
+    }
+    let num_failed: usize = statuses.values().sum();
+
+    println!();
+    println!("Transactions:        {}", outputs.len());
+    println!("Executed:            {}", outputs.len() - num_failed);
+    println!("Failed:              {}", num_failed);
+    let mut statuses: Vec<_> = statuses.into_iter().collect();
+    statuses.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+    for (status, count) in statuses {
+        println!("  {:>8}  {}", count, status);
+    }
+    println!(
+        "Parallel TPS:        {} ({:?})",
+        tps(outputs.len(), parallel_time),
+        parallel_time
+    );
+    println!(
+        "Sequential TPS:      {} ({:?})",
+        tps(outputs.len(), sequential_time),
+        sequential_time
+    );
+    println!(
+        "Speedup:             {:.2}x",
+        sequential_time.as_secs_f64() / parallel_time.as_secs_f64()
+    );
+    println!("Re-executions:       {}", conflicts.num_aborts);
+    println!("Suspensions:         {}", conflicts.num_dependencies);
+
+    let hot_keys = conflicts.hot_keys(num_hot_keys);
+    if hot_keys.is_empty() {
+        println!("No conflicts between transactions");
+        return;
+    }
+    println!("Hot keys (conflicts, key):");
+    for (access_path, count) in hot_keys {
+        println!("  {:>8}  {}", count, display_access_path(access_path));
+    }
+}
+
+fn tps(num_txns: usize, time: Duration) -> u64 {
+    (num_txns as f64 / time.as_secs_f64()) as u64
+}
+
+fn display_access_path(access_path: &AccessPath) -> String {
+    match access_path.get_path() {
+        AccessPathKind::Resource(tag) => format!("{} {}", access_path.address, tag),
+        AccessPathKind::Code(module_id) => format!("{} (code)", module_id),
+    }
+}
+
+/// The snapshot of the network state with the writes of the transactions setting up the
+/// benchmark applied on top of it.
+struct OverlayStateView<'a, S> {
+    base: &'a S,
+    writes: HashMap<AccessPath, WriteOp>,
+}
+
+impl<'a, S: StateView> OverlayStateView<'a, S> {
+    fn new(base: &'a S) -> Self {
+        Self {
+            base,
+            writes: HashMap::new(),
+        }
+    }
+
+    fn apply(&mut self, write_set: &WriteSet) {
+        for (access_path, write_op) in write_set {
+            self.writes.insert(access_path.clone(), write_op.clone());
+        }
+    }
+}
+
+impl<'a, S: StateView> StateView for OverlayStateView<'a, S> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        match self.writes.get(access_path) {
+            Some(WriteOp::Value(value)) => Ok(Some(value.clone())),
+            Some(WriteOp::Deletion) => Ok(None),
+            None => self.base.get(access_path),
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use diem_types::write_set::WriteSetMut;
+    use move_core_types::{ident_str, language_storage::ModuleId};
+
+    struct MapStateView(HashMap<AccessPath, Vec<u8>>);
+
+    impl StateView for MapStateView {
+        fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+            Ok(self.0.get(access_path).cloned())
+        }
+
+        fn is_genesis(&self) -> bool {
+            false
+        }
+    }
+
+    fn access_path(path: u8) -> AccessPath {
+        AccessPath::new(AccountAddress::random(), vec![path])
+    }
+
+    #[test]
+    fn test_overlay_state_view() {
+        let (kept, overwritten, deleted, added) = (
+            access_path(0),
+            access_path(1),
+            access_path(2),
+            access_path(3),
+        );
+        let base = MapStateView(
+            vec![
+                (kept.clone(), vec![0]),
+                (overwritten.clone(), vec![1]),
+                (deleted.clone(), vec![2]),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let mut state_view = OverlayStateView::new(&base);
+        state_view.apply(
+            &WriteSetMut::new(vec![
+                (overwritten.clone(), WriteOp::Value(vec![4])),
+                (deleted.clone(), WriteOp::Deletion),
+                (added.clone(), WriteOp::Value(vec![3])),
+            ])
+            .freeze()
+            .unwrap(),
+        );
+        assert_eq!(state_view.get(&kept).unwrap(), Some(vec![0]));
+        assert_eq!(state_view.get(&overwritten).unwrap(), Some(vec![4]));
+        assert_eq!(state_view.get(&deleted).unwrap(), None);
+        assert_eq!(state_view.get(&added).unwrap(), Some(vec![3]));
+        assert_eq!(base.get(&overwritten).unwrap(), Some(vec![1]));
+    }
+
+    #[test]
+    fn test_script_function() {
+        let function = script_function(
+            ScriptFunctionId::from_str("0x1::Message::set_message").unwrap(),
+            vec![TypeTag::U64],
+            vec![TransactionArgument::U8Vector(b"hello".to_vec())],
+        );
+        assert_eq!(
+            function.module(),
+            &ModuleId::new(
+                account_config::CORE_CODE_ADDRESS,
+                ident_str!("Message").to_owned()
+            )
+        );
+        assert_eq!(function.function(), ident_str!("set_message"));
+        assert_eq!(function.ty_args(), &[TypeTag::U64]);
+        assert_eq!(
+            function.args(),
+            &[bcs::to_bytes(&b"hello".to_vec()).unwrap()]
+        );
+    }
+}
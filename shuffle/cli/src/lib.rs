@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod account;
+pub mod bench;
 pub mod build;
 pub mod console;
 pub mod context;
@@ -6,11 +6,17 @@ use crate::{
     test::TestCommand,
 };
 use anyhow::{anyhow, Result};
-use diem_types::account_address::AccountAddress;
+use diem_api_types::ScriptFunctionId;
+use diem_types::{
+    account_address::AccountAddress,
+    transaction::{parse_transaction_argument, TransactionArgument},
+    waypoint::Waypoint,
+};
+use move_core_types::{language_storage::TypeTag, parser::parse_type_tag};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-use shuffle::{account, build, console, deploy, new, node, shared, test, transactions};
+use shuffle::{account, bench, build, console, deploy, new, node, shared, test, transactions};
 
 #[tokio::main]
 pub async fn main() -> Result<()> {
@@ -85,6 +91,27 @@ pub async fn main() -> Result<()> {
             )
             .await
         }
+        Subcommand::Bench {
+            function,
+            type_args,
+            args,
+            network,
+            num_txns,
+            num_accounts,
+            hot_keys,
+            waypoint,
+            root,
+        } => bench::handle(
+            &home,
+            home.get_network_struct_from_toml(normalized_network_name(network).as_str())?
+                .get_json_rpc_url(),
+            waypoint,
+            &root.unwrap_or_else(|| home.get_root_key_path().to_path_buf()),
+            bench::script_function(function, type_args, args),
+            num_txns,
+            num_accounts,
+            hot_keys,
+        ),
     }
 }
 
@@ -187,6 +214,57 @@ pub enum Subcommand {
         #[structopt(short, help = "Blocks and streams future transactions as they happen")]
         tail: Option<Option<bool>>,
     },
+    #[structopt(
+        about = "Executes concurrent calls to a script function as one block with the parallel executor on a snapshot of the network, reporting TPS, aborts and conflicting keys"
+    )]
+    Bench {
+        #[structopt(help = "Script function to call, e.g. 0x1::Message::set_message")]
+        function: ScriptFunctionId,
+
+        #[structopt(long, parse(try_from_str = parse_type_tag))]
+        type_args: Vec<TypeTag>,
+
+        #[structopt(
+            long,
+            parse(try_from_str = parse_transaction_argument),
+            help = "Arguments of the script function, e.g. b\"hello\" or 42u64"
+        )]
+        args: Vec<TransactionArgument>,
+
+        #[structopt(short, long)]
+        network: Option<String>,
+
+        #[structopt(short = "t", long, default_value = bench::DEFAULT_NUM_TXNS)]
+        num_txns: usize,
+
+        #[structopt(
+            short = "a",
+            long,
+            help = "Number of accounts sending the transactions, defaults to one per transaction"
+        )]
+        num_accounts: Option<usize>,
+
+        #[structopt(
+            long,
+            default_value = bench::DEFAULT_NUM_HOT_KEYS,
+            help = "Number of conflicting keys to report"
+        )]
+        hot_keys: usize,
+
+        #[structopt(
+            short,
+            long,
+            help = "Waypoint to verify the network state from, defaults to the genesis waypoint of the local node"
+        )]
+        waypoint: Option<Waypoint>,
+
+        #[structopt(
+            short,
+            long,
+            help = "Treasury compliance key funding the accounts, defaults to the mint.key of the local node"
+        )]
+        root: Option<PathBuf>,
+    },
 }
 
 fn normalized_address(
@@ -60,4 +60,5 @@ pub enum LogEvent {
     ResponseError,
     ResponseSuccess,
     SendRequest,
+    SubrequestError,
 }
@@ -5,6 +5,9 @@ use crate::{
     diemnet::{
         logging::{LogEntry, LogEvent, LogSchema},
         metrics::{increment_counter, start_timer},
+        reassembly::{
+            check_part, split_request, ChunkWithProof, MultiPeerResponseCallback, VersionRange,
+        },
         state::{ErrorType, PeerStates},
     },
     DiemDataClient, Error, GlobalDataSummary, Response, ResponseCallback, ResponseContext,
@@ -31,7 +34,7 @@ use network::{
     protocols::{rpc::error::RpcError, wire::handshake::v1::ProtocolId},
 };
 use rand::seq::SliceRandom;
-use std::{convert::TryFrom, fmt, sync::Arc, time::Duration};
+use std::{collections::HashSet, convert::TryFrom, fmt, sync::Arc, time::Duration};
 use storage_service_client::StorageServiceClient;
 use storage_service_types::{
     AccountStatesChunkWithProofRequest, Epoch, EpochEndingLedgerInfoRequest, StorageServerSummary,
@@ -41,6 +44,7 @@ use storage_service_types::{
 
 mod logging;
 mod metrics;
+mod reassembly;
 mod state;
 #[cfg(test)]
 mod tests;
@@ -61,9 +65,11 @@ const POLLER_ERROR_LOG_FREQ_SECS: u64 = 1;
 ///    and upper client reports of invalid or malicious data.
 /// 5. Selects high quality peers to send each request to.
 /// 6. Exposes a condensed data summary of our peers' data advertisements.
+/// 7. Splits requests for large ranges of transactions (or transaction outputs)
+///    across several peers, and reassembles their responses in order.
 ///
-/// The client currently assumes 1-request => 1-response. Streaming responses
-/// are handled at an upper layer.
+/// The client currently assumes 1-request => 1-response for each peer.
+/// Streaming responses are handled at an upper layer.
 ///
 /// The client is expected to be cloneable and usable from many concurrent tasks
 /// and/or threads.
@@ -79,6 +85,8 @@ pub struct DiemNetDataClient {
     global_summary_cache: Arc<RwLock<GlobalDataSummary>>,
     /// Used for generating the next request/response id.
     response_id_generator: Arc<U64IdGenerator>,
+    /// Used for measuring the response latencies of the peers.
+    time_service: TimeService,
 }
 
 impl DiemNetDataClient {
@@ -94,6 +102,7 @@ impl DiemNetDataClient {
             peer_states: Arc::new(RwLock::new(PeerStates::new(storage_service_config))),
             global_summary_cache: Arc::new(RwLock::new(GlobalDataSummary::empty())),
             response_id_generator: Arc::new(U64IdGenerator::new()),
+            time_service: time_service.clone(),
         };
         let poller = DataSummaryPoller::new(
             time_service,
@@ -121,6 +130,21 @@ impl DiemNetDataClient {
     /// Choose a connected peer that can service the given request. Returns an
     /// error if no such peer can be found.
     fn choose_peer(&self, request: &StorageServiceRequest) -> Result<PeerNetworkId, Error> {
+        self.choose_peers(request, 1, &HashSet::new())
+            .map(|peers| peers[0])
+    }
+
+    /// Choose up to `num_peers` distinct connected peers, other than the
+    /// excluded ones, that can service the given request. Data requests favor
+    /// the peers with higher scores, while data summary requests choose
+    /// uniformly so that ignored peers keep being polled and can recover.
+    /// Returns an error if no such peer can be found.
+    fn choose_peers(
+        &self,
+        request: &StorageServiceRequest,
+        num_peers: usize,
+        excluded_peers: &HashSet<PeerNetworkId>,
+    ) -> Result<Vec<PeerNetworkId>, Error> {
         let all_connected = {
             let network_peer_metadata = self.network_client.peer_metadata_storage();
             network_peer_metadata
@@ -145,17 +169,47 @@ impl DiemNetDataClient {
         let internal_peer_states = self.peer_states.read();
         let all_serviceable = all_connected
             .into_iter()
+            .filter(|peer| !excluded_peers.contains(peer))
             .filter(|peer| internal_peer_states.can_service_request(peer, request))
             .collect::<Vec<_>>();
 
-        all_serviceable
-            .choose(&mut rand::thread_rng())
-            .copied()
-            .ok_or_else(|| {
-                Error::DataIsUnavailable(
-                    "No connected peers are advertising that they can serve this data range"
-                        .to_owned(),
-                )
+        if all_serviceable.is_empty() {
+            return Err(Error::DataIsUnavailable(
+                "No connected peers are advertising that they can serve this data range".to_owned(),
+            ));
+        }
+
+        let mut rng = rand::thread_rng();
+        if request.is_get_storage_server_summary() {
+            Ok(all_serviceable
+                .choose_multiple(&mut rng, num_peers)
+                .copied()
+                .collect())
+        } else {
+            all_serviceable
+                .choose_multiple_weighted(&mut rng, num_peers, |peer| {
+                    internal_peer_states.selection_weight(peer)
+                })
+                .map(|peers| peers.copied().collect())
+                .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))
+        }
+    }
+
+    /// Choose peers for the given request, logging the failure to find any.
+    fn choose_peers_for_request(
+        &self,
+        request: &StorageServiceRequest,
+        num_peers: usize,
+    ) -> Result<Vec<PeerNetworkId>, Error> {
+        self.choose_peers(request, num_peers, &HashSet::new())
+            .map_err(|error| {
+                error!(
+                    (LogSchema::new(LogEntry::StorageServiceRequest)
+                        .event(LogEvent::PeerSelectionError)
+                        .message("Unable to select next peer")
+                        .error(&error))
+                );
+                error
             })
     }
 
@@ -167,19 +221,129 @@ impl DiemNetDataClient {
         T: TryFrom<StorageServiceResponse, Error = E>,
         E: Into<Error>,
     {
-        let peer = self.choose_peer(&request).map_err(|error| {
-            error!(
-                (LogSchema::new(LogEntry::StorageServiceRequest)
-                    .event(LogEvent::PeerSelectionError)
-                    .message("Unable to select next peer")
-                    .error(&error))
-            );
-            error
-        })?;
+        let peer = self.choose_peers_for_request(&request, 1)?[0];
         let _timer = start_timer(&metrics::REQUEST_LATENCIES, request.get_label().into());
         self.send_request_to_peer_and_decode(peer, request).await
     }
 
+    /// Sends a request for a range of transactions (or transaction outputs).
+    /// Large ranges are split into subrequests that are sent to different peers
+    /// concurrently, and the parts are reassembled in order, whatever the order
+    /// the responses arrive in.
+    async fn send_chunk_request_and_decode<T, E>(
+        &self,
+        request: StorageServiceRequest,
+    ) -> Result<Response<T>>
+    where
+        T: ChunkWithProof + TryFrom<StorageServiceResponse, Error = E>,
+        E: Into<Error>,
+    {
+        let peers = self.choose_peers_for_request(
+            &request,
+            self.data_client_config.max_num_subrequests.max(1) as usize,
+        )?;
+        let subrequests = split_request(
+            &request,
+            peers.len() as u64,
+            self.data_client_config.min_subrequest_size,
+        );
+
+        let _timer = start_timer(&metrics::REQUEST_LATENCIES, request.get_label().into());
+        if subrequests.len() == 1 {
+            return self
+                .send_request_to_peer_and_decode(peers[0], request)
+                .await;
+        }
+
+        increment_counter(&metrics::SPLIT_REQUESTS, request.get_label().into());
+        let parts =
+            futures::future::join_all(subrequests.into_iter().zip(peers).map(
+                |(subrequest, peer)| self.send_subrequest_and_decode::<T, E>(peer, subrequest),
+            ))
+            .await;
+
+        let mut chunk: Option<T> = None;
+        let mut callbacks = vec![];
+        for part in parts {
+            let (context, payload) = part?.into_parts();
+            callbacks.push(context.response_callback);
+            match chunk.as_mut() {
+                Some(chunk) => chunk.append(payload),
+                None => chunk = Some(payload),
+            }
+        }
+
+        let context = ResponseContext {
+            id: self.next_response_id(),
+            response_callback: Box::new(MultiPeerResponseCallback::new(callbacks)),
+        };
+        Ok(Response::new(
+            context,
+            chunk.expect("A split request has at least two parts"),
+        ))
+    }
+
+    /// Sends a subrequest to the given peer. If the request fails, or the
+    /// response doesn't hold the requested range with a valid proof, the peer is
+    /// penalized and the subrequest is sent to another peer, up to
+    /// `max_subrequest_retries` times.
+    async fn send_subrequest_and_decode<T, E>(
+        &self,
+        mut peer: PeerNetworkId,
+        request: StorageServiceRequest,
+    ) -> Result<Response<T>>
+    where
+        T: ChunkWithProof + TryFrom<StorageServiceResponse, Error = E>,
+        E: Into<Error>,
+    {
+        let range = VersionRange::from_request(&request).expect("Subrequests are range requests");
+        let mut failed_peers = HashSet::new();
+        loop {
+            let error = match self
+                .send_request_to_peer_and_decode::<T, E>(peer, request.clone())
+                .await
+            {
+                Ok(response) => {
+                    let advertised_ledger_infos = self
+                        .peer_states
+                        .read()
+                        .advertised_ledger_infos(range.proof_version);
+                    match check_part(&response.payload, &range, &advertised_ledger_infos) {
+                        Ok(()) => return Ok(response),
+                        Err(response_error) => {
+                            response
+                                .context
+                                .response_callback
+                                .notify_bad_response(response_error.clone());
+                            Error::InvalidResponse(format!(
+                                "Bad response to a subrequest: {:?}",
+                                response_error
+                            ))
+                        }
+                    }
+                }
+                Err(error) => error,
+            };
+
+            warn!(
+                (LogSchema::new(LogEntry::StorageServiceResponse)
+                    .event(LogEvent::SubrequestError)
+                    .request_type(request.get_label())
+                    .peer(&peer)
+                    .error(&error))
+            );
+
+            failed_peers.insert(peer);
+            if failed_peers.len() as u64 > self.data_client_config.max_subrequest_retries {
+                return Err(error);
+            }
+            peer = match self.choose_peers(&request, 1, &failed_peers) {
+                Ok(peers) => peers[0],
+                Err(_) => return Err(error),
+            };
+        }
+    }
+
     async fn send_request_to_peer_and_decode<T, E>(
         &self,
         peer: PeerNetworkId,
@@ -224,6 +388,7 @@ impl DiemNetDataClient {
 
         increment_counter(&metrics::SENT_REQUESTS, request.get_label().into());
 
+        let start_time = self.time_service.now();
         let result = self
             .network_client
             .send_request(
@@ -250,8 +415,17 @@ impl DiemNetDataClient {
                 // consumer notify both success and failure via the callback.
                 // On the one hand, scoring dynamics are simpler when each request
                 // is successful or failed but not both; on the other hand, this
-                // feels simpler for the consumer.
-                self.peer_states.write().update_score_success(peer);
+                // feels simpler for the consumer. Slow responses demote the peer.
+                let latency = self.time_service.now().duration_since(start_time);
+                if latency
+                    > Duration::from_millis(self.data_client_config.slow_response_threshold_ms)
+                {
+                    self.peer_states
+                        .write()
+                        .update_score_error(peer, ErrorType::Slow);
+                } else {
+                    self.peer_states.write().update_score_success(peer);
+                }
 
                 // Package up all of the context needed to fully report an error
                 // with this RPC.
@@ -366,7 +540,7 @@ impl DiemDataClient for DiemNetDataClient {
                 end_version,
             },
         );
-        self.send_request_and_decode(request).await
+        self.send_chunk_request_and_decode(request).await
     }
 
     async fn get_transactions_with_proof(
@@ -383,7 +557,7 @@ impl DiemDataClient for DiemNetDataClient {
                 end_version,
                 include_events,
             });
-        self.send_request_and_decode(request).await
+        self.send_chunk_request_and_decode(request).await
     }
 }
 
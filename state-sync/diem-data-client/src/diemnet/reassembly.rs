@@ -0,0 +1,251 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Splitting requests for large ranges of transactions (or transaction outputs)
+//! into subrequests that can be sent to different peers concurrently, and
+//! reassembling the responses into a single chunk with proof.
+
+use crate::{ResponseCallback, ResponseError};
+use diem_types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    proof::{TransactionAccumulatorRangeProof, TransactionInfoListWithProof},
+    transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
+};
+use std::cmp::min;
+use storage_service_types::{
+    StorageServiceRequest, TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+};
+
+/// The range of versions requested by a transaction or transaction output
+/// request, and the version the data is proven against.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct VersionRange {
+    pub start_version: Version,
+    pub end_version: Version,
+    pub proof_version: Version,
+}
+
+impl VersionRange {
+    /// Returns the range of the request, or `None` if the request is not for a
+    /// range of transactions or transaction outputs.
+    pub fn from_request(request: &StorageServiceRequest) -> Option<Self> {
+        match request {
+            StorageServiceRequest::GetTransactionsWithProof(request) => Some(Self {
+                start_version: request.start_version,
+                end_version: request.end_version,
+                proof_version: request.proof_version,
+            }),
+            StorageServiceRequest::GetTransactionOutputsWithProof(request) => Some(Self {
+                start_version: request.start_version,
+                end_version: request.end_version,
+                proof_version: request.proof_version,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of versions in the range.
+    pub fn num_versions(&self) -> u64 {
+        (self.end_version - self.start_version).saturating_add(1)
+    }
+}
+
+/// Returns the request for the given subrange of a transaction or transaction
+/// output request.
+fn subrequest(
+    request: &StorageServiceRequest,
+    start_version: Version,
+    end_version: Version,
+) -> StorageServiceRequest {
+    match request {
+        StorageServiceRequest::GetTransactionsWithProof(request) => {
+            StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+                start_version,
+                end_version,
+                ..request.clone()
+            })
+        }
+        StorageServiceRequest::GetTransactionOutputsWithProof(request) => {
+            StorageServiceRequest::GetTransactionOutputsWithProof(
+                TransactionOutputsWithProofRequest {
+                    start_version,
+                    end_version,
+                    ..request.clone()
+                },
+            )
+        }
+        _ => unreachable!("Only range requests can be split: {:?}", request),
+    }
+}
+
+/// Splits a transaction or transaction output request into at most
+/// `max_num_subrequests` requests for consecutive ranges of at least
+/// `min_subrequest_size` versions each. Any other request, or a range too small
+/// to be split, is returned as is.
+pub(crate) fn split_request(
+    request: &StorageServiceRequest,
+    max_num_subrequests: u64,
+    min_subrequest_size: u64,
+) -> Vec<StorageServiceRequest> {
+    let range = match VersionRange::from_request(request) {
+        Some(range) if range.start_version <= range.end_version => range,
+        _ => return vec![request.clone()],
+    };
+
+    let num_versions = range.num_versions();
+    let num_subrequests =
+        (num_versions / min_subrequest_size.max(1)).clamp(1, max_num_subrequests.max(1));
+    if num_subrequests == 1 {
+        return vec![request.clone()];
+    }
+
+    // Spread the versions as evenly as possible, the first subrequests get the
+    // remainder.
+    let subrequest_size = num_versions / num_subrequests;
+    let remainder = num_versions % num_subrequests;
+    let mut subrequests = vec![];
+    let mut start_version = range.start_version;
+    for index in 0..num_subrequests {
+        let size = subrequest_size + if index < remainder { 1 } else { 0 };
+        let end_version = min(start_version + (size - 1), range.end_version);
+        subrequests.push(subrequest(request, start_version, end_version));
+        start_version = end_version.saturating_add(1);
+    }
+    subrequests
+}
+
+/// A chunk of data over consecutive versions, with a proof against a ledger
+/// info, that can be fetched in parts from different peers and reassembled.
+pub(crate) trait ChunkWithProof {
+    /// Returns the version of the first item, or `None` if the chunk is empty.
+    fn first_version(&self) -> Option<Version>;
+
+    /// Returns the number of items in the chunk.
+    fn num_items(&self) -> u64;
+
+    /// Returns true iff the items of the chunk are proven by the ledger info.
+    fn verify_proof(&self, ledger_info: &LedgerInfo) -> bool;
+
+    /// Appends the next consecutive part of the chunk.
+    fn append(&mut self, next: Self);
+}
+
+impl ChunkWithProof for TransactionListWithProof {
+    fn first_version(&self) -> Option<Version> {
+        self.first_transaction_version
+    }
+
+    fn num_items(&self) -> u64 {
+        self.transactions.len() as u64
+    }
+
+    fn verify_proof(&self, ledger_info: &LedgerInfo) -> bool {
+        self.verify(ledger_info, self.first_transaction_version)
+            .is_ok()
+    }
+
+    fn append(&mut self, next: Self) {
+        if self.first_transaction_version.is_none() {
+            *self = next;
+            return;
+        }
+        self.transactions.extend(next.transactions);
+        self.events = match (self.events.take(), next.events) {
+            (Some(mut events), Some(next_events)) => {
+                events.extend(next_events);
+                Some(events)
+            }
+            _ => None,
+        };
+        append_proof(&mut self.proof, next.proof);
+    }
+}
+
+impl ChunkWithProof for TransactionOutputListWithProof {
+    fn first_version(&self) -> Option<Version> {
+        self.first_transaction_output_version
+    }
+
+    fn num_items(&self) -> u64 {
+        self.transactions_and_outputs.len() as u64
+    }
+
+    fn verify_proof(&self, ledger_info: &LedgerInfo) -> bool {
+        self.verify(ledger_info, self.first_transaction_output_version)
+            .is_ok()
+    }
+
+    fn append(&mut self, next: Self) {
+        if self.first_transaction_output_version.is_none() {
+            *self = next;
+            return;
+        }
+        self.transactions_and_outputs
+            .extend(next.transactions_and_outputs);
+        append_proof(&mut self.proof, next.proof);
+    }
+}
+
+/// Appends the proof of the next consecutive transaction infos. The left
+/// siblings of an accumulator range proof only depend on the first leaf of the
+/// range, and the right siblings only on the last one, so the proof of the
+/// whole range is made of the left siblings of the first part and the right
+/// siblings of the last part.
+fn append_proof(proof: &mut TransactionInfoListWithProof, next: TransactionInfoListWithProof) {
+    proof.ledger_info_to_transaction_infos_proof = TransactionAccumulatorRangeProof::new(
+        proof
+            .ledger_info_to_transaction_infos_proof
+            .left_siblings()
+            .clone(),
+        next.ledger_info_to_transaction_infos_proof
+            .right_siblings()
+            .clone(),
+    );
+    proof.transaction_infos.extend(next.transaction_infos);
+}
+
+/// Checks that a part holds exactly the requested range and, if any peer
+/// advertises a ledger info at the proof version, that the part is proven by
+/// one of them. The consumer verifies the reassembled chunk against a trusted
+/// ledger info anyway, this check is about knowing which peer sent bad data.
+pub(crate) fn check_part<T: ChunkWithProof>(
+    part: &T,
+    range: &VersionRange,
+    advertised_ledger_infos: &[LedgerInfoWithSignatures],
+) -> Result<(), ResponseError> {
+    if part.first_version() != Some(range.start_version) || part.num_items() != range.num_versions()
+    {
+        return Err(ResponseError::InvalidData);
+    }
+    if !advertised_ledger_infos.is_empty()
+        && !advertised_ledger_infos
+            .iter()
+            .any(|ledger_info| part.verify_proof(ledger_info.ledger_info()))
+    {
+        return Err(ResponseError::ProofVerificationError);
+    }
+    Ok(())
+}
+
+/// Reports a bad reassembled response to every peer that served a part of it.
+/// The parts were checked individually before reassembly whenever possible, so
+/// a reassembled response that still turns out to be bad implicates all of
+/// them.
+#[derive(Debug)]
+pub(crate) struct MultiPeerResponseCallback {
+    callbacks: Vec<Box<dyn ResponseCallback>>,
+}
+
+impl MultiPeerResponseCallback {
+    pub fn new(callbacks: Vec<Box<dyn ResponseCallback>>) -> Self {
+        Self { callbacks }
+    }
+}
+
+impl ResponseCallback for MultiPeerResponseCallback {
+    fn notify_bad_response(&self, error: ResponseError) {
+        for callback in &self.callbacks {
+            callback.notify_bad_response(error.clone());
+        }
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
 };
 use diem_config::{config::StorageServiceConfig, network_id::PeerNetworkId};
 use diem_logger::debug;
+use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use std::collections::HashMap;
 use storage_service_types::{StorageServerSummary, StorageServiceRequest};
 
@@ -18,10 +19,14 @@ const STARTING_SCORE: f64 = 50.0;
 const SUCCESSFUL_RESPONSE_DELTA: f64 = 1.0;
 /// Not necessarily a malicious response, but not super useful.
 const NOT_USEFUL_MULTIPLIER: f64 = 0.95;
+/// A valid response, but slower than the slow response threshold.
+const SLOW_RESPONSE_MULTIPLIER: f64 = 0.98;
 /// Likely to be a malicious response.
 const MALICIOUS_MULTIPLIER: f64 = 0.8;
 /// Ignore a peer when their score dips below this threshold.
 const IGNORE_PEER_THRESHOLD: f64 = 25.0;
+/// The selection weight of a peer that is about to be ignored.
+const MIN_SELECTION_WEIGHT: f64 = 0.1;
 
 pub(crate) enum ErrorType {
     /// A response or error that's not actively malicious but also doesn't help
@@ -30,6 +35,10 @@ pub(crate) enum ErrorType {
     /// A response or error that appears to be actively hindering progress or
     /// attempting to deceive us, e.g., invalid proof.
     Malicious,
+    /// A valid response that took longer than the slow response threshold.
+    /// Slow peers are selected less often and are eventually ignored if they
+    /// don't speed up.
+    Slow,
 }
 
 impl From<ResponseError> for ErrorType {
@@ -78,6 +87,7 @@ impl PeerState {
         let multiplier = match error {
             ErrorType::NotUseful => NOT_USEFUL_MULTIPLIER,
             ErrorType::Malicious => MALICIOUS_MULTIPLIER,
+            ErrorType::Slow => SLOW_RESPONSE_MULTIPLIER,
         };
         self.score = f64::max(self.score * multiplier, MIN_SCORE);
     }
@@ -121,6 +131,29 @@ impl PeerStates {
             .unwrap_or(false)
     }
 
+    /// Returns the weight of a peer when choosing the peers to send a data
+    /// request to: peers with higher scores are proportionally more likely to
+    /// be chosen.
+    pub fn selection_weight(&self, peer: &PeerNetworkId) -> f64 {
+        let score = self
+            .inner
+            .get(peer)
+            .map_or(STARTING_SCORE, |peer_state| peer_state.score);
+        f64::max(score - IGNORE_PEER_THRESHOLD, MIN_SELECTION_WEIGHT)
+    }
+
+    /// Returns the synced ledger infos at the given version advertised by the
+    /// peers that are not ignored.
+    pub fn advertised_ledger_infos(&self, version: Version) -> Vec<LedgerInfoWithSignatures> {
+        self.inner
+            .values()
+            .filter_map(PeerState::storage_summary_if_not_ignored)
+            .filter_map(|summary| summary.data_summary.synced_ledger_info.as_ref())
+            .filter(|ledger_info| ledger_info.ledger_info().version() == version)
+            .cloned()
+            .collect()
+    }
+
     pub fn update_score_success(&mut self, peer: PeerNetworkId) {
         let old_score = self.inner.entry(peer).or_default().score;
         self.inner.entry(peer).or_default().update_score_success();
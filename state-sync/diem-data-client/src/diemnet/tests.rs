@@ -1,19 +1,29 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{DataSummaryPoller, DiemDataClient, DiemNetDataClient, Error};
+use super::{
+    reassembly::{split_request, VersionRange},
+    DataSummaryPoller, DiemDataClient, DiemNetDataClient, Error,
+};
+use crate::ResponseError;
 use channel::{diem_channel, message_queues::QueueStyle};
 use claim::{assert_err, assert_matches};
 use diem_config::{
     config::{DiemDataClientConfig, StorageServiceConfig},
     network_id::{NetworkId, PeerNetworkId},
 };
-use diem_crypto::HashValue;
+use diem_crypto::{hash::CryptoHash, HashValue};
 use diem_time_service::{MockTimeService, TimeService};
 use diem_types::{
     block_info::BlockInfo,
+    block_metadata::BlockMetadata,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    transaction::{TransactionListWithProof, Version},
+    proof::{
+        TransactionAccumulatorInternalNode, TransactionAccumulatorRangeProof,
+        TransactionInfoListWithProof,
+    },
+    transaction::{Transaction, TransactionInfo, TransactionListWithProof, Version},
+    vm_status::KeptVMStatus,
     PeerId,
 };
 use futures::StreamExt;
@@ -24,7 +34,11 @@ use network::{
     protocols::{network::NewNetworkSender, wire::handshake::v1::ProtocolId},
     transport::ConnectionMetadata,
 };
-use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 use storage_service_client::{StorageServiceClient, StorageServiceNetworkSender};
 use storage_service_server::network::{NetworkRequest, ResponseSender};
 use storage_service_types::{
@@ -34,9 +48,24 @@ use storage_service_types::{
 };
 
 fn mock_ledger_info(version: Version) -> LedgerInfoWithSignatures {
+    mock_ledger_info_with_root(version, HashValue::zero())
+}
+
+fn mock_ledger_info_with_root(
+    version: Version,
+    accumulator_root_hash: HashValue,
+) -> LedgerInfoWithSignatures {
     LedgerInfoWithSignatures::new(
         LedgerInfo::new(
-            BlockInfo::new(0, 0, HashValue::zero(), HashValue::zero(), version, 0, None),
+            BlockInfo::new(
+                0,
+                0,
+                HashValue::zero(),
+                accumulator_root_hash,
+                version,
+                0,
+                None,
+            ),
             HashValue::zero(),
         ),
         BTreeMap::new(),
@@ -44,6 +73,13 @@ fn mock_ledger_info(version: Version) -> LedgerInfoWithSignatures {
 }
 
 fn mock_storage_summary(version: Version) -> StorageServerSummary {
+    mock_storage_summary_with_ledger_info(mock_ledger_info(version))
+}
+
+fn mock_storage_summary_with_ledger_info(
+    ledger_info: LedgerInfoWithSignatures,
+) -> StorageServerSummary {
+    let version = ledger_info.ledger_info().version();
     StorageServerSummary {
         protocol_metadata: ProtocolMetadata {
             max_epoch_chunk_size: 1000,
@@ -52,7 +88,7 @@ fn mock_storage_summary(version: Version) -> StorageServerSummary {
             max_account_states_chunk_size: 1000,
         },
         data_summary: DataSummary {
-            synced_ledger_info: Some(mock_ledger_info(version)),
+            synced_ledger_info: Some(ledger_info),
             epoch_ending_ledger_infos: None,
             transactions: Some(CompleteDataRange::new(0, version).unwrap()),
             transaction_outputs: None,
@@ -66,15 +102,140 @@ struct MockNetwork {
     peer_infos: Arc<PeerMetadataStorage>,
 }
 
+/// A ledger of mock transactions, with the transaction accumulator needed to
+/// prove any range of them.
+struct MockLedger {
+    transactions: Vec<Transaction>,
+    transaction_infos: Vec<TransactionInfo>,
+    /// The accumulator nodes, level by level from the leaves to the root.
+    accumulator: Vec<Vec<HashValue>>,
+}
+
+impl MockLedger {
+    /// Creates a ledger of `num_transactions` transactions, which must be a
+    /// power of two so that the accumulator is a perfect binary tree.
+    fn new(num_transactions: usize) -> Self {
+        assert!(num_transactions.is_power_of_two());
+        let transactions: Vec<_> = (0..num_transactions)
+            .map(|round| {
+                Transaction::BlockMetadata(BlockMetadata::new(
+                    HashValue::random(),
+                    round as u64,
+                    0,
+                    vec![],
+                    PeerId::random(),
+                ))
+            })
+            .collect();
+        let transaction_infos: Vec<_> = transactions
+            .iter()
+            .map(|transaction| {
+                TransactionInfo::new(
+                    transaction.hash(),
+                    HashValue::zero(),
+                    HashValue::zero(),
+                    0,
+                    KeptVMStatus::Executed,
+                )
+            })
+            .collect();
+
+        let mut accumulator = vec![transaction_infos
+            .iter()
+            .map(CryptoHash::hash)
+            .collect::<Vec<_>>()];
+        while accumulator.last().unwrap().len() > 1 {
+            let parents = accumulator
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|children| {
+                    TransactionAccumulatorInternalNode::new(children[0], children[1]).hash()
+                })
+                .collect();
+            accumulator.push(parents);
+        }
+
+        Self {
+            transactions,
+            transaction_infos,
+            accumulator,
+        }
+    }
+
+    fn ledger_info(&self) -> LedgerInfoWithSignatures {
+        mock_ledger_info_with_root(
+            self.transactions.len() as u64 - 1,
+            self.accumulator.last().unwrap()[0],
+        )
+    }
+
+    fn transactions_with_proof(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> TransactionListWithProof {
+        let mut left_siblings = vec![];
+        let mut right_siblings = vec![];
+        for (level, nodes) in self.accumulator[..self.accumulator.len() - 1]
+            .iter()
+            .enumerate()
+        {
+            let first_position = (start_version >> level) as usize;
+            if first_position % 2 == 1 {
+                left_siblings.push(nodes[first_position - 1]);
+            }
+            let last_position = (end_version >> level) as usize;
+            if last_position % 2 == 0 {
+                right_siblings.push(nodes[last_position + 1]);
+            }
+        }
+
+        let range = start_version as usize..=end_version as usize;
+        TransactionListWithProof::new(
+            self.transactions[range.clone()].to_vec(),
+            None,
+            Some(start_version),
+            TransactionInfoListWithProof::new(
+                TransactionAccumulatorRangeProof::new(left_siblings, right_siblings),
+                self.transaction_infos[range].to_vec(),
+            ),
+        )
+    }
+
+    /// Returns the transactions with a proof that doesn't match the ledger.
+    fn transactions_with_corrupt_proof(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> TransactionListWithProof {
+        let mut transactions_with_proof = self.transactions_with_proof(start_version, end_version);
+        transactions_with_proof.proof.transaction_infos[0] = TransactionInfo::new(
+            transactions_with_proof.transactions[0].hash(),
+            HashValue::random(),
+            HashValue::zero(),
+            0,
+            KeptVMStatus::Executed,
+        );
+        transactions_with_proof
+    }
+}
+
 impl MockNetwork {
     fn new() -> (Self, MockTimeService, DiemNetDataClient, DataSummaryPoller) {
+        Self::new_with_config(DiemDataClientConfig::default())
+    }
+
+    fn new_with_config(
+        data_client_config: DiemDataClientConfig,
+    ) -> (Self, MockTimeService, DiemNetDataClient, DataSummaryPoller) {
         let queue_cfg = diem_channel::Config::new(10).queue_style(QueueStyle::FIFO);
         let (peer_mgr_reqs_tx, peer_mgr_reqs_rx) = queue_cfg.build();
         let (connection_reqs_tx, _connection_reqs_rx) = queue_cfg.build();
 
         let network_sender = MultiNetworkSender::new(hashmap! {
             NetworkId::Validator => StorageServiceNetworkSender::new(
-                PeerManagerRequestSender::new(peer_mgr_reqs_tx),
+                PeerManagerRequestSender::new(peer_mgr_reqs_tx, network::constants::MAX_FRAME_SIZE),
                 ConnectionRequestSender::new(connection_reqs_tx),
             )
         });
@@ -84,7 +245,7 @@ impl MockNetwork {
 
         let mock_time = TimeService::mock();
         let (client, poller) = DiemNetDataClient::new(
-            DiemDataClientConfig::default(),
+            data_client_config,
             StorageServiceConfig::default(),
             mock_time.clone(),
             network_client,
@@ -423,3 +584,258 @@ async fn bad_peer_is_eventually_added_back() {
         .transactions
         .contains(&CompleteDataRange::new(0, 200).unwrap()));
 }
+
+#[test]
+fn split_request_covers_range() {
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version: 100,
+        start_version: 10,
+        end_version: 19,
+        include_events: true,
+    });
+    let ranges = |max_num_subrequests, min_subrequest_size| {
+        split_request(&request, max_num_subrequests, min_subrequest_size)
+            .iter()
+            .map(|subrequest| {
+                let range = VersionRange::from_request(subrequest).unwrap();
+                assert_eq!(range.proof_version, 100);
+                (range.start_version, range.end_version)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The first subrequests get the remainder.
+    assert_eq!(ranges(3, 2), vec![(10, 13), (14, 16), (17, 19)]);
+    // Subrequests are never smaller than the minimum size.
+    assert_eq!(ranges(4, 4), vec![(10, 14), (15, 19)]);
+    // Small ranges are not split.
+    assert_eq!(ranges(4, 20), vec![(10, 19)]);
+    assert_eq!(ranges(1, 1), vec![(10, 19)]);
+}
+
+#[tokio::test]
+async fn large_request_is_split_across_peers_and_reassembled() {
+    ::diem_logger::Logger::init_for_testing();
+    let data_client_config = DiemDataClientConfig {
+        max_num_subrequests: 4,
+        min_subrequest_size: 2,
+        ..DiemDataClientConfig::default()
+    };
+    let (mut mock_network, _mock_time, client, _poller) =
+        MockNetwork::new_with_config(data_client_config);
+
+    // All peers advertise the whole ledger.
+    let ledger = Arc::new(MockLedger::new(8));
+    for _ in 0..4 {
+        let peer = mock_network.add_connected_peer();
+        client.update_summary(
+            peer,
+            mock_storage_summary_with_ledger_info(ledger.ledger_info()),
+        );
+    }
+    client.update_global_summary_cache();
+
+    // Wait for all the subrequests, then respond in the reverse order.
+    let responder_ledger = ledger.clone();
+    let responder = tokio::spawn(async move {
+        let mut requests = vec![];
+        for _ in 0..4 {
+            requests.push(mock_network.next_request().await.unwrap());
+        }
+        let mut served_ranges = vec![];
+        for (peer, _, request, response_sender) in requests.into_iter().rev() {
+            let range = VersionRange::from_request(&request).unwrap();
+            served_ranges.push((peer, range.start_version, range.end_version));
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                responder_ledger.transactions_with_proof(range.start_version, range.end_version),
+            )));
+        }
+        served_ranges
+    });
+
+    // The parts are reassembled in order, with a valid proof for the whole range.
+    let response = client
+        .get_transactions_with_proof(7, 0, 7, false)
+        .await
+        .unwrap();
+    assert_eq!(response.payload, ledger.transactions_with_proof(0, 7));
+    response
+        .payload
+        .verify(ledger.ledger_info().ledger_info(), Some(0))
+        .unwrap();
+
+    // Each peer served a different part of the range.
+    let mut served_ranges = responder.await.unwrap();
+    let peers: HashSet<_> = served_ranges.iter().map(|(peer, _, _)| *peer).collect();
+    assert_eq!(peers.len(), 4);
+    served_ranges.sort_by_key(|(_, start_version, _)| *start_version);
+    let ranges: Vec<_> = served_ranges
+        .into_iter()
+        .map(|(_, start_version, end_version)| (start_version, end_version))
+        .collect();
+    assert_eq!(ranges, vec![(0, 1), (2, 3), (4, 5), (6, 7)]);
+}
+
+#[tokio::test]
+async fn corrupt_subresponse_is_refetched_and_lying_peer_is_ignored() {
+    ::diem_logger::Logger::init_for_testing();
+    let data_client_config = DiemDataClientConfig {
+        max_num_subrequests: 2,
+        min_subrequest_size: 2,
+        ..DiemDataClientConfig::default()
+    };
+    let (mut mock_network, _mock_time, client, _poller) =
+        MockNetwork::new_with_config(data_client_config);
+
+    // Both peers advertise the whole ledger, but one of them sends corrupt proofs.
+    let ledger = Arc::new(MockLedger::new(8));
+    let honest_peer = mock_network.add_connected_peer();
+    let lying_peer = mock_network.add_connected_peer();
+    for peer in &[honest_peer, lying_peer] {
+        client.update_summary(
+            *peer,
+            mock_storage_summary_with_ledger_info(ledger.ledger_info()),
+        );
+    }
+    client.update_global_summary_cache();
+
+    let responder_ledger = ledger.clone();
+    tokio::spawn(async move {
+        while let Some((peer, _, request, response_sender)) = mock_network.next_request().await {
+            let range = VersionRange::from_request(&request).unwrap();
+            let transactions_with_proof = if peer == lying_peer.peer_id() {
+                responder_ledger
+                    .transactions_with_corrupt_proof(range.start_version, range.end_version)
+            } else {
+                responder_ledger.transactions_with_proof(range.start_version, range.end_version)
+            };
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                transactions_with_proof,
+            )));
+        }
+    });
+
+    // The corrupt parts are fetched again from the honest peer, so every
+    // response is valid.
+    for _ in 0..10 {
+        let response = client
+            .get_transactions_with_proof(7, 0, 7, false)
+            .await
+            .unwrap();
+        assert_eq!(response.payload, ledger.transactions_with_proof(0, 7));
+    }
+
+    // The lying peer is eventually ignored.
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version: 7,
+        start_version: 0,
+        end_version: 7,
+        include_events: false,
+    });
+    let peer_states = client.peer_states.read();
+    assert!(!peer_states.can_service_request(&lying_peer, &request));
+    assert!(peer_states.can_service_request(&honest_peer, &request));
+}
+
+#[tokio::test]
+async fn bad_reassembled_response_is_reported_to_all_peers() {
+    ::diem_logger::Logger::init_for_testing();
+    let data_client_config = DiemDataClientConfig {
+        max_num_subrequests: 2,
+        min_subrequest_size: 2,
+        ..DiemDataClientConfig::default()
+    };
+    let (mut mock_network, _mock_time, client, _poller) =
+        MockNetwork::new_with_config(data_client_config);
+
+    // The peers only advertise a ledger info at a later version than the proof
+    // version, so the parts can't be checked before they are reassembled.
+    let ledger = Arc::new(MockLedger::new(8));
+    let peers = vec![
+        mock_network.add_connected_peer(),
+        mock_network.add_connected_peer(),
+    ];
+    for peer in &peers {
+        client.update_summary(*peer, mock_storage_summary(8));
+    }
+    client.update_global_summary_cache();
+
+    let responder_ledger = ledger.clone();
+    tokio::spawn(async move {
+        while let Some((_, _, request, response_sender)) = mock_network.next_request().await {
+            let range = VersionRange::from_request(&request).unwrap();
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                responder_ledger.transactions_with_proof(range.start_version, range.end_version),
+            )));
+        }
+    });
+
+    // Keep reporting the reassembled responses as invalid, until both peers
+    // are ignored.
+    let mut seen_data_unavailable_err = false;
+    for _ in 0..10 {
+        match client.get_transactions_with_proof(7, 0, 7, false).await {
+            Ok(response) => {
+                assert!(!seen_data_unavailable_err);
+                response
+                    .context
+                    .response_callback
+                    .notify_bad_response(ResponseError::ProofVerificationError);
+            }
+            Err(Error::DataIsUnavailable(_)) => seen_data_unavailable_err = true,
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+    }
+    assert!(seen_data_unavailable_err);
+}
+
+#[tokio::test]
+async fn slow_peer_is_eventually_ignored() {
+    ::diem_logger::Logger::init_for_testing();
+    let (mut mock_network, mock_time, client, _poller) = MockNetwork::new();
+
+    let fast_peer = mock_network.add_connected_peer();
+    let slow_peer = mock_network.add_connected_peer();
+
+    // Fast peer advertises txns 0 -> 100, slow peer advertises txns 0 -> 200,
+    // so the upper range can only be served by the slow peer.
+    client.update_summary(fast_peer, mock_storage_summary(100));
+    client.update_summary(slow_peer, mock_storage_summary(200));
+    client.update_global_summary_cache();
+
+    // The slow peer takes longer than the slow response threshold to respond.
+    let slow_response_latency =
+        Duration::from_millis(DiemDataClientConfig::default().slow_response_threshold_ms * 2);
+    tokio::spawn(async move {
+        while let Some((peer, _, _, response_sender)) = mock_network.next_request().await {
+            if peer == slow_peer.peer_id() {
+                mock_time.advance(slow_response_latency);
+            }
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                TransactionListWithProof::new_empty(),
+            )));
+        }
+    });
+
+    // The slow peer keeps serving the requests until its score drops below
+    // the threshold, then the range is no longer serviceable.
+    let mut seen_data_unavailable_err = false;
+    for _ in 0..50 {
+        match client
+            .get_transactions_with_proof(200, 200, 200, false)
+            .await
+        {
+            Ok(_) => assert!(!seen_data_unavailable_err),
+            Err(Error::DataIsUnavailable(_)) => seen_data_unavailable_err = true,
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+    }
+    assert!(seen_data_unavailable_err);
+
+    // The fast peer is still used.
+    let response = client
+        .get_transactions_with_proof(100, 50, 100, false)
+        .await
+        .unwrap();
+    assert_eq!(response.payload, TransactionListWithProof::new_empty());
+}
@@ -60,6 +60,19 @@ impl NewNetworkSender for StateSyncSender {
     }
 }
 
+impl StateSyncSender {
+    /// Send a message over the compressed state sync protocol. Callers must
+    /// only use this for peers that advertised support for it in the handshake.
+    pub fn send_compressed_to(
+        &self,
+        recipient: PeerId,
+        message: StateSyncMessage,
+    ) -> Result<(), NetworkError> {
+        let protocol = ProtocolId::StateSyncDirectSendCompressed;
+        self.inner.send_to(recipient, protocol, message)
+    }
+}
+
 #[async_trait]
 impl ApplicationNetworkSender<StateSyncMessage> for StateSyncSender {
     fn send_to(&self, recipient: PeerId, message: StateSyncMessage) -> Result<(), NetworkError> {
@@ -80,7 +93,10 @@ impl ApplicationNetworkSender<StateSyncMessage> for StateSyncSender {
 /// Configuration for the network endpoints to support state sync.
 pub fn network_endpoint_config() -> AppConfig {
     AppConfig::p2p(
-        [ProtocolId::StateSyncDirectSend],
+        [
+            ProtocolId::StateSyncDirectSend,
+            ProtocolId::StateSyncDirectSendCompressed,
+        ],
         diem_channel::Config::new(STATE_SYNC_MAX_BUFFER_SIZE)
             .queue_style(QueueStyle::LIFO)
             .counters(&counters::PENDING_STATE_SYNC_NETWORK_EVENTS),
@@ -15,7 +15,10 @@ use diem_config::{
 use diem_logger::prelude::*;
 use itertools::Itertools;
 use netcore::transport::ConnectionOrigin;
-use network::{protocols::network::ApplicationNetworkSender, transport::ConnectionMetadata};
+use network::{
+    error::NetworkError, protocols::network::ApplicationNetworkSender,
+    transport::ConnectionMetadata, ProtocolId,
+};
 use rand::{
     distributions::{Distribution, WeightedIndex},
     thread_rng,
@@ -25,7 +28,7 @@ use std::{
     cmp::Ordering,
     collections::{
         hash_map::Entry::{Occupied, Vacant},
-        BTreeMap, HashMap,
+        BTreeMap, HashMap, HashSet,
     },
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
@@ -86,6 +89,8 @@ pub struct RequestManager {
     // NetworkId.
     multicast_network_level: NetworkId,
     network_senders: HashMap<NetworkId, StateSyncSender>,
+    // Peers that negotiated the compressed state sync protocol during the handshake
+    compression_peers: HashSet<PeerNetworkId>,
 }
 
 impl RequestManager {
@@ -104,6 +109,7 @@ impl RequestManager {
             multicast_timeout,
             multicast_network_level,
             network_senders,
+            compression_peers: HashSet::new(),
         }
     }
 
@@ -112,6 +118,15 @@ impl RequestManager {
         peer: PeerNetworkId,
         metadata: ConnectionMetadata,
     ) -> Result<(), Error> {
+        // Chunk responses are also sent to peers that aren't valid upstream
+        // peers, so track compression support before filtering them out.
+        if metadata
+            .application_protocols
+            .contains(ProtocolId::StateSyncDirectSendCompressed)
+        {
+            self.compression_peers.insert(peer);
+        }
+
         if !self.is_valid_state_sync_peer(&peer, metadata.origin) {
             return Err(Error::InvalidStateSyncPeer(
                 peer.to_string(),
@@ -145,6 +160,7 @@ impl RequestManager {
 
     pub fn disable_peer(&mut self, peer: &PeerNetworkId) -> Result<(), Error> {
         info!(LogSchema::new(LogEntry::LostPeer).peer(peer));
+        self.compression_peers.remove(peer);
 
         if self.peer_scores.contains_key(peer) {
             counters::ACTIVE_UPSTREAM_PEERS
@@ -277,9 +293,8 @@ impl RequestManager {
         let mut failed_peer_sends = vec![];
 
         for peer in peers {
-            let sender = self.get_network_sender(&peer);
             let peer_id = peer.peer_id();
-            let send_result = sender.send_to(peer_id, msg.clone());
+            let send_result = self.send_to_peer(&peer, msg.clone());
             let curr_log = log.clone().peer(&peer);
             let result_label = if let Err(e) = send_result {
                 failed_peer_sends.push(peer);
@@ -320,14 +335,26 @@ impl RequestManager {
             .clone()
     }
 
+    /// Sends a message to the peer, compressing it if the peer supports it.
+    fn send_to_peer(
+        &mut self,
+        peer: &PeerNetworkId,
+        message: StateSyncMessage,
+    ) -> Result<(), NetworkError> {
+        let sender = self.get_network_sender(peer);
+        if self.compression_peers.contains(peer) {
+            sender.send_compressed_to(peer.peer_id(), message)
+        } else {
+            sender.send_to(peer.peer_id(), message)
+        }
+    }
+
     pub fn send_chunk_response(
         &mut self,
         peer: &PeerNetworkId,
         message: StateSyncMessage,
     ) -> Result<(), Error> {
-        self.get_network_sender(peer)
-            .send_to(peer.peer_id(), message)
-            .map_err(|err| err.into())
+        self.send_to_peer(peer, message).map_err(|err| err.into())
     }
 
     pub fn add_request(&mut self, version: u64, peers: Vec<PeerNetworkId>) -> ChunkRequestInfo {
@@ -569,6 +596,14 @@ fn update_multicast_network_counter(multicast_network_level: NetworkId) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chunk_request::TargetType;
+    use channel::{diem_channel, message_queues::QueueStyle};
+    use futures::{executor::block_on, StreamExt};
+    use network::{
+        peer_manager::{ConnectionRequestSender, PeerManagerRequest, PeerManagerRequestSender},
+        protocols::{network::NewNetworkSender, wire::handshake::v1::ProtocolIdSet},
+    };
+    use std::iter::FromIterator;
 
     const NUM_CHUNKS_TO_PROCESS: u64 = 50;
     const NUM_PICKS_TO_MAKE: u64 = 1000;
@@ -596,6 +631,66 @@ mod tests {
         assert!(!request_manager.no_available_peers());
     }
 
+    #[test]
+    fn test_compressed_chunk_responses() {
+        let (network_reqs_tx, mut network_reqs_rx) = diem_channel::new(QueueStyle::FIFO, 8, None);
+        let (connection_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
+        let network_sender = StateSyncSender::new(
+            PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
+            ConnectionRequestSender::new(connection_reqs_tx),
+        );
+        let mut request_manager = RequestManager::new(
+            Duration::from_secs(10),
+            Duration::from_secs(30),
+            vec![(NetworkId::Validator, network_sender)]
+                .into_iter()
+                .collect(),
+        );
+
+        // Enable one peer that supports compression and one that doesn't
+        let compression_peer = PeerNetworkId::random_validator();
+        let mut metadata = ConnectionMetadata::mock(compression_peer.peer_id());
+        metadata.application_protocols = ProtocolIdSet::from_iter([
+            ProtocolId::StateSyncDirectSend,
+            ProtocolId::StateSyncDirectSendCompressed,
+        ]);
+        request_manager
+            .enable_peer(compression_peer, metadata)
+            .unwrap();
+        let legacy_peer = PeerNetworkId::random_validator();
+        add_validator_to_request_manager(&mut request_manager, &legacy_peer, PeerRole::Validator);
+
+        // Verify each peer receives the encoding it negotiated
+        for (peer, expected_protocol) in [
+            (compression_peer, ProtocolId::StateSyncDirectSendCompressed),
+            (legacy_peer, ProtocolId::StateSyncDirectSend),
+        ] {
+            let message = StateSyncMessage::GetChunkRequest(Box::new(GetChunkRequest::new(
+                0,
+                0,
+                250,
+                TargetType::HighestAvailable {
+                    target_li: None,
+                    timeout_ms: 10_000,
+                },
+            )));
+            request_manager.send_chunk_response(&peer, message).unwrap();
+            match block_on(network_reqs_rx.next()).unwrap() {
+                PeerManagerRequest::SendDirectSend(recipient, message) => {
+                    assert_eq!(recipient, peer.peer_id());
+                    assert_eq!(message.protocol_id, expected_protocol);
+                }
+                request => panic!("Unexpected network request: {:?}", request),
+            }
+        }
+
+        // Disabling the peer forgets that it supports compression
+        request_manager.disable_peer(&compression_peer).unwrap();
+        assert!(!request_manager
+            .compression_peers
+            .contains(&compression_peer));
+    }
+
     #[test]
     fn test_score_chunk_success() {
         let num_validators = 4;
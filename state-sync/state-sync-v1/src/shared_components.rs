@@ -185,7 +185,7 @@ pub(crate) mod test_utils {
         let (network_reqs_tx, _network_reqs_rx) = diem_channel::new(QueueStyle::FIFO, 8, None);
         let (connection_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
         let network_sender = StateSyncSender::new(
-            PeerManagerRequestSender::new(network_reqs_tx),
+            PeerManagerRequestSender::new(network_reqs_tx, network::constants::MAX_FRAME_SIZE),
             ConnectionRequestSender::new(connection_reqs_tx),
         );
         let network_id = NetworkId::Validator;
@@ -359,10 +359,17 @@ impl StateSyncEnvironment {
                     diem_channel::new(QueueStyle::LIFO, 1, None);
                 let (conn_status_tx, conn_status_rx) = conn_notifs_channel::new();
                 let network_sender = StateSyncSender::new(
-                    PeerManagerRequestSender::new(network_reqs_tx),
+                    PeerManagerRequestSender::new(
+                        network_reqs_tx,
+                        network::constants::MAX_FRAME_SIZE,
+                    ),
                     ConnectionRequestSender::new(connection_reqs_tx),
                 );
-                let network_events = StateSyncEvents::new(network_notifs_rx, conn_status_rx);
+                let network_events = StateSyncEvents::new(
+                    network_notifs_rx,
+                    conn_status_rx,
+                    network::constants::MAX_FRAME_SIZE,
+                );
                 self.network_reqs_rxs.insert(peer_id, network_reqs_rx);
                 self.network_notifs_txs.insert(peer_id, network_notifs_tx);
                 self.network_conn_event_notifs_txs
@@ -424,7 +431,7 @@ impl StateSyncEnvironment {
             let receiver_network_notif_tx = self.network_notifs_txs.get_mut(&receiver_id).unwrap();
             receiver_network_notif_tx
                 .push(
-                    (sender_peer_id, ProtocolId::StateSyncDirectSend),
+                    (sender_peer_id, msg.protocol_id),
                     PeerManagerNotification::RecvMessage(sender_peer_id, msg.clone()),
                 )
                 .unwrap();
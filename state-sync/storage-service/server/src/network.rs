@@ -44,8 +44,9 @@ impl NewNetworkEvents for StorageServiceNetworkEvents {
     fn new(
         peer_mgr_notifs_rx: diem_channel::Receiver<(PeerId, ProtocolId), PeerManagerNotification>,
         connection_notifs_rx: diem_channel::Receiver<PeerId, ConnectionNotification>,
+        max_message_size: usize,
     ) -> Self {
-        let events = NetworkEvents::new(peer_mgr_notifs_rx, connection_notifs_rx)
+        let events = NetworkEvents::new(peer_mgr_notifs_rx, connection_notifs_rx, max_message_size)
             .filter_map(|event| future::ready(Self::event_to_request(event)))
             .boxed();
 
@@ -324,8 +324,11 @@ impl MockClient {
             .unwrap();
         let (peer_mgr_notifs_tx, peer_mgr_notifs_rx) = queue_cfg.build();
         let (_connection_notifs_tx, connection_notifs_rx) = queue_cfg.build();
-        let network_requests =
-            StorageServiceNetworkEvents::new(peer_mgr_notifs_rx, connection_notifs_rx);
+        let network_requests = StorageServiceNetworkEvents::new(
+            peer_mgr_notifs_rx,
+            connection_notifs_rx,
+            network::constants::MAX_FRAME_SIZE,
+        );
 
         let executor = tokio::runtime::Handle::current();
         let storage_server = StorageServiceServer::new(
@@ -364,7 +367,7 @@ impl MockClient {
         // wait for the response and deserialize
         let response = res_rx.await.unwrap().unwrap();
         let response = protocol_id
-            .from_bytes::<StorageServiceMessage>(&response)
+            .from_bytes::<StorageServiceMessage>(&response, network::constants::MAX_FRAME_SIZE)
             .unwrap();
         match response {
             StorageServiceMessage::Response(response) => response,
@@ -58,7 +58,8 @@ async fn main_impl() -> Result<()> {
         false, /* read_only */
         None,  /* pruner */
         opt.rocksdb_opt.into(),
-        true, /* account_count_migration */
+        true,  /* account_count_migration */
+        false, /* enable_resource_type_index */
     )?)
     .get_restore_handler();
     ReplayVerifyCoordinator::new(
@@ -173,6 +173,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
                 None,  /* pruner */
                 opt.rocksdb_opt.into(),
                 opt.account_count_migration,
+                false, /* enable_resource_type_index */
             )?)
             .get_restore_handler();
             RestoreRunMode::Restore { restore_handler }
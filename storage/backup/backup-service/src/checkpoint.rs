@@ -0,0 +1,414 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Online DB checkpoints.
+//!
+//! A checkpoint directory mirrors the layout of the node's storage dir, so that it can be used as
+//! the storage dir of a new node directly:
+//!
+//! ```text
+//! <checkpoint>/diemdb/          physical DiemDB checkpoint
+//! <checkpoint>/consensusdb/     physical ConsensusDB checkpoint, validators only
+//! <checkpoint>/checkpoint.json  DbCheckpointManifest
+//! ```
+
+use anyhow::{ensure, format_err, Result};
+use diem_config::config::{NodeConfig, RocksdbConfig};
+use diem_logger::prelude::*;
+use diem_types::{
+    account_config::diem_root_address, epoch_change::Verifier,
+    ledger_info::LedgerInfoWithSignatures, transaction::Version, waypoint::Waypoint,
+};
+use diemdb::DiemDB;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use storage_interface::DbReader;
+
+pub const DIEMDB_DIR: &str = "diemdb";
+pub const CONSENSUSDB_DIR: &str = "consensusdb";
+pub const MANIFEST_FILE: &str = "checkpoint.json";
+
+/// Creates a physical checkpoint of an auxiliary DB (e.g. ConsensusDB) in the given directory.
+pub type AuxDbCheckpointFn = Box<dyn Fn(&Path) -> Result<()> + Send + Sync>;
+
+/// Describes a checkpoint made by `DbCheckpointer`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DbCheckpointManifest {
+    /// Latest committed version in the DiemDB checkpoint.
+    pub version: Version,
+    /// Epoch of the latest ledger info in the DiemDB checkpoint.
+    pub epoch: u64,
+    /// Waypoint of the latest ledger info in the DiemDB checkpoint.
+    pub waypoint: Waypoint,
+    /// Whether the checkpoint packages a ConsensusDB as well.
+    pub has_consensus_db: bool,
+}
+
+impl DbCheckpointManifest {
+    pub fn load(checkpoint_dir: &Path) -> Result<Self> {
+        let path = checkpoint_dir.join(MANIFEST_FILE);
+        let bytes =
+            fs::read(&path).map_err(|e| format_err!("Failed to read {}: {}", path.display(), e))?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn save(&self, checkpoint_dir: &Path) -> Result<()> {
+        fs::write(
+            checkpoint_dir.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Makes consistent checkpoints of a running node's DBs.
+pub struct DbCheckpointer {
+    diem_db: Arc<DiemDB>,
+    consensus_db: Option<AuxDbCheckpointFn>,
+    root_dir: PathBuf,
+    auth_token: String,
+}
+
+impl DbCheckpointer {
+    pub fn new(
+        diem_db: Arc<DiemDB>,
+        consensus_db: Option<AuxDbCheckpointFn>,
+        root_dir: PathBuf,
+        auth_token: String,
+    ) -> Self {
+        Self {
+            diem_db,
+            consensus_db,
+            root_dir,
+            auth_token,
+        }
+    }
+
+    /// Checks the value of the `Authorization` header of a request.
+    pub fn is_authorized(&self, authorization: Option<&str>) -> bool {
+        match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+            Some(token) => constant_time_eq(token.as_bytes(), self.auth_token.as_bytes()),
+            None => false,
+        }
+    }
+
+    /// Creates a checkpoint under the root dir and returns its path and manifest.
+    ///
+    /// The DiemDB checkpoint is taken first; the committed version it carries is read back from
+    /// the checkpoint itself, so the manifest is consistent with the data even if commits land
+    /// while the checkpoint is being made. The ConsensusDB is checkpointed afterwards, and
+    /// consensus can only be ahead of the ledger, which state sync tolerates.
+    pub fn create_checkpoint(&self) -> Result<(PathBuf, DbCheckpointManifest)> {
+        fs::create_dir_all(&self.root_dir)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let staging_dir = self.root_dir.join(format!(".staging-{}", timestamp));
+        ensure!(
+            !staging_dir.exists(),
+            "Checkpoint staging dir {} already exists.",
+            staging_dir.display(),
+        );
+        fs::create_dir_all(&staging_dir)?;
+
+        let result = self.create_checkpoint_impl(&staging_dir);
+        let manifest = match result {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                fs::remove_dir_all(&staging_dir).unwrap_or_else(|err| {
+                    warn!(
+                        "Failed to clean up checkpoint staging dir {}: {}",
+                        staging_dir.display(),
+                        err
+                    )
+                });
+                return Err(e);
+            }
+        };
+
+        let checkpoint_dir = self
+            .root_dir
+            .join(format!("checkpoint-v{}-{}", manifest.version, timestamp));
+        fs::rename(&staging_dir, &checkpoint_dir)?;
+        info!(
+            version = manifest.version,
+            epoch = manifest.epoch,
+            path = checkpoint_dir.as_path(),
+            "Created DB checkpoint."
+        );
+        Ok((checkpoint_dir, manifest))
+    }
+
+    fn create_checkpoint_impl(&self, staging_dir: &Path) -> Result<DbCheckpointManifest> {
+        self.diem_db
+            .create_checkpoint(staging_dir.join(DIEMDB_DIR))?;
+        let latest_li = {
+            let checkpoint_db = open_checkpoint_db(staging_dir)?;
+            checkpoint_db.get_latest_ledger_info()?
+        };
+        if let Some(checkpoint_consensus_db) = &self.consensus_db {
+            checkpoint_consensus_db(&staging_dir.join(CONSENSUSDB_DIR))?;
+        }
+
+        let manifest = DbCheckpointManifest {
+            version: latest_li.ledger_info().version(),
+            epoch: latest_li.ledger_info().epoch(),
+            waypoint: Waypoint::new_any(latest_li.ledger_info()),
+            has_consensus_db: self.consensus_db.is_some(),
+        };
+        manifest.save(staging_dir)?;
+        Ok(manifest)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn open_checkpoint_db(checkpoint_dir: &Path) -> Result<DiemDB> {
+    DiemDB::open(
+        checkpoint_dir,
+        true, /* readonly */
+        None, /* pruner */
+        RocksdbConfig::default(),
+        false, /* account_count_migration, ignored anyway */
+        false, /* enable_resource_type_index */
+    )
+}
+
+/// Verifies the checkpoint in `checkpoint_dir` against a trusted waypoint.
+///
+/// The ledger info at the waypoint version must match the waypoint, and every epoch change from
+/// there on up to the latest ledger info in the checkpoint must be signed by the validator set of
+/// the epoch before it. The latest transaction and the state tree in the checkpoint must then be
+/// proven by the latest ledger info. Returns the verified latest ledger info.
+pub fn verify_checkpoint(
+    checkpoint_dir: &Path,
+    trusted_waypoint: Waypoint,
+) -> Result<LedgerInfoWithSignatures> {
+    let manifest = DbCheckpointManifest::load(checkpoint_dir)?;
+    let db = open_checkpoint_db(checkpoint_dir)?;
+
+    let latest_li = db.get_latest_ledger_info()?;
+    let latest_version = latest_li.ledger_info().version();
+    ensure!(
+        latest_version == manifest.version && latest_li.ledger_info().epoch() == manifest.epoch,
+        "Checkpoint manifest (version {}, epoch {}) doesn't match its DB (version {}, epoch {}).",
+        manifest.version,
+        manifest.epoch,
+        latest_version,
+        latest_li.ledger_info().epoch(),
+    );
+    manifest.waypoint.verify(latest_li.ledger_info())?;
+    ensure!(
+        trusted_waypoint.version() <= latest_version,
+        "Trusted waypoint version {} is ahead of the checkpoint version {}.",
+        trusted_waypoint.version(),
+        latest_version,
+    );
+
+    let waypoint_li = db.get_epoch_ending_ledger_info(trusted_waypoint.version())?;
+    ensure!(
+        waypoint_li.ledger_info().version() == trusted_waypoint.version(),
+        "Trusted waypoint version {} is not an epoch ending version in the checkpoint.",
+        trusted_waypoint.version(),
+    );
+    trusted_waypoint.verify(waypoint_li.ledger_info())?;
+
+    let mut epoch_state = waypoint_li
+        .ledger_info()
+        .next_epoch_state()
+        .ok_or_else(|| format_err!("Waypoint LedgerInfo doesn't carry a ValidatorSet"))?
+        .clone();
+    let end_epoch = latest_li.ledger_info().next_block_epoch();
+    while epoch_state.epoch < end_epoch {
+        let proof = DbReader::get_epoch_ending_ledger_infos(&db, epoch_state.epoch, end_epoch)?;
+        epoch_state = proof
+            .verify(&epoch_state)?
+            .ledger_info()
+            .next_epoch_state()
+            .ok_or_else(|| format_err!("LedgerInfo doesn't carry a ValidatorSet"))?
+            .clone();
+    }
+    // An epoch ending latest ledger info has been verified as part of the epoch change proofs.
+    if !latest_li.ledger_info().ends_epoch() {
+        epoch_state.verify(&latest_li)?;
+    }
+
+    // Tie the DB contents to the verified ledger info: the latest transaction, its events and its
+    // info must be proven by the transaction accumulator, and the state tree must be the one
+    // hashing to the state root hash of that transaction info.
+    DbReader::get_transactions(
+        &db,
+        latest_version,
+        1,
+        latest_version,
+        true, /* fetch_events */
+    )?
+    .verify(latest_li.ledger_info(), Some(latest_version))
+    .map_err(|e| format_err!("Checkpoint transactions don't match its ledger info: {}", e))?;
+    DbReader::get_account_state_with_proof(
+        &db,
+        diem_root_address(),
+        latest_version,
+        latest_version,
+    )?
+    .verify(latest_li.ledger_info(), latest_version, diem_root_address())
+    .map_err(|e| format_err!("Checkpoint state doesn't match its ledger info: {}", e))?;
+
+    info!(
+        version = latest_version,
+        epoch = latest_li.ledger_info().epoch(),
+        trusted_waypoint = %trusted_waypoint,
+        "Verified DB checkpoint."
+    );
+    Ok(latest_li)
+}
+
+/// Populates the (empty) storage dir of `config` with a checkpoint made by `DbCheckpointer`, after
+/// validating the checkpoint against the node's trusted waypoint. State sync then continues from
+/// the checkpoint version once the node starts.
+pub fn bootstrap_from_checkpoint(config: &NodeConfig, checkpoint_dir: &Path) -> Result<()> {
+    let manifest = DbCheckpointManifest::load(checkpoint_dir)?;
+    let trusted_waypoint = config.base.waypoint.waypoint();
+    let latest_li = verify_checkpoint(checkpoint_dir, trusted_waypoint)?;
+
+    let storage_dir = config.storage.dir();
+    let target_diemdb = storage_dir.join(DIEMDB_DIR);
+    let target_consensusdb = storage_dir.join(CONSENSUSDB_DIR);
+    ensure!(
+        !target_diemdb.exists() && !target_consensusdb.exists(),
+        "Refusing to bootstrap from checkpoint: {} already contains a DB.",
+        storage_dir.display(),
+    );
+    fs::create_dir_all(&storage_dir)?;
+    copy_dir(&checkpoint_dir.join(DIEMDB_DIR), &target_diemdb)?;
+    if manifest.has_consensus_db && config.validator_network.is_some() {
+        copy_dir(&checkpoint_dir.join(CONSENSUSDB_DIR), &target_consensusdb)?;
+    }
+
+    info!(
+        version = latest_li.ledger_info().version(),
+        epoch = latest_li.ledger_info().epoch(),
+        "Bootstrapped storage from DB checkpoint."
+    );
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_config::config::WaypointConfig;
+    use diem_crypto::HashValue;
+    use diem_temppath::TempPath;
+    use diem_types::{block_metadata::BlockMetadata, transaction::Transaction};
+    use diem_vm::DiemVM;
+    use executor_test_helpers::bootstrap_genesis;
+    use storage_interface::DbReaderWriter;
+
+    #[test]
+    fn create_verify_and_bootstrap() {
+        let db_dir = TempPath::new();
+        let diem_db = Arc::new(DiemDB::new_for_test(&db_dir));
+        let waypoint = bootstrap_genesis::<DiemVM>(
+            &DbReaderWriter::from_arc(diem_db.clone()),
+            &vm_genesis::test_genesis_transaction(),
+        )
+        .unwrap();
+
+        let root_dir = TempPath::new();
+        let checkpointer = DbCheckpointer::new(
+            diem_db,
+            None,
+            root_dir.path().to_path_buf(),
+            "token".to_string(),
+        );
+        let (checkpoint_dir, manifest) = checkpointer.create_checkpoint().unwrap();
+        assert_eq!(manifest.version, waypoint.version());
+        assert_eq!(manifest.waypoint, waypoint);
+        assert!(!manifest.has_consensus_db);
+        assert_eq!(
+            DbCheckpointManifest::load(&checkpoint_dir).unwrap(),
+            manifest
+        );
+
+        let latest_li = verify_checkpoint(&checkpoint_dir, waypoint).unwrap();
+        assert_eq!(latest_li.ledger_info().version(), manifest.version);
+
+        let data_dir = TempPath::new();
+        let mut config = NodeConfig::default_for_public_full_node();
+        config.set_data_dir(data_dir.path().to_path_buf());
+        config.base.waypoint = WaypointConfig::FromConfig(waypoint);
+        bootstrap_from_checkpoint(&config, &checkpoint_dir).unwrap();
+
+        let bootstrapped_db = DiemDB::open(
+            config.storage.dir(),
+            true, /* readonly */
+            None, /* pruner */
+            RocksdbConfig::default(),
+            false, /* account_count_migration */
+            false, /* enable_resource_type_index */
+        )
+        .unwrap();
+        assert_eq!(bootstrapped_db.get_latest_ledger_info().unwrap(), latest_li);
+
+        // The storage dir is populated now.
+        assert!(bootstrap_from_checkpoint(&config, &checkpoint_dir).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_corrupted_checkpoint() {
+        let db_dir = TempPath::new();
+        let diem_db = Arc::new(DiemDB::new_for_test(&db_dir));
+        let waypoint = bootstrap_genesis::<DiemVM>(
+            &DbReaderWriter::from_arc(diem_db.clone()),
+            &vm_genesis::test_genesis_transaction(),
+        )
+        .unwrap();
+
+        let root_dir = TempPath::new();
+        let checkpointer = DbCheckpointer::new(
+            diem_db,
+            None,
+            root_dir.path().to_path_buf(),
+            "token".to_string(),
+        );
+        let (checkpoint_dir, manifest) = checkpointer.create_checkpoint().unwrap();
+        verify_checkpoint(&checkpoint_dir, waypoint).unwrap();
+
+        // Replace the latest transaction, leaving the ledger info and the epoch changes intact.
+        {
+            let checkpoint_db = DiemDB::new_for_test(&checkpoint_dir);
+            checkpoint_db
+                .put_transaction_for_test(
+                    manifest.version,
+                    &Transaction::BlockMetadata(BlockMetadata::new(
+                        HashValue::zero(),
+                        1,
+                        1,
+                        vec![],
+                        diem_root_address(),
+                    )),
+                )
+                .unwrap();
+        }
+        assert!(verify_checkpoint(&checkpoint_dir, waypoint).is_err());
+    }
+}
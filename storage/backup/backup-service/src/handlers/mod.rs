@@ -3,14 +3,19 @@
 
 mod utils;
 
-use crate::handlers::utils::{
-    handle_rejection, reply_with_async_channel_writer, reply_with_bcs_bytes,
-    send_size_prefixed_bcs_bytes, unwrap_or_500, LATENCY_HISTOGRAM,
+use crate::{
+    checkpoint::DbCheckpointer,
+    handlers::utils::{
+        handle_rejection, reply_with_async_channel_writer, reply_with_bcs_bytes,
+        send_size_prefixed_bcs_bytes, unwrap_or_500, LATENCY_HISTOGRAM,
+    },
 };
+use anyhow::Result;
 use diem_crypto::hash::HashValue;
 use diem_types::transaction::Version;
 use diemdb::backup::backup_handler::BackupHandler;
-use warp::{filters::BoxedFilter, reply::Reply, Filter};
+use std::sync::Arc;
+use warp::{filters::BoxedFilter, http::StatusCode, reply::Reply, Filter, Rejection};
 
 static DB_STATE: &str = "db_state";
 static STATE_RANGE_PROOF: &str = "state_range_proof";
@@ -19,8 +24,12 @@ static STATE_ROOT_PROOF: &str = "state_root_proof";
 static EPOCH_ENDING_LEDGER_INFOS: &str = "epoch_ending_ledger_infos";
 static TRANSACTIONS: &str = "transactions";
 static TRANSACTION_RANGE_PROOF: &str = "transaction_range_proof";
+static CHECKPOINT: &str = "checkpoint";
 
-pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Reply,)> {
+pub(crate) fn get_routes(
+    backup_handler: BackupHandler,
+    checkpointer: Option<Arc<DbCheckpointer>>,
+) -> BoxedFilter<(impl Reply,)> {
     // GET db_state
     let bh = backup_handler.clone();
     let db_state = warp::path::end()
@@ -117,9 +126,28 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .or(warp::path(TRANSACTIONS).and(transactions))
         .or(warp::path(TRANSACTION_RANGE_PROOF).and(transaction_range_proof));
 
-    // Serve all routes for GET only.
+    // POST checkpoint, authenticated by header "Authorization: Bearer <token>"
+    let checkpoint = warp::post()
+        .and(warp::path(CHECKPOINT))
+        .and(warp::path::end())
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(move |authorization: Option<String>| {
+            let checkpointer = checkpointer.clone();
+            async move {
+                // Checkpointing and copying the DB blocks, keep it off the async runtime.
+                let result = tokio::task::spawn_blocking(move || {
+                    reply_with_checkpoint(checkpointer.as_deref(), authorization.as_deref())
+                })
+                .await
+                .unwrap_or_else(|e| Err(e.into()));
+                Ok::<_, Rejection>(unwrap_or_500(result))
+            }
+        });
+
+    // Serve all backup routes for GET only, and the checkpoint route for POST only.
     warp::get()
         .and(routes)
+        .or(checkpoint)
         .with(warp::log::custom(|info| {
             let endpoint = info.path().split('/').nth(1).unwrap_or("-");
             LATENCY_HISTOGRAM
@@ -128,3 +156,18 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         }))
         .boxed()
 }
+
+fn reply_with_checkpoint(
+    checkpointer: Option<&DbCheckpointer>,
+    authorization: Option<&str>,
+) -> Result<Box<dyn Reply>> {
+    let checkpointer = match checkpointer {
+        Some(checkpointer) => checkpointer,
+        None => return Ok(Box::new(StatusCode::NOT_FOUND)),
+    };
+    if !checkpointer.is_authorized(authorization) {
+        return Ok(Box::new(StatusCode::UNAUTHORIZED));
+    }
+    let (_path, manifest) = checkpointer.create_checkpoint()?;
+    Ok(Box::new(warp::reply::json(&manifest)))
+}
@@ -1,17 +1,28 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod checkpoint;
 mod handlers;
 
-use crate::handlers::get_routes;
+use crate::{checkpoint::DbCheckpointer, handlers::get_routes};
 use diem_logger::prelude::*;
 use diemdb::DiemDB;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::runtime::{Builder, Runtime};
 
 pub fn start_backup_service(address: SocketAddr, db: Arc<DiemDB>) -> Runtime {
+    start_backup_service_with_checkpointer(address, db, None)
+}
+
+/// Starts the backup service, additionally serving the online checkpoint endpoint if
+/// `checkpointer` is provided.
+pub fn start_backup_service_with_checkpointer(
+    address: SocketAddr,
+    db: Arc<DiemDB>,
+    checkpointer: Option<DbCheckpointer>,
+) -> Runtime {
     let backup_handler = db.get_backup_handler();
-    let routes = get_routes(backup_handler);
+    let routes = get_routes(backup_handler, checkpointer.map(Arc::new));
 
     let runtime = Builder::new_multi_thread()
         .thread_name("backup")
@@ -39,7 +50,7 @@ mod tests {
     use diem_config::utils::get_available_port;
     use diem_crypto::hash::HashValue;
     use diem_temppath::TempPath;
-    use reqwest::blocking::get;
+    use reqwest::blocking::{get, Client};
     use std::net::{IpAddr, Ipv4Addr};
 
     /// 404 - endpoint not found
@@ -94,4 +105,63 @@ mod tests {
         assert_eq!(resp.content_length(), None);
         assert!(resp.bytes().is_err());
     }
+
+    #[test]
+    fn checkpoint_auth_and_error_codes() {
+        let tmpdir = TempPath::new();
+        let db = Arc::new(DiemDB::new_for_test(&tmpdir));
+        let checkpoint_dir = TempPath::new();
+        let checkpointer = DbCheckpointer::new(
+            Arc::clone(&db),
+            None,
+            checkpoint_dir.path().to_path_buf(),
+            "secret".to_string(),
+        );
+        let port = get_available_port();
+        let _rt = start_backup_service_with_checkpointer(
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+            db,
+            Some(checkpointer),
+        );
+        let url = format!("http://127.0.0.1:{}/checkpoint", port);
+        let client = Client::new();
+
+        // GET is not served.
+        let resp = get(&url).unwrap();
+        assert_eq!(resp.status(), 405);
+
+        // Missing or wrong token.
+        let resp = client.post(&url).send().unwrap();
+        assert_eq!(resp.status(), 401);
+        let resp = client
+            .post(&url)
+            .header("Authorization", "Bearer wrong")
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 401);
+
+        // Request handler raised Error (non-bootstrapped DB), and no checkpoint is left behind.
+        let resp = client
+            .post(&url)
+            .header("Authorization", "Bearer secret")
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 500);
+        assert_eq!(std::fs::read_dir(checkpoint_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn checkpoint_disabled() {
+        let tmpdir = TempPath::new();
+        let db = Arc::new(DiemDB::new_for_test(&tmpdir));
+        let port = get_available_port();
+        let _rt = start_backup_service(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port), db);
+
+        let resp = Client::new()
+            .post(&format!("http://127.0.0.1:{}/checkpoint", port))
+            .header("Authorization", "Bearer secret")
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 404);
+    }
 }
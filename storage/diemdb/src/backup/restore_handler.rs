@@ -133,6 +133,11 @@ impl RestoreHandler {
             self.transaction_store
                 .put_transaction(first_version + idx as Version, txn, &mut cs)?;
         }
+        let block_index_complete = self.transaction_store.lock_block_index();
+        if *block_index_complete {
+            self.transaction_store
+                .put_block_index(first_version, txns, &mut cs)?;
+        }
         self.ledger_store
             .put_transaction_infos(first_version, txn_infos, &mut cs)?;
         self.event_store
@@ -136,6 +136,30 @@ pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWit
     );
     let (_, ledger_infos_with_sigs): (Vec<_>, Vec<_>) = input.iter().cloned().unzip();
     verify_epochs(&db, &ledger_infos_with_sigs);
+
+    // Without block metadata transactions, everything is in the genesis block.
+    let ledger_version = latest_ledger_info.ledger_info().version();
+    let genesis_block = CommittedBlock {
+        height: 0,
+        first_version: 0,
+        last_version: ledger_version,
+        metadata: None,
+    };
+    assert_eq!(
+        db.get_block_by_height(0, ledger_version).unwrap(),
+        Some(genesis_block.clone())
+    );
+    assert_eq!(
+        db.get_block_by_version(ledger_version, ledger_version)
+            .unwrap(),
+        Some(genesis_block)
+    );
+    assert_eq!(db.get_block_by_height(1, ledger_version).unwrap(), None);
+    assert_eq!(
+        db.get_block_by_version(ledger_version + 1, ledger_version)
+            .unwrap(),
+        None
+    );
 }
 
 fn test_sync_transactions_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
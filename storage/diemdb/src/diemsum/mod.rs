@@ -25,7 +25,8 @@ impl Diemsum {
             true, /* read only */
             None, /* no prune_window */
             RocksdbConfig::default(),
-            true, /* account_count_migration, ignored anyway */
+            true,  /* account_count_migration, ignored anyway */
+            false, /* enable_resource_type_index */
         )?;
         Ok(Diemsum { db })
     }
@@ -25,6 +25,7 @@ mod event_store;
 mod ledger_counters;
 mod ledger_store;
 mod pruner;
+mod resource_type_index_store;
 mod state_store;
 mod system_store;
 mod transaction_store;
@@ -50,10 +51,11 @@ use crate::{
         DIEM_STORAGE_OTHER_TIMERS_SECONDS, DIEM_STORAGE_ROCKSDB_PROPERTIES,
     },
     pruner::Pruner,
+    resource_type_index_store::ResourceTypeIndexStore,
     schema::*,
     state_store::StateStore,
     system_store::SystemStore,
-    transaction_store::TransactionStore,
+    transaction_store::{BlockIndexBackfill, IndexedBlock, TransactionStore},
 };
 use anyhow::{ensure, format_err, Result};
 use diem_config::config::RocksdbConfig;
@@ -73,9 +75,9 @@ use diem_types::{
     },
     state_proof::StateProof,
     transaction::{
-        AccountTransactionsWithProof, TransactionInfo, TransactionListWithProof, TransactionOutput,
-        TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version,
-        PRE_GENESIS_VERSION,
+        AccountTransactionsWithProof, Transaction, TransactionInfo, TransactionListWithProof,
+        TransactionOutput, TransactionOutputListWithProof, TransactionToCommit,
+        TransactionWithProof, Version, PRE_GENESIS_VERSION,
     },
 };
 use itertools::zip_eq;
@@ -95,7 +97,8 @@ use std::{
     time::{Duration, Instant},
 };
 use storage_interface::{
-    DbReader, DbWriter, MoveDbReader, Order, StartupInfo, StateSnapshotReceiver, TreeState,
+    CommittedBlock, DbReader, DbWriter, MoveDbReader, Order, StartupInfo, StateSnapshotReceiver,
+    TreeState,
 };
 
 const MAX_LIMIT: u64 = 5000;
@@ -216,16 +219,20 @@ pub struct DiemDB {
     transaction_store: Arc<TransactionStore>,
     state_store: Arc<StateStore>,
     event_store: Arc<EventStore>,
+    resource_type_index_store: Option<Arc<ResourceTypeIndexStore>>,
     system_store: SystemStore,
     rocksdb_property_reporter: RocksdbPropertyReporter,
     pruner: Option<Pruner>,
     prune_window: Option<u64>,
+    block_index_backfill: Option<BlockIndexBackfill>,
 }
 
 impl DiemDB {
     fn column_families() -> Vec<ColumnFamilyName> {
         vec![
             /* LedgerInfo CF = */ DEFAULT_CF_NAME,
+            BLOCK_BY_HEIGHT_CF_NAME,
+            BLOCK_BY_VERSION_CF_NAME,
             EPOCH_BY_VERSION_CF_NAME,
             EVENT_ACCUMULATOR_CF_NAME,
             EVENT_BY_KEY_CF_NAME,
@@ -233,6 +240,7 @@ impl DiemDB {
             EVENT_CF_NAME,
             JELLYFISH_MERKLE_NODE_CF_NAME,
             LEDGER_COUNTERS_CF_NAME,
+            RESOURCE_TYPE_INDEX_CF_NAME,
             STALE_NODE_INDEX_CF_NAME,
             TRANSACTION_CF_NAME,
             TRANSACTION_ACCUMULATOR_CF_NAME,
@@ -243,8 +251,18 @@ impl DiemDB {
         ]
     }
 
-    fn new_with_db(db: DB, prune_window: Option<u64>, account_count_migration: bool) -> Self {
+    fn new_with_db(
+        db: DB,
+        prune_window: Option<u64>,
+        account_count_migration: bool,
+        enable_resource_type_index: bool,
+    ) -> Self {
         let db = Arc::new(db);
+        let resource_type_index_store = if enable_resource_type_index {
+            Some(Arc::new(ResourceTypeIndexStore::new(Arc::clone(&db))))
+        } else {
+            None
+        };
 
         DiemDB {
             db: Arc::clone(&db),
@@ -254,8 +272,11 @@ impl DiemDB {
             transaction_store: Arc::new(TransactionStore::new(Arc::clone(&db))),
             system_store: SystemStore::new(Arc::clone(&db)),
             rocksdb_property_reporter: RocksdbPropertyReporter::new(Arc::clone(&db)),
-            pruner: prune_window.map(|n| Pruner::new(Arc::clone(&db), n)),
+            pruner: prune_window
+                .map(|n| Pruner::new(Arc::clone(&db), n, resource_type_index_store.clone())),
+            resource_type_index_store,
             prune_window,
+            block_index_backfill: None,
         }
     }
 
@@ -265,6 +286,7 @@ impl DiemDB {
         prune_window: Option<u64>,
         rocksdb_config: RocksdbConfig,
         account_count_migration: bool, // ignored when opening readonly
+        enable_resource_type_index: bool,
     ) -> Result<Self> {
         ensure!(
             prune_window.is_none() || !readonly,
@@ -300,7 +322,17 @@ impl DiemDB {
             )
         };
 
-        let ret = Self::new_with_db(db, prune_window, account_count_migration);
+        let mut ret = Self::new_with_db(
+            db,
+            prune_window,
+            account_count_migration,
+            enable_resource_type_index,
+        );
+        if !readonly && !ret.transaction_store.check_block_index_complete()? {
+            info!("Backfilling block index in the background.");
+            ret.block_index_backfill =
+                Some(BlockIndexBackfill::new(Arc::clone(&ret.transaction_store)));
+        }
         info!(
             path = path,
             time_ms = %instant.elapsed().as_millis(),
@@ -328,8 +360,9 @@ impl DiemDB {
                 Self::column_families(),
                 &rocksdb_opts,
             )?,
-            None, // prune_window
-            true, // account_count_migration
+            None,  // prune_window
+            true,  // account_count_migration
+            false, // enable_resource_type_index
         ))
     }
 
@@ -342,10 +375,25 @@ impl DiemDB {
             None,  /* pruner */
             RocksdbConfig::default(),
             true, /* account_count_migration */
+            true, /* enable_resource_type_index */
         )
         .expect("Unable to open DiemDB")
     }
 
+    /// Overwrites the transaction at `version`, leaving everything proving it untouched, to test
+    /// that a corrupted DB is detected.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn put_transaction_for_test(
+        &self,
+        version: Version,
+        transaction: &Transaction,
+    ) -> Result<()> {
+        let mut cs = ChangeSet::new();
+        self.transaction_store
+            .put_transaction(version, transaction, &mut cs)?;
+        self.db.write_schemas(cs.batch)
+    }
+
     /// This force the db to update rocksdb properties immediately.
     pub fn update_rocksdb_properties(&self) -> Result<()> {
         update_rocksdb_properties(&self.db)
@@ -564,6 +612,7 @@ impl DiemDB {
         &self,
         txns_to_commit: &[TransactionToCommit],
         first_version: u64,
+        index_blocks: bool,
         mut cs: &mut ChangeSet,
     ) -> Result<HashValue> {
         let last_version = first_version + txns_to_commit.len() as u64 - 1;
@@ -616,10 +665,26 @@ impl DiemDB {
                         txn_to_commit.transaction(),
                         &mut cs,
                     )?;
+                    if let Some(resource_type_index_store) = &self.resource_type_index_store {
+                        resource_type_index_store.put_write_set(
+                            ver,
+                            txn_to_commit.write_set(),
+                            &mut cs,
+                        )?;
+                    }
                     self.transaction_store
                         .put_write_set(ver, txn_to_commit.write_set(), &mut cs)
                 },
             )?;
+            if index_blocks {
+                self.transaction_store.put_block_index(
+                    first_version,
+                    txns_to_commit
+                        .iter()
+                        .map(|txn_to_commit| txn_to_commit.transaction()),
+                    &mut cs,
+                )?;
+            }
             // Transaction accumulator updates. Get result root hash.
             let txn_infos: Vec<_> = txns_to_commit
                 .iter()
@@ -642,6 +707,48 @@ impl DiemDB {
         Ok(())
     }
 
+    /// Completes a block of the block index with its metadata, as of `ledger_version`.
+    fn get_committed_block(
+        &self,
+        block: IndexedBlock,
+        ledger_version: Version,
+    ) -> Result<Option<CommittedBlock>> {
+        if block.first_version > ledger_version {
+            return Ok(None);
+        }
+        let last_version = match block.next_block_version {
+            Some(next_block_version) => std::cmp::min(next_block_version - 1, ledger_version),
+            None => ledger_version,
+        };
+        let metadata = match self
+            .transaction_store
+            .get_transaction(block.first_version)?
+        {
+            Transaction::BlockMetadata(block_meta) => Some(block_meta),
+            _ => None,
+        };
+        Ok(Some(CommittedBlock {
+            height: block.height,
+            first_version: block.first_version,
+            last_version,
+            metadata,
+        }))
+    }
+
+    fn error_if_version_is_pruned(&self, data_type: &str, version: Version) -> Result<()> {
+        if let Some(pruner) = self.pruner.as_ref() {
+            let least_readable_version = pruner.least_readable_version();
+            ensure!(
+                version >= least_readable_version,
+                "{} at version {} is pruned, min available version is {}.",
+                data_type,
+                version,
+                least_readable_version,
+            );
+        }
+        Ok(())
+    }
+
     fn wake_pruner(&self, latest_version: Version) {
         if let Some(pruner) = self.pruner.as_ref() {
             pruner.wake(latest_version)
@@ -1202,6 +1309,56 @@ impl DbReader for DiemDB {
     fn get_state_prune_window(&self) -> Option<usize> {
         self.prune_window.map(|u| u as usize)
     }
+
+    fn get_accounts_by_resource_type(
+        &self,
+        struct_tag: &StructTag,
+        start_after: Option<AccountAddress>,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<AccountAddress>> {
+        gauged_api("get_accounts_by_resource_type", || {
+            error_if_too_many_requested(limit, MAX_LIMIT)?;
+            self.error_if_version_is_pruned("Resource type index", ledger_version)?;
+            let store = self
+                .resource_type_index_store
+                .as_ref()
+                .ok_or_else(|| format_err!("Resource type index is not enabled."))?;
+            store.get_accounts(struct_tag, start_after, limit as usize, ledger_version)
+        })
+    }
+
+    fn get_block_by_height(
+        &self,
+        height: u64,
+        ledger_version: Version,
+    ) -> Result<Option<CommittedBlock>> {
+        gauged_api("get_block_by_height", || {
+            match self.transaction_store.get_indexed_block_by_height(height)? {
+                Some(block) => self.get_committed_block(block, ledger_version),
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn get_block_by_version(
+        &self,
+        version: Version,
+        ledger_version: Version,
+    ) -> Result<Option<CommittedBlock>> {
+        gauged_api("get_block_by_version", || {
+            if version > ledger_version {
+                return Ok(None);
+            }
+            match self
+                .transaction_store
+                .get_indexed_block_by_version(version)?
+            {
+                Some(block) => self.get_committed_block(block, ledger_version),
+                None => Ok(None),
+            }
+        })
+    }
 }
 
 impl ModuleResolver for DiemDB {
@@ -1273,8 +1430,14 @@ impl DbWriter for DiemDB {
             // Gather db mutations to `batch`.
             let mut cs = ChangeSet::new();
 
-            let new_root_hash =
-                self.save_transactions_impl(txns_to_commit, first_version, &mut cs)?;
+            // The blocks are indexed as they're committed once the backfill is done.
+            let block_index_complete = self.transaction_store.lock_block_index();
+            let new_root_hash = self.save_transactions_impl(
+                txns_to_commit,
+                first_version,
+                *block_index_complete,
+                &mut cs,
+            )?;
 
             // If expected ledger info is provided, verify result root hash and save the ledger info.
             if let Some(x) = ledger_info_with_sigs {
@@ -1297,6 +1460,7 @@ impl DbWriter for DiemDB {
                     .start_timer();
                 self.commit(sealed_cs)?;
             }
+            drop(block_index_complete);
 
             // Once everything is successfully persisted, update the latest in-memory ledger info.
             if let Some(x) = ledger_info_with_sigs {
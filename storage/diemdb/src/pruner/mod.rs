@@ -9,6 +9,7 @@ use crate::{
         DIEM_STORAGE_OTHER_TIMERS_SECONDS, DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION,
         DIEM_STORAGE_PRUNE_WINDOW,
     },
+    resource_type_index_store::ResourceTypeIndexStore,
     schema::{
         jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
     },
@@ -45,15 +46,22 @@ pub(crate) struct Pruner {
     worker_thread: Option<JoinHandle<()>>,
     /// The sender side of the channel talking to the worker thread.
     command_sender: Mutex<Sender<Command>>,
-    /// (For tests) A way for the worker thread to inform the `Pruner` the pruning progress. If it
-    /// sets this atomic value to `V`, all versions before `V` can no longer be accessed.
-    #[allow(dead_code)]
+    /// A way for the worker thread to inform the `Pruner` the pruning progress. If it sets this
+    /// atomic value to `V`, all versions before `V` can no longer be accessed.
     worker_progress: Arc<AtomicU64>,
+    /// The latest pruning target sent to the worker thread. Versions before it are being pruned,
+    /// or will be soon.
+    target_least_readable_version: AtomicU64,
 }
 
 impl Pruner {
-    /// Creates a worker thread that waits on a channel for pruning commands.
-    pub fn new(db: Arc<DB>, historical_versions_to_keep: u64) -> Self {
+    /// Creates a worker thread that waits on a channel for pruning commands. The resource type
+    /// index, if provided, is pruned along with the state.
+    pub fn new(
+        db: Arc<DB>,
+        historical_versions_to_keep: u64,
+        resource_type_index_store: Option<Arc<ResourceTypeIndexStore>>,
+    ) -> Self {
         let (command_sender, command_receiver) = channel();
 
         let worker_progress = Arc::new(AtomicU64::new(0));
@@ -62,7 +70,15 @@ impl Pruner {
         DIEM_STORAGE_PRUNE_WINDOW.set(historical_versions_to_keep as i64);
         let worker_thread = std::thread::Builder::new()
             .name("diemdb_pruner".into())
-            .spawn(move || Worker::new(db, command_receiver, worker_progress_clone).work())
+            .spawn(move || {
+                Worker::new(
+                    db,
+                    command_receiver,
+                    worker_progress_clone,
+                    resource_type_index_store,
+                )
+                .work()
+            })
             .expect("Creating pruner thread should succeed.");
 
         Self {
@@ -70,6 +86,7 @@ impl Pruner {
             worker_thread: Some(worker_thread),
             command_sender: Mutex::new(command_sender),
             worker_progress,
+            target_least_readable_version: AtomicU64::new(0),
         }
     }
 
@@ -77,6 +94,8 @@ impl Pruner {
     pub fn wake(&self, latest_version: Version) {
         if latest_version > self.historical_versions_to_keep {
             let least_readable_version = latest_version - self.historical_versions_to_keep;
+            self.target_least_readable_version
+                .fetch_max(least_readable_version, Ordering::Relaxed);
             self.command_sender
                 .lock()
                 .send(Command::Prune {
@@ -86,6 +105,15 @@ impl Pruner {
         }
     }
 
+    /// Returns the version before which data might have been pruned, or is being pruned, and
+    /// can't be read consistently anymore.
+    pub fn least_readable_version(&self) -> Version {
+        std::cmp::max(
+            self.worker_progress.load(Ordering::Relaxed),
+            self.target_least_readable_version.load(Ordering::Relaxed),
+        )
+    }
+
     /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
     /// an internal counter.
     #[cfg(test)]
@@ -142,6 +170,7 @@ struct Worker {
     blocking_recv: bool,
     index_min_nonpurged_version: Version,
     index_purged_at: Instant,
+    resource_type_index_store: Option<Arc<ResourceTypeIndexStore>>,
 }
 
 impl Worker {
@@ -151,6 +180,7 @@ impl Worker {
         db: Arc<DB>,
         command_receiver: Receiver<Command>,
         least_readable_version: Arc<AtomicU64>,
+        resource_type_index_store: Option<Arc<ResourceTypeIndexStore>>,
     ) -> Self {
         Self {
             db,
@@ -160,6 +190,7 @@ impl Worker {
             blocking_recv: true,
             index_min_nonpurged_version: 0,
             index_purged_at: Instant::now(),
+            resource_type_index_store,
         }
     }
 
@@ -177,6 +208,10 @@ impl Worker {
                 Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
             ) {
                 Ok(new_least_readable_version) => {
+                    self.maybe_prune_resource_type_index(
+                        least_readable_version,
+                        new_least_readable_version,
+                    );
                     self.record_progress(new_least_readable_version);
 
                     // Make next recv() blocking if nothing left to do.
@@ -203,6 +238,27 @@ impl Worker {
         }
     }
 
+    /// Prunes the resource type index along with the state, if the index is enabled.
+    fn maybe_prune_resource_type_index(
+        &self,
+        least_readable_version: Version,
+        new_least_readable_version: Version,
+    ) {
+        if let Some(store) = &self.resource_type_index_store {
+            if new_least_readable_version > least_readable_version {
+                let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
+                    .with_label_values(&["pruner_resource_type_index"])
+                    .start_timer();
+                if let Err(e) = store.prune(least_readable_version, new_least_readable_version) {
+                    warn!(
+                        error = ?e,
+                        "Failed pruning resource type index, ignored.",
+                    );
+                }
+            }
+        }
+    }
+
     /// Find out the first undeleted item in the stale node index.
     ///
     /// Seeking from the beginning (version 0) is potentially costly, we do it once upon worker
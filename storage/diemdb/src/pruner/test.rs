@@ -50,7 +50,11 @@ fn test_pruner() {
     let tmp_dir = TempPath::new();
     let db = DiemDB::new_for_test(&tmp_dir).db;
     let state_store = &StateStore::new(Arc::clone(&db), true /* account_count_migration */);
-    let pruner = Pruner::new(Arc::clone(&db), 0 /* historical_versions_to_keep */);
+    let pruner = Pruner::new(
+        Arc::clone(&db),
+        0,    /* historical_versions_to_keep */
+        None, /* resource_type_index_store */
+    );
 
     let _root0 = put_account_state_set(
         &db,
@@ -81,6 +85,7 @@ fn test_pruner() {
     // Prune till version=1.
     {
         pruner.wake_and_wait(1 /* latest_version */).unwrap();
+        assert_eq!(pruner.least_readable_version(), 1);
         // root0 is gone.
         assert!(state_store
             .get_account_state_with_proof_by_version(address, 0)
@@ -92,6 +97,7 @@ fn test_pruner() {
     // Prune till version=2.
     {
         pruner.wake_and_wait(2 /* latest_version */).unwrap();
+        assert_eq!(pruner.least_readable_version(), 2);
         // root1 is gone.
         assert!(state_store
             .get_account_state_with_proof_by_version(address, 1)
@@ -0,0 +1,143 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file defines the store of the optional secondary index answering "which accounts hold a
+//! resource of type X". See `schema::resource_type_index` for the physical layout.
+//!
+//! The index is only complete if it has been enabled since genesis, because it is built from the
+//! write sets being committed. Write sets are not available to DBs restored from backups.
+
+use crate::{
+    change_set::ChangeSet,
+    schema::{resource_type_index::ResourceTypeIndexSchema, write_set::WriteSetSchema},
+};
+use anyhow::Result;
+use diem_crypto::HashValue;
+use diem_types::{
+    access_path::AccessPath, account_address::AccountAddress, transaction::Version,
+    write_set::WriteSet,
+};
+use move_core_types::language_storage::{StructTag, RESOURCE_TAG};
+use schemadb::{ReadOptions, SchemaBatch, DB};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub(crate) struct ResourceTypeIndexStore {
+    db: Arc<DB>,
+}
+
+impl ResourceTypeIndexStore {
+    pub fn new(db: Arc<DB>) -> Self {
+        Self { db }
+    }
+
+    fn path_hash_of_struct_tag(struct_tag: &StructTag) -> HashValue {
+        HashValue::sha3_256_of(&AccessPath::resource_access_vec(struct_tag.clone()))
+    }
+
+    fn resource_writes(
+        write_set: &WriteSet,
+    ) -> impl Iterator<Item = (HashValue, AccountAddress, bool)> + '_ {
+        write_set
+            .iter()
+            .filter(|(access_path, _)| access_path.path.first() == Some(&RESOURCE_TAG))
+            .map(|(access_path, write_op)| {
+                (
+                    HashValue::sha3_256_of(&access_path.path),
+                    access_path.address,
+                    !write_op.is_deletion(),
+                )
+            })
+    }
+
+    /// Adds an index entry for every resource written by the transaction at `version`.
+    pub fn put_write_set(
+        &self,
+        version: Version,
+        write_set: &WriteSet,
+        cs: &mut ChangeSet,
+    ) -> Result<()> {
+        Self::resource_writes(write_set).try_for_each(|(path_hash, address, exists)| {
+            cs.batch
+                .put::<ResourceTypeIndexSchema>(&(path_hash, address, version), &exists)
+        })
+    }
+
+    /// Returns at most `limit` accounts that hold a resource of type `struct_tag` at
+    /// `ledger_version`, in ascending address order, starting right after `start_after`.
+    pub fn get_accounts(
+        &self,
+        struct_tag: &StructTag,
+        start_after: Option<AccountAddress>,
+        limit: usize,
+        ledger_version: Version,
+    ) -> Result<Vec<AccountAddress>> {
+        let path_hash = Self::path_hash_of_struct_tag(struct_tag);
+        let mut iter = self
+            .db
+            .iter::<ResourceTypeIndexSchema>(ReadOptions::default())?;
+        match start_after {
+            Some(address) => iter.seek(&(path_hash, address, Version::max_value()))?,
+            None => iter.seek(&path_hash)?,
+        }
+
+        let mut accounts = Vec::new();
+        while accounts.len() < limit {
+            let address = match iter.next().transpose()? {
+                Some(((hash, address, _version), _exists)) if hash == path_hash => address,
+                _ => break,
+            };
+
+            // The latest entry of the account no later than `ledger_version` tells whether the
+            // resource exists at `ledger_version`.
+            iter.seek_for_prev(&(path_hash, address, ledger_version))?;
+            if let Some(((hash, addr, _version), exists)) = iter.next().transpose()? {
+                if hash == path_hash && addr == address && exists {
+                    accounts.push(address);
+                }
+            }
+
+            // Skip the rest of the entries of this account.
+            iter.seek(&(path_hash, address, Version::max_value()))?;
+        }
+
+        Ok(accounts)
+    }
+
+    /// Deletes the entries superseded by a later entry written in `[begin_version, end_version]`,
+    /// where `end_version` is the least readable version after pruning. The latest entry of each
+    /// account as of `end_version` is kept, so that queries at any readable version remain
+    /// answerable, unless it records a deletion: no entry at all answers the same.
+    pub fn prune(&self, begin_version: Version, end_version: Version) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        // Nothing precedes entries at version 0.
+        for version in std::cmp::max(begin_version, 1)..=end_version {
+            // Write sets are missing for versions restored from backups, which are not indexed
+            // either.
+            let write_set = match self.db.get::<WriteSetSchema>(&version)? {
+                Some(write_set) => write_set,
+                None => continue,
+            };
+            for (path_hash, address, exists) in Self::resource_writes(&write_set) {
+                if !exists {
+                    batch.delete::<ResourceTypeIndexSchema>(&(path_hash, address, version))?;
+                }
+                let mut iter = self
+                    .db
+                    .rev_iter::<ResourceTypeIndexSchema>(ReadOptions::default())?;
+                iter.seek_for_prev(&(path_hash, address, version - 1))?;
+                for res in iter {
+                    let ((hash, addr, prev_version), _exists) = res?;
+                    if hash != path_hash || addr != address {
+                        break;
+                    }
+                    batch.delete::<ResourceTypeIndexSchema>(&(path_hash, address, prev_version))?;
+                }
+            }
+        }
+        self.db.write_schemas(batch)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,166 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::DiemDB;
+use diem_config::config::RocksdbConfig;
+use diem_temppath::TempPath;
+use diem_types::{
+    account_config::{xus_tag, AccountResource, BalanceResource},
+    write_set::{WriteOp, WriteSetMut},
+};
+use move_core_types::move_resource::MoveStructType;
+use storage_interface::DbReader;
+
+fn write_set(ops: Vec<(AccountAddress, &StructTag, bool)>) -> WriteSet {
+    WriteSetMut::new(
+        ops.into_iter()
+            .map(|(address, struct_tag, exists)| {
+                let access_path =
+                    AccessPath::new(address, AccessPath::resource_access_vec(struct_tag.clone()));
+                let write_op = if exists {
+                    WriteOp::Value(vec![1])
+                } else {
+                    WriteOp::Deletion
+                };
+                (access_path, write_op)
+            })
+            .collect(),
+    )
+    .freeze()
+    .unwrap()
+}
+
+fn put(db: &DiemDB, version: Version, write_set: &WriteSet) {
+    let store = db.resource_type_index_store.as_ref().unwrap();
+    let mut cs = ChangeSet::new();
+    store.put_write_set(version, write_set, &mut cs).unwrap();
+    db.transaction_store
+        .put_write_set(version, write_set, &mut cs)
+        .unwrap();
+    db.db.write_schemas(cs.batch).unwrap();
+}
+
+#[test]
+fn test_get_accounts() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = db.resource_type_index_store.as_ref().unwrap();
+
+    let account = AccountResource::struct_tag();
+    let balance = BalanceResource::struct_tag_for_currency(xus_tag());
+    let a = AccountAddress::new([1; AccountAddress::LENGTH]);
+    let b = AccountAddress::new([2; AccountAddress::LENGTH]);
+    let c = AccountAddress::new([3; AccountAddress::LENGTH]);
+
+    put(
+        &db,
+        0,
+        &write_set(vec![(a, &account, true), (b, &account, true)]),
+    );
+    put(
+        &db,
+        1,
+        &write_set(vec![(c, &account, true), (a, &balance, true)]),
+    );
+    put(&db, 2, &write_set(vec![(b, &account, false)]));
+    put(&db, 3, &write_set(vec![(a, &account, true)]));
+
+    assert_eq!(
+        store.get_accounts(&account, None, 10, 0).unwrap(),
+        vec![a, b]
+    );
+    assert_eq!(
+        store.get_accounts(&account, None, 10, 1).unwrap(),
+        vec![a, b, c]
+    );
+    assert_eq!(
+        store.get_accounts(&account, None, 10, 3).unwrap(),
+        vec![a, c]
+    );
+    assert_eq!(store.get_accounts(&balance, None, 10, 0).unwrap(), vec![]);
+    assert_eq!(store.get_accounts(&balance, None, 10, 3).unwrap(), vec![a]);
+
+    // Pagination.
+    assert_eq!(store.get_accounts(&account, None, 1, 1).unwrap(), vec![a]);
+    assert_eq!(
+        store.get_accounts(&account, Some(a), 1, 1).unwrap(),
+        vec![b]
+    );
+    assert_eq!(
+        store.get_accounts(&account, Some(b), 10, 1).unwrap(),
+        vec![c]
+    );
+    assert_eq!(
+        store.get_accounts(&account, Some(c), 10, 1).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn test_prune() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = db.resource_type_index_store.as_ref().unwrap();
+
+    let account = AccountResource::struct_tag();
+    let a = AccountAddress::new([1; AccountAddress::LENGTH]);
+    let b = AccountAddress::new([2; AccountAddress::LENGTH]);
+
+    put(&db, 0, &write_set(vec![(a, &account, true)]));
+    put(&db, 1, &write_set(vec![(b, &account, true)]));
+    put(&db, 2, &write_set(vec![(a, &account, true)]));
+    put(&db, 3, &write_set(vec![(b, &account, false)]));
+
+    store.prune(0, 2).unwrap();
+    let num_entries = || {
+        let mut iter = db
+            .db
+            .iter::<ResourceTypeIndexSchema>(ReadOptions::default())
+            .unwrap();
+        iter.seek_to_first();
+        iter.count()
+    };
+    // Only the entry of `a` at version 0 is superseded as of version 2.
+    assert_eq!(num_entries(), 3);
+    assert_eq!(
+        store.get_accounts(&account, None, 10, 2).unwrap(),
+        vec![a, b]
+    );
+    assert_eq!(store.get_accounts(&account, None, 10, 3).unwrap(), vec![a]);
+
+    // `b` has no entry left once its resource is deleted.
+    store.prune(2, 3).unwrap();
+    assert_eq!(num_entries(), 1);
+    assert_eq!(store.get_accounts(&account, None, 10, 3).unwrap(), vec![a]);
+}
+
+#[test]
+fn test_reject_pruned_version() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::open(
+        &tmp_dir,
+        false,   /* readonly */
+        Some(1), /* pruner */
+        RocksdbConfig::default(),
+        false, /* account_count_migration */
+        true,  /* enable_resource_type_index */
+    )
+    .unwrap();
+
+    let account = AccountResource::struct_tag();
+    let a = AccountAddress::new([1; AccountAddress::LENGTH]);
+    put(&db, 0, &write_set(vec![(a, &account, true)]));
+    put(&db, 1, &write_set(vec![(a, &account, true)]));
+    put(&db, 2, &write_set(vec![(a, &account, true)]));
+
+    db.wake_pruner(2 /* latest_version */);
+    assert!(db
+        .get_accounts_by_resource_type(&account, None, 10, 0)
+        .is_err());
+    assert_eq!(
+        db.get_accounts_by_resource_type(&account, None, 10, 1)
+            .unwrap(),
+        vec![a]
+    );
+}
@@ -0,0 +1,56 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the block index, which maps the height of a
+//! block to the version of its first transaction. Block 0 is the genesis transaction, every
+//! following block starts with a block metadata transaction.
+//!
+//! ```text
+//! |<--key-->|<------value---->|
+//! | height  | first_version   |
+//! ```
+//!
+//! `height` is serialized in big endian so that records in RocksDB will be in order of their
+//! numeric value.
+
+use crate::schema::{ensure_slice_len_eq, BLOCK_BY_HEIGHT_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use diem_types::transaction::Version;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    BlockByHeightSchema,
+    u64, // height
+    Version,
+    BLOCK_BY_HEIGHT_CF_NAME
+);
+
+impl KeyCodec<BlockByHeightSchema> for u64 {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<BlockByHeightSchema> for Version {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,18 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        height in any::<u64>(),
+        first_version in any::<Version>(),
+    ) {
+        assert_encode_decode::<BlockByHeightSchema>(&height, &first_version);
+    }
+}
+
+test_no_panic_decoding!(BlockByHeightSchema);
@@ -0,0 +1,56 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the reverse block index, which maps the version
+//! of the first transaction of a block to the height of the block. The block containing a version
+//! is found by seeking to the last record at or preceding the version.
+//!
+//! ```text
+//! |<------key------>|<-value->|
+//! | first_version   | height  |
+//! ```
+//!
+//! `first_version` is serialized in big endian so that records in RocksDB will be in order of
+//! their numeric value.
+
+use crate::schema::{ensure_slice_len_eq, BLOCK_BY_VERSION_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use diem_types::transaction::Version;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    BlockByVersionSchema,
+    Version,
+    u64, // height
+    BLOCK_BY_VERSION_CF_NAME
+);
+
+impl KeyCodec<BlockByVersionSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<BlockByVersionSchema> for u64 {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+#[cfg(test)]
+mod test;
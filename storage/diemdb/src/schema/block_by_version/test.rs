@@ -0,0 +1,18 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        first_version in any::<Version>(),
+        height in any::<u64>(),
+    ) {
+        assert_encode_decode::<BlockByVersionSchema>(&first_version, &height);
+    }
+}
+
+test_no_panic_decoding!(BlockByVersionSchema);
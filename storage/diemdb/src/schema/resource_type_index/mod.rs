@@ -0,0 +1,87 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an optional secondary index via which the
+//! accounts holding a resource of a given type can be found, without scanning every account blob.
+//!
+//! Every write to a resource adds an entry keyed by the hash of the resource path (which is the BCS
+//! bytes of `Path::Resource(struct_tag)`), the account address and the version of the write. The
+//! value tells whether the resource exists after the write or has been deleted.
+//!
+//! ```text
+//! |<---------------key--------------->|<-value->|
+//! | path_hash | address | txn_version | exists  |
+//! ```
+//!
+//! `txn_version` is serialized in big endian so that entries of one account are in order of its
+//! numeric value.
+
+use crate::schema::{ensure_slice_len_eq, RESOURCE_TYPE_INDEX_CF_NAME};
+use anyhow::{bail, Result};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use diem_crypto::HashValue;
+use diem_types::{account_address::AccountAddress, transaction::Version};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, SeekKeyCodec, ValueCodec},
+};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    ResourceTypeIndexSchema,
+    Key,
+    bool,
+    RESOURCE_TYPE_INDEX_CF_NAME
+);
+
+type Key = (HashValue, AccountAddress, Version);
+
+const KEY_LEN: usize = HashValue::LENGTH + AccountAddress::LENGTH + size_of::<Version>();
+
+impl KeyCodec<ResourceTypeIndexSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref path_hash, ref address, version) = *self;
+
+        let mut encoded = path_hash.to_vec();
+        encoded.extend(address.to_vec());
+        encoded.write_u64::<BigEndian>(version)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, KEY_LEN)?;
+
+        const ADDRESS_OFFSET: usize = HashValue::LENGTH;
+        const VERSION_OFFSET: usize = ADDRESS_OFFSET + AccountAddress::LENGTH;
+        let path_hash = HashValue::from_slice(&data[..ADDRESS_OFFSET])?;
+        let address = AccountAddress::try_from(&data[ADDRESS_OFFSET..VERSION_OFFSET])?;
+        let version = (&data[VERSION_OFFSET..]).read_u64::<BigEndian>()?;
+
+        Ok((path_hash, address, version))
+    }
+}
+
+impl ValueCodec<ResourceTypeIndexSchema> for bool {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(vec![*self as u8])
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 1)?;
+        match data[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => bail!("Unexpected resource existence flag {}.", b),
+        }
+    }
+}
+
+impl SeekKeyCodec<ResourceTypeIndexSchema> for HashValue {
+    fn encode_seek_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,20 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        path_hash in any::<HashValue>(),
+        address in any::<AccountAddress>(),
+        version in any::<Version>(),
+        exists in any::<bool>(),
+    ) {
+        assert_encode_decode::<ResourceTypeIndexSchema>(&(path_hash, address, version), &exists);
+    }
+}
+
+test_no_panic_decoding!(ResourceTypeIndexSchema);
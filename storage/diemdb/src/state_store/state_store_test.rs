@@ -408,6 +408,7 @@ proptest! {
                 None,  /* pruner */
                 RocksdbConfig::default(),
                 true, /* account_count_migration */
+                false, /* enable_resource_type_index */
             ).unwrap();
             let store2 = &db2.state_store;
             // confirm that leaf counts were not written
@@ -498,6 +499,7 @@ proptest! {
                 None,
                 RocksdbConfig::default(),
                 false, /* account_count_migration */
+                false, /* enable_resource_type_index */
             ).unwrap();
             let store = &db.state_store;
             init_store(store, before.into_iter());
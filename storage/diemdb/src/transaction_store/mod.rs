@@ -7,12 +7,15 @@ use crate::{
     change_set::ChangeSet,
     errors::DiemDbError,
     schema::{
+        block_by_height::BlockByHeightSchema, block_by_version::BlockByVersionSchema,
         transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema,
         transaction_by_hash::TransactionByHashSchema, write_set::WriteSetSchema,
     },
 };
 use anyhow::{ensure, format_err, Result};
 use diem_crypto::{hash::CryptoHash, HashValue};
+use diem_infallible::{Mutex, MutexGuard};
+use diem_logger::prelude::*;
 use diem_types::{
     account_address::AccountAddress,
     block_metadata::BlockMetadata,
@@ -20,16 +23,31 @@ use diem_types::{
     write_set::WriteSet,
 };
 use schemadb::{ReadOptions, SchemaIterator, DB};
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+// Must be larger than a block size, otherwise a NotFound error will be raised wrongly.
+const MAX_VERSIONS_TO_SEARCH: usize = 1000 * 100;
 
 #[derive(Debug)]
 pub(crate) struct TransactionStore {
     db: Arc<DB>,
+    /// Whether all the committed blocks are indexed, so that the blocks are indexed as they're
+    /// committed. The writers of the block index hold the lock.
+    block_index_complete: Mutex<bool>,
 }
 
 impl TransactionStore {
     pub fn new(db: Arc<DB>) -> Self {
-        Self { db }
+        Self {
+            db,
+            block_index_complete: Mutex::new(false),
+        }
     }
 
     /// Gets the version of a transaction by the sender `address` and `sequence_number`.
@@ -128,8 +146,12 @@ impl TransactionStore {
     /// Returns None if there's no such transaction at or preceding `version` (it's likely the genesis
     /// version 0).
     pub fn get_block_metadata(&self, version: Version) -> Result<Option<(Version, BlockMetadata)>> {
-        // Must be larger than a block size, otherwise a NotFound error will be raised wrongly.
-        const MAX_VERSIONS_TO_SEARCH: usize = 1000 * 100;
+        if let Some(block) = self.get_indexed_block_by_version(version)? {
+            return Ok(match self.get_transaction(block.first_version)? {
+                Transaction::BlockMetadata(block_meta) => Some((block.first_version, block_meta)),
+                _ => None,
+            });
+        }
 
         // Linear search via `DB::rev_iter()` here, NOT expecting performance hit, due to the fact
         // that the iterator caches data block and that there are limited number of transactions in
@@ -168,6 +190,160 @@ impl TransactionStore {
         Ok(())
     }
 
+    /// Indexes the blocks starting among `transactions`, the first of which is at `first_version`.
+    /// Block 0 is the genesis transaction, and every block metadata transaction starts the next
+    /// block. The blocks before `first_version` must be indexed, see `lock_block_index`.
+    ///
+    /// The height of a block is only known with the ledger history from genesis on, so nothing is
+    /// indexed on a db that doesn't start with genesis, e.g. a db restored from a backup that
+    /// doesn't start with genesis.
+    pub fn put_block_index<'a>(
+        &self,
+        first_version: Version,
+        transactions: impl IntoIterator<Item = &'a Transaction>,
+        cs: &mut ChangeSet,
+    ) -> Result<()> {
+        let mut last_height = if first_version == 0 {
+            None
+        } else {
+            match self.get_last_indexed_block_height()? {
+                Some(height) => Some(height),
+                None => return Ok(()),
+            }
+        };
+        for (version, transaction) in (first_version..).zip(transactions) {
+            if version == 0 || matches!(transaction, Transaction::BlockMetadata(_)) {
+                let height = last_height.map_or(0, |height| height + 1);
+                cs.batch.put::<BlockByHeightSchema>(&height, &version)?;
+                cs.batch.put::<BlockByVersionSchema>(&version, &height)?;
+                last_height = Some(height);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_last_indexed_block_height(&self) -> Result<Option<u64>> {
+        Ok(self.get_last_indexed_block()?.map(|(height, _)| height))
+    }
+
+    /// Returns the height and the first version of the last indexed block.
+    fn get_last_indexed_block(&self) -> Result<Option<(u64, Version)>> {
+        let mut iter = self.db.iter::<BlockByHeightSchema>(Default::default())?;
+        iter.seek_to_last();
+        iter.next().transpose()
+    }
+
+    /// Locks the block index for writing. The guard tells whether all the committed blocks are
+    /// indexed, in which case the transactions being committed can be passed to
+    /// `put_block_index` and written while holding the guard. Otherwise, they're indexed by
+    /// `backfill_block_index`.
+    pub fn lock_block_index(&self) -> MutexGuard<'_, bool> {
+        self.block_index_complete.lock()
+    }
+
+    /// Checks whether all the committed blocks are indexed, i.e. the last indexed block is the
+    /// block of the latest transaction, or blocks can't be indexed at all without genesis.
+    pub fn check_block_index_complete(&self) -> Result<bool> {
+        let mut block_index_complete = self.lock_block_index();
+        if !*block_index_complete {
+            *block_index_complete = self.is_block_index_complete()?;
+        }
+        Ok(*block_index_complete)
+    }
+
+    fn is_block_index_complete(&self) -> Result<bool> {
+        // heights are unknown without genesis, also the case of an empty db
+        if self.db.get::<TransactionSchema>(&0)?.is_none() {
+            return Ok(true);
+        }
+        let last_indexed_version = self.get_last_indexed_block()?.map(|(_, version)| version);
+        let mut iter = self.db.rev_iter::<TransactionSchema>(Default::default())?;
+        iter.seek_to_last();
+        for res in iter.take(MAX_VERSIONS_TO_SEARCH) {
+            let (version, txn) = res?;
+            if version == 0 || matches!(txn, Transaction::BlockMetadata(_)) {
+                return Ok(last_indexed_version == Some(version));
+            }
+        }
+        Ok(false)
+    }
+
+    /// Indexes the blocks committed without the block index, e.g. by a version that didn't have
+    /// it. The scan starts from the last indexed block, or from genesis if nothing is indexed yet,
+    /// and is written in chunks so that an interrupted backfill resumes where it stopped. Once
+    /// the latest transaction is indexed, the block index is complete and the blocks are indexed
+    /// as they're committed.
+    ///
+    /// Returns the number of scanned transactions, when done or when `quit` is set.
+    pub fn backfill_block_index(&self, quit: &AtomicBool) -> Result<u64> {
+        const MAX_TRANSACTIONS_PER_BATCH: usize = 10_000;
+
+        let mut num_scanned = 0;
+        while !quit.load(Ordering::Relaxed) {
+            let mut block_index_complete = self.lock_block_index();
+            let start_version = match self.get_last_indexed_block()? {
+                Some((_height, first_version)) => first_version + 1,
+                // heights are unknown without genesis
+                None if self.db.get::<TransactionSchema>(&0)?.is_none() => {
+                    *block_index_complete = true;
+                    break;
+                }
+                None => 0,
+            };
+            let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+            iter.seek(&start_version)?;
+            let txns = iter
+                .take(MAX_TRANSACTIONS_PER_BATCH)
+                .collect::<Result<Vec<_>>>()?;
+            let first_version = match txns.first() {
+                Some((version, _)) => *version,
+                None => {
+                    *block_index_complete = true;
+                    break;
+                }
+            };
+            let mut cs = ChangeSet::new();
+            self.put_block_index(first_version, txns.iter().map(|(_, txn)| txn), &mut cs)?;
+            self.db.write_schemas(cs.batch)?;
+            num_scanned += txns.len() as u64;
+        }
+        Ok(num_scanned)
+    }
+
+    /// Gets the block at `height` from the block index.
+    pub fn get_indexed_block_by_height(&self, height: u64) -> Result<Option<IndexedBlock>> {
+        let first_version = match self.db.get::<BlockByHeightSchema>(&height)? {
+            Some(first_version) => first_version,
+            None => return Ok(None),
+        };
+        let next_block_version = match height.checked_add(1) {
+            Some(next_height) => self.db.get::<BlockByHeightSchema>(&next_height)?,
+            None => None,
+        };
+        Ok(Some(IndexedBlock {
+            height,
+            first_version,
+            next_block_version,
+        }))
+    }
+
+    /// Gets the block containing `version` from the block index.
+    pub fn get_indexed_block_by_version(&self, version: Version) -> Result<Option<IndexedBlock>> {
+        let mut iter = self.db.iter::<BlockByVersionSchema>(Default::default())?;
+        iter.seek_for_prev(&version)?;
+        let (first_version, height) = match iter.next().transpose()? {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let next_block_version = iter.next().transpose()?.map(|(version, _)| version);
+        Ok(Some(IndexedBlock {
+            height,
+            first_version,
+            next_block_version,
+        }))
+    }
+
     /// Get executed transaction vm output given `version`
     pub fn get_write_set(&self, version: Version) -> Result<WriteSet> {
         self.db
@@ -193,6 +369,57 @@ impl TransactionStore {
     }
 }
 
+/// Runs `TransactionStore::backfill_block_index` in the background, so that opening a db
+/// committed without the block index isn't delayed by the scan of its whole history. The backfill
+/// stops when this is dropped, and resumes when the db is opened again.
+#[derive(Debug)]
+pub(crate) struct BlockIndexBackfill {
+    quit: Arc<AtomicBool>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl BlockIndexBackfill {
+    pub fn new(transaction_store: Arc<TransactionStore>) -> Self {
+        let quit = Arc::new(AtomicBool::new(false));
+        let quit_clone = Arc::clone(&quit);
+        let join_handle = std::thread::Builder::new()
+            .name("diemdb_block_index".into())
+            .spawn(
+                move || match transaction_store.backfill_block_index(&quit_clone) {
+                    Ok(num_scanned) => {
+                        info!(num_scanned = num_scanned, "Block index backfill stopped.")
+                    }
+                    Err(e) => error!(error = ?e, "Failed backfilling block index."),
+                },
+            )
+            .expect("Creating block index backfill thread should succeed.");
+        Self {
+            quit,
+            join_handle: Some(join_handle),
+        }
+    }
+}
+
+impl Drop for BlockIndexBackfill {
+    fn drop(&mut self) {
+        self.quit.store(true, Ordering::Relaxed);
+        self.join_handle
+            .take()
+            .expect("Block index backfill thread must exist.")
+            .join()
+            .expect("Block index backfill thread should join peacefully.");
+    }
+}
+
+/// A block of the block index.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IndexedBlock {
+    pub height: u64,
+    pub first_version: Version,
+    /// The first version of the next block, if it's committed.
+    pub next_block_version: Option<Version>,
+}
+
 pub struct TransactionIter<'a> {
     inner: SchemaIterator<'a, TransactionSchema>,
     expected_next_version: Version,
@@ -166,6 +166,78 @@ proptest! {
         }
     }
 
+    #[test]
+    fn test_block_index(
+        txns in vec(
+            prop_oneof![
+                any::<BlockMetadata>().prop_map(Transaction::BlockMetadata),
+                any::<SignedTransaction>().prop_map(Transaction::UserTransaction),
+            ],
+            1..100,
+        ),
+        split in any::<Index>(),
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let store = &db.transaction_store;
+
+        // save in two batches, the second one continuing the index of the first one
+        let split = split.index(txns.len());
+        for (first_version, chunk) in [(0, &txns[..split]), (split, &txns[split..])] {
+            let mut cs = ChangeSet::new();
+            for (idx, txn) in chunk.iter().enumerate() {
+                store
+                    .put_transaction((first_version + idx) as Version, txn, &mut cs)
+                    .unwrap();
+            }
+            store
+                .put_block_index(first_version as Version, chunk, &mut cs)
+                .unwrap();
+            store.db.write_schemas(cs.batch).unwrap();
+        }
+
+        let block_starts: Vec<Version> = txns
+            .iter()
+            .enumerate()
+            .filter(|(ver, txn)| *ver == 0 || matches!(txn, Transaction::BlockMetadata(_)))
+            .map(|(ver, _)| ver as Version)
+            .collect();
+        for (height, first_version) in block_starts.iter().enumerate() {
+            let expected = IndexedBlock {
+                height: height as u64,
+                first_version: *first_version,
+                next_block_version: block_starts.get(height + 1).cloned(),
+            };
+            prop_assert_eq!(
+                store.get_indexed_block_by_height(height as u64).unwrap(),
+                Some(expected)
+            );
+            let last_version = expected
+                .next_block_version
+                .unwrap_or(txns.len() as Version) - 1;
+            for version in *first_version..=last_version {
+                prop_assert_eq!(
+                    store.get_indexed_block_by_version(version).unwrap(),
+                    Some(expected)
+                );
+            }
+        }
+        prop_assert!(store
+            .get_indexed_block_by_height(block_starts.len() as u64)
+            .unwrap()
+            .is_none());
+
+        // the block metadata is found through the index
+        for ver in 0..txns.len() as Version {
+            let block_start = *block_starts.iter().rev().find(|v| **v <= ver).unwrap();
+            let expected = match &txns[block_start as usize] {
+                Transaction::BlockMetadata(block_meta) => Some((block_start, block_meta.clone())),
+                _ => None,
+            };
+            prop_assert_eq!(store.get_block_metadata(ver).unwrap(), expected);
+        }
+    }
+
     #[test]
     fn test_get_account_transaction_version_iter(
         universe in any_with::<AccountInfoUniverse>(5),
@@ -250,6 +322,84 @@ proptest! {
     }
 }
 
+#[test]
+fn test_block_index_requires_history_from_genesis() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = &db.transaction_store;
+
+    let txns = vec![Transaction::BlockMetadata(BlockMetadata::new(
+        HashValue::zero(),
+        0,
+        1,
+        vec![],
+        AccountAddress::ZERO,
+    ))];
+    let mut cs = ChangeSet::new();
+    store.put_transaction(10, &txns[0], &mut cs).unwrap();
+    store.put_block_index(10, &txns, &mut cs).unwrap();
+    store.db.write_schemas(cs.batch).unwrap();
+
+    assert_eq!(store.get_indexed_block_by_height(0).unwrap(), None);
+    assert_eq!(store.get_indexed_block_by_version(10).unwrap(), None);
+}
+
+#[test]
+fn test_backfill_block_index() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    // the block index of an empty db is complete
+    assert!(*db.transaction_store.lock_block_index());
+    let store = TransactionStore::new(Arc::clone(&db.db));
+
+    let txns: Vec<_> = (0..6)
+        .map(|round| {
+            Transaction::BlockMetadata(BlockMetadata::new(
+                HashValue::zero(),
+                0,
+                round,
+                vec![],
+                AccountAddress::ZERO,
+            ))
+        })
+        .collect();
+    // the first blocks are indexed, the next ones were committed without the index
+    let mut cs = ChangeSet::new();
+    for (ver, txn) in txns.iter().enumerate() {
+        store.put_transaction(ver as Version, txn, &mut cs).unwrap();
+    }
+    store.put_block_index(0, &txns[..3], &mut cs).unwrap();
+    store.db.write_schemas(cs.batch).unwrap();
+    assert_eq!(store.get_indexed_block_by_height(3).unwrap(), None);
+    assert!(!store.check_block_index_complete().unwrap());
+
+    // nothing is scanned once told to quit
+    assert_eq!(
+        store.backfill_block_index(&AtomicBool::new(true)).unwrap(),
+        0
+    );
+    assert!(!*store.lock_block_index());
+
+    // the backfill resumes after the last indexed block
+    let quit = AtomicBool::new(false);
+    assert_eq!(store.backfill_block_index(&quit).unwrap(), 3);
+    assert!(*store.lock_block_index());
+    for height in 0..6 {
+        assert_eq!(
+            store.get_indexed_block_by_height(height).unwrap(),
+            Some(IndexedBlock {
+                height,
+                first_version: height,
+                next_block_version: Some(height + 1).filter(|ver| *ver < 6),
+            })
+        );
+    }
+    assert_eq!(store.backfill_block_index(&quit).unwrap(), 0);
+    assert!(TransactionStore::new(Arc::clone(&db.db))
+        .check_block_index_complete()
+        .unwrap());
+}
+
 fn init_store(
     mut universe: AccountInfoUniverse,
     gens: Vec<(Index, SignatureCheckedTransactionGen)>,
@@ -14,6 +14,7 @@ use storage_interface::DbReader;
 use diem_types::{
     account_address::AccountAddress, account_config::AccountResource, account_state::AccountState,
 };
+use move_core_types::{language_storage::StructTag, parser::parse_struct_tag};
 use std::convert::TryFrom;
 use structopt::StructOpt;
 
@@ -38,7 +39,13 @@ enum Command {
         address: AccountAddress,
     },
     #[structopt(name = "list-accounts")]
-    ListAccounts,
+    ListAccounts {
+        /// Only list accounts holding a resource of this type, e.g.
+        /// `0x1::DiemAccount::Balance<0x1::XDX::XDX>`. Scans the resource type index, which must
+        /// have been enabled on the node that wrote the DB.
+        #[structopt(long, parse(try_from_str = parse_struct_tag))]
+        resource_type: Option<StructTag>,
+    },
 }
 
 /// Print out latest information stored in the DB.
@@ -152,6 +159,28 @@ fn list_accounts(db: &DiemDB) {
     info!("Total Accounts: {}", num_account);
 }
 
+fn list_accounts_by_resource_type(db: &DiemDB, struct_tag: &StructTag) -> Result<()> {
+    const PAGE_SIZE: u64 = 1000;
+
+    let version = db.get_latest_version()?;
+    let mut num_account = 0;
+    let mut start_after = None;
+    loop {
+        let accounts =
+            db.get_accounts_by_resource_type(struct_tag, start_after, PAGE_SIZE, version)?;
+        for address in &accounts {
+            info!("Address: {:?}", address);
+        }
+        num_account += accounts.len();
+        if (accounts.len() as u64) < PAGE_SIZE {
+            break;
+        }
+        start_after = accounts.last().cloned();
+    }
+    info!("Total Accounts holding {}: {}", struct_tag, num_account);
+    Ok(())
+}
+
 fn main() {
     ::diem_logger::DiemLogger::builder().build();
 
@@ -173,6 +202,7 @@ fn main() {
         None, /* pruner */
         RocksdbConfig::default(),
         true, /* account_count_migration, ignored anyway */
+        true, /* enable_resource_type_index */
     )
     .expect("Unable to open DiemDB");
     info!("DB opened successfully.");
@@ -188,9 +218,11 @@ fn main() {
             Command::PrintAccount { address } => {
                 print_account(&db, address);
             }
-            Command::ListAccounts => {
-                list_accounts(&db);
-            }
+            Command::ListAccounts { resource_type } => match resource_type {
+                Some(struct_tag) => list_accounts_by_resource_type(&db, &struct_tag)
+                    .expect("Unable to list accounts by resource type"),
+                None => list_accounts(&db),
+            },
         }
     } else {
         print_head(&db).expect("Unable to read information from DB");
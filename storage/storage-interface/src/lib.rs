@@ -9,6 +9,7 @@ use diem_types::{
     account_config::diem_root_address,
     account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof, AccountStatesChunkWithProof},
+    block_metadata::BlockMetadata,
     contract_event::{ContractEvent, EventByVersionWithProof, EventWithProof},
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
@@ -28,7 +29,10 @@ use diem_types::{
         TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version,
     },
 };
-use move_core_types::resolver::{ModuleResolver, ResourceResolver};
+use move_core_types::{
+    language_storage::StructTag,
+    resolver::{ModuleResolver, ResourceResolver},
+};
 use serde::{Deserialize, Serialize};
 use std::{convert::TryFrom, sync::Arc};
 use thiserror::Error;
@@ -132,6 +136,18 @@ impl TreeState {
     }
 }
 
+/// A committed block, as found in the block index of the storage.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CommittedBlock {
+    /// The number of blocks committed before this one, the genesis block being at height 0.
+    pub height: u64,
+    pub first_version: Version,
+    /// The last version of the block, which is the ledger version if the block is the latest one.
+    pub last_version: Version,
+    /// The metadata of the block, `None` for the genesis block.
+    pub metadata: Option<BlockMetadata>,
+}
+
 pub trait StateSnapshotReceiver<V>: Send {
     fn add_chunk(
         &mut self,
@@ -503,6 +519,49 @@ pub trait DbReader: Send + Sync {
     fn get_state_prune_window(&self) -> Option<usize> {
         unimplemented!()
     }
+
+    /// Returns at most `limit` accounts holding a resource of type `struct_tag` at
+    /// `ledger_version`, in ascending address order, starting right after `start_after`. Pass the
+    /// last returned address as `start_after` to get the next page.
+    ///
+    /// Only available if the resource type index is enabled in the storage config.
+    fn get_accounts_by_resource_type(
+        &self,
+        struct_tag: &StructTag,
+        start_after: Option<AccountAddress>,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<AccountAddress>> {
+        unimplemented!()
+    }
+
+    /// Returns the block at `height` as of `ledger_version`, from the block index, or `None` if
+    /// it's not committed.
+    ///
+    /// Blocks are only indexed if the storage holds the ledger history from genesis on, e.g. not
+    /// after a restore from a backup that doesn't start with genesis. The blocks committed by a
+    /// version without the block index are indexed in the background after an upgrade.
+    fn get_block_by_height(
+        &self,
+        height: u64,
+        ledger_version: Version,
+    ) -> Result<Option<CommittedBlock>> {
+        unimplemented!()
+    }
+
+    /// Returns the block containing `version` as of `ledger_version`, from the block index, or
+    /// `None` if the version is not committed.
+    ///
+    /// Blocks are only indexed if the storage holds the ledger history from genesis on, e.g. not
+    /// after a restore from a backup that doesn't start with genesis. The blocks committed by a
+    /// version without the block index are indexed in the background after an upgrade.
+    fn get_block_by_version(
+        &self,
+        version: Version,
+        ledger_version: Version,
+    ) -> Result<Option<CommittedBlock>> {
+        unimplemented!()
+    }
 }
 
 impl MoveStorage for &dyn DbReader {
@@ -4,7 +4,12 @@
 use diem_rest_client::Client as RestClient;
 use diem_sdk::{move_types::account_address::AccountAddress, transaction_builder::Currency};
 use forge::{ForgeConfig, Options, Result, *};
-use std::{env, num::NonZeroUsize, process, time::Duration};
+use std::{
+    env,
+    num::{NonZeroU64, NonZeroUsize},
+    process,
+    time::Duration,
+};
 use structopt::StructOpt;
 use testcases::{
     compatibility_test::SimpleValidatorUpgrade, fixed_tps_test::FixedTpsTest,
@@ -26,6 +31,22 @@ struct Args {
     wait_millis: u64,
     #[structopt(long)]
     burst: bool,
+    #[structopt(
+        long,
+        help = "Weighted mix of transaction types, e.g. p2p=60,zipf-p2p:1.2=20,hot-account:4=20",
+        default_value = "p2p=1"
+    )]
+    workload: Workload,
+    #[structopt(
+        long,
+        help = "Target TPS over time: constant:TPS, ramp:FROM:TO:SECS, step:TPS:SECS,... or burst:BASE:BURST:PERIOD_SECS:BURST_SECS"
+    )]
+    tps_profile: Option<TpsProfile>,
+    #[structopt(
+        long,
+        help = "Submit transactions at this rate without waiting for commits, measuring latency from the scheduled send time"
+    )]
+    open_loop_tps: Option<NonZeroU64>,
     #[structopt(flatten)]
     options: Options,
     #[structopt(long, help = "Specify a test suite to run")]
@@ -167,7 +188,14 @@ fn main() -> Result<()> {
         .thread_params(EmitThreadParams {
             wait_millis: args.wait_millis,
             wait_committed: !args.burst,
-        });
+        })
+        .workload(args.workload.clone());
+    if let Some(tps_profile) = &args.tps_profile {
+        global_emit_job_request = global_emit_job_request.tps_profile(tps_profile.clone());
+    }
+    if let Some(target_tps) = args.open_loop_tps {
+        global_emit_job_request = global_emit_job_request.open_loop(target_tps);
+    }
     if let Some(workers_per_endpoint) = args.workers_per_ac {
         global_emit_job_request =
             global_emit_job_request.workers_per_endpoint(workers_per_endpoint);
@@ -4,7 +4,7 @@
 use anyhow::bail;
 use diem_rest_client::Transaction;
 use diem_sdk::{transaction_builder::Currency, types::account_address::AccountAddress};
-use diem_transaction_replay::DiemDebugger;
+use diem_transaction_replay::{DiemDebugger, ExecutionMode};
 use forge::{PublicUsageContext, PublicUsageTest, Result, Test};
 use tokio::runtime::Runtime;
 
@@ -53,6 +53,11 @@ impl PublicUsageTest for ReplayTooling {
             .unwrap();
         assert_eq!(replay_result.gas_used(), txn_gas_used);
 
+        // Replaying with both executors checks them against each other and the committed info.
+        DiemDebugger::json_rpc(ctx.url())?
+            .with_execution_mode(ExecutionMode::Both)
+            .execute_past_transactions(txn_version, 1, false)?;
+
         let script_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
             .join("../../diem-move/transaction-replay/examples/account_exists.move")
             .canonicalize()?;
@@ -509,6 +509,13 @@ pub struct SignedTransaction {
 pub struct SignatureCheckedTransaction(SignedTransaction);
 
 impl SignatureCheckedTransaction {
+    /// Skips the signature check, only for the VM to simulate transactions that are never
+    /// committed. The authenticator must still carry the public keys of the signers, which the
+    /// prologue checks against their authentication keys.
+    pub fn new_unchecked_for_simulation(txn: SignedTransaction) -> Self {
+        Self(txn)
+    }
+
     /// Returns the `SignedTransaction` within.
     pub fn into_inner(self) -> SignedTransaction {
         self.0